@@ -0,0 +1,28 @@
+//! A generated async client that can run in the browser.
+//!
+//! Build it for WebAssembly with:
+//!
+//! ```sh
+//! cargo build --example wasm_client --target wasm32-unknown-unknown \
+//!     --no-default-features --features async
+//! ```
+
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people {
+    use wadl::Resource;
+    include!("../tests/people-wadl-async.rs.golden");
+}
+
+/// Return the number of people whose name matches `text`
+pub async fn count_people(
+    client: &reqwest::Client,
+    root: &people::ServiceRootJson,
+    text: &str,
+) -> Result<i32, wadl::Error> {
+    let people = root
+        .people_collection_link()
+        .ok_or(wadl::Error::InvalidUrl)?;
+    Ok(people.find(client, text, None).await?.total_size)
+}
+
+fn main() {}
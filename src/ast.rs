@@ -1,14 +1,34 @@
 //! Abstract syntax tree for WADL documents.
 use iri_string::spec::IriSpec;
 use iri_string::types::RiReferenceString;
-use std::collections::HashMap;
+use serde::{Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
 use url::Url;
 
 /// Identifier for a resource, method, parameter, etc.
 pub type Id = String;
 
+/// Serialize a value by its string form, for types that don't implement `Serialize`
+fn serialize_display<T: std::fmt::Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn serialize_optional_display<T: std::fmt::Display, S: Serializer>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
 /// Parameter style
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ParamStyle {
     /// Specifies a component of the representation formatted as a string encoding of the parameter value according to the rules of the media type.
     Plain,
@@ -27,7 +47,7 @@ pub enum ParamStyle {
 }
 
 /// A WADL application.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Application {
     /// Resources defined at the application level.
     pub resources: Vec<Resources>,
@@ -61,6 +81,191 @@ impl Application {
         }
     }
 
+    /// Merge the definitions of another application into this one
+    ///
+    /// This is for APIs that are described by several documents. Resources are added to those
+    /// with the same base URL, if any; docs and grammars are appended. Resource types and
+    /// representations are only added if no definition with the same id exists yet, so that
+    /// definitions repeated across documents are shared.
+    pub fn merge(&mut self, other: Application) {
+        for resources in other.resources {
+            match self.resources.iter_mut().find(|r| r.base == resources.base) {
+                Some(existing) => existing.resources.extend(resources.resources),
+                None => self.resources.push(resources),
+            }
+        }
+        for resource_type in other.resource_types {
+            if self.get_resource_type_by_id(&resource_type.id).is_none() {
+                self.resource_types.push(resource_type);
+            }
+        }
+        for representation in other.representations {
+            if representation.id.is_none()
+                || !self
+                    .representations
+                    .iter()
+                    .any(|r| r.id == representation.id)
+            {
+                self.representations.push(representation);
+            }
+        }
+        self.docs.extend(other.docs);
+        self.grammars.extend(other.grammars);
+    }
+
+    /// Copy the definitions that are referenced in other documents into this application
+    ///
+    /// References to resource types and representations in other documents are replaced by
+    /// references to copies of their definitions, so that the application is self-contained.
+    /// The definitions that copied definitions refer to are copied in turn, along with the
+    /// grammars of their documents. Grammars are made absolute. References to definitions that
+    /// can not be found are left alone.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of this document
+    /// * `load` - Loads the document at a URL; it is called once for each document
+    pub fn resolve_references<E>(
+        &mut self,
+        url: &Url,
+        mut load: impl FnMut(&Url) -> Result<Application, E>,
+    ) -> Result<(), E> {
+        let own = document_url(url);
+        absolutize_grammars(&mut self.grammars, &own);
+        let mut documents: HashMap<Url, Application> = HashMap::new();
+        loop {
+            let mut links = vec![];
+            self.for_each_reference(&mut |r| {
+                if let Some((kind, link)) = r.link() {
+                    links.push((kind, link.clone()));
+                }
+            });
+
+            let mut resolved = HashMap::new();
+            for (kind, link) in links {
+                let Some(id) = link.fragment() else {
+                    continue;
+                };
+                let document = document_url(&link);
+                if document == own {
+                    resolved.insert((kind, link.clone()), id.to_string());
+                    continue;
+                }
+                if !documents.contains_key(&document) {
+                    let mut app = load(&document)?;
+                    absolutize_grammars(&mut app.grammars, &document);
+                    app.for_each_reference(&mut |mut r| r.make_absolute(&document));
+                    documents.insert(document.clone(), app);
+                }
+                let app = &documents[&document];
+                let found = match kind {
+                    DefinitionKind::ResourceType => {
+                        app.get_resource_type_by_id(id).map(|resource_type| {
+                            if self.get_resource_type_by_id(id).is_none() {
+                                self.resource_types.push(resource_type.clone());
+                            }
+                        })
+                    }
+                    DefinitionKind::Representation => app
+                        .representations
+                        .iter()
+                        .find(|r| r.id.as_deref() == Some(id))
+                        .map(|representation| {
+                            if !self
+                                .representations
+                                .iter()
+                                .any(|r| r.id.as_deref() == Some(id))
+                            {
+                                self.representations.push(representation.clone());
+                            }
+                        }),
+                };
+                if found.is_none() {
+                    log::warn!("Unable to resolve reference to {}", link);
+                    continue;
+                }
+                for grammar in &app.grammars {
+                    if !self
+                        .grammars
+                        .iter()
+                        .any(|g| g.href.as_str() == grammar.href.as_str())
+                    {
+                        self.grammars.push(grammar.clone());
+                    }
+                }
+                resolved.insert((kind, link.clone()), id.to_string());
+            }
+
+            if resolved.is_empty() {
+                return Ok(());
+            }
+            self.for_each_reference(&mut |mut r| {
+                let id = r
+                    .link()
+                    .and_then(|(kind, link)| resolved.get(&(kind, link.clone())))
+                    .cloned();
+                if let Some(id) = id {
+                    r.set_id(id);
+                }
+            });
+        }
+    }
+
+    /// Copy the definitions of resource types into the resources of those types
+    ///
+    /// Afterwards, resources no longer refer to resource types. Resource types that are the
+    /// target of links are kept, and the others are removed. References to resource types in
+    /// other documents are left alone, see [`Application::resolve_references`]. Methods that
+    /// would end up with the same id get a numeric suffix.
+    pub fn flatten_resource_types(&mut self) {
+        let mut linked = HashSet::new();
+        self.for_each_reference(&mut |r| {
+            if let Reference::LinkTarget(ResourceTypeRef::Id(id)) = r {
+                linked.insert(id.clone());
+            }
+        });
+
+        let resource_types = self.resource_types.clone();
+        let mut method_ids = resource_types
+            .iter()
+            .filter(|rt| linked.contains(&rt.id))
+            .flat_map(|rt| rt.methods.iter().map(|m| m.id.clone()))
+            .collect::<HashSet<_>>();
+        for resources in &self.resources {
+            collect_method_ids(&resources.resources, &mut method_ids);
+        }
+
+        for resources in &mut self.resources {
+            for resource in &mut resources.resources {
+                flatten_resource(resource, &resource_types, &mut method_ids, &mut vec![]);
+            }
+        }
+
+        let mut referenced = HashSet::new();
+        self.for_each_reference(&mut |r| {
+            if let Reference::Type(ResourceTypeRef::Id(id))
+            | Reference::LinkTarget(ResourceTypeRef::Id(id)) = r
+            {
+                referenced.insert(id.clone());
+            }
+        });
+        self.resource_types.retain(|rt| referenced.contains(&rt.id));
+    }
+
+    /// Call a function for each reference to a resource type or representation
+    pub(crate) fn for_each_reference(&mut self, f: &mut dyn FnMut(Reference)) {
+        for resources in &mut self.resources {
+            resources_references(&mut resources.resources, f);
+        }
+        for resource_type in &mut self.resource_types {
+            params_references(&mut resource_type.params, f);
+            methods_references(&mut resource_type.methods, f);
+            resources_references(&mut resource_type.subresources, f);
+        }
+        for representation in &mut self.representations {
+            params_references(&mut representation.params, f);
+        }
+    }
+
     /// Iterate over all resources defined in this application.
     pub fn iter_resources(&self) -> impl Iterator<Item = (Url, &Resource)> {
         self.resources
@@ -68,9 +273,31 @@ impl Application {
             .flat_map(|rs| rs.resources.iter().map(|r| (r.url(rs.base.as_ref()), r)))
     }
 
-    /// Get a resource by its ID.
+    /// Iterate over all resources defined in this application, including nested resources.
+    ///
+    /// The URL of a nested resource is relative to the URL of its parent.
+    pub fn iter_all_resources(&self) -> impl Iterator<Item = (Url, &Resource)> {
+        let mut stack = self.iter_resources().collect::<Vec<_>>();
+        stack.reverse();
+        std::iter::from_fn(move || {
+            let (url, resource) = stack.pop()?;
+            let mut parent = url.clone();
+            if !parent.path().ends_with('/') {
+                parent.set_path(&format!("{}/", parent.path()));
+            }
+            for subresource in resource.subresources.iter().rev() {
+                let path = subresource.path.as_deref().unwrap_or_default();
+                if let Ok(url) = parent.join(path.trim_start_matches('/')) {
+                    stack.push((url, subresource));
+                }
+            }
+            Some((url, resource))
+        })
+    }
+
+    /// Get a resource by its URL, including nested resources.
     pub fn get_resource_by_href(&self, href: &Url) -> Option<&Resource> {
-        self.iter_resources()
+        self.iter_all_resources()
             .find(|(url, _)| url == href)
             .map(|(_, r)| r)
     }
@@ -103,6 +330,204 @@ impl Application {
     }
 }
 
+/// The kinds of definitions that can be referenced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum DefinitionKind {
+    ResourceType,
+    Representation,
+}
+
+/// A reference to a definition, see [`Application::for_each_reference`]
+pub(crate) enum Reference<'a> {
+    /// The type of a resource
+    Type(&'a mut ResourceTypeRef),
+
+    /// The resource type of the target of a link
+    LinkTarget(&'a mut ResourceTypeRef),
+
+    /// A representation of a request or response
+    Representation(&'a mut RepresentationRef),
+}
+
+impl Reference<'_> {
+    /// Return the kind of definition and the URL this refers to, if it is in another document
+    fn link(&self) -> Option<(DefinitionKind, &Url)> {
+        match self {
+            Reference::Type(r) | Reference::LinkTarget(r) => match &**r {
+                ResourceTypeRef::Link(url) => Some((DefinitionKind::ResourceType, url)),
+                _ => None,
+            },
+            Reference::Representation(r) => match &**r {
+                RepresentationRef::Link(url) => Some((DefinitionKind::Representation, url)),
+                RepresentationRef::Id(_) => None,
+            },
+        }
+    }
+
+    /// Refer to a definition in the same document
+    fn set_id(&mut self, id: Id) {
+        match self {
+            Reference::Type(r) | Reference::LinkTarget(r) => **r = ResourceTypeRef::Id(id),
+            Reference::Representation(r) => **r = RepresentationRef::Id(id),
+        }
+    }
+
+    /// Make a reference to a definition in the same document refer to it by URL instead
+    fn make_absolute(&mut self, document: &Url) {
+        let link = |id: &str| {
+            let mut url = document.clone();
+            url.set_fragment(Some(id));
+            url
+        };
+        match self {
+            Reference::Type(r) | Reference::LinkTarget(r) => {
+                if let ResourceTypeRef::Id(id) = &**r {
+                    **r = ResourceTypeRef::Link(link(id));
+                }
+            }
+            Reference::Representation(r) => {
+                if let RepresentationRef::Id(id) = &**r {
+                    **r = RepresentationRef::Link(link(id));
+                }
+            }
+        }
+    }
+}
+
+fn params_references(params: &mut [Param], f: &mut dyn FnMut(Reference)) {
+    for link in params.iter_mut().flat_map(|p| p.links.iter_mut()) {
+        if let Some(resource_type) = link.resource_type.as_mut() {
+            f(Reference::LinkTarget(resource_type));
+        }
+    }
+}
+
+fn representations_references(
+    representations: &mut [Representation],
+    f: &mut dyn FnMut(Reference),
+) {
+    for representation in representations {
+        match representation {
+            Representation::Reference(r) => f(Reference::Representation(r)),
+            Representation::Definition(d) => params_references(&mut d.params, f),
+        }
+    }
+}
+
+fn methods_references(methods: &mut [Method], f: &mut dyn FnMut(Reference)) {
+    for method in methods {
+        params_references(&mut method.request.params, f);
+        representations_references(&mut method.request.representations, f);
+        for response in &mut method.responses {
+            params_references(&mut response.params, f);
+            representations_references(&mut response.representations, f);
+        }
+    }
+}
+
+fn resources_references(resources: &mut [Resource], f: &mut dyn FnMut(Reference)) {
+    for resource in resources {
+        for resource_type in &mut resource.r#type {
+            f(Reference::Type(resource_type));
+        }
+        params_references(&mut resource.params, f);
+        methods_references(&mut resource.methods, f);
+        resources_references(&mut resource.subresources, f);
+    }
+}
+
+/// Return the URL of the document that a URL refers to, i.e. without its fragment
+fn document_url(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
+/// Resolve the hrefs of grammars against the URL of their document
+fn absolutize_grammars(grammars: &mut [Grammar], document: &Url) {
+    for grammar in grammars {
+        match document
+            .join(grammar.href.as_str())
+            .map(|url| url.as_str().parse())
+        {
+            Ok(Ok(href)) => grammar.href = href,
+            _ => log::warn!("Unable to resolve grammar {}", grammar.href),
+        }
+    }
+}
+
+fn collect_method_ids(resources: &[Resource], ids: &mut HashSet<Id>) {
+    for resource in resources {
+        ids.extend(resource.methods.iter().map(|m| m.id.clone()));
+        collect_method_ids(&resource.subresources, ids);
+    }
+}
+
+/// Return an id that is not in `ids` yet, based on `id`, and add it
+fn unique_id(id: &str, ids: &mut HashSet<Id>) -> Id {
+    if id.is_empty() {
+        return Id::new();
+    }
+    let mut candidate = id.to_string();
+    let mut n = 2;
+    while !ids.insert(candidate.clone()) {
+        candidate = format!("{}-{}", id, n);
+        n += 1;
+    }
+    candidate
+}
+
+/// Copy the definitions of the types of a resource and its subresources into them
+///
+/// `expanding` holds the resource types that are being copied into the ancestors of the
+/// resource, which are not copied again so that recursive resource types terminate.
+fn flatten_resource(
+    resource: &mut Resource,
+    resource_types: &[ResourceType],
+    method_ids: &mut HashSet<Id>,
+    expanding: &mut Vec<Id>,
+) {
+    let depth = expanding.len();
+    let mut remaining = vec![];
+    for r in std::mem::take(&mut resource.r#type) {
+        let resource_type = match &r {
+            ResourceTypeRef::Id(id) if !expanding.contains(id) => {
+                resource_types.iter().find(|rt| &rt.id == id)
+            }
+            _ => None,
+        };
+        let Some(resource_type) = resource_type else {
+            remaining.push(r);
+            continue;
+        };
+        expanding.push(resource_type.id.clone());
+        if resource.docs.is_empty() {
+            resource.docs = resource_type.docs.clone();
+        }
+        if resource.query_type == mime::APPLICATION_WWW_FORM_URLENCODED {
+            resource.query_type = resource_type.query_type.clone();
+        }
+        for param in &resource_type.params {
+            if !resource.params.iter().any(|p| p.name == param.name) {
+                resource.params.push(param.clone());
+            }
+        }
+        for method in &resource_type.methods {
+            let mut method = method.clone();
+            method.id = unique_id(&method.id, method_ids);
+            resource.methods.push(method);
+        }
+        resource
+            .subresources
+            .extend(resource_type.subresources.iter().cloned());
+    }
+    resource.r#type = remaining;
+    for subresource in &mut resource.subresources {
+        flatten_resource(subresource, resource_types, method_ids, expanding);
+    }
+    expanding.truncate(depth);
+}
+
 impl std::str::FromStr for Application {
     type Err = crate::parse::Error;
 
@@ -111,7 +536,7 @@ impl std::str::FromStr for Application {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 /// A collection of resources.
 pub struct Resources {
     /// The base URL for the resources.
@@ -121,10 +546,11 @@ pub struct Resources {
     pub resources: Vec<Resource>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 /// A grammar
 pub struct Grammar {
     /// The href of the grammar.
+    #[serde(serialize_with = "serialize_display")]
     pub href: RiReferenceString<IriSpec>,
 }
 
@@ -186,9 +612,31 @@ impl ResourceTypeRef {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Serialized as it would be written in a `type` attribute, e.g. `#person`
+impl Serialize for ResourceTypeRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ResourceTypeRef::Id(id) => serializer.collect_str(&format_args!("#{}", id)),
+            ResourceTypeRef::Link(url) => serializer.serialize_str(url.as_str()),
+            ResourceTypeRef::Empty => serializer.serialize_str(""),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 /// An option element defines one of a set of possible values for the parameter represented by its parent param element.
-pub struct Options(HashMap<String, Option<mime::Mime>>);
+///
+/// Options are compared and hashed by their values and media types; their documentation is
+/// ignored.
+pub struct Options(HashMap<String, Option<mime::Mime>>, HashMap<String, Doc>);
+
+impl PartialEq for Options {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Options {}
 
 impl std::hash::Hash for Options {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -204,7 +652,7 @@ impl std::hash::Hash for Options {
 impl Options {
     /// Create a new options object
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self(HashMap::new(), HashMap::new())
     }
 
     /// Number of items in this Options
@@ -212,14 +660,20 @@ impl Options {
         self.0.len()
     }
 
-    /// Iterate over all items in this Options
+    /// Iterate over all items in this Options, sorted by key
     pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&mime::Mime>)> {
-        self.0.iter().map(|(k, v)| (k.as_str(), v.as_ref()))
+        let mut items = self
+            .0
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_ref()))
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(k, _)| *k);
+        items.into_iter()
     }
 
-    /// Return an iterator over all keys
+    /// Return an iterator over all keys, in sorted order
     pub fn keys(&self) -> impl Iterator<Item = &str> {
-        self.0.keys().map(|k| k.as_str())
+        self.iter().map(|(k, _)| k)
     }
 
     /// Check if this Options is empty
@@ -236,21 +690,75 @@ impl Options {
     pub fn get(&self, key: &str) -> Option<&Option<mime::Mime>> {
         self.0.get(key)
     }
+
+    /// Set the documentation for a key
+    pub fn set_doc(&mut self, key: &str, doc: Doc) {
+        self.1.insert(key.to_string(), doc);
+    }
+
+    /// Get the documentation for a key
+    pub fn get_doc(&self, key: &str) -> Option<&Doc> {
+        self.1.get(key)
+    }
+}
+
+#[test]
+fn test_options_sorted() {
+    let options = Options::from(vec!["zulu", "alpha", "mike", "bravo"]);
+    assert_eq!(
+        options.keys().collect::<Vec<_>>(),
+        vec!["alpha", "bravo", "mike", "zulu"]
+    );
+}
+
+#[test]
+fn test_options_eq_ignores_docs() {
+    use std::hash::{BuildHasher, RandomState};
+
+    let options = Options::from(vec!["open", "closed"]);
+    let mut documented = options.clone();
+    documented.set_doc("open", Doc::new("Still open".to_string()));
+    assert_eq!(options, documented);
+    let state = RandomState::new();
+    assert_eq!(state.hash_one(&options), state.hash_one(&documented));
+    assert_ne!(options, Options::from(vec!["open"]));
 }
 
 impl From<Vec<String>> for Options {
     fn from(v: Vec<String>) -> Self {
-        Self(v.into_iter().map(|s| (s, None)).collect())
+        Self(v.into_iter().map(|s| (s, None)).collect(), HashMap::new())
+    }
+}
+
+/// Serialized as a list of the options, sorted by value
+impl Serialize for Options {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct OptionValue<'a> {
+            value: &'a str,
+            #[serde(serialize_with = "serialize_optional_display")]
+            media_type: Option<&'a mime::Mime>,
+            doc: Option<&'a Doc>,
+        }
+
+        serializer.collect_seq(self.iter().map(|(value, media_type)| OptionValue {
+            value,
+            media_type,
+            doc: self.get_doc(value),
+        }))
     }
 }
 
 impl From<Vec<&str>> for Options {
     fn from(v: Vec<&str>) -> Self {
-        Self(v.into_iter().map(|s| (s.to_string(), None)).collect())
+        Self(
+            v.into_iter().map(|s| (s.to_string(), None)).collect(),
+            HashMap::new(),
+        )
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 /// A resource
 pub struct Resource {
     /// The ID of the resource.
@@ -263,6 +771,7 @@ pub struct Resource {
     pub r#type: Vec<ResourceTypeRef>,
 
     /// The query type of the resource.
+    #[serde(serialize_with = "serialize_display")]
     pub query_type: mime::Mime,
 
     /// The methods defined at this level.
@@ -326,7 +835,58 @@ fn test_resource_url() {
     );
 }
 
-#[derive(Debug, Clone)]
+#[test]
+fn test_iter_all_resources() {
+    let app = crate::parse_string(
+        r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+            <resources base="https://api.example.com/1.0/">
+                <resource path="people" id="people">
+                    <resource path="{name}" id="person">
+                        <resource path="/bugs" id="person-bugs"/>
+                    </resource>
+                </resource>
+                <resource path="bugs" id="bugs"/>
+            </resources>
+        </application>"#,
+    )
+    .unwrap();
+    let resources = app
+        .iter_all_resources()
+        .map(|(url, r)| (url.to_string(), r.id.clone().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        resources,
+        vec![
+            (
+                "https://api.example.com/1.0/people".to_string(),
+                "people".to_string()
+            ),
+            (
+                "https://api.example.com/1.0/people/%7Bname%7D".to_string(),
+                "person".to_string()
+            ),
+            (
+                "https://api.example.com/1.0/people/%7Bname%7D/bugs".to_string(),
+                "person-bugs".to_string()
+            ),
+            (
+                "https://api.example.com/1.0/bugs".to_string(),
+                "bugs".to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        app.get_resource_by_href(
+            &"https://api.example.com/1.0/people/%7Bname%7D/bugs"
+                .parse()
+                .unwrap()
+        )
+        .and_then(|r| r.id.as_deref()),
+        Some("person-bugs")
+    );
+}
+
+#[derive(Debug, Clone, Serialize)]
 /// A HTTP Method
 pub struct Method {
     /// Identifier of this method
@@ -346,6 +906,14 @@ pub struct Method {
 }
 
 impl Method {
+    /// Return the HTTP method, including extension methods like `PROPFIND`
+    ///
+    /// Returns `None` if the name is not a valid HTTP method name.
+    #[cfg(feature = "http")]
+    pub fn http_method(&self) -> Option<reqwest::Method> {
+        reqwest::Method::from_bytes(self.name.as_bytes()).ok()
+    }
+
     fn iter_all_params(&self) -> impl Iterator<Item = &Param> {
         self.request
             .iter_all_params()
@@ -353,7 +921,269 @@ impl Method {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[test]
+fn test_application_merge() {
+    let mut people: Application = r##"<application xmlns="http://research.sun.com/wadl/2006/10">
+        <resources base="https://api.example.com/1.0/">
+            <resource path="people" type="#people"/>
+        </resources>
+        <resource_type id="people"/>
+        <representation id="person" mediaType="application/json"/>
+    </application>"##
+        .parse()
+        .unwrap();
+    let bugs: Application = r##"<application xmlns="http://research.sun.com/wadl/2006/10">
+        <resources base="https://api.example.com/1.0/">
+            <resource path="bugs" type="#bugs"/>
+        </resources>
+        <resources base="https://files.example.com/">
+            <resource path="attachments"/>
+        </resources>
+        <resource_type id="bugs"/>
+        <representation id="person" mediaType="application/json"/>
+        <representation id="bug" mediaType="application/json"/>
+    </application>"##
+        .parse()
+        .unwrap();
+    people.merge(bugs);
+    assert_eq!(people.resources.len(), 2);
+    assert_eq!(
+        people
+            .iter_resources()
+            .map(|(url, _)| url.to_string())
+            .collect::<Vec<_>>(),
+        [
+            "https://api.example.com/1.0/people",
+            "https://api.example.com/1.0/bugs",
+            "https://files.example.com/attachments"
+        ]
+    );
+    assert!(people.get_resource_type_by_id("bugs").is_some());
+    assert_eq!(
+        people
+            .representations
+            .iter()
+            .map(|r| r.id.as_deref().unwrap())
+            .collect::<Vec<_>>(),
+        ["person", "bug"]
+    );
+}
+
+#[test]
+fn test_application_serialize() {
+    let app: Application = r##"<application xmlns="http://wadl.dev.java.net/2009/02">
+        <grammars><include href="people.xsd"/></grammars>
+        <resources base="https://api.example.com/1.0/">
+            <resource path="people" type="#people"/>
+        </resources>
+        <resource_type id="people">
+            <method name="GET" id="people-get">
+                <request>
+                    <param name="status" style="query">
+                        <option value="active" mediaType="application/json"/>
+                        <option value="inactive"/>
+                    </param>
+                </request>
+                <response status="200">
+                    <representation href="#person"/>
+                    <representation mediaType="text/plain"/>
+                </response>
+            </method>
+        </resource_type>
+    </application>"##
+        .parse()
+        .unwrap();
+    let value = serde_json::to_value(&app).unwrap();
+    assert_eq!(value["grammars"][0]["href"], "people.xsd");
+    let resources = &value["resources"][0];
+    assert_eq!(resources["base"], "https://api.example.com/1.0/");
+    assert_eq!(resources["resources"][0]["type"][0], "#people");
+    assert_eq!(
+        resources["resources"][0]["query_type"],
+        "application/x-www-form-urlencoded"
+    );
+    let method = &value["resource_types"][0]["methods"][0];
+    assert_eq!(method["id"], "people-get");
+    let param = &method["request"]["params"][0];
+    assert_eq!(param["style"], "query");
+    assert_eq!(
+        param["options"],
+        serde_json::json!([
+            {"value": "active", "media_type": "application/json", "doc": null},
+            {"value": "inactive", "media_type": null, "doc": null},
+        ])
+    );
+    let response = &method["responses"][0];
+    assert_eq!(response["status"], 200);
+    assert_eq!(response["representations"][0], "#person");
+    assert_eq!(response["representations"][1]["media_type"], "text/plain");
+}
+
+#[test]
+fn test_resolve_references() {
+    let mut app: Application = r##"<application xmlns="http://wadl.dev.java.net/2009/02">
+        <grammars><include href="api.xsd"/></grammars>
+        <resources base="https://api.example.com/1.0/">
+            <resource path="bugs" type="https://example.com/common.wadl#paged"/>
+            <resource path="people" type="https://example.com/api.wadl#people"/>
+        </resources>
+        <resource_type id="people">
+            <method name="GET" id="people-get">
+                <response>
+                    <representation href="https://example.com/common.wadl#page"/>
+                </response>
+            </method>
+        </resource_type>
+    </application>"##
+        .parse()
+        .unwrap();
+    let common = r##"<application xmlns="http://wadl.dev.java.net/2009/02">
+        <grammars><include href="schemas/common.xsd"/></grammars>
+        <resource_type id="paged">
+            <method name="GET" id="paged-get">
+                <response>
+                    <representation href="#page"/>
+                </response>
+            </method>
+        </resource_type>
+        <representation id="page" mediaType="application/json">
+            <param name="next_link" style="plain" path="$.next_link">
+                <link resource_type="#paged"/>
+            </param>
+        </representation>
+    </application>"##;
+
+    let mut loaded = vec![];
+    app.resolve_references(
+        &"https://example.com/api.wadl".parse().unwrap(),
+        |url| -> Result<Application, crate::parse::Error> {
+            loaded.push(url.to_string());
+            common.parse()
+        },
+    )
+    .unwrap();
+    assert_eq!(loaded, ["https://example.com/common.wadl"]);
+
+    let types = app
+        .iter_resources()
+        .map(|(_, r)| r.r#type.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            vec![ResourceTypeRef::Id("paged".to_string())],
+            vec![ResourceTypeRef::Id("people".to_string())]
+        ]
+    );
+    assert_eq!(
+        app.resource_types
+            .iter()
+            .map(|rt| rt.id.as_str())
+            .collect::<Vec<_>>(),
+        ["people", "paged"]
+    );
+    let paged = app.get_resource_type_by_id("paged").unwrap();
+    assert!(matches!(
+        &paged.methods[0].responses[0].representations[0],
+        Representation::Reference(RepresentationRef::Id(id)) if id == "page"
+    ));
+    assert_eq!(app.representations.len(), 1);
+    assert_eq!(
+        app.representations[0].params[0].links[0].resource_type,
+        Some(ResourceTypeRef::Id("paged".to_string()))
+    );
+    assert_eq!(
+        app.grammars
+            .iter()
+            .map(|g| g.href.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "https://example.com/api.xsd",
+            "https://example.com/schemas/common.xsd"
+        ]
+    );
+}
+
+#[test]
+fn test_flatten_resource_types() {
+    let mut app: Application = r##"<application xmlns="http://wadl.dev.java.net/2009/02">
+        <resources base="https://api.example.com/1.0/">
+            <resource path="people" type="#people"/>
+            <resource path="~{name}" type="#person">
+                <param name="name" style="template"/>
+            </resource>
+            <resource path="bugs" type="#bugs #unknown"/>
+        </resources>
+        <resource_type id="people">
+            <method name="GET" id="people-get"/>
+        </resource_type>
+        <resource_type id="person">
+            <doc>A person</doc>
+            <param name="name" style="template"/>
+            <method name="GET" id="person-get"/>
+            <resource path="bugs" type="#bugs"/>
+        </resource_type>
+        <resource_type id="bugs">
+            <method name="GET" id="bugs-get"/>
+        </resource_type>
+        <representation id="person-full" mediaType="application/json">
+            <param name="bugs_link" style="plain" path="$.bugs_link">
+                <link resource_type="#bugs"/>
+            </param>
+        </representation>
+    </application>"##
+        .parse()
+        .unwrap();
+    app.flatten_resource_types();
+
+    // Only resource types that are linked to are kept
+    assert_eq!(
+        app.resource_types
+            .iter()
+            .map(|rt| rt.id.as_str())
+            .collect::<Vec<_>>(),
+        ["bugs"]
+    );
+
+    let resources = &app.resources[0].resources;
+    assert!(resources[0].r#type.is_empty());
+    assert_eq!(resources[0].methods[0].id, "people-get");
+
+    let person = &resources[1];
+    assert_eq!(person.docs[0].content, "A person");
+    assert_eq!(person.params.len(), 1);
+    assert_eq!(person.methods[0].id, "person-get");
+    assert_eq!(person.subresources[0].methods[0].id, "bugs-get-2");
+
+    let bugs = &resources[2];
+    assert_eq!(
+        bugs.r#type,
+        vec![ResourceTypeRef::Id("unknown".to_string())]
+    );
+    assert_eq!(bugs.methods[0].id, "bugs-get-3");
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_method_http_method() {
+    let mut m = Method {
+        id: "foo-propfind".to_string(),
+        name: "PROPFIND".to_string(),
+        docs: vec![],
+        request: Request::default(),
+        responses: vec![],
+    };
+    assert_eq!(
+        m.http_method(),
+        Some(reqwest::Method::from_bytes(b"PROPFIND").unwrap())
+    );
+    m.name = "GET".to_string();
+    assert_eq!(m.http_method(), Some(reqwest::Method::GET));
+    m.name = "NOT VALID".to_string();
+    assert_eq!(m.http_method(), None);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 /// Documentation
 pub struct Doc {
     /// The title of the documentation.
@@ -379,7 +1209,7 @@ impl Doc {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 /// A link to another resource.
 pub struct Link {
     /// The resource type of the link.
@@ -400,7 +1230,7 @@ pub struct Link {
     pub doc: Option<Doc>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 /// A parameter
 pub struct Param {
     /// The style of the parameter.
@@ -427,6 +1257,9 @@ pub struct Param {
     /// The fixed value of the parameter.
     pub fixed: Option<String>,
 
+    /// The default value of the parameter.
+    pub default: Option<String>,
+
     /// The documentation for the parameter.
     pub doc: Option<Doc>,
 
@@ -437,13 +1270,14 @@ pub struct Param {
     pub options: Option<Options>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 /// A representation definition
 pub struct RepresentationDef {
     /// The ID of the representation.
     pub id: Option<Id>,
 
     /// The media type of the representation.
+    #[serde(serialize_with = "serialize_optional_display")]
     pub media_type: Option<mime::Mime>,
 
     /// The element of the representation.
@@ -485,8 +1319,21 @@ impl RepresentationRef {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Serialized as it would be written in an `href` attribute, e.g. `#person-full`
+impl Serialize for RepresentationRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RepresentationRef::Id(id) => serializer.collect_str(&format_args!("#{}", id)),
+            RepresentationRef::Link(url) => serializer.serialize_str(url.as_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 /// A representation
+///
+/// When serialized, references are written as their href and definitions as objects.
+#[serde(untagged)]
 pub enum Representation {
     /// A reference to a representation defined in the same document.
     Reference(RepresentationRef),
@@ -586,7 +1433,7 @@ impl RepresentationDef {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 /// A request
 pub struct Request {
     /// The docs for the request.
@@ -610,7 +1457,7 @@ impl Request {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 /// A response
 pub struct Response {
     /// The docs for the response.
@@ -637,13 +1484,14 @@ impl Response {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 /// A resource type
 pub struct ResourceType {
     /// The ID of the resource type.
     pub id: Id,
 
     /// The query type of the resource type.
+    #[serde(serialize_with = "serialize_display")]
     pub query_type: mime::Mime,
 
     /// The methods defined at this level.
@@ -1,17 +1,407 @@
-/// Generate rust code from wadl
+/// Print the abstract syntax tree of a wadl document
 use clap::Parser;
 use std::path::PathBuf;
+use wadl::ast::*;
+
+/// The formats that the syntax tree can be printed in
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Format {
+    /// Rust debug formatting
+    Debug,
+    /// JSON, for consumption by scripts
+    Json,
+    /// YAML
+    Yaml,
+}
 
 #[derive(Parser)]
 struct Args {
     input: PathBuf,
+
+    /// Format to print the syntax tree in
+    #[clap(long, value_enum, default_value = "debug")]
+    format: Format,
+
+    /// Only print the nodes matching a path of `kind:key` steps, e.g.
+    /// `resource_type:person/method:GET`
+    ///
+    /// Kinds are `resource_type`, `resource` (by path), `method` (by name or id), `request`,
+    /// `response` (by status), `representation` (by id or media type) and `param` (by name).
+    /// The key can be left out to match all nodes of a kind.
+    #[clap(long, value_name = "PATH")]
+    select: Option<String>,
+}
+
+/// The kinds of node that can be selected
+const KINDS: &[&str] = &[
+    "resource_type",
+    "resource",
+    "method",
+    "request",
+    "response",
+    "representation",
+    "param",
+];
+
+/// A node of the syntax tree
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Application(&'a Application),
+    ResourceType(&'a ResourceType),
+    Resource(&'a Resource),
+    Method(&'a Method),
+    Request(&'a Request),
+    Response(&'a Response),
+    Representation(&'a Representation),
+    RepresentationDef(&'a RepresentationDef),
+    Param(&'a Param),
+}
+
+/// Nodes are serialized and formatted as the syntax tree node they refer to
+impl serde::Serialize for Node<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Node::Application(n) => n.serialize(serializer),
+            Node::ResourceType(n) => n.serialize(serializer),
+            Node::Resource(n) => n.serialize(serializer),
+            Node::Method(n) => n.serialize(serializer),
+            Node::Request(n) => n.serialize(serializer),
+            Node::Response(n) => n.serialize(serializer),
+            Node::Representation(n) => n.serialize(serializer),
+            Node::RepresentationDef(n) => n.serialize(serializer),
+            Node::Param(n) => n.serialize(serializer),
+        }
+    }
+}
+
+impl std::fmt::Debug for Node<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Node::Application(n) => n.fmt(f),
+            Node::ResourceType(n) => n.fmt(f),
+            Node::Resource(n) => n.fmt(f),
+            Node::Method(n) => n.fmt(f),
+            Node::Request(n) => n.fmt(f),
+            Node::Response(n) => n.fmt(f),
+            Node::Representation(n) => n.fmt(f),
+            Node::RepresentationDef(n) => n.fmt(f),
+            Node::Param(n) => n.fmt(f),
+        }
+    }
+}
+
+fn print<T: std::fmt::Debug + serde::Serialize>(value: &T, format: Format) -> Result<(), String> {
+    match format {
+        Format::Debug => println!("{:#?}", value),
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(value).map_err(|e| e.to_string())?
+        ),
+        Format::Yaml => print!(
+            "{}",
+            serde_yaml::to_string(value).map_err(|e| e.to_string())?
+        ),
+    }
+    Ok(())
+}
+
+/// Split a selection into its steps
+///
+/// Resource paths can contain slashes, so a slash only starts a new step if it is followed by
+/// a kind.
+fn parse_selection(selection: &str) -> Result<Vec<(&str, Option<&str>)>, String> {
+    let mut steps: Vec<(&str, Option<&str>)> = vec![];
+    let mut start = 0;
+    for (i, _) in selection.match_indices('/') {
+        let rest = &selection[i + 1..];
+        if KINDS.iter().any(|kind| {
+            rest == *kind
+                || rest.starts_with(&format!("{}:", kind))
+                || rest.starts_with(&format!("{}/", kind))
+        }) {
+            steps.push(parse_step(&selection[start..i])?);
+            start = i + 1;
+        }
+    }
+    steps.push(parse_step(&selection[start..])?);
+    Ok(steps)
+}
+
+fn parse_step(step: &str) -> Result<(&str, Option<&str>), String> {
+    let (kind, key) = match step.split_once(':') {
+        Some((kind, key)) => (kind, Some(key)),
+        None => (step, None),
+    };
+    if !KINDS.contains(&kind) {
+        return Err(format!(
+            "unknown kind {:?}, expected one of {}",
+            kind,
+            KINDS.join(", ")
+        ));
+    }
+    Ok((kind, key))
+}
+
+/// Return the resources matching a path, which may continue into their subresources
+fn matching_resources<'a>(resources: &'a [Resource], path: Option<&str>, ret: &mut Vec<Node<'a>>) {
+    for resource in resources {
+        let Some(path) = path.map(|p| p.trim_matches('/')) else {
+            ret.push(Node::Resource(resource));
+            continue;
+        };
+        let own = resource
+            .path
+            .as_deref()
+            .unwrap_or_default()
+            .trim_matches('/');
+        if path == own {
+            ret.push(Node::Resource(resource));
+        } else if let Some(rest) = path.strip_prefix(own).and_then(|r| r.strip_prefix('/')) {
+            matching_resources(&resource.subresources, Some(rest), ret);
+        } else if own.is_empty() {
+            matching_resources(&resource.subresources, Some(path), ret);
+        }
+    }
 }
 
-fn main() {
+fn matching_params<'a>(params: &'a [Param], name: Option<&str>) -> Vec<Node<'a>> {
+    params
+        .iter()
+        .filter(|p| name.is_none_or(|name| p.name == name))
+        .map(Node::Param)
+        .collect()
+}
+
+fn matching_methods<'a>(methods: &'a [Method], key: Option<&str>) -> Vec<Node<'a>> {
+    methods
+        .iter()
+        .filter(|m| key.is_none_or(|key| m.name.eq_ignore_ascii_case(key) || m.id == key))
+        .map(Node::Method)
+        .collect()
+}
+
+fn matching_representations<'a>(
+    representations: &'a [Representation],
+    key: Option<&str>,
+) -> Vec<Node<'a>> {
+    representations
+        .iter()
+        .filter(|r| {
+            key.is_none_or(|key| match r {
+                Representation::Reference(r) => r.id() == Some(key),
+                Representation::Definition(d) => {
+                    d.id.as_deref() == Some(key)
+                        || d.media_type
+                            .as_ref()
+                            .is_some_and(|m| m.essence_str() == key)
+                }
+            })
+        })
+        .map(Node::Representation)
+        .collect()
+}
+
+/// Return the children of a node with the given kind and key
+fn children<'a>(node: Node<'a>, kind: &str, key: Option<&str>) -> Result<Vec<Node<'a>>, String> {
+    let mut ret = vec![];
+    match (node, kind) {
+        (Node::Application(app), "resource_type") => ret.extend(
+            app.resource_types
+                .iter()
+                .filter(|rt| key.is_none_or(|key| rt.id == key))
+                .map(Node::ResourceType),
+        ),
+        (Node::Application(app), "resource") => {
+            for resources in &app.resources {
+                matching_resources(&resources.resources, key, &mut ret);
+            }
+        }
+        (Node::Application(app), "representation") => ret.extend(
+            app.representations
+                .iter()
+                .filter(|r| key.is_none_or(|key| r.id.as_deref() == Some(key)))
+                .map(Node::RepresentationDef),
+        ),
+        (Node::ResourceType(rt), "resource") => matching_resources(&rt.subresources, key, &mut ret),
+        (Node::Resource(r), "resource") => matching_resources(&r.subresources, key, &mut ret),
+        (Node::ResourceType(rt), "method") => ret = matching_methods(&rt.methods, key),
+        (Node::Resource(r), "method") => ret = matching_methods(&r.methods, key),
+        (Node::ResourceType(rt), "param") => ret = matching_params(&rt.params, key),
+        (Node::Resource(r), "param") => ret = matching_params(&r.params, key),
+        (Node::Method(m), "request") => ret.push(Node::Request(&m.request)),
+        (Node::Method(m), "response") => ret.extend(
+            m.responses
+                .iter()
+                .filter(|r| {
+                    key.is_none_or(|key| r.status.map(|s| s.to_string()).as_deref() == Some(key))
+                })
+                .map(Node::Response),
+        ),
+        (Node::Method(m), "param") => ret = matching_params(&m.request.params, key),
+        (Node::Request(r), "param") => ret = matching_params(&r.params, key),
+        (Node::Response(r), "param") => ret = matching_params(&r.params, key),
+        (Node::Request(r), "representation") => {
+            ret = matching_representations(&r.representations, key)
+        }
+        (Node::Response(r), "representation") => {
+            ret = matching_representations(&r.representations, key)
+        }
+        (Node::Representation(Representation::Definition(d)), "param")
+        | (Node::RepresentationDef(d), "param") => ret = matching_params(&d.params, key),
+        (_, kind) => return Err(format!("{} can not be selected here", kind)),
+    }
+    Ok(ret)
+}
+
+fn select<'a>(app: &'a Application, selection: &str) -> Result<Vec<Node<'a>>, String> {
+    let mut nodes = vec![Node::Application(app)];
+    for (kind, key) in parse_selection(selection)? {
+        let mut next = vec![];
+        for node in nodes {
+            next.extend(children(node, kind, key)?);
+        }
+        nodes = next;
+    }
+    Ok(nodes)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let app =
+        wadl::parse_file(&args.input).map_err(|e| format!("{}: {}", args.input.display(), e))?;
+
+    match args.select.as_deref() {
+        None => print(&app, args.format),
+        Some(selection) => {
+            let nodes = select(&app, selection)?;
+            match nodes.as_slice() {
+                [] => Err(format!("nothing matches {}", selection)),
+                [node] => print(node, args.format),
+                // Several matches are printed as a list, so that the output is one document
+                nodes => print(&nodes, args.format),
+            }
+        }
+    }
+}
+
+fn main() -> std::process::ExitCode {
     env_logger::init();
-    let input = Args::parse().input;
+    match run(Args::parse()) {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(
+            parse_selection("resource_type:person/method:GET").unwrap(),
+            [("resource_type", Some("person")), ("method", Some("GET"))]
+        );
+        // Slashes that are not followed by a kind are part of the key
+        assert_eq!(
+            parse_selection("resource:people/{name}/param:name").unwrap(),
+            [("resource", Some("people/{name}")), ("param", Some("name"))]
+        );
+        assert_eq!(
+            parse_selection("resource:people/methods").unwrap(),
+            [("resource", Some("people/methods"))]
+        );
+        assert_eq!(
+            parse_selection("resource_type/method/request/param").unwrap(),
+            [
+                ("resource_type", None),
+                ("method", None),
+                ("request", None),
+                ("param", None)
+            ]
+        );
+        assert_eq!(
+            parse_selection("representation:").unwrap(),
+            [("representation", Some(""))]
+        );
+        assert_eq!(
+            parse_selection("widget:x").unwrap_err(),
+            "unknown kind \"widget\", expected one of resource_type, resource, method, request, \
+             response, representation, param"
+        );
+        assert!(parse_selection("").is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let app = wadl::parse_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/people-wadl.xml"
+        ))
+        .unwrap();
+        let ids = |selection: &str| {
+            select(&app, selection)
+                .unwrap()
+                .into_iter()
+                .map(|node| match node {
+                    Node::ResourceType(rt) => rt.id.clone(),
+                    Node::Resource(r) => r.path.clone().unwrap_or_default(),
+                    Node::Method(m) => m.id.clone(),
+                    Node::Response(r) => format!("{:?}", r.status),
+                    Node::RepresentationDef(r) => r.id.clone().unwrap_or_default(),
+                    Node::Param(p) => p.name.clone(),
+                    node => format!("{:?}", node),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids("resource_type:person/method:GET"), ["person-get"]);
+        assert_eq!(
+            ids("resource_type:person/method:person-patch"),
+            ["person-patch"]
+        );
+        assert_eq!(
+            ids("resource_type/method:get"),
+            ["service-root-get", "people-find", "person-get"]
+        );
+        assert_eq!(ids("resource:people/{name}"), ["{name}"]);
+        assert_eq!(ids("resource:/people/{name}/param:name"), ["name"]);
+        assert_eq!(ids("resource:people/nobody"), Vec::<String>::new());
+        assert_eq!(
+            ids("resource_type:people/method:POST/response:201/param"),
+            ["Location"]
+        );
+        assert_eq!(
+            ids("resource_type:people/method:people-find/request/param:text"),
+            ["text"]
+        );
+        assert_eq!(
+            ids("representation:person-page/param:total_size"),
+            ["total_size"]
+        );
 
-    let app: wadl::ast::Application = wadl::parse_file(input).unwrap();
+        assert_eq!(
+            select(&app, "method:GET").unwrap_err(),
+            "method can not be selected here"
+        );
+        assert_eq!(
+            select(&app, "resource_type:person/request").unwrap_err(),
+            "request can not be selected here"
+        );
+    }
 
-    println!("{:#?}", app);
+    #[test]
+    fn test_several_matches_are_one_document() {
+        let app = wadl::parse_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/people-wadl.xml"
+        ))
+        .unwrap();
+        let nodes = select(&app, "resource_type/method").unwrap();
+        let json = serde_json::to_value(&nodes).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 5);
+        assert_eq!(json[1]["id"], "people-find");
+    }
 }
@@ -1,26 +1,1612 @@
 /// Generate rust code from wadl
 use clap::Parser;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Exit code for a WADL document that can not be parsed (`EX_DATAERR`)
+const EXIT_PARSE: u8 = 65;
+
+/// Exit code for a failure to read, write or fetch a file (`EX_IOERR`)
+const EXIT_IO: u8 = 74;
+
+/// Exit code for an invalid configuration, or generated code that is not valid Rust
+/// (`EX_CONFIG`)
+const EXIT_VALIDATION: u8 = 78;
 
 #[derive(Parser)]
+#[clap(
+    after_help = "Exit codes: 1 if `wadlc lint` reports errors, `wadlc --check` finds \
+generated code that is out of date or `wadlc invoke` gets an error response, 65 if an input document can not be parsed, 74 if a file can not be read, written or fetched, and 78 if the configuration or \
+the generated code is invalid.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// WADL files or URLs to generate code for; the applications they describe are merged
+    ///
+    /// OpenAPI 3 and Swagger 2 documents are imported, as with `wadlc import`.
+    #[clap(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// File to write the code to, or a directory to write `mod.rs` in (default: stdout)
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+
+    /// Directory to write `mod.rs` and a file per module to (implies `--header`)
+    ///
+    /// Without `[modules]` in the configuration, each resource type is placed in a module of
+    /// its own. Files generated by previous runs that are no longer generated are removed.
+    #[clap(long, value_name = "DIR", conflicts_with = "output")]
+    out_dir: Option<PathBuf>,
+
+    /// Keep WADL downloaded from URLs next to the output, and only download it again if its
+    /// ETag changed
+    #[clap(long)]
+    cache: bool,
+
+    /// Code generation configuration, in TOML or JSON format (e.g. `wadl-codegen.toml`)
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// Generate async code, rather than blocking code
+    #[clap(long = "async", conflicts_with = "both")]
+    r#async: bool,
+
+    /// Generate blocking code only, even if the configuration asks for async code
+    #[clap(long, conflicts_with_all = ["async", "both"])]
+    blocking: bool,
+
+    /// Generate both blocking and async code
+    #[clap(long)]
+    both: bool,
+
+    /// Generate server stubs for a web framework rather than a client
+    ///
+    /// With an output directory, `mod.rs` has the router and handler traits and `types.rs`
+    /// the representation types, so the directory can be used as a module of a server crate.
+    #[clap(long, value_enum, value_name = "FRAMEWORK", conflicts_with_all = ["async", "both"])]
+    server: Option<ServerFramework>,
+
+    /// Format the generated code with prettyplease (the default) or rustfmt
+    ///
+    /// rustfmt is run from `$RUSTFMT` if set, and picks up `rustfmt.toml` from the current
+    /// directory.
+    #[clap(
+        long,
+        value_enum,
+        value_name = "FORMATTER",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "prettyplease"
+    )]
+    format: Option<Formatter>,
+
+    /// Emit a header recording the version of wadlc and the hash of the input
+    #[clap(long)]
+    header: bool,
+
+    /// Allow a lint in the whole generated file (implies `--header`)
+    #[clap(long = "allow", value_name = "LINT")]
+    allow: Vec<String>,
+
+    /// Keep running, and generate the code again whenever an input file or the configuration
+    /// changes
+    #[clap(long, conflicts_with = "check")]
+    watch: bool,
+
+    /// Don't write the code, but check that the output is up to date with it
+    ///
+    /// Prints a unified diff of the files that differ.
+    #[clap(long)]
+    check: bool,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Write a standalone WADL document, with the definitions that it refers to in other
+    /// documents copied into it
+    Resolve(ResolveArgs),
+
+    /// Convert a WADL document to another API description format
+    Convert(ConvertArgs),
+
+    /// Write a WADL document describing the API of an OpenAPI 3 or Swagger 2 document
+    Import(ImportArgs),
+
+    /// Print statistics about the definitions in a WADL document and their documentation
+    Stats(StatsArgs),
+
+    /// Check a WADL document for common problems, such as methods without ids or params
+    /// without docs
+    Lint(LintArgs),
+
+    /// Send the request for a method, built from its description in a WADL document, and
+    /// print the response
+    Invoke(InvokeArgs),
+}
+
+#[derive(clap::Args)]
+struct ResolveArgs {
+    /// WADL file or URL to resolve
+    input: PathBuf,
+
+    /// File to write the WADL to (default: stdout)
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+
+    /// Also copy the definitions of resource types into the resources of those types
+    ///
+    /// Resource types that are not the target of links are removed, so no code is generated
+    /// for them.
+    #[clap(long)]
+    flatten: bool,
+}
+
+#[derive(clap::Args)]
+struct ConvertArgs {
+    /// Format to convert to
+    #[clap(long, value_enum)]
+    to: ConvertFormat,
+
+    /// WADL file or URL to convert
+    input: PathBuf,
+
+    /// File to write to; written as JSON if it ends in `.json`, YAML otherwise
+    /// (default: YAML on stdout)
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct ImportArgs {
+    /// OpenAPI or Swagger file or URL, in YAML or JSON, to import
     input: PathBuf,
+
+    /// File to write the WADL to (default: stdout)
+    #[clap(short, long)]
     output: Option<PathBuf>,
 }
 
-fn main() {
+#[derive(clap::Args)]
+struct StatsArgs {
+    /// WADL file or URL to print statistics for
+    input: PathBuf,
+}
+
+#[derive(clap::Args)]
+struct LintArgs {
+    /// WADL file or URL to check
+    #[clap(required_unless_present = "list_rules")]
+    input: Option<PathBuf>,
+
+    /// Run a rule, optionally with a severity of `info`, `warning` or `error`
+    /// (default: the severity of the rule)
+    #[clap(long, value_name = "RULE[=SEVERITY]", value_parser = parse_rule_severity)]
+    enable: Vec<(wadl::lint::Rule, Option<wadl::lint::Severity>)>,
+
+    /// Don't run a rule
+    #[clap(long, value_name = "RULE")]
+    disable: Vec<wadl::lint::Rule>,
+
+    /// Only run the rules passed to `--enable`
+    #[clap(long)]
+    no_default_rules: bool,
+
+    /// List the rules, with their default severities
+    #[clap(long)]
+    list_rules: bool,
+}
+
+#[derive(clap::Args)]
+struct InvokeArgs {
+    /// WADL file or URL describing the API
+    input: PathBuf,
+
+    /// Id of the method to invoke, or its HTTP method and resource path (e.g.
+    /// `"GET /people/{id}"`) if the id is used by several resources
+    method: String,
+
+    /// Value of a param; repeat for params that can have several values
+    ///
+    /// Template, matrix, query and header params are placed in the request as their style
+    /// says, and plain params of form representations are sent as the request body.
+    #[clap(short, long = "param", value_name = "NAME=VALUE", value_parser = parse_param)]
+    params: Vec<(String, String)>,
+
+    /// File to send as the request body, or `-` for stdin, with the media type of the
+    /// request representation of the method
+    #[clap(long, value_name = "FILE")]
+    body: Option<PathBuf>,
+
+    /// Header to send with the request
+    #[clap(short = 'H', long = "header", value_name = "NAME: VALUE", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+
+    /// URL that resource paths are resolved against (default: the base in the WADL)
+    #[clap(long, value_name = "URL")]
+    base_url: Option<url::Url>,
+
+    /// Bearer token to authenticate with
+    #[clap(long, env = "WADL_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// User name and password to authenticate with using HTTP basic authentication
+    #[clap(
+        long,
+        value_name = "USER[:PASSWORD]",
+        env = "WADL_USER",
+        hide_env_values = true,
+        conflicts_with = "token"
+    )]
+    user: Option<String>,
+
+    /// Print the status and headers of the response before its body
+    #[clap(short, long)]
+    include: bool,
+}
+
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got {:?}", s))
+}
+
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    s.split_once(':')
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .ok_or_else(|| format!("expected NAME: VALUE, got {:?}", s))
+}
+
+fn parse_rule_severity(
+    s: &str,
+) -> Result<(wadl::lint::Rule, Option<wadl::lint::Severity>), String> {
+    match s.split_once('=') {
+        Some((rule, severity)) => Ok((rule.parse()?, Some(severity.parse()?))),
+        None => Ok((s.parse()?, None)),
+    }
+}
+
+/// The formats that WADL documents can be converted to
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ConvertFormat {
+    /// OpenAPI 3
+    Openapi3,
+}
+
+/// The web frameworks that server stubs can be generated for
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ServerFramework {
+    /// axum
+    Axum,
+}
+
+/// The formatters that generated code can be run through
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Formatter {
+    /// Format with prettyplease, which is built in
+    Prettyplease,
+    /// Format with an external rustfmt binary
+    Rustfmt,
+}
+
+impl Formatter {
+    /// Format generated code
+    fn format(self, code: &str, config: &wadl::codegen::Config) -> Result<String, Failure> {
+        match self {
+            Formatter::Prettyplease => wadl::codegen::format_generated(code, config).map_err(|e| {
+                Failure::Validation(format!("generated code is not valid Rust: {}", e))
+            }),
+            Formatter::Rustfmt => rustfmt(code),
+        }
+    }
+}
+
+/// A failure of wadlc, which determines its exit code
+#[derive(Debug)]
+enum Failure {
+    /// A WADL document could not be parsed
+    Parse(PathBuf, wadl::ParseError),
+
+    /// An OpenAPI document could not be imported
+    Import(PathBuf, wadl::openapi::ImportError),
+
+    /// A file could not be read, written or fetched
+    Io(String),
+
+    /// The configuration or the generated code is invalid
+    Validation(String),
+
+    /// Linting reported this many findings with error severity
+    Lint(usize),
+
+    /// This many generated files are out of date, see [`Args::check`]
+    OutOfDate(usize),
+
+    /// The server responded to a request with an error status, see [`Command::Invoke`]
+    #[cfg_attr(not(feature = "blocking"), allow(dead_code))]
+    Status(u16),
+}
+
+impl Failure {
+    /// Return a failure for an I/O error on a path
+    fn io(path: &Path, err: impl std::fmt::Display) -> Self {
+        Failure::Io(format!("{}: {}", path.display(), err))
+    }
+
+    fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Failure::Parse(..) | Failure::Import(..) => EXIT_PARSE,
+            Failure::Io(_) => EXIT_IO,
+            Failure::Validation(_) => EXIT_VALIDATION,
+            Failure::Lint(_) | Failure::OutOfDate(_) | Failure::Status(_) => 1,
+        })
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Failure::Parse(path, err) => write!(f, "{}: {}", path.display(), err),
+            Failure::Import(path, err) => write!(f, "{}: {}", path.display(), err),
+            Failure::Io(msg) | Failure::Validation(msg) => f.write_str(msg),
+            Failure::Lint(count) => write!(f, "{} problem(s) with error severity", count),
+            Failure::OutOfDate(count) => write!(f, "{} generated file(s) out of date", count),
+            Failure::Status(status) => write!(f, "request failed with status {}", status),
+        }
+    }
+}
+
+/// Run code through rustfmt
+fn rustfmt(code: &str) -> Result<String, Failure> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let rustfmt = PathBuf::from(std::env::var_os("RUSTFMT").unwrap_or_else(|| "rustfmt".into()));
+    let mut child = Command::new(&rustfmt)
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| Failure::io(&rustfmt, e))?;
+    let mut stdin = child.stdin.take().unwrap();
+    let code = code.to_string();
+    // Write from another thread, so that rustfmt can't block on a full stdout pipe
+    let writer = std::thread::spawn(move || stdin.write_all(code.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| Failure::io(&rustfmt, e))?;
+    // rustfmt may exit without reading all of its input if it is invalid
+    let _ = writer.join().unwrap();
+    if !output.status.success() {
+        return Err(Failure::Validation(format!(
+            "generated code could not be formatted by rustfmt: {}",
+            output.status
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|e| Failure::io(&rustfmt, e))
+}
+
+fn main() -> ExitCode {
     env_logger::init();
-    let input = Args::parse().input;
-    let output = Args::parse().output;
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("error: {}", failure);
+            failure.exit_code()
+        }
+    }
+}
+
+fn run(mut args: Args) -> Result<(), Failure> {
+    match args.command.take() {
+        Some(Command::Resolve(args)) => return resolve(args),
+        Some(Command::Convert(args)) => return convert(args),
+        Some(Command::Import(args)) => return import(args),
+        Some(Command::Stats(args)) => return stats(args),
+        Some(Command::Lint(args)) => return lint(args),
+        Some(Command::Invoke(args)) => return invoke(args),
+        None => {}
+    }
+
+    // Support the older `wadlc input.wadl output.rs` form
+    if args.output.is_none()
+        && args.inputs.len() > 1
+        && args.inputs.last().unwrap().extension() == Some("rs".as_ref())
+    {
+        args.output = args.inputs.pop();
+    }
 
-    let input: wadl::ast::Application = wadl::parse_file(input).unwrap();
+    if args.watch {
+        return watch(&args);
+    }
+    generate(&args)
+}
 
-    let code = wadl::codegen::generate(&input, &wadl::codegen::Config::default());
+/// Generate code for the inputs, see [`Args`]
+fn generate(args: &Args) -> Result<(), Failure> {
+    let cache_dir = args.cache.then(|| match (&args.out_dir, &args.output) {
+        (Some(dir), _) => dir.clone(),
+        (None, Some(output)) if is_dir_output(output) => output.clone(),
+        (None, Some(output)) => output.parent().map(Path::to_path_buf).unwrap_or_default(),
+        (None, None) => PathBuf::new(),
+    });
+
+    let mut contents = Vec::new();
+    let mut input: Option<wadl::ast::Application> = None;
+    for path in &args.inputs {
+        let data = read_input(path, cache_dir.as_deref())?;
+        let application = parse_input(path, &data)?;
+        match input.as_mut() {
+            Some(input) => input.merge(application),
+            None => input = Some(application),
+        }
+        contents.extend(data);
+    }
+    let input = input.unwrap();
+
+    let mut config = match args.config.as_deref() {
+        Some(path) => match wadl::codegen::ConfigFile::load(path) {
+            Ok(file) => file.into(),
+            Err(wadl::codegen::ConfigFileError::Io(e)) => return Err(Failure::io(path, e)),
+            Err(e) => return Err(Failure::Validation(format!("{}: {}", path.display(), e))),
+        },
+        None => wadl::codegen::Config::default(),
+    };
+
+    if args.r#async || args.blocking {
+        config.r#async = args.r#async;
+        config.blocking_and_async = false;
+    }
+    if args.both {
+        config.blocking_and_async = true;
+    }
+
+    if args.out_dir.is_some() && config.module_path.is_none() {
+        config.module_path = Some(Box::new(|kind, id| {
+            (kind == wadl::codegen::ItemKind::ResourceType)
+                .then(|| wadl::codegen::snake_case_name(id))
+        }));
+    }
+
+    if args.header || !args.allow.is_empty() || args.out_dir.is_some() {
+        let source = args
+            .inputs
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut header = wadl::codegen::FileHeader::for_source(&source, &contents);
+        header.allow = args.allow.clone();
+        config.header = Some(header);
+    }
+
+    if args.server.is_none() {
+        config
+            .validate(&input)
+            .map_err(|e| Failure::Validation(e.to_string()))?;
+    }
+
+    let out_dir = match (&args.out_dir, &args.output) {
+        (Some(out_dir), _) => Some(out_dir.as_path()),
+        // Server stubs are always split into the files of a module
+        (None, Some(output)) if args.server.is_some() && is_dir_output(output) => {
+            Some(output.as_path())
+        }
+        (None, _) => None,
+    };
+
+    if let Some(out_dir) = out_dir {
+        let mut files = match args.server {
+            Some(ServerFramework::Axum) => wadl::codegen::generate_server_files(&input, &config),
+            None => wadl::codegen::generate_files(&input, &config),
+        };
+        if let Some(formatter) = args.format {
+            for code in files.values_mut() {
+                *code = formatter.format(code, &config)?;
+            }
+        }
+        if args.check {
+            return check_files(out_dir, &files);
+        }
+        return write_files(out_dir, &files).map_err(|e| Failure::io(out_dir, e));
+    }
+
+    let mut code = match args.server {
+        Some(ServerFramework::Axum) => {
+            wadl::codegen::generate_server_types(&input, &config)
+                + &wadl::codegen::generate_server(&input, &config)
+        }
+        None => wadl::codegen::generate(&input, &config),
+    };
+    if let Some(formatter) = args.format {
+        code = formatter.format(&code, &config)?;
+    }
+
+    if args.check {
+        let Some(mut output) = args.output.clone() else {
+            return Err(Failure::Validation(
+                "--check requires --output or --out-dir".to_string(),
+            ));
+        };
+        if is_dir_output(&output) {
+            output.push("mod.rs");
+        }
+        return match diff_file(&output, Some(&code))? {
+            Some(diff) => {
+                print!("{}", diff);
+                Err(Failure::OutOfDate(1))
+            }
+            None => Ok(()),
+        };
+    }
 
     // If output isn't specified, write to stdout
-    if let Some(output) = output {
-        std::fs::write(output, code).unwrap();
+    if let Some(mut output) = args.output.clone() {
+        if is_dir_output(&output) {
+            std::fs::create_dir_all(&output).map_err(|e| Failure::io(&output, e))?;
+            output.push("mod.rs");
+        }
+        std::fs::write(&output, code).map_err(|e| Failure::io(&output, e))?;
     } else {
         println!("{}", code);
     }
+    Ok(())
+}
+
+/// Generate code whenever an input file or the configuration changes, see [`Args::watch`]
+///
+/// The directories containing the files are watched rather than the files themselves, since
+/// editors often replace a file rather than writing to it.
+fn watch(args: &Args) -> Result<(), Failure> {
+    use notify::{RecursiveMode, Watcher};
+
+    if args.output.is_none() && args.out_dir.is_none() {
+        return Err(Failure::Validation(
+            "--watch requires --output or --out-dir".to_string(),
+        ));
+    }
+
+    let files = args
+        .inputs
+        .iter()
+        .filter(|path| input_url(path).is_none())
+        .chain(args.config.as_ref())
+        .map(|path| std::path::absolute(path).map_err(|e| Failure::io(path, e)))
+        .collect::<Result<std::collections::HashSet<_>, _>>()?;
+    if files.is_empty() {
+        return Err(Failure::Validation(
+            "nothing to watch, since all inputs are URLs and there is no --config".to_string(),
+        ));
+    }
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).map_err(|e| Failure::Io(e.to_string()))?;
+    let dirs = files
+        .iter()
+        .filter_map(|path| path.parent())
+        .collect::<std::collections::BTreeSet<_>>();
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| Failure::io(dir, e))?;
+    }
+
+    let regenerate = || match generate(args) {
+        Ok(()) => eprintln!("generated code"),
+        Err(failure) => eprintln!("error: {}", failure),
+    };
+    regenerate();
+    loop {
+        let event = receiver
+            .recv()
+            .map_err(|e| Failure::Io(e.to_string()))?
+            .map_err(|e| Failure::Io(e.to_string()))?;
+        if event.kind.is_access() || !event.paths.iter().any(|p| files.contains(p)) {
+            continue;
+        }
+        // Saving a file often causes several events; wait for them to settle
+        while receiver
+            .recv_timeout(std::time::Duration::from_millis(100))
+            .is_ok()
+        {}
+        regenerate();
+    }
+}
+
+/// Write a standalone WADL document, see [`Command::Resolve`]
+fn resolve(args: ResolveArgs) -> Result<(), Failure> {
+    let data = read_input(&args.input, None)?;
+    let mut app = wadl::parse_bytes(&data).map_err(|e| Failure::Parse(args.input.clone(), e))?;
+
+    let url = match input_url(&args.input) {
+        Some(url) => url,
+        None => std::env::current_dir()
+            .ok()
+            .and_then(|dir| url::Url::from_file_path(dir.join(&args.input)).ok())
+            .ok_or_else(|| Failure::io(&args.input, "unable to determine the URL"))?,
+    };
+    app.resolve_references(&url, |url| {
+        log::info!("Loading {}", url);
+        let data = match url.to_file_path() {
+            Ok(path) if url.scheme() == "file" => {
+                std::fs::read(&path).map_err(|e| Failure::io(&path, e))?
+            }
+            _ => fetch_wadl(url, None)?,
+        };
+        wadl::parse_bytes(&data).map_err(|e| Failure::Parse(url.as_str().into(), e))
+    })?;
+    if args.flatten {
+        app.flatten_resource_types();
+    }
+
+    let xml = wadl::write_string(&app);
+    match args.output {
+        Some(output) => std::fs::write(&output, xml).map_err(|e| Failure::io(&output, e)),
+        None => {
+            print!("{}", xml);
+            Ok(())
+        }
+    }
+}
+
+/// Convert a WADL document to another format, see [`Command::Convert`]
+fn convert(args: ConvertArgs) -> Result<(), Failure> {
+    let data = read_input(&args.input, None)?;
+    let app = parse_input(&args.input, &data)?;
+
+    let spec = match args.to {
+        ConvertFormat::Openapi3 => wadl::openapi::convert(&app),
+    };
+    let json = args
+        .output
+        .as_ref()
+        .is_some_and(|output| output.extension() == Some("json".as_ref()));
+    let text = if json {
+        serde_json::to_string_pretty(&spec)
+            .map(|text| text + "\n")
+            .map_err(|e| e.to_string())
+    } else {
+        serde_yaml::to_string(&spec).map_err(|e| e.to_string())
+    }
+    .map_err(Failure::Validation)?;
+    match args.output {
+        Some(output) => std::fs::write(&output, text).map_err(|e| Failure::io(&output, e)),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+/// Write a WADL document for an OpenAPI document, see [`Command::Import`]
+fn import(args: ImportArgs) -> Result<(), Failure> {
+    let data = read_input(&args.input, None)?;
+    let app = import_openapi(&args.input, &data)?;
+
+    let xml = wadl::write_string(&app);
+    match args.output {
+        Some(output) => std::fs::write(&output, xml).map_err(|e| Failure::io(&output, e)),
+        None => {
+            print!("{}", xml);
+            Ok(())
+        }
+    }
+}
+
+/// Print statistics about a WADL document, see [`Command::Stats`]
+fn stats(args: StatsArgs) -> Result<(), Failure> {
+    let data = read_input(&args.input, None)?;
+    let stats = parse_input(&args.input, &data)?.stats();
+
+    println!("Resources: {}", stats.resources);
+    println!("Resource types: {}", stats.resource_types.total);
+    println!("Methods: {}", stats.methods.total);
+    println!("Params: {}", stats.params.total);
+    println!(
+        "Representations: {} ({} inline)",
+        stats.representations.total + stats.inline_representations,
+        stats.inline_representations
+    );
+
+    if !stats.media_types.is_empty() {
+        println!("\nMedia types:");
+        for (media_type, count) in &stats.media_types {
+            println!("  {}: {}", media_type, count);
+        }
+    }
+
+    let kinds = [
+        ("resource types", &stats.resource_types),
+        ("methods", &stats.methods),
+        ("params", &stats.params),
+        ("representations", &stats.representations),
+    ];
+    println!("\nDoc coverage: {:.0}%", stats.doc_coverage() * 100.0);
+    for (kind, coverage) in kinds {
+        println!(
+            "  {}: {} of {}",
+            kind,
+            coverage.documented(),
+            coverage.total
+        );
+    }
+    for (kind, coverage) in kinds {
+        if !coverage.undocumented.is_empty() {
+            println!("\nUndocumented {}:", kind);
+            for name in &coverage.undocumented {
+                println!("  {}", name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check a WADL document for common problems, see [`Command::Lint`]
+fn lint(args: LintArgs) -> Result<(), Failure> {
+    use wadl::lint::{Linter, Rule, Severity};
+
+    if args.list_rules {
+        for rule in Rule::ALL {
+            println!(
+                "{} ({}): {}",
+                rule,
+                rule.default_severity(),
+                rule.description()
+            );
+        }
+        return Ok(());
+    }
+
+    let mut linter = if args.no_default_rules {
+        Linter::none()
+    } else {
+        Linter::default()
+    };
+    for (rule, severity) in args.enable {
+        linter.enable(rule, severity.unwrap_or_else(|| rule.default_severity()));
+    }
+    for rule in args.disable {
+        linter.disable(rule);
+    }
+
+    let input = args.input.unwrap();
+    let data = read_input(&input, None)?;
+    let findings = linter.lint(&parse_input(&input, &data)?);
+    for finding in &findings {
+        println!("{}", finding);
+    }
+    match findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .count()
+    {
+        0 => Ok(()),
+        errors => Err(Failure::Lint(errors)),
+    }
+}
+
+/// A method of a resource with a known path, and the params that apply to it
+#[cfg(feature = "blocking")]
+#[derive(Debug)]
+struct Endpoint<'a> {
+    base: Option<&'a url::Url>,
+    path: String,
+    method: &'a wadl::ast::Method,
+    params: Vec<&'a wadl::ast::Param>,
+}
+
+/// Return the methods of resources with their paths, including the methods of their
+/// resource types
+#[cfg(feature = "blocking")]
+fn endpoints<'a>(
+    app: &'a wadl::ast::Application,
+    base: Option<&'a url::Url>,
+    prefix: &str,
+    inherited: &[&'a wadl::ast::Param],
+    resources: &'a [wadl::ast::Resource],
+    types_seen: &mut Vec<&'a str>,
+) -> Vec<Endpoint<'a>> {
+    let mut ret = vec![];
+    for resource in resources {
+        let path = match resource.path.as_deref() {
+            Some(path) => format!(
+                "{}/{}",
+                prefix.trim_end_matches('/'),
+                path.trim_start_matches('/')
+            ),
+            None => prefix.to_string(),
+        };
+        // Resource types that contain themselves would otherwise be expanded forever
+        let types = resource
+            .r#type
+            .iter()
+            .filter_map(|rt| app.get_resource_type_by_id(rt.id()?))
+            .filter(|rt| !types_seen.contains(&rt.id.as_str()))
+            .collect::<Vec<_>>();
+        let mut params = inherited.to_vec();
+        params.extend(&resource.params);
+        params.extend(types.iter().flat_map(|rt| &rt.params));
+
+        let methods = resource
+            .methods
+            .iter()
+            .chain(types.iter().flat_map(|rt| &rt.methods));
+        for method in methods {
+            let mut params = params.clone();
+            params.extend(&method.request.params);
+            ret.push(Endpoint {
+                base,
+                path: path.clone(),
+                method,
+                params,
+            });
+        }
+
+        let depth = types_seen.len();
+        types_seen.extend(types.iter().map(|rt| rt.id.as_str()));
+        let subresources = std::iter::once(&resource.subresources[..])
+            .chain(types.iter().map(|rt| &rt.subresources[..]));
+        for subresources in subresources {
+            ret.extend(endpoints(
+                app,
+                base,
+                &path,
+                &params,
+                subresources,
+                types_seen,
+            ));
+        }
+        types_seen.truncate(depth);
+    }
+    ret
+}
+
+/// Find the endpoint of a method, given its id or its HTTP method and resource path (e.g.
+/// `GET /people/{id}`)
+#[cfg(feature = "blocking")]
+fn find_endpoint<'a>(endpoints: Vec<Endpoint<'a>>, method: &str) -> Result<Endpoint<'a>, String> {
+    let by_id = endpoints
+        .iter()
+        .any(|endpoint| endpoint.method.id == method);
+    let mut found = endpoints
+        .into_iter()
+        .filter(|endpoint| {
+            if by_id {
+                return endpoint.method.id == method;
+            }
+            method.split_once(' ').is_some_and(|(name, path)| {
+                endpoint.method.name.eq_ignore_ascii_case(name)
+                    && endpoint.path.trim_matches('/') == path.trim().trim_matches('/')
+            })
+        })
+        .collect::<Vec<_>>();
+    match found.len() {
+        0 => Err(format!(
+            "no method {:?} in a resource with a known path",
+            method
+        )),
+        1 => Ok(found.remove(0)),
+        _ => Err(format!(
+            "method {:?} is ambiguous, since it is at {}; pick one with e.g. \"{} {}\"",
+            method,
+            found
+                .iter()
+                .map(|endpoint| endpoint.path.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            found[0].method.name,
+            found[0].path
+        )),
+    }
+}
+
+/// Percent-encode a value for use as a path segment
+#[cfg(feature = "blocking")]
+fn encode_path_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Send the request for a method and print the response, see [`Command::Invoke`]
+#[cfg(feature = "blocking")]
+fn invoke(args: InvokeArgs) -> Result<(), Failure> {
+    use std::io::Write;
+    use wadl::ast::{ParamStyle, Representation};
+    use wadl::blocking::Client;
+
+    let data = read_input(&args.input, None)?;
+    let app = parse_input(&args.input, &data)?;
+    let endpoints = app
+        .resources
+        .iter()
+        .flat_map(|r| endpoints(&app, r.base.as_ref(), "", &[], &r.resources, &mut vec![]))
+        .collect();
+    let endpoint = find_endpoint(endpoints, &args.method)
+        .map_err(|e| Failure::Validation(format!("{}: {}", args.input.display(), e)))?;
+
+    // The params of form representations of the request are sent as the request body
+    let form = endpoint
+        .method
+        .request
+        .representations
+        .iter()
+        .filter_map(|r| match r {
+            Representation::Definition(def) => Some(def),
+            Representation::Reference(r) => app
+                .representations
+                .iter()
+                .find(|def| def.id.is_some() && def.id.as_deref() == r.id()),
+        })
+        .find(|def| {
+            def.media_type
+                .as_ref()
+                .is_some_and(|m| m.essence_str() == "application/x-www-form-urlencoded")
+        });
+    let mut params = endpoint.params.clone();
+    if args.body.is_none() {
+        params.extend(form.iter().flat_map(|def| &def.params));
+    }
+
+    if let Some((name, _)) = args
+        .params
+        .iter()
+        .find(|(name, _)| !params.iter().any(|p| &p.name == name))
+    {
+        return Err(Failure::Validation(format!(
+            "method {} has no param {:?}; it has {}",
+            args.method,
+            name,
+            params
+                .iter()
+                .filter(|p| p.fixed.is_none())
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    let mut path = endpoint.path.trim_start_matches('/').to_string();
+    let mut query = vec![];
+    let mut headers = vec![];
+    let mut fields = vec![];
+    for param in params {
+        let mut values = match param.fixed.as_deref() {
+            Some(fixed) => vec![fixed],
+            None => args
+                .params
+                .iter()
+                .filter(|(name, _)| name == &param.name)
+                .map(|(_, value)| value.as_str())
+                .collect(),
+        };
+        if values.is_empty() {
+            match param.default.as_deref() {
+                Some(default) if param.style == ParamStyle::Template => values.push(default),
+                _ if param.required || param.style == ParamStyle::Template => {
+                    return Err(Failure::Validation(format!(
+                        "missing value for param {}",
+                        param.name
+                    )))
+                }
+                _ => continue,
+            }
+        }
+        for value in values {
+            match param.style {
+                ParamStyle::Template => {
+                    path =
+                        path.replace(&format!("{{{}}}", param.name), &encode_path_segment(value));
+                }
+                ParamStyle::Matrix => {
+                    path.push_str(&format!(";{}={}", param.name, encode_path_segment(value)))
+                }
+                ParamStyle::Query => query.push((param.name.as_str(), value)),
+                ParamStyle::Header => headers.push((param.name.as_str(), value)),
+                ParamStyle::Plain => fields.push((param.name.as_str(), value)),
+            }
+        }
+    }
+
+    let base = args.base_url.as_ref().or(endpoint.base).ok_or_else(|| {
+        Failure::Validation("the WADL has no base URL; pass --base-url".to_string())
+    })?;
+    let mut base = base.clone();
+    if !base.path().ends_with('/') {
+        base.set_path(&format!("{}/", base.path()));
+    }
+    let mut url = base
+        .join(&path)
+        .map_err(|e| Failure::Validation(format!("{}{}: {}", base, path, e)))?;
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+
+    let mut client = wadl::blocking::WadlClient::new(
+        wadl::blocking::client_builder()
+            .and_then(|builder| Ok(builder.build()?))
+            .map_err(|e| Failure::Io(e.to_string()))?,
+    );
+    if let Some(token) = args.token {
+        client = client.with_auth(wadl::Auth::Bearer(token));
+    } else if let Some(user) = args.user {
+        let (username, password) = match user.split_once(':') {
+            Some((username, password)) => (username.to_string(), Some(password.to_string())),
+            None => (user, None),
+        };
+        client = client.with_auth(wadl::Auth::Basic { username, password });
+    }
+
+    let method = endpoint.method.http_method().ok_or_else(|| {
+        Failure::Validation(format!("unsupported HTTP method {}", endpoint.method.name))
+    })?;
+    let mut req = client.request(method, url.clone());
+    for (name, value) in headers
+        .into_iter()
+        .chain(args.headers.iter().map(|(n, v)| (n.as_str(), v.as_str())))
+    {
+        req = req.header(name, value);
+    }
+    if let Some(path) = args.body.as_ref() {
+        let body = if path.as_os_str() == "-" {
+            let mut body = vec![];
+            std::io::Read::read_to_end(&mut std::io::stdin(), &mut body)
+                .map_err(|e| Failure::io(path, e))?;
+            body
+        } else {
+            std::fs::read(path).map_err(|e| Failure::io(path, e))?
+        };
+        let media_type = endpoint
+            .method
+            .request
+            .representations
+            .iter()
+            .find_map(|r| match r {
+                Representation::Definition(def) => def.media_type.as_ref(),
+                Representation::Reference(r) => app
+                    .representations
+                    .iter()
+                    .find(|def| def.id.is_some() && def.id.as_deref() == r.id())
+                    .and_then(|def| def.media_type.as_ref()),
+            });
+        if let Some(media_type) = media_type.filter(|_| {
+            !args
+                .headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        }) {
+            req = req.header(reqwest::header::CONTENT_TYPE, media_type.as_ref());
+        }
+        req = req.body(body);
+    } else if !fields.is_empty() {
+        req = req.form(&fields);
+    }
+
+    let resp = client
+        .send(req)
+        .map_err(|e| Failure::Io(format!("{}: {}", url, e)))?;
+    let status = resp.status();
+    let mut stdout = std::io::stdout().lock();
+    let stdout_failure = |e: std::io::Error| Failure::Io(format!("stdout: {}", e));
+    if args.include {
+        writeln!(stdout, "{:?} {}", resp.version(), status).map_err(stdout_failure)?;
+        for (name, value) in resp.headers() {
+            writeln!(stdout, "{}: {}", name, value.to_str().unwrap_or("<binary>"))
+                .map_err(stdout_failure)?;
+        }
+        writeln!(stdout).map_err(stdout_failure)?;
+    }
+    let body = resp
+        .bytes()
+        .map_err(|e| Failure::Io(format!("{}: {}", url, e)))?;
+    stdout.write_all(&body).map_err(stdout_failure)?;
+    if !body.is_empty() && !body.ends_with(b"\n") {
+        writeln!(stdout).map_err(stdout_failure)?;
+    }
+    if !status.is_success() {
+        return Err(Failure::Status(status.as_u16()));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "blocking"))]
+fn invoke(_args: InvokeArgs) -> Result<(), Failure> {
+    Err(Failure::Io(
+        "invoking methods requires the blocking feature".to_string(),
+    ))
+}
+
+fn import_openapi(path: &Path, data: &[u8]) -> Result<wadl::ast::Application, Failure> {
+    let text = std::str::from_utf8(data).map_err(|e| Failure::io(path, e))?;
+    wadl::openapi::import_str(text).map_err(|e| Failure::Import(path.to_path_buf(), e))
+}
+
+/// Parse an input, importing it if it is an OpenAPI document rather than WADL
+fn parse_input(path: &Path, data: &[u8]) -> Result<wadl::ast::Application, Failure> {
+    if data.trim_ascii_start().starts_with(b"<") {
+        wadl::parse_bytes(data).map_err(|e| Failure::Parse(path.to_path_buf(), e))
+    } else {
+        import_openapi(path, data)
+    }
+}
+
+/// Read an input, which is either a path or a URL
+fn read_input(path: &Path, cache_dir: Option<&Path>) -> Result<Vec<u8>, Failure> {
+    match input_url(path) {
+        Some(url) => fetch_wadl(&url, cache_dir),
+        None => std::fs::read(path).map_err(|e| Failure::io(path, e)),
+    }
+}
+
+/// Return whether the output is a directory to write `mod.rs` in
+fn is_dir_output(output: &Path) -> bool {
+    output.is_dir() || output.as_os_str().to_string_lossy().ends_with('/')
+}
+
+/// Return the URL of an input, if it is one rather than a path
+fn input_url(path: &Path) -> Option<url::Url> {
+    let url = url::Url::parse(path.to_str()?).ok()?;
+    matches!(url.scheme(), "http" | "https").then_some(url)
+}
+
+/// Return the name of the file that WADL downloaded from a URL is cached in
+#[cfg(feature = "blocking")]
+fn cache_file_name(url: &url::Url) -> String {
+    let mut parts = vec![url.host_str().unwrap_or("wadl")];
+    parts.extend(url.path_segments().into_iter().flatten());
+    let name = parts
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    format!("{}.wadl", name.trim_end_matches(".wadl"))
+}
+
+/// Download WADL, following redirects
+///
+/// If `cache_dir` is given, the WADL is stored there along with its ETag, which is sent
+/// along with later downloads so that the cached copy is used if the WADL did not change.
+#[cfg(feature = "blocking")]
+fn fetch_wadl(url: &url::Url, cache_dir: Option<&Path>) -> Result<Vec<u8>, Failure> {
+    use reqwest::header::{ACCEPT, ETAG, IF_NONE_MATCH};
+
+    let fetch_failure = |e: &dyn std::fmt::Display| Failure::Io(format!("{}: {}", url, e));
+
+    let cache = cache_dir.map(|dir| dir.join(cache_file_name(url)));
+    let etag_path = cache.as_ref().map(|cache| {
+        let mut path = cache.clone().into_os_string();
+        path.push(".etag");
+        PathBuf::from(path)
+    });
+
+    let client = wadl::blocking::client_builder()
+        .map_err(|e| fetch_failure(&e))?
+        .build()
+        .map_err(|e| fetch_failure(&e))?;
+    let mut req = client.get(url.clone()).header(ACCEPT, wadl::WADL_MIME_TYPE);
+    if let Some((cache, etag_path)) = cache.as_ref().zip(etag_path.as_ref()) {
+        if let (true, Ok(etag)) = (cache.exists(), std::fs::read_to_string(etag_path)) {
+            req = req.header(IF_NONE_MATCH, etag.trim());
+        }
+    }
+
+    let resp = req.send().map_err(|e| fetch_failure(&e))?;
+    if resp.url() != url {
+        log::info!("{} redirected to {}", url, resp.url());
+    }
+    if let (reqwest::StatusCode::NOT_MODIFIED, Some(cache)) = (resp.status(), cache.as_ref()) {
+        log::info!("{} not modified, using {}", url, cache.display());
+        return std::fs::read(cache).map_err(|e| Failure::io(cache, e));
+    }
+    let resp = resp.error_for_status().map_err(|e| fetch_failure(&e))?;
+    let etag = resp
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(|etag| etag.to_string());
+    let data = resp.bytes().map_err(|e| fetch_failure(&e))?.to_vec();
+
+    if let Some((cache, etag_path)) = cache.zip(etag_path) {
+        if let Some(parent) = cache.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Failure::io(parent, e))?;
+        }
+        std::fs::write(&cache, &data).map_err(|e| Failure::io(&cache, e))?;
+        match etag {
+            Some(etag) => std::fs::write(&etag_path, format!("{}\n", etag))
+                .map_err(|e| Failure::io(&etag_path, e))?,
+            None => {
+                let _ = std::fs::remove_file(etag_path);
+            }
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(not(feature = "blocking"))]
+fn fetch_wadl(url: &url::Url, _cache_dir: Option<&Path>) -> Result<Vec<u8>, Failure> {
+    Err(Failure::Io(format!(
+        "{}: fetching WADL requires the blocking feature",
+        url
+    )))
+}
+
+/// Write generated files to a directory, removing files generated by previous runs that are
+/// not generated anymore
+fn write_files(dir: &Path, files: &BTreeMap<PathBuf, String>) -> std::io::Result<()> {
+    for (path, code) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, code)?;
+    }
+    for path in stale_files(dir, Path::new(""), files)? {
+        log::info!("Removing stale file {}", dir.join(&path).display());
+        std::fs::remove_file(dir.join(&path))?;
+        // Remove the directories that are left empty
+        for parent in path.ancestors().skip(1) {
+            if parent.as_os_str().is_empty()
+                || std::fs::read_dir(dir.join(parent))?.next().is_some()
+            {
+                break;
+            }
+            std::fs::remove_dir(dir.join(parent))?;
+        }
+    }
+    Ok(())
+}
+
+/// Return the generated files in a directory that are not in `files`, relative to `root`
+///
+/// Files are recognized as generated by their header, so that other files are left alone.
+fn stale_files(
+    root: &Path,
+    dir: &Path,
+    files: &BTreeMap<PathBuf, String>,
+) -> std::io::Result<Vec<PathBuf>> {
+    let mut stale = vec![];
+    for entry in std::fs::read_dir(root.join(dir))? {
+        let entry = entry?;
+        let path = dir.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            stale.extend(stale_files(root, &path, files)?);
+        } else if path.extension() == Some("rs".as_ref())
+            && !files.contains_key(&path)
+            && std::fs::read_to_string(entry.path())
+                .is_ok_and(|code| wadl::codegen::FileHeader::parse(&code).is_some())
+        {
+            stale.push(path);
+        }
+    }
+    stale.sort();
+    Ok(stale)
+}
+
+/// Check that the files in a directory match the generated files, see [`Args::check`]
+fn check_files(dir: &Path, files: &BTreeMap<PathBuf, String>) -> Result<(), Failure> {
+    let mut out_of_date = 0;
+    for (path, code) in files {
+        if let Some(diff) = diff_file(&dir.join(path), Some(code))? {
+            print!("{}", diff);
+            out_of_date += 1;
+        }
+    }
+    let stale = match stale_files(dir, Path::new(""), files) {
+        Ok(stale) => stale,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(Failure::io(dir, e)),
+    };
+    for path in stale {
+        if let Some(diff) = diff_file(&dir.join(path), None)? {
+            print!("{}", diff);
+            out_of_date += 1;
+        }
+    }
+    match out_of_date {
+        0 => Ok(()),
+        count => Err(Failure::OutOfDate(count)),
+    }
+}
+
+/// Return a unified diff from the contents of a file to the code that should be in it, or to
+/// nothing if the file should not exist
+///
+/// Returns `None` if they do not differ.
+fn diff_file(path: &Path, code: Option<&str>) -> Result<Option<String>, Failure> {
+    let current = match std::fs::read_to_string(path) {
+        Ok(current) => Some(current),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(Failure::io(path, e)),
+    };
+    if current.as_deref() == code {
+        return Ok(None);
+    }
+    let name = path.display().to_string();
+    let diff = similar::TextDiff::from_lines(
+        current.as_deref().unwrap_or_default(),
+        code.unwrap_or_default(),
+    );
+    Ok(Some(
+        diff.unified_diff()
+            .header(
+                if current.is_some() {
+                    &name
+                } else {
+                    "/dev/null"
+                },
+                if code.is_some() { &name } else { "/dev/null" },
+            )
+            .to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty directory for a test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wadlc-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn generated(code: &str) -> String {
+        wadl::codegen::FileHeader::new().render() + code
+    }
+
+    #[test]
+    fn test_write_files_removes_stale_files() {
+        let dir = test_dir("stale");
+        let out_dir = dir.join("out");
+        std::fs::create_dir_all(out_dir.join("old/nested")).unwrap();
+        std::fs::write(out_dir.join("gone.rs"), generated("struct Gone;\n")).unwrap();
+        std::fs::write(out_dir.join("old/nested/gone.rs"), generated("")).unwrap();
+
+        let files = maplit::btreemap! {
+            PathBuf::from("mod.rs") => generated("pub mod people;\n"),
+            PathBuf::from("people.rs") => generated("struct Person;\n"),
+        };
+        write_files(&out_dir, &files).unwrap();
+
+        assert!(!out_dir.join("gone.rs").exists());
+        // Directories that are left empty are removed too
+        assert!(!out_dir.join("old").exists());
+        assert_eq!(
+            std::fs::read_to_string(out_dir.join("people.rs")).unwrap(),
+            files[Path::new("people.rs")]
+        );
+        assert!(stale_files(&out_dir, Path::new(""), &files)
+            .unwrap()
+            .is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_files_keeps_other_files() {
+        let dir = test_dir("keep");
+        let out_dir = dir.join("out");
+        std::fs::create_dir_all(out_dir.join("extra")).unwrap();
+        std::fs::write(out_dir.join("hand_written.rs"), "struct Mine;\n").unwrap();
+        std::fs::write(out_dir.join("extra/helpers.rs"), "fn helper() {}\n").unwrap();
+        // Only Rust files are generated
+        std::fs::write(out_dir.join("notes.txt"), generated("")).unwrap();
+        // Files next to the output directory are never looked at
+        std::fs::write(dir.join("sibling.rs"), generated("")).unwrap();
+        std::fs::create_dir_all(dir.join("other")).unwrap();
+        std::fs::write(dir.join("other/gone.rs"), generated("")).unwrap();
+
+        let files = maplit::btreemap! {
+            PathBuf::from("mod.rs") => generated(""),
+        };
+        write_files(&out_dir, &files).unwrap();
+
+        for path in [
+            out_dir.join("hand_written.rs"),
+            out_dir.join("extra/helpers.rs"),
+            out_dir.join("notes.txt"),
+            dir.join("sibling.rs"),
+            dir.join("other/gone.rs"),
+        ] {
+            assert!(path.exists(), "{} was removed", path.display());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_files_does_not_follow_symlinks() {
+        let dir = test_dir("symlink");
+        let out_dir = dir.join("out");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        std::fs::create_dir_all(dir.join("elsewhere")).unwrap();
+        std::fs::write(dir.join("elsewhere/gone.rs"), generated("")).unwrap();
+        std::os::unix::fs::symlink(dir.join("elsewhere"), out_dir.join("linked")).unwrap();
+
+        write_files(&out_dir, &BTreeMap::new()).unwrap();
+
+        assert!(dir.join("elsewhere/gone.rs").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watch_urls_only() {
+        let args = Args::parse_from([
+            "wadlc",
+            "--watch",
+            "https://api.example.com/app.wadl",
+            "-o",
+            "generated.rs",
+        ]);
+        let failure = watch(&args).unwrap_err();
+        assert_eq!(
+            failure.to_string(),
+            "nothing to watch, since all inputs are URLs and there is no --config"
+        );
+        assert_eq!(failure.exit_code(), ExitCode::from(EXIT_VALIDATION));
+    }
+
+    const PEOPLE_WADL: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/people-wadl.xml");
+
+    #[test]
+    fn test_check_out_of_date() {
+        let dir = test_dir("check-stale");
+        let output = dir.join("people.rs");
+        std::fs::write(&output, "struct Old;\n").unwrap();
+
+        let args = Args::parse_from(["wadlc", PEOPLE_WADL, "-o", output.to_str().unwrap()]);
+        let code = wadl::codegen::generate(
+            &parse_input(Path::new(PEOPLE_WADL), &std::fs::read(PEOPLE_WADL).unwrap()).unwrap(),
+            &Default::default(),
+        );
+        let diff = diff_file(&output, Some(&code)).unwrap().unwrap();
+        assert!(
+            diff.starts_with(&format!("--- {0}\n+++ {0}\n", output.display())),
+            "{}",
+            diff
+        );
+        assert!(diff.contains("\n-struct Old;\n"), "{}", diff);
+
+        let failure = generate(&Args {
+            check: true,
+            ..args
+        })
+        .unwrap_err();
+        assert!(matches!(failure, Failure::OutOfDate(1)), "{}", failure);
+        assert_eq!(failure.exit_code(), ExitCode::from(1));
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "struct Old;\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_check_up_to_date() {
+        let dir = test_dir("check-current");
+        let out_dir = dir.join("generated");
+        let args = |check: &[&str]| {
+            let mut argv = vec!["wadlc", PEOPLE_WADL, "--out-dir", out_dir.to_str().unwrap()];
+            argv.extend(check);
+            Args::parse_from(argv)
+        };
+        generate(&args(&[])).unwrap();
+        let written = std::fs::read_dir(&out_dir)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path(), entry.metadata().unwrap().modified().unwrap())
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        generate(&args(&["--check"])).unwrap();
+
+        let checked = std::fs::read_dir(&out_dir)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path(), entry.metadata().unwrap().modified().unwrap())
+            })
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(written, checked);
+
+        // A generated file that would be removed is out of date too
+        std::fs::write(out_dir.join("gone.rs"), generated("")).unwrap();
+        let failure = generate(&args(&["--check"])).unwrap_err();
+        assert!(matches!(failure, Failure::OutOfDate(1)), "{}", failure);
+        assert!(out_dir.join("gone.rs").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "blocking")]
+    fn people_endpoints(app: &wadl::ast::Application) -> Vec<Endpoint<'_>> {
+        endpoints(
+            app,
+            app.resources[0].base.as_ref(),
+            "",
+            &[],
+            &app.resources[0].resources,
+            &mut vec![],
+        )
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_find_endpoint() {
+        let app = wadl::parse_string(
+            r##"<application xmlns="http://wadl.dev.java.net/2009/02">
+                <resources base="https://api.example.com/1.0/">
+                    <resource path="people">
+                        <method name="GET" id="people-find">
+                            <request><param name="text" style="query"/></request>
+                        </method>
+                        <resource path="{id}" type="#person">
+                            <param name="id" style="template" required="true"/>
+                        </resource>
+                    </resource>
+                    <resource path="/me/" type="#person"/>
+                </resources>
+                <resource_type id="person">
+                    <method name="GET" id="person-get"/>
+                    <resource path="friends" type="#person"/>
+                </resource_type>
+            </application>"##,
+        )
+        .unwrap();
+
+        let endpoint = find_endpoint(people_endpoints(&app), "people-find").unwrap();
+        assert_eq!(endpoint.path, "/people");
+        assert_eq!(
+            endpoint.base.unwrap().as_str(),
+            "https://api.example.com/1.0/"
+        );
+        assert_eq!(
+            endpoint.params.iter().map(|p| &p.name).collect::<Vec<_>>(),
+            ["text"]
+        );
+
+        // The resource type is used by two resources; the resource it contains of its own
+        // type is not expanded again
+        let err = find_endpoint(people_endpoints(&app), "person-get").unwrap_err();
+        assert_eq!(
+            err,
+            "method \"person-get\" is ambiguous, since it is at /people/{id}, /me/; pick one with e.g. \"GET /people/{id}\""
+        );
+        let endpoint = find_endpoint(people_endpoints(&app), "GET /people/{id}").unwrap();
+        assert_eq!(endpoint.method.id, "person-get");
+        assert_eq!(
+            endpoint.params.iter().map(|p| &p.name).collect::<Vec<_>>(),
+            ["id"]
+        );
+        let endpoint = find_endpoint(people_endpoints(&app), "get me").unwrap();
+        assert_eq!(endpoint.path, "/me/");
+
+        for method in ["person-delete", "POST /people", "GET /people/{id}/enemies"] {
+            assert_eq!(
+                find_endpoint(people_endpoints(&app), method).err(),
+                Some(format!(
+                    "no method {:?} in a resource with a known path",
+                    method
+                ))
+            );
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(encode_path_segment("jdoe"), "jdoe");
+        assert_eq!(encode_path_segment("a/b"), "a%2Fb");
+        assert_eq!(encode_path_segment("100%"), "100%25");
+        assert_eq!(encode_path_segment("John Doe"), "John%20Doe");
+        assert_eq!(encode_path_segment("~user_1.x-y"), "~user_1.x-y");
+        assert_eq!(encode_path_segment("café"), "caf%C3%A9");
+    }
 }
@@ -0,0 +1,320 @@
+//! Caching of parsed WADL documents
+//!
+//! An [`ApplicationCache`] keeps the most recently used WADL documents in memory, and optionally
+//! on disk, along with the validators the server sent for them. Documents are revalidated with a
+//! conditional GET every time they are fetched, so a cached document is only reused (without
+//! downloading or parsing it again) if the server responds with `304 Not Modified`.
+//!
+//! Set a cache on a `WadlClient` with `with_cache` to use it for
+//! `get_wadl_resource_by_href`, or fetch documents through it directly with
+//! `get_wadl_application`.
+
+use crate::ast::Application;
+use crate::Error;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// A cached WADL document, along with the validators the server sent for it
+#[derive(Debug, Clone)]
+pub struct CachedApplication {
+    /// The parsed document
+    pub application: Arc<Application>,
+
+    /// The value of the `ETag` header of the response, if any
+    pub etag: Option<String>,
+
+    /// The value of the `Last-Modified` header of the response, if any
+    pub last_modified: Option<String>,
+}
+
+impl CachedApplication {
+    /// Return the headers that make a request for the document conditional
+    pub fn conditional_headers(&self) -> Vec<(reqwest::header::HeaderName, String)> {
+        let mut headers = vec![];
+        if let Some(etag) = self.etag.as_ref() {
+            headers.push((reqwest::header::IF_NONE_MATCH, etag.clone()));
+        }
+        if let Some(last_modified) = self.last_modified.as_ref() {
+            headers.push((reqwest::header::IF_MODIFIED_SINCE, last_modified.clone()));
+        }
+        headers
+    }
+}
+
+/// A document as stored on disk
+///
+/// The AST can not be serialized, so the document is stored as it was fetched, and parsed
+/// again when it is loaded.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DiskEntry {
+    url: Url,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    wadl: String,
+}
+
+/// A cache of parsed WADL documents, keyed by URL
+///
+/// The least recently used documents are dropped from memory once the capacity is reached.
+/// Documents stored on disk are kept until they are replaced or removed.
+#[derive(Debug)]
+pub struct ApplicationCache {
+    capacity: usize,
+    directory: Option<PathBuf>,
+    // Ordered from least to most recently used
+    entries: Mutex<Vec<(Url, CachedApplication)>>,
+}
+
+impl Default for ApplicationCache {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+impl ApplicationCache {
+    /// Create an in-memory cache that holds at most `capacity` documents
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            directory: None,
+            entries: Mutex::new(vec![]),
+        }
+    }
+
+    /// Also store documents in a directory, so that they survive restarts
+    ///
+    /// The directory is created when the first document is stored. Failures to read or write
+    /// it are logged, rather than returned.
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Return the number of documents held in memory
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Return whether no documents are held in memory
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Look up the document at a URL, in memory and then on disk
+    pub fn get(&self, url: &Url) -> Option<CachedApplication> {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(index) = entries.iter().position(|(u, _)| u == url) {
+                let entry = entries.remove(index);
+                let cached = entry.1.clone();
+                entries.push(entry);
+                #[cfg(feature = "tracing")]
+                tracing::trace!(url = %url, "WADL cache hit in memory");
+                return Some(cached);
+            }
+        }
+        let cached = self.load(url);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(url = %url, hit = cached.is_some(), "WADL cache lookup on disk");
+        let cached = cached?;
+        self.remember(url, cached.clone());
+        Some(cached)
+    }
+
+    /// Parse and store the document fetched from a URL
+    ///
+    /// # Arguments
+    /// * `url` - The URL the document was fetched from
+    /// * `wadl` - The document
+    /// * `etag` - The value of the `ETag` header of the response, if any
+    /// * `last_modified` - The value of the `Last-Modified` header of the response, if any
+    pub fn insert(
+        &self,
+        url: &Url,
+        wadl: &str,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    ) -> Result<Arc<Application>, Error> {
+        let application = Arc::new(crate::parse_string(wadl)?);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(url = %url, size = wadl.len(), "storing WADL in cache");
+        if let Some(path) = self.path(url) {
+            let entry = DiskEntry {
+                url: url.clone(),
+                etag: etag.clone(),
+                last_modified: last_modified.clone(),
+                wadl: wadl.to_string(),
+            };
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, serde_json::to_vec(&entry)?));
+            if let Err(err) = written {
+                log::warn!("Unable to store {} in {}: {}", url, path.display(), err);
+            }
+        }
+        self.remember(
+            url,
+            CachedApplication {
+                application: application.clone(),
+                etag,
+                last_modified,
+            },
+        );
+        Ok(application)
+    }
+
+    /// Remove the document at a URL, from memory and from disk
+    pub fn remove(&self, url: &Url) {
+        self.entries.lock().unwrap().retain(|(u, _)| u != url);
+        if let Some(path) = self.path(url) {
+            if let Err(err) = std::fs::remove_file(&path) {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("Unable to remove {}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+
+    fn remember(&self, url: &Url, cached: CachedApplication) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(u, _)| u != url);
+        entries.push((url.clone(), cached));
+        let excess = entries.len().saturating_sub(self.capacity);
+        entries.drain(..excess);
+    }
+
+    /// Return the path of the file that the document at a URL is stored in, if any
+    ///
+    /// Files are named after the SHA-256 hash of the URL, since URLs can be longer than file
+    /// names may be. The URL is stored in the file, so that a collision is a cache miss.
+    fn path(&self, url: &Url) -> Option<PathBuf> {
+        let mut name = Sha256::digest(url.as_str())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        name.push_str(".json");
+        Some(self.directory.as_ref()?.join(name))
+    }
+
+    fn load(&self, url: &Url) -> Option<CachedApplication> {
+        let path = self.path(url)?;
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                log::warn!("Unable to read {}: {}", path.display(), err);
+                return None;
+            }
+        };
+        let entry = match serde_json::from_slice::<DiskEntry>(&data) {
+            Ok(entry) if &entry.url == url => entry,
+            Ok(_) => return None,
+            Err(err) => {
+                log::warn!("Unable to parse {}: {}", path.display(), err);
+                return None;
+            }
+        };
+        match crate::parse_string(&entry.wadl) {
+            Ok(application) => Some(CachedApplication {
+                application: Arc::new(application),
+                etag: entry.etag,
+                last_modified: entry.last_modified,
+            }),
+            Err(err) => {
+                log::warn!("Unable to parse the WADL in {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WADL: &str = r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+        <resources base="https://api.example.com/1.0/">
+            <resource path="people" id="people"/>
+        </resources>
+    </application>"#;
+
+    fn url(path: &str) -> Url {
+        format!("https://api.example.com/1.0/{}", path)
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_lru() {
+        let cache = ApplicationCache::new(2);
+        assert!(cache.get(&url("a")).is_none());
+        cache
+            .insert(&url("a"), WADL, Some("\"1\"".to_string()), None)
+            .unwrap();
+        cache.insert(&url("b"), WADL, None, None).unwrap();
+        assert_eq!(
+            cache.get(&url("a")).unwrap().conditional_headers(),
+            vec![(reqwest::header::IF_NONE_MATCH, "\"1\"".to_string())]
+        );
+        // "b" is now the least recently used
+        cache.insert(&url("c"), WADL, None, None).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&url("b")).is_none());
+        assert!(cache.get(&url("a")).is_some());
+
+        cache.remove(&url("a"));
+        assert!(cache.get(&url("a")).is_none());
+        assert!(cache.insert(&url("d"), "<not wadl", None, None).is_err());
+    }
+
+    #[test]
+    fn test_directory() {
+        let directory = std::env::temp_dir().join(format!("wadl-cache-{}", std::process::id()));
+        let cache = ApplicationCache::new(1).with_directory(&directory);
+        cache
+            .insert(
+                &url("a"),
+                WADL,
+                None,
+                Some("Sun, 06 Nov 1994 08:49:37 GMT".to_string()),
+            )
+            .unwrap();
+
+        // A new cache loads the document from disk
+        let cache = ApplicationCache::new(1).with_directory(&directory);
+        assert!(cache.is_empty());
+        let cached = cache.get(&url("a")).unwrap();
+        assert_eq!(
+            cached.last_modified.as_deref(),
+            Some("Sun, 06 Nov 1994 08:49:37 GMT")
+        );
+        assert!(cached
+            .application
+            .get_resource_by_href(&url("people"))
+            .is_some());
+        assert_eq!(cache.len(), 1);
+
+        cache.remove(&url("a"));
+        let cache = ApplicationCache::new(1).with_directory(&directory);
+        assert!(cache.get(&url("a")).is_none());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_directory_long_url() {
+        let directory =
+            std::env::temp_dir().join(format!("wadl-cache-long-{}", std::process::id()));
+        let long = url(&format!("people?{}", "ws.op=x&".repeat(40)));
+        assert!(long.as_str().len() > 255);
+        let cache = ApplicationCache::new(1).with_directory(&directory);
+        cache.insert(&long, WADL, None, None).unwrap();
+
+        let cache = ApplicationCache::new(1).with_directory(&directory);
+        assert!(cache.get(&long).is_some());
+        assert!(cache.get(&url("people")).is_none());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
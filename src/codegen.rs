@@ -1,7 +1,31 @@
 //! Generate Rust code from WADL files
 
 use crate::ast::*;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+mod build;
+mod config_builder;
+mod config_file;
+mod header;
+mod hook;
+mod server;
+#[cfg(feature = "templates")]
+mod templates;
+mod verify;
+pub use build::{BuildError, BuildHelper};
+pub use config_builder::ConfigBuilder;
+pub use config_file::{ConfigFile, ConfigFileError, VisibilityTable};
+pub use header::{source_hash, FileHeader};
+pub use hook::CodegenHook;
+pub use server::{generate_server, generate_server_files, generate_server_types};
+#[cfg(feature = "templates")]
+pub use templates::{
+    FieldContext, MethodContext, OptionsEnumContext, RepresentationContext, TemplateError,
+    TemplateKind, Templates, VariantContext,
+};
+pub use verify::{assert_golden, verify, CompileError, Verifier};
 
 /// MIME type for XHTML
 pub const XHTML_MIME_TYPE: &str = "application/xhtml+xml";
@@ -11,6 +35,7 @@ pub enum ParamContainer<'a> {
     Request(&'a Method, &'a Request),
     Response(&'a Method, &'a Response),
     Representation(&'a RepresentationDef),
+    Resource(&'a Resource),
 }
 
 /// Convert wadl names (with dashes) to camel-case Rust names
@@ -34,8 +59,50 @@ pub fn camel_case_name(name: &str) -> String {
     result
 }
 
+thread_local! {
+    /// Whether [`snake_case_name`] uses the conversion of older versions, see
+    /// [`Config::legacy_snake_case`]
+    static LEGACY_SNAKE_CASE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
 /// Convert wadl names (with dashes) to snake-case Rust names
+///
+/// Other characters that can't be part of an identifier, such as dots, are also replaced by
+/// underscores.
+///
+/// Runs of capitals are treated as acronyms, e.g. `XMLParser` becomes `xml_parser`.
 pub fn snake_case_name(name: &str) -> String {
+    if LEGACY_SNAKE_CASE.with(|l| l.get()) {
+        return legacy_snake_case_name(name);
+    }
+    let chars = name
+        .replace(|c: char| !c.is_alphanumeric() && c != '_', "_")
+        .chars()
+        .collect::<Vec<_>>();
+    let mut result = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let boundary = match i.checked_sub(1).map(|j| chars[j]) {
+                Some(prev) if prev.is_lowercase() || prev.is_ascii_digit() => true,
+                // The last capital of an acronym starts the next word
+                Some(prev) if prev.is_uppercase() => {
+                    chars.get(i + 1).is_some_and(|next| next.is_lowercase())
+                }
+                _ => false,
+            };
+            if boundary && !result.ends_with('_') {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(*c);
+        }
+    }
+    result
+}
+
+/// Convert wadl names to snake-case Rust names, the way older versions did
+fn legacy_snake_case_name(name: &str) -> String {
     let mut name = name.to_string();
     name = name.replace('-', "_");
     let it = name.chars().peekable();
@@ -56,6 +123,214 @@ pub fn snake_case_name(name: &str) -> String {
     result
 }
 
+/// Return the text content of an element, with whitespace collapsed
+fn element_text(element: &xmltree::Element) -> String {
+    let mut text = String::new();
+    fn collect(element: &xmltree::Element, text: &mut String) {
+        for child in &element.children {
+            match child {
+                xmltree::XMLNode::Text(t) | xmltree::XMLNode::CData(t) => {
+                    text.push_str(t);
+                    text.push(' ');
+                }
+                xmltree::XMLNode::Element(e) => collect(e, text),
+                _ => {}
+            }
+        }
+    }
+    collect(element, &mut text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Convert a HTML table to markdown
+///
+/// Simple tables are converted to markdown tables. Tables with merged cells or nested tables
+/// are rendered as a fenced text block, with the columns aligned.
+fn html_table_to_markdown(table: &xmltree::Element) -> String {
+    fn collect_rows<'a>(element: &'a xmltree::Element, rows: &mut Vec<&'a xmltree::Element>) {
+        for child in element.children.iter().filter_map(|c| c.as_element()) {
+            match child.name.as_str() {
+                "tr" => rows.push(child),
+                "thead" | "tbody" | "tfoot" => collect_rows(child, rows),
+                _ => {}
+            }
+        }
+    }
+    let mut trs = vec![];
+    collect_rows(table, &mut trs);
+
+    let mut simple = true;
+    let mut cells: Vec<Vec<String>> = vec![];
+    let mut header = false;
+    for (i, tr) in trs.iter().enumerate() {
+        let tds = tr
+            .children
+            .iter()
+            .filter_map(|c| c.as_element())
+            .filter(|c| c.name == "td" || c.name == "th")
+            .collect::<Vec<_>>();
+        if i == 0 {
+            header = tds.iter().all(|td| td.name == "th");
+        }
+        for td in &tds {
+            let merged = ["colspan", "rowspan"]
+                .iter()
+                .any(|attr| td.attributes.get(*attr).is_some_and(|v| v.trim() != "1"));
+            if merged || td.get_child("table").is_some() {
+                simple = false;
+            }
+        }
+        cells.push(tds.iter().map(|td| element_text(td)).collect());
+    }
+
+    let ncols = cells.iter().map(|r| r.len()).max().unwrap_or(0);
+    if cells.iter().any(|r| r.len() != ncols) {
+        simple = false;
+    }
+    for row in cells.iter_mut() {
+        row.resize(ncols, String::new());
+    }
+
+    let mut lines = vec![];
+    if simple && ncols > 0 {
+        let escape = |c: &str| c.replace('|', "\\|");
+        let mut rows = cells.iter();
+        let header_row = if header {
+            rows.next().unwrap().clone()
+        } else {
+            vec![String::new(); ncols]
+        };
+        lines.push(format!(
+            "| {} |",
+            header_row
+                .iter()
+                .map(|c| escape(c))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        ));
+        lines.push(format!("|{}|", vec!["---"; ncols].join("|")));
+        for row in rows {
+            lines.push(format!(
+                "| {} |",
+                row.iter()
+                    .map(|c| escape(c))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ));
+        }
+    } else {
+        let widths = (0..ncols)
+            .map(|i| {
+                cells
+                    .iter()
+                    .map(|r| r[i].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect::<Vec<_>>();
+        lines.push("```text".to_string());
+        for row in &cells {
+            lines.push(
+                row.iter()
+                    .zip(widths.iter())
+                    .map(|(c, w)| format!("{:w$}", c, w = w))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string(),
+            );
+        }
+        lines.push("```".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Convert XHTML to markdown, converting tables with [`html_table_to_markdown`]
+fn html_tables_to_markdown(html: &str) -> String {
+    let mut parts = vec![];
+    let mut rest = html;
+    while let Some(start) = rest.find("<table") {
+        // Find the matching end tag, taking nested tables into account
+        let mut depth = 0;
+        let mut pos = start;
+        let mut end = None;
+        while let Some(i) = rest[pos..].find("table") {
+            let i = pos + i;
+            if rest[..i].ends_with('<') {
+                depth += 1;
+            } else if rest[..i].ends_with("</") {
+                depth -= 1;
+                if depth == 0 {
+                    end = rest[i..].find('>').map(|j| i + j + 1);
+                    break;
+                }
+            }
+            pos = i + "table".len();
+        }
+        let Some(end) = end else {
+            break;
+        };
+        let before = html2md::parse_html(&rest[..start]);
+        if !before.trim().is_empty() {
+            parts.push(before.trim().to_string());
+        }
+        match xmltree::Element::parse(&rest.as_bytes()[start..end]) {
+            Ok(table) => parts.push(html_table_to_markdown(&table)),
+            Err(_) => parts.push(html2md::parse_html(&rest[start..end])),
+        }
+        rest = &rest[end..];
+    }
+    let after = html2md::parse_html(rest);
+    if !after.trim().is_empty() {
+        parts.push(after.trim().to_string());
+    }
+    parts.join("\n\n")
+}
+
+/// Resolve relative markdown link targets in a line against a base URL
+fn resolve_doc_links(line: &str, base: &url::Url) -> String {
+    let mut ret = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("](") {
+        let (before, after) = rest.split_at(start + 2);
+        ret.push_str(before);
+        let Some(end) = after.find(')') else {
+            rest = after;
+            continue;
+        };
+        let target = &after[..end];
+        match url::Url::parse(target) {
+            Err(url::ParseError::RelativeUrlWithoutBase) if !target.contains(' ') => {
+                match base.join(target) {
+                    Ok(url) => ret.push_str(url.as_str()),
+                    Err(_) => ret.push_str(target),
+                }
+            }
+            _ => ret.push_str(target),
+        }
+        rest = &after[end..];
+    }
+    ret.push_str(rest);
+    ret
+}
+
+/// Resolve relative links in the doc comments of generated code
+fn resolve_generated_doc_links(lines: Vec<String>, base: Option<&url::Url>) -> Vec<String> {
+    let Some(base) = base else {
+        return lines;
+    };
+    lines
+        .into_iter()
+        .map(|line| {
+            if line.trim_start().starts_with("//") {
+                resolve_doc_links(&line, base)
+            } else {
+                line
+            }
+        })
+        .collect()
+}
+
 fn strip_code_examples(input: String) -> String {
     let mut in_example = false;
     input
@@ -75,6 +350,305 @@ fn strip_code_examples(input: String) -> String {
         .join("\n")
 }
 
+thread_local! {
+    /// Overrides [`Config::r#async`] while generating one of the modules for
+    /// [`Config::blocking_and_async`]
+    static ASYNC_CLIENT_OVERRIDE: std::cell::Cell<Option<bool>> = const { std::cell::Cell::new(None) };
+}
+
+/// Run `f` with [`Config::r#async`] overridden
+fn with_async_client<R>(r#async: bool, f: impl FnOnce() -> R) -> R {
+    let previous = ASYNC_CLIENT_OVERRIDE.with(|o| o.replace(Some(r#async)));
+    let ret = f();
+    ASYNC_CLIENT_OVERRIDE.with(|o| o.set(previous));
+    ret
+}
+
+/// The media type and profile of a representation
+type MediaType = (Option<mime::Mime>, Option<String>);
+
+thread_local! {
+    /// The Rust names of the representations and resource types of the application that is
+    /// being generated, as resolved by [`resolve_type_names`]
+    static TYPE_NAMES: std::cell::RefCell<HashMap<(ItemKind, String), String>> =
+        std::cell::RefCell::new(HashMap::new());
+
+    /// The media types of the representations of the application that is being generated,
+    /// keyed by their id
+    static REPRESENTATION_MEDIA_TYPES: std::cell::RefCell<HashMap<String, MediaType>> =
+        std::cell::RefCell::new(HashMap::new());
+
+    /// The expressions for the URL of the next page of the page representations of the
+    /// application that is being generated, keyed by their Rust name, see
+    /// [`Config::pagination`]
+    static NEXT_PAGE_EXPRS: std::cell::RefCell<HashMap<String, String>> =
+        std::cell::RefCell::new(HashMap::new());
+
+    /// The XSD types and format strings of the custom date and time formats that are used by
+    /// the application that is being generated, see [`date_time_format_module`]
+    static DATE_TIME_FORMATS: std::cell::RefCell<Vec<(String, String)>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// The thread-local state of [`with_application`], restored when dropped
+///
+/// Dropping also happens when generating code panics, so that a caller that catches the panic
+/// does not see the state of another application.
+struct SavedApplicationState {
+    legacy_snake_case: bool,
+    type_names: HashMap<(ItemKind, String), String>,
+    media_types: HashMap<String, MediaType>,
+    next_page_exprs: HashMap<String, String>,
+    date_time_formats: Vec<(String, String)>,
+}
+
+impl SavedApplicationState {
+    /// Take the current state, leaving the defaults in its place
+    fn take() -> Self {
+        Self {
+            legacy_snake_case: LEGACY_SNAKE_CASE.with(|l| l.replace(false)),
+            type_names: TYPE_NAMES.with(|n| n.take()),
+            media_types: REPRESENTATION_MEDIA_TYPES.with(|m| m.take()),
+            next_page_exprs: NEXT_PAGE_EXPRS.with(|p| p.take()),
+            date_time_formats: DATE_TIME_FORMATS.with(|f| f.take()),
+        }
+    }
+}
+
+impl Drop for SavedApplicationState {
+    fn drop(&mut self) {
+        LEGACY_SNAKE_CASE.with(|l| l.set(self.legacy_snake_case));
+        TYPE_NAMES.with(|n| n.replace(std::mem::take(&mut self.type_names)));
+        REPRESENTATION_MEDIA_TYPES.with(|m| m.replace(std::mem::take(&mut self.media_types)));
+        NEXT_PAGE_EXPRS.with(|p| p.replace(std::mem::take(&mut self.next_page_exprs)));
+        DATE_TIME_FORMATS.with(|f| f.replace(std::mem::take(&mut self.date_time_formats)));
+    }
+}
+
+/// Run `f` with the names and media types of the items in `app` available
+fn with_application<R>(app: &Application, config: &Config, f: impl FnOnce() -> R) -> R {
+    let _saved = SavedApplicationState::take();
+    LEGACY_SNAKE_CASE.with(|l| l.set(config.legacy_snake_case));
+    let media_types = app
+        .representations
+        .iter()
+        .filter_map(|r| {
+            r.id.as_ref()
+                .map(|id| (id.clone(), (r.media_type.clone(), r.profile.clone())))
+        })
+        .collect();
+    TYPE_NAMES.with(|n| n.replace(resolve_type_names(app, config)));
+    REPRESENTATION_MEDIA_TYPES.with(|m| m.replace(media_types));
+    // Field names and types depend on the type names
+    let next_page_exprs = app
+        .representations
+        .iter()
+        .filter_map(|r| {
+            let pagination = config.pagination_for(r)?;
+            Some((
+                type_name(ItemKind::Representation, r.id.as_ref()?),
+                next_page_expr(r, &pagination, config),
+            ))
+        })
+        .collect();
+    NEXT_PAGE_EXPRS.with(|p| p.replace(next_page_exprs));
+    f()
+}
+
+/// Return the media type and profile of a representation
+///
+/// References to representations that are not defined in the application are assumed to be
+/// JSON.
+fn representation_media_type(representation: &Representation) -> (mime::Mime, Option<String>) {
+    let (media_type, profile) = match representation {
+        Representation::Definition(d) => (d.media_type.clone(), d.profile.clone()),
+        Representation::Reference(r) => r
+            .id()
+            .and_then(|id| REPRESENTATION_MEDIA_TYPES.with(|m| m.borrow().get(id).cloned()))
+            .unwrap_or_default(),
+    };
+    (media_type.unwrap_or(mime::APPLICATION_JSON), profile)
+}
+
+/// Return the media type of the representation with the given id, assuming JSON if unknown
+fn representation_id_media_type(id: &str) -> mime::Mime {
+    REPRESENTATION_MEDIA_TYPES
+        .with(|m| {
+            m.borrow()
+                .get(id)
+                .and_then(|(media_type, _)| media_type.clone())
+        })
+        .unwrap_or(mime::APPLICATION_JSON)
+}
+
+/// Return the Rust name of the item of the given kind with the given WADL id
+fn type_name(kind: ItemKind, id: &str) -> String {
+    TYPE_NAMES
+        .with(|n| n.borrow().get(&(kind, id.to_string())).cloned())
+        .unwrap_or_else(|| camel_case_name(id))
+}
+
+/// Return whether a Rust name is used for a representation or resource type
+fn type_name_taken(name: &str) -> bool {
+    TYPE_NAMES.with(|n| n.borrow().values().any(|v| v == name))
+}
+
+/// Determine the Rust names of all representations and resource types in the application
+///
+/// Different ids can map to the same name, e.g. `team_member` and `team-member`. Items are
+/// named in the order in which they are defined, representations first; an item whose name is
+/// already taken gets underscores appended until it is unique.
+fn resolve_type_names(app: &Application, config: &Config) -> HashMap<(ItemKind, String), String> {
+    let ids = app
+        .representations
+        .iter()
+        .filter_map(|r| r.id.as_deref())
+        .map(|id| (ItemKind::Representation, id))
+        .chain(
+            app.resource_types
+                .iter()
+                .map(|rt| (ItemKind::ResourceType, rt.id.as_str())),
+        );
+    let mut names: HashMap<(ItemKind, String), String> = HashMap::new();
+    for (kind, id) in ids {
+        if names.contains_key(&(kind, id.to_string())) {
+            continue;
+        }
+        let proposed = config
+            .type_renames
+            .get(id)
+            .cloned()
+            .or_else(|| config.type_name.as_ref().and_then(|f| f(kind, id)))
+            .unwrap_or_else(|| camel_case_name(id));
+        let mut name = proposed.clone();
+        while names.values().any(|v| v == &name) {
+            name.push('_');
+        }
+        if name != proposed {
+            log::warn!(
+                "Name {} for {} is already taken, using {} instead",
+                proposed,
+                id,
+                name
+            );
+        }
+        names.insert((kind, id.to_string()), name);
+    }
+    names
+}
+
+lazy_static::lazy_static! {
+    static ref METHOD_SIGNATURE: regex::Regex =
+        regex::Regex::new(r"^    (?:pub(?:\([^)]*\))? )?((?:async )?fn .*) \{\n$").unwrap();
+}
+
+/// Turn inherent methods into a trait that declares them, and an implementation of that trait
+///
+/// This is used for [`Config::blocking_and_async`], since the blocking and async variants of a
+/// method can not both be inherent methods of the same type.
+/// Return the attributes of a trait with the generated methods, and of its implementation
+///
+/// Without [`Syntax::ImplTraitInTrait`], async traits are implemented with `async_trait`, which
+/// is told not to require the futures to be `Send`, like async fn in traits.
+fn async_trait_attrs(config: &Config) -> (Vec<String>, Vec<String>) {
+    if !config.is_async() {
+        (vec![], vec![])
+    } else if config.supports(Syntax::ImplTraitInTrait) {
+        (vec!["#[allow(async_fn_in_trait)]\n".to_string()], vec![])
+    } else {
+        let attr = "#[wadl::r#async::async_trait(?Send)]\n".to_string();
+        (vec![attr.clone()], vec![attr])
+    }
+}
+
+fn methods_to_trait(
+    trait_name: &str,
+    type_name: &str,
+    doc: &str,
+    cfg: &[String],
+    methods: &[String],
+    config: &Config,
+) -> Vec<String> {
+    let mut decl = vec![];
+    let mut body = vec![];
+    let mut attrs: Vec<&str> = vec![];
+    let mut in_body = false;
+    for line in methods.iter().flat_map(|l| l.split_inclusive('\n')) {
+        if in_body {
+            body.push(line.to_string());
+            in_body = line != "    }\n";
+        } else if let Some(m) = METHOD_SIGNATURE.captures(line) {
+            decl.extend(attrs.iter().map(|a| a.to_string()));
+            decl.push(format!("    {};\n", &m[1]));
+            body.extend(
+                attrs
+                    .iter()
+                    .filter(|a| a.starts_with("    #[cfg") || a.starts_with("    #[allow"))
+                    .map(|a| a.to_string()),
+            );
+            body.push(format!("    {} {{\n", &m[1]));
+            attrs.clear();
+            in_body = true;
+        } else if line.trim().is_empty() {
+            decl.push(line.to_string());
+            body.push(line.to_string());
+        } else {
+            attrs.push(line);
+        }
+    }
+
+    let mut lines = vec![];
+    lines.extend(cfg.iter().cloned());
+    lines.push(format!("/// {} [`{}`]\n", doc, type_name));
+    let (trait_attrs, impl_attrs) = async_trait_attrs(config);
+    lines.extend(trait_attrs);
+    lines.push(format!(
+        "{}trait {} {{\n",
+        config
+            .item_visibility(ItemKind::Trait, trait_name)
+            .unwrap_or_default()
+            .prefix(),
+        trait_name
+    ));
+    lines.extend(decl);
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines.extend(cfg.iter().cloned());
+    lines.extend(impl_attrs);
+    lines.push(format!("impl {} for {} {{\n", trait_name, type_name));
+    lines.extend(body);
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+lazy_static::lazy_static! {
+    static ref DEPRECATION_MARKERS: Vec<regex::Regex> = [
+        r"\bDEPRECATED\b",
+        r"(?i)\bdeprecated:",
+        r"(?i)\b(?:this|the) (?:operation|method|field|attribute|parameter) is (?:deprecated|obsolete)\b",
+    ]
+    .iter()
+    .map(|re| regex::Regex::new(re).unwrap())
+    .collect();
+}
+
+/// Detect deprecation markers in documentation, using a set of common phrasings.
+///
+/// This recognizes e.g. "DEPRECATED: use foo instead" and "This operation is obsolete".
+///
+/// # Arguments
+/// * `text` - The documentation, in markdown.
+///
+/// # Returns
+/// The line containing the marker, to use as deprecation note, or `None` if the
+/// documentation does not mark the item as deprecated.
+pub fn default_deprecation_note(text: &str) -> Option<String> {
+    text.lines()
+        .find(|line| DEPRECATION_MARKERS.iter().any(|re| re.is_match(line)))
+        .map(|line| line.trim().to_string())
+}
+
 /// Format the given `Doc` object into a string.
 ///
 /// # Arguments
@@ -86,7 +660,11 @@ fn strip_code_examples(input: String) -> String {
 fn format_doc(input: &Doc, config: &Config) -> String {
     match input.xmlns.as_ref().map(|x| x.as_str()) {
         Some("http://www.w3.org/1999/xhtml") => {
-            let mut text = html2md::parse_html(&input.content);
+            let mut text = if config.markdown_tables {
+                html_tables_to_markdown(&input.content)
+            } else {
+                html2md::parse_html(&input.content)
+            };
             if config.strip_code_examples {
                 text = strip_code_examples(text);
             }
@@ -136,19 +714,32 @@ fn generate_resource_type_ref_accessors(
     field_name: &str,
     input: &ResourceTypeRef,
     param: &Param,
+    boxed: bool,
     config: &Config,
 ) -> Vec<String> {
     let mut lines = vec![];
     if let Some(id) = input.id() {
-        let deprecated = config
-            .deprecated_param
-            .as_ref()
-            .map(|x| x(param))
-            .unwrap_or(false);
-        if let Some(doc) = param.doc.as_ref() {
-            lines.extend(generate_doc(doc, 1, config));
-        }
-        let field_type = camel_case_name(id);
+        let deprecated = config.deprecated_attr(param.doc.as_ref(), 1).or_else(|| {
+            config
+                .deprecated_param
+                .as_ref()
+                .is_some_and(|x| x(param))
+                .then(|| "    #[deprecated]\n".to_string())
+        });
+        let field_type = type_name(ItemKind::ResourceType, id);
+        // Typed links convert from and to resources, rather than being URLs themselves
+        let (to_url, from_url) = if config.is_typed_link(input) {
+            ("url().clone()", ".into()")
+        } else {
+            ("clone()", "")
+        };
+        let cfg = cfg_line(
+            &config
+                .resource_type_feature(field_type.as_str())
+                .into_iter()
+                .collect::<Vec<_>>(),
+            1,
+        );
         let mut ret_type = field_type.to_string();
         let map_fn = if let Some((map_type, map_fn)) = config
             .map_type_for_accessor
@@ -171,12 +762,15 @@ fn generate_resource_type_ref_accessors(
         .unwrap_or_else(|| field_name.to_string());
 
         let visibility = config
-            .accessor_visibility
-            .as_ref()
-            .and_then(|x| x(accessor_name.as_str(), field_type.as_str()))
-            .unwrap_or_else(|| "pub".to_string());
-        if deprecated {
-            lines.push("    #[deprecated]".to_string());
+            .item_visibility(ItemKind::Accessor, accessor_name.as_str())
+            .unwrap_or_default()
+            .to_string();
+        lines.extend(cfg.clone());
+        if let Some(doc) = param.doc.as_ref() {
+            lines.extend(generate_doc(doc, 1, config));
+        }
+        if let Some(deprecated) = deprecated.as_ref() {
+            lines.push(deprecated.clone());
         }
         lines.push(format!(
             "    {}fn {}(&self) -> {} {{\n",
@@ -188,23 +782,27 @@ fn generate_resource_type_ref_accessors(
             accessor_name,
             ret_type
         ));
+        let field = if boxed {
+            format!("(*self.{})", field_name)
+        } else {
+            format!("self.{}", field_name)
+        };
         if param.required {
             if let Some(map_fn) = map_fn {
                 lines.push(format!(
-                    "        {}({}(self.{}.clone())\n",
-                    map_fn, field_type, field_name
+                    "        {}({}({}.{}))\n",
+                    map_fn, field_type, field, to_url
                 ));
             } else {
-                lines.push(format!(
-                    "        {}(self.{}.clone())\n",
-                    field_type, field_name
-                ));
+                lines.push(format!("        {}({}.{})\n", field_type, field, to_url));
             }
         } else {
             lines.push(format!(
-                "        self.{}.as_ref().map(|x| {}(x.clone())){}\n",
+                "        self.{}.{}().map(|x| {}(x.{})){}\n",
                 field_name,
+                if boxed { "as_deref" } else { "as_ref" },
                 field_type,
+                to_url,
                 if let Some(map_fn) = map_fn {
                     format!(".map({})", map_fn)
                 } else {
@@ -215,8 +813,9 @@ fn generate_resource_type_ref_accessors(
         lines.push("    }\n".to_string());
         lines.push("\n".to_string());
 
-        if deprecated {
-            lines.push("    #[deprecated]".to_string());
+        lines.extend(cfg);
+        if let Some(deprecated) = deprecated.as_ref() {
+            lines.push(deprecated.clone());
         }
 
         lines.push(format!(
@@ -226,19 +825,29 @@ fn generate_resource_type_ref_accessors(
             } else {
                 format!("{} ", visibility)
             },
-            accessor_name,
+            accessor_name.trim_start_matches("r#"),
             ret_type
         ));
 
-        if param.required {
+        if param.required && boxed {
             lines.push(format!(
-                "        self.{} = value.url().clone();\n",
-                field_name
+                "        self.{} = Box::new(value.url().clone(){});\n",
+                field_name, from_url
+            ));
+        } else if param.required {
+            lines.push(format!(
+                "        self.{} = value.url().clone(){};\n",
+                field_name, from_url
+            ));
+        } else if boxed {
+            lines.push(format!(
+                "        self.{} = value.map(|x| Box::new(x.url().clone(){}));\n",
+                field_name, from_url
             ));
         } else {
             lines.push(format!(
-                "        self.{} = value.map(|x| x.url().clone());\n",
-                field_name
+                "        self.{} = value.map(|x| x.url().clone(){});\n",
+                field_name, from_url
             ));
         }
         lines.push("    }\n".to_string());
@@ -255,1411 +864,7742 @@ fn generate_resource_type_ref_accessors(
     lines
 }
 
-fn generate_representation(
+/// Generate a method that fetches the resource a link with a relation points to
+///
+/// This is only possible if the linked resource type has a `GET` method without arguments.
+fn generate_link_navigation(
+    field_name: &str,
+    link: &Link,
+    param: &Param,
     input: &RepresentationDef,
+    resource_types: &[ResourceType],
     config: &Config,
     options_names: &HashMap<Options, String>,
 ) -> Vec<String> {
     let mut lines = vec![];
-    if input.media_type == Some(mime::APPLICATION_JSON) {
-        lines.extend(generate_representation_struct_json(
-            input,
-            config,
-            options_names,
-        ));
-    } else {
-        panic!("Unknown media type: {:?}", input.media_type);
-    }
-
-    let name = input.id.as_ref().unwrap().as_str();
-    let name = camel_case_name(name);
-
-    lines.push(format!("impl {} {{\n", name));
+    let (Some(relation), Some(id)) = (
+        link.relation.as_ref(),
+        link.resource_type.as_ref().and_then(|r| r.id()),
+    ) else {
+        return lines;
+    };
+    let Some(resource_type) = resource_types.iter().find(|rt| rt.id == id) else {
+        return lines;
+    };
+    let Some(method) = resource_type
+        .methods
+        .iter()
+        .find(|m| m.name == "GET" && method_args(m, config, options_names).is_empty())
+    else {
+        return lines;
+    };
 
-    for param in &input.params {
-        let field_name = snake_case_name(param.name.as_str());
-        // We expect to support multiple types here in the future
-        for link in &param.links {
-            if let Some(r) = link.resource_type.as_ref() {
-                lines.extend(generate_resource_type_ref_accessors(
-                    &field_name,
-                    r,
-                    param,
-                    config,
-                ));
-            }
-        }
+    let name = snake_case_name(relation)
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if name.is_empty()
+        || name.starts_with(|c: char| c.is_ascii_digit())
+        || ["self", "super", "crate"].contains(&name.as_str())
+        || input
+            .params
+            .iter()
+            .any(|p| field_rust_name(input, p, config) == name)
+    {
+        log::warn!(
+            "Not generating navigation method for link relation {:?}",
+            relation
+        );
+        return lines;
     }
 
-    lines.push("}\n".to_string());
-    lines.push("\n".to_string());
-
-    if let Some(generate) = config.generate_representation_traits.as_ref() {
-        lines.extend(generate(input, name.as_str(), input, config).unwrap_or(vec![]));
+    let type_name = type_name(ItemKind::ResourceType, id);
+    let method_name = method_rust_name(method, id, config);
+    let (mut ret_type, _map_fn) = method_return_type(method, &method_name, config, options_names);
+    if !param.required {
+        ret_type = format!("Option<{}>", ret_type);
     }
+    let call = format!(
+        "{}(url.{}).{}(client){}",
+        type_name,
+        if link
+            .resource_type
+            .as_ref()
+            .is_some_and(|r| config.is_typed_link(r))
+        {
+            "url().clone()"
+        } else {
+            "clone()"
+        },
+        method_name,
+        if config.is_async() { ".await" } else { "" }
+    );
 
-    lines
-}
-
-/// Generate the Rust type for a representation
-fn resource_type_rust_type(r: &ResourceTypeRef) -> String {
-    if let Some(id) = r.id() {
-        camel_case_name(id)
+    let mut features = config
+        .resource_type_feature(type_name.as_str())
+        .into_iter()
+        .collect::<Vec<_>>();
+    for feature in config.method_features(method, id) {
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+    }
+    lines.extend(cfg_line(&features, 1));
+    if let Some(doc) = link.doc.as_ref() {
+        lines.extend(generate_doc(doc, 1, config));
     } else {
-        "url::Url".to_string()
+        lines.push(format!(
+            "    /// Fetch the resource linked with the `{}` relation\n",
+            relation
+        ));
     }
+    lines.push(format!(
+        "    pub {}fn {}<'a>(&self, client: {}) -> std::result::Result<{}, wadl::Error> {{\n",
+        if config.is_async() { "async " } else { "" },
+        name,
+        config.client_arg_type(),
+        ret_type
+    ));
+    let boxed = config.is_boxed_field(input, param);
+    if param.required {
+        lines.push(format!(
+            "        let url = &{}self.{};\n",
+            if boxed { "*" } else { "" },
+            field_name
+        ));
+        lines.push(format!("        {}\n", call));
+    } else {
+        lines.push(format!(
+            "        match self.{}.{}() {{\n",
+            field_name,
+            if boxed { "as_deref" } else { "as_ref" }
+        ));
+        lines.push(format!("            Some(url) => Ok(Some({}?)),\n", call));
+        lines.push("            None => Ok(None),\n".to_string());
+        lines.push("        }\n".to_string());
+    }
+    lines.push("    }\n".to_string());
+    lines.push("\n".to_string());
+    lines
 }
 
-fn simple_type_rust_type(
-    container: &ParamContainer,
-    type_name: &str,
+/// Return whether a link refers to a representation, rather than to a resource type
+fn is_representation_link(
+    id: &str,
+    resource_types: &[ResourceType],
+    representations: &[RepresentationDef],
+) -> bool {
+    !resource_types.iter().any(|rt| rt.id == id)
+        && representations.iter().any(|r| r.id.as_deref() == Some(id))
+}
+
+/// Generate a method that fetches the representation a link param refers to
+fn generate_representation_link_fetch(
+    field_name: &str,
+    id: &str,
     param: &Param,
+    boxed: bool,
     config: &Config,
-) -> (String, Vec<String>) {
-    let tn = if let Some(override_name) = config.override_type_name.as_ref() {
-        override_name(container, type_name, param.name.as_str(), config)
+    constants: &Constants,
+) -> Vec<String> {
+    let mut lines = vec![];
+    let representation_type = type_name(ItemKind::Representation, id);
+    let ret_type = if param.required {
+        representation_type.clone()
     } else {
-        None
+        format!("Option<{}>", representation_type)
     };
-
-    if let Some(tn) = tn {
-        return (tn, vec![]);
+    let name = if let Some(rename_fn) = config.param_accessor_rename.as_ref() {
+        rename_fn(param.name.as_str(), ret_type.as_str())
+    } else {
+        None
     }
+    .unwrap_or_else(|| field_name.to_string());
 
-    match type_name.split_once(':').map_or(type_name, |(_, n)| n) {
-        "date" => ("chrono::NaiveDate".to_string(), vec![]),
-        "dateTime" => ("chrono::DateTime<chrono::Utc>".to_string(), vec![]),
-        "time" => ("(chrono::Time".to_string(), vec![]),
-        "int" => ("i32".to_string(), vec![]),
-        "string" => ("String".to_string(), vec![]),
-        "binary" => ("Vec<u8>".to_string(), vec![]),
-        u => panic!("Unknown type: {}", u),
+    if let Some(doc) = param.doc.as_ref() {
+        lines.extend(generate_doc(doc, 1, config));
+    } else {
+        lines.push(format!(
+            "    /// Fetch the [`{}`] that `{}` links to\n",
+            representation_type, field_name
+        ));
+    }
+    lines.push(format!(
+        "    pub {}fn {}<'a>(&self, client: {}) -> std::result::Result<{}, wadl::Error> {{\n",
+        if config.is_async() { "async " } else { "" },
+        name,
+        config.client_arg_type(),
+        ret_type
+    ));
+    let optional_url = format!(
+        "self.{}.{}()",
+        field_name,
+        if boxed { "as_deref().cloned" } else { "clone" }
+    );
+    if param.required && boxed {
+        lines.push(format!(
+            "        let url_ = (*self.{}).clone();\n",
+            field_name
+        ));
+    } else if param.required {
+        lines.push(format!("        let url_ = self.{}.clone();\n", field_name));
+    } else if config.supports(Syntax::LetElse) {
+        lines.push(format!(
+            "        let Some(url_) = {} else {{ return Ok(None); }};\n",
+            optional_url
+        ));
+    } else {
+        lines.push(format!(
+            "        let url_ = match {} {{ Some(url_) => url_, None => return Ok(None) }};\n",
+            optional_url
+        ));
+    }
+    lines.push(format!("        {}", new_request("GET", config)));
+    lines.push(format!(
+        "        req = req.header({}::header::ACCEPT, {});\n",
+        config.http_module(),
+        constants.media_type(representation_id_media_type(id).as_ref())
+    ));
+    lines.extend(indent(2, send_request(false, None, config).into_iter()));
+    let value = parse_json_response_expr(&representation_type, "&[]", config);
+    if param.required {
+        lines.push(format!("        {}\n", value));
+    } else {
+        lines.push(format!("        Ok(Some({}?))\n", value));
     }
+    lines.push("    }\n".to_string());
+    lines.push("\n".to_string());
+    lines
 }
 
-fn param_rust_type(
-    container: &ParamContainer,
-    param: &Param,
+/// Generate the navigation methods for all links with a relation in a representation
+fn generate_representation_link_navigation(
+    input: &RepresentationDef,
+    resource_types: &[ResourceType],
+    representations: &[RepresentationDef],
     config: &Config,
-    resource_type_rust_type: impl Fn(&ResourceTypeRef) -> String,
     options_names: &HashMap<Options, String>,
-) -> (String, Vec<String>) {
-    let (mut param_type, annotations) = if !param.links.is_empty() {
-        if let Some(rt) = param.links[0].resource_type.as_ref() {
-            let name = resource_type_rust_type(rt);
-
-            if let Some(override_type_name) = config
-                .override_type_name
+    constants: &Constants,
+) -> Vec<String> {
+    let mut lines = vec![];
+    for param in &input.params {
+        let field_name = escape_rust_reserved(&field_rust_name(input, param, config)).into_owned();
+        for link in &param.links {
+            if let Some(id) = link
+                .resource_type
                 .as_ref()
-                .and_then(|x| x(container, name.as_str(), param.name.as_str(), config))
+                .and_then(|r| r.id())
+                .filter(|id| is_representation_link(id, resource_types, representations))
             {
-                (override_type_name, vec![])
-            } else {
-                (name, vec![])
+                lines.extend(generate_representation_link_fetch(
+                    &field_name,
+                    id,
+                    param,
+                    config.is_boxed_field(input, param),
+                    config,
+                    constants,
+                ));
+                continue;
             }
-        } else {
-            ("url::Url".to_string(), vec![])
-        }
-    } else if let Some(os) = param.options.as_ref() {
-        let options_name = options_names.get(os).unwrap_or_else(|| {
-            panic!("Unknown options {:?} for {}", os, param.name);
-        });
-        (options_name.clone(), vec![])
-    } else {
-        simple_type_rust_type(container, param.r#type.as_str(), param, config)
-    };
-
-    if param.repeating {
-        param_type = format!("Vec<{}>", param_type);
-    }
-
-    if !param.required {
-        param_type = format!("Option<{}>", param_type);
-    }
-
-    (param_type, annotations)
-}
-
-fn readonly_rust_type(name: &str) -> String {
-    if name.starts_with("Option<") && name.ends_with('>') {
-        return format!(
-            "Option<{}>",
-            readonly_rust_type(name[7..name.len() - 1].trim())
-        );
-    }
-    match name {
-        "String" => "&str".to_string(),
-        x if x.starts_with("Vec<") && x.ends_with('>') => {
-            format!("&[{}]", x[4..x.len() - 1].trim())
+            lines.extend(generate_link_navigation(
+                &field_name,
+                link,
+                param,
+                input,
+                resource_types,
+                config,
+                options_names,
+            ));
         }
-        x if x.starts_with('*') => x[1..].to_string(),
-        x => format!("&{}", x),
-    }
-}
-
-fn representation_rust_type(r: &RepresentationRef) -> String {
-    if let Some(id) = r.id() {
-        camel_case_name(id)
-    } else {
-        "serde_json::Value".to_string()
     }
+    lines
 }
 
-fn escape_rust_reserved(name: &str) -> &str {
-    match name {
-        "type" => "r#type",
-        "match" => "r#match",
-        "move" => "r#move",
-        "use" => "r#use",
-        "loop" => "r#loop",
-        "continue" => "r#continue",
-        "break" => "r#break",
-        "fn" => "r#fn",
-        "struct" => "r#struct",
-        "enum" => "r#enum",
-        "trait" => "r#trait",
-        "impl" => "r#impl",
-        "pub" => "r#pub",
-        "as" => "r#as",
-        "const" => "r#const",
-        "let" => "r#let",
-        name => name,
+/// Build the context that representation templates are rendered with
+#[cfg(feature = "templates")]
+fn representation_context(
+    input: &RepresentationDef,
+    name: &str,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+    default: String,
+) -> RepresentationContext {
+    let container = ParamContainer::Representation(input);
+    RepresentationContext {
+        name: name.to_string(),
+        id: input.id.clone().unwrap_or_default(),
+        doc: (!input.docs.is_empty()).then(|| {
+            input
+                .docs
+                .iter()
+                .map(|doc| format_doc(doc, config))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        }),
+        derives: config.representation_derives(input, name),
+        fields: input
+            .params
+            .iter()
+            .map(|param| FieldContext {
+                name: escape_rust_reserved(field_rust_name(input, param, config).as_str())
+                    .to_string(),
+                wadl_name: param.name.clone(),
+                rust_type: {
+                    let (rust_type, _annotations) = param_rust_type(
+                        &container,
+                        param,
+                        config,
+                        |r| config.link_field_type(r),
+                        options_names,
+                    );
+                    if config.is_boxed_field(input, param) {
+                        boxed_type(rust_type.as_str())
+                    } else if config.is_nullable_field(input, param) {
+                        nullable_type(rust_type.as_str(), config.nullable_field_style)
+                    } else {
+                        rust_type
+                    }
+                },
+                doc: param.doc.as_ref().map(|doc| format_doc(doc, config)),
+            })
+            .collect(),
+        default,
     }
 }
 
-fn generate_representation_struct_json(
+fn generate_representation(
     input: &RepresentationDef,
+    resource_types: &[ResourceType],
+    representations: &[RepresentationDef],
     config: &Config,
     options_names: &HashMap<Options, String>,
+    constants: &Constants,
 ) -> Vec<String> {
-    let mut lines: Vec<String> = vec![];
-    let name = input.id.as_ref().unwrap().as_str();
-    let name = camel_case_name(name);
-
-    let container = ParamContainer::Representation(input);
-
-    for doc in &input.docs {
-        lines.extend(generate_doc(doc, 0, config));
+    let mut lines = vec![];
+    let name = type_name(ItemKind::Representation, input.id.as_ref().unwrap());
+    for hook in &config.hooks {
+        lines.extend(hook.before_representation(input, name.as_str(), config));
     }
-
-    if input.docs.is_empty() {
-        lines.push(format!(
-            "/// Representation of the `{}` resource\n",
-            input.id.as_ref().unwrap()
-        ));
+    let form = is_form_media_type(input.media_type.as_ref());
+    if input.media_type == Some(mime::APPLICATION_JSON) || form {
+        let struct_lines = generate_representation_struct_json(input, config, options_names);
+        #[cfg(feature = "templates")]
+        let struct_lines = templates::apply(
+            config.templates.as_ref(),
+            TemplateKind::Representation,
+            struct_lines,
+            |default| representation_context(input, name.as_str(), config, options_names, default),
+        );
+        lines.extend(struct_lines);
+    } else {
+        panic!("Unknown media type: {:?}", input.media_type);
     }
 
-    let derive_default = input.params.iter().all(|x| !x.required);
-
-    lines.push(
-        "#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]\n".to_string(),
-    );
-
-    let visibility = config
-        .representation_visibility
-        .as_ref()
-        .and_then(|x| x(name.as_str()))
-        .unwrap_or_else(|| "pub".to_string());
-
-    lines.push(format!(
-        "{}struct {} {{\n",
-        if visibility.is_empty() {
-            "".to_string()
-        } else {
-            format!("{} ", visibility)
-        },
-        name
-    ));
-
-    for param in &input.params {
-        let param_name = snake_case_name(param.name.as_str());
-
-        let param_name = escape_rust_reserved(param_name.as_str());
+    lines.push(format!("impl {} {{\n", name));
 
-        let (param_type, annotations) = param_rust_type(
-            &container,
-            param,
+    if form {
+        lines.extend(generate_representation_form_parser(
+            input,
             config,
-            |_x| "url::Url".to_string(),
             options_names,
-        );
-
-        // We provide accessors for resource types
-        let is_pub = true;
+        ));
+    }
 
-        lines.push(format!("    // was: {}\n", param.r#type));
-        if let Some(doc) = param.doc.as_ref() {
-            lines.extend(generate_doc(doc, 1, config));
+    // Repeating links are left as they are, rather than converted element by element
+    for param in input.params.iter().filter(|p| !p.repeating) {
+        let field_name = escape_rust_reserved(&field_rust_name(input, param, config)).into_owned();
+        // We expect to support multiple types here in the future
+        for link in &param.links {
+            if let Some(r) = link.resource_type.as_ref().filter(|r| {
+                !r.id()
+                    .is_some_and(|id| is_representation_link(id, resource_types, representations))
+            }) {
+                lines.extend(generate_resource_type_ref_accessors(
+                    &field_name,
+                    r,
+                    param,
+                    config.is_boxed_field(input, param),
+                    config,
+                ));
+            }
         }
+    }
 
-        for ann in annotations {
-            lines.push(format!("    {}\n", ann));
-        }
-        lines.push(format!(
-            "    {}{}: {},\n",
-            if is_pub { "pub " } else { "" },
-            param_name,
-            param_type
+    if !config.blocking_and_async {
+        lines.extend(generate_representation_link_navigation(
+            input,
+            resource_types,
+            representations,
+            config,
+            options_names,
+            constants,
         ));
-        lines.push("\n".to_string());
     }
 
     lines.push("}\n".to_string());
+    lines.push("\n".to_string());
 
-    if derive_default {
-        lines.push(format!("impl Default for {} {{\n", name));
-        lines.push("    fn default() -> Self {\n".to_string());
-        lines.push("        Self {\n".to_string());
-        for param in &input.params {
-            let param_name = snake_case_name(param.name.as_str());
-
-            let param_name = escape_rust_reserved(param_name.as_str());
-
-            lines.push(format!("            {}: Default::default(),\n", param_name));
-        }
+    if let Some(pagination) = config.pagination_for(input) {
+        lines.extend(generate_page_into_iterator(
+            input,
+            name.as_str(),
+            &pagination,
+            config,
+            options_names,
+        ));
+    }
 
-        lines.push("        }\n".to_string());
-        lines.push("    }\n".to_string());
-        lines.push("}\n".to_string());
-        lines.push("\n".to_string());
+    if let Some(generate) = config.generate_representation_traits.as_ref() {
+        lines.extend(generate(input, name.as_str(), input, config).unwrap_or(vec![]));
     }
 
-    lines.push("\n".to_string());
+    if let Some(extend_representation) = config.extend_representation.as_ref() {
+        lines.extend(extend_representation(input, name.as_str(), config));
+    }
 
     lines
 }
 
-fn supported_representation_def(_d: &RepresentationDef) -> bool {
-    false
+/// Return the expression for the URL of the next page of a page, given as `page`
+fn next_page_expr(input: &RepresentationDef, pagination: &Pagination, config: &Config) -> String {
+    let param = input
+        .params
+        .iter()
+        .find(|p| p.name == pagination.next)
+        .unwrap();
+    let field = escape_rust_reserved(&field_rust_name(input, param, config)).into_owned();
+    if param.links[0]
+        .resource_type
+        .as_ref()
+        .is_some_and(|r| config.is_typed_link(r))
+    {
+        format!("page.{}.as_ref().map(|url| url.url().clone())", field)
+    } else if config.is_boxed_field(input, param) {
+        format!("page.{}.as_deref().cloned()", field)
+    } else {
+        format!("page.{}.clone()", field)
+    }
 }
 
-/// Generate the Rust type for a representation
-///
-/// # Arguments
-/// * `input` - The representation to generate the Rust type for
-/// * `name` - The name of the representation
-///
-/// # Returns
-///
-/// The Rust type for the representation
-fn rust_type_for_response(
-    method: &Method,
-    input: &Response,
+/// Generate the `IntoIterator` implementation of a page representation, over its items
+fn generate_page_into_iterator(
+    input: &RepresentationDef,
     name: &str,
+    pagination: &Pagination,
+    config: &Config,
     options_names: &HashMap<Options, String>,
-) -> String {
-    let container = ParamContainer::Response(method, input);
-    let representations = input
-        .representations
+) -> Vec<String> {
+    let param = input
+        .params
         .iter()
-        .filter(|r| match r {
-            Representation::Definition(ref d) => supported_representation_def(d),
-            _ => true,
-        })
-        .collect::<Vec<_>>();
-    if representations.len() == 1 {
-        assert!(input.params.is_empty());
-        match representations[0] {
-            Representation::Reference(ref r) => {
-                let id = r.id().unwrap().to_string();
-                camel_case_name(id.as_str())
-            }
-            Representation::Definition(ref d) => {
-                assert!(d.params.iter().all(|p| p.style == ParamStyle::Header));
+        .find(|p| p.name == pagination.items)
+        .unwrap();
+    let field = escape_rust_reserved(&field_rust_name(input, param, config)).into_owned();
+    let (field_type, _annotations) = param_rust_type(
+        &ParamContainer::Representation(input),
+        param,
+        config,
+        |r| config.link_field_type(r),
+        options_names,
+    );
+    let (field_type, value) = match field_type
+        .strip_prefix("Option<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        Some(t) => (t.to_string(), format!("self.{}.unwrap_or_default()", field)),
+        None => (field_type.clone(), format!("self.{}", field)),
+    };
+    let (item_type, iter_type) = match field_type
+        .strip_prefix("Vec<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        Some(t) => (t.to_string(), format!("std::vec::IntoIter<{}>", t)),
+        // The type of the items was overridden
+        None => (
+            format!("<{} as IntoIterator>::Item", field_type),
+            format!("<{} as IntoIterator>::IntoIter", field_type),
+        ),
+    };
+    vec![
+        format!("impl IntoIterator for {} {{\n", name),
+        format!("    type Item = {};\n", item_type),
+        format!("    type IntoIter = {};\n", iter_type),
+        "\n".to_string(),
+        "    fn into_iter(self) -> Self::IntoIter {\n".to_string(),
+        format!("        {}.into_iter()\n", value),
+        "    }\n".to_string(),
+        "}\n".to_string(),
+        "\n".to_string(),
+    ]
+}
 
-                let mut ret = Vec::new();
-                for param in &input.params {
-                    let (param_type, _annotations) = param_rust_type(
-                        &container,
-                        param,
-                        &Config::default(),
-                        resource_type_rust_type,
-                        options_names,
-                    );
-                    ret.push(param_type);
-                }
-                if ret.len() == 1 {
-                    ret[0].clone()
-                } else {
-                    format!("({})", ret.join(", "))
-                }
+/// Return whether a media type is `application/x-www-form-urlencoded`
+fn is_form_media_type(media_type: Option<&mime::Mime>) -> bool {
+    media_type
+        .is_some_and(|m| m.essence_str() == mime::APPLICATION_WWW_FORM_URLENCODED.essence_str())
+}
+
+/// Return whether a representation is a JSON merge patch
+fn is_merge_patch(input: &RepresentationDef) -> bool {
+    input
+        .media_type
+        .as_ref()
+        .is_some_and(|m| m.essence_str() == MERGE_PATCH_MIME_TYPE)
+}
+
+/// Generate the function that parses a representation from a form-urlencoded body
+///
+/// Fields are converted to their types with [`std::str::FromStr`].
+fn generate_representation_form_parser(
+    input: &RepresentationDef,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let container = ParamContainer::Representation(input);
+    let mut lines = vec![
+        "    /// Parse a form-urlencoded body\n".to_string(),
+        "    pub fn from_form_urlencoded(body: &[u8]) -> Result<Self, wadl::Error> {\n".to_string(),
+        "        let fields = wadl::response::FormFields::parse(body);\n".to_string(),
+        "        Ok(Self {\n".to_string(),
+    ];
+    for param in &input.params {
+        let param_name = field_rust_name(input, param, config);
+        let param_name = escape_rust_reserved(param_name.as_str());
+        let (param_type, _annotations) = param_rust_type(
+            &container,
+            param,
+            config,
+            |r| config.link_field_type(r),
+            options_names,
+        );
+        let value = if param_type.starts_with("Option<Vec<") {
+            format!("fields.optional_values({:?})?", param.name)
+        } else if param_type.starts_with("Option<") {
+            format!("fields.value({:?})?", param.name)
+        } else if param_type.starts_with("Vec<") {
+            format!("fields.values({:?})?", param.name)
+        } else if let Some(default) = param
+            .default
+            .as_ref()
+            .and_then(|default| default_value_expr(param, param_type.as_str(), default))
+        {
+            format!(
+                "fields.value({:?})?.unwrap_or_else(|| {})",
+                param.name, default
+            )
+        } else {
+            format!("fields.required({:?})?", param.name)
+        };
+        let value = if !config.is_boxed_field(input, param) {
+            value
+        } else if param_type.starts_with("Option<") {
+            format!("{}.map(Box::new)", value)
+        } else {
+            format!("Box::new({})", value)
+        };
+        lines.push(format!("            {}: {},\n", param_name, value));
+    }
+    if let Some(extra_field) = extra_field_name(input, config) {
+        lines.push(format!(
+            "            {}: fields.unknown(&[{}]),\n",
+            extra_field,
+            input
+                .params
+                .iter()
+                .map(|p| format!("{:?}", p.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    lines.push("        })\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+/// Generate the Rust type for a representation
+fn resource_type_rust_type(r: &ResourceTypeRef) -> String {
+    if let Some(id) = r.id() {
+        type_name(ItemKind::ResourceType, id)
+    } else {
+        "url::Url".to_string()
+    }
+}
+
+/// XSD types whose values are binary data
+const XSD_BINARY_TYPES: &[&str] = &["binary", "base64Binary", "hexBinary"];
+
+/// The Rust type of a param with a simple type, as returned by [`simple_type_rust_type`]
+struct SimpleType {
+    /// The Rust type
+    rust_type: String,
+
+    /// Serde attributes for fields of the type
+    annotations: Vec<String>,
+
+    /// Whether values are binary data, i.e. the type is one of [`XSD_BINARY_TYPES`] or the
+    /// Rust type is `Vec<u8>`
+    ///
+    /// Binary types are strings in most representations, but files in multipart bodies.
+    binary: bool,
+}
+
+impl SimpleType {
+    fn new(rust_type: impl Into<String>) -> Self {
+        let rust_type = rust_type.into();
+        Self {
+            binary: rust_type == "Vec<u8>",
+            rust_type,
+            annotations: vec![],
+        }
+    }
+}
+
+fn simple_type_rust_type(
+    container: &ParamContainer,
+    type_name: &str,
+    param: &Param,
+    config: &Config,
+) -> SimpleType {
+    if let Some(tn) = config.override_type(container, type_name, param.name.as_str()) {
+        return SimpleType::new(tn);
+    }
+
+    let local_name = type_name.split_once(':').map_or(type_name, |(_, n)| n);
+    let binary = XSD_BINARY_TYPES.contains(&local_name);
+
+    if let Some(tn) = config
+        .xsd_type_map
+        .get(type_name)
+        .or_else(|| config.xsd_type_map.get(local_name))
+    {
+        let mut ret = SimpleType::new(tn.as_str());
+        ret.binary |= binary;
+        return ret;
+    }
+
+    if let Some(tn) = config.date_time_crate.rust_type(local_name) {
+        let format = config
+            .date_time_format
+            .as_ref()
+            .and_then(|f| f(container, param))
+            .unwrap_or_default();
+        let mut annotations = vec![];
+        // Repeated values keep the default format, since serde has no way to apply a module
+        // to the items of a Vec
+        if param.repeating {
+            if format != DateTimeFormat::Rfc3339 {
+                log::warn!(
+                    "Ignoring format {:?} for repeating param {}",
+                    format,
+                    param.name
+                );
             }
+        } else if let Some((required, optional)) =
+            date_time_serde_with(config.date_time_crate, local_name, &format, &param.name)
+        {
+            annotations.push(if param.required {
+                format!("#[serde(with = \"{}\")]", required)
+            } else {
+                format!("#[serde(default, with = \"{}\")]", optional)
+            });
         }
-    } else if representations.is_empty() {
-        let mut ret = Vec::new();
-        for param in &input.params {
-            let (param_type, _annotations) = param_rust_type(
-                &container,
-                param,
-                &Config::default(),
-                resource_type_rust_type,
-                options_names,
+        SimpleType {
+            annotations,
+            ..SimpleType::new(tn)
+        }
+    } else if local_name == "decimal" {
+        SimpleType::new(config.decimal_type.rust_type())
+    } else if let Some(tn) = xsd_rust_type(local_name) {
+        SimpleType {
+            binary,
+            ..SimpleType::new(tn)
+        }
+    } else {
+        log::warn!(
+            "Unknown type {} for param {}, falling back to String",
+            type_name,
+            param.name
+        );
+        SimpleType::new("String")
+    }
+}
+
+/// Return the modules to (de)serialize a required and an optional value of an XSD date or
+/// time type with, in the given format
+///
+/// Returns `None` if the default serde implementation of the Rust type can be used.
+fn date_time_serde_with(
+    date_time_crate: DateTimeCrate,
+    local_name: &str,
+    format: &DateTimeFormat,
+    param_name: &str,
+) -> Option<(String, String)> {
+    match format {
+        DateTimeFormat::Rfc3339 => {}
+        DateTimeFormat::EpochSeconds | DateTimeFormat::EpochMillis if local_name == "dateTime" => {
+            let (required, optional) =
+                date_time_crate.epoch_serde_with(format == &DateTimeFormat::EpochMillis);
+            return Some((required.to_string(), optional.to_string()));
+        }
+        DateTimeFormat::Custom(f) if date_time_crate.custom_format_code(local_name).is_some() => {
+            let module = date_time_format_module(local_name, f);
+            return Some((module.clone(), format!("{}::option", module)));
+        }
+        _ => {
+            log::warn!(
+                "Format {:?} is not supported for xsd:{} param {}, using the default",
+                format,
+                local_name,
+                param_name
             );
-            ret.push(param_type);
         }
-        if ret.len() == 1 {
-            ret[0].clone()
+    }
+    date_time_crate
+        .serde_with(local_name)
+        .map(|with| (with.to_string(), format!("{}::option", with)))
+}
+
+/// Return the name of the module that (de)serializes an XSD date or time type in a custom
+/// format
+///
+/// The module is emitted at the end of the generated code, by
+/// [`generate_date_time_format_modules`].
+fn date_time_format_module(local_name: &str, format: &str) -> String {
+    DATE_TIME_FORMATS.with(|f| {
+        let mut formats = f.borrow_mut();
+        let index = formats
+            .iter()
+            .position(|(n, f)| n == local_name && f == format)
+            .unwrap_or_else(|| {
+                formats.push((local_name.to_string(), format.to_string()));
+                formats.len() - 1
+            });
+        format!("date_time_format_{}", index)
+    })
+}
+
+/// Generate the modules for the custom date and time formats that were used
+fn generate_date_time_format_modules(config: &Config) -> Vec<String> {
+    let formats = DATE_TIME_FORMATS.with(|f| f.borrow().clone());
+    let mut lines = vec![];
+    for (index, (local_name, format)) in formats.iter().enumerate() {
+        let (rust_type, (format_body, parse_expr)) = match (
+            config.date_time_crate.rust_type(local_name),
+            config.date_time_crate.custom_format_code(local_name),
+        ) {
+            (Some(rust_type), Some(code)) => (rust_type, code),
+            _ => unreachable!("unsupported custom format for xsd:{}", local_name),
+        };
+        lines.push(format!(
+            "/// (De)serialize `{}` values in the format `{:?}`\n",
+            rust_type, format
+        ));
+        lines.push("#[allow(dead_code)]\n".to_string());
+        lines.push(format!("mod date_time_format_{} {{\n", index));
+        lines.push(format!("    type Value = {};\n", rust_type));
+        lines.push("\n".to_string());
+        lines.push(format!("    const FORMAT: &str = {:?};\n", format));
+        lines.push("\n".to_string());
+        lines.push("    fn format_value(value: &Value) -> Result<String, String> {\n".to_string());
+        lines.extend(indent(1, format_body.lines().map(|l| format!("{}\n", l))));
+        lines.push("    }\n".to_string());
+        lines.push("\n".to_string());
+        lines.push("    fn parse_value(s: &str) -> Result<Value, String> {\n".to_string());
+        lines.push(format!(
+            "        {}.map_err(|e| e.to_string())\n",
+            parse_expr
+        ));
+        lines.push("    }\n".to_string());
+        lines.push("\n".to_string());
+        lines.push("    pub fn serialize<S: serde::Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {\n".to_string());
+        lines.push("        serializer.serialize_str(&format_value(value).map_err(serde::ser::Error::custom)?)\n".to_string());
+        lines.push("    }\n".to_string());
+        lines.push("\n".to_string());
+        lines.push("    pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {\n".to_string());
+        lines.push(
+            "        let s = <String as serde::Deserialize>::deserialize(deserializer)?;\n"
+                .to_string(),
+        );
+        lines.push("        parse_value(&s).map_err(serde::de::Error::custom)\n".to_string());
+        lines.push("    }\n".to_string());
+        lines.push("\n".to_string());
+        lines.push("    pub mod option {\n".to_string());
+        lines.push("        use super::Value;\n".to_string());
+        lines.push("\n".to_string());
+        lines.push("        pub fn serialize<S: serde::Serializer>(value: &Option<Value>, serializer: S) -> Result<S::Ok, S::Error> {\n".to_string());
+        lines.push("            match value {\n".to_string());
+        lines.push("                Some(value) => serializer.serialize_some(&super::format_value(value).map_err(serde::ser::Error::custom)?),\n".to_string());
+        lines.push("                None => serializer.serialize_none(),\n".to_string());
+        lines.push("            }\n".to_string());
+        lines.push("        }\n".to_string());
+        lines.push("\n".to_string());
+        lines.push("        pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {\n".to_string());
+        lines.push(
+            "            <Option<String> as serde::Deserialize>::deserialize(deserializer)?\n"
+                .to_string(),
+        );
+        lines.push(
+            "                .map(|s| super::parse_value(&s).map_err(serde::de::Error::custom))\n"
+                .to_string(),
+        );
+        lines.push("                .transpose()\n".to_string());
+        lines.push("        }\n".to_string());
+        lines.push("    }\n".to_string());
+        lines.push("}\n".to_string());
+        lines.push("\n".to_string());
+    }
+    lines
+}
+
+/// Return the default Rust type for an XSD simple type
+///
+/// Durations and binary data are kept as strings in their XSD text forms (e.g. `PT5M`, or
+/// base64), since the serde implementations of `chrono::Duration` and `Vec<u8>` use other
+/// encodings.
+///
+/// # Arguments
+/// * `name` - The local name of the XSD type, without namespace prefix
+///
+/// # Returns
+/// The Rust type, or `None` if the type is not known
+pub fn xsd_rust_type(name: &str) -> Option<&'static str> {
+    let ret = match name {
+        "string" | "normalizedString" | "token" | "language" | "Name" | "NCName" | "NMTOKEN"
+        | "NMTOKENS" | "ID" | "IDREF" | "IDREFS" | "ENTITY" | "ENTITIES" | "QName" | "NOTATION"
+        | "gYear" | "gYearMonth" | "gMonth" | "gMonthDay" | "gDay" => "String",
+        "boolean" => "bool",
+        "byte" => "i8",
+        "short" => "i16",
+        "int" => "i32",
+        "long" | "integer" | "negativeInteger" | "nonPositiveInteger" => "i64",
+        "unsignedByte" => "u8",
+        "unsignedShort" => "u16",
+        "unsignedInt" => "u32",
+        "unsignedLong" | "positiveInteger" | "nonNegativeInteger" => "u64",
+        "float" => "f32",
+        "double" | "decimal" => "f64",
+        "anyURI" => "url::Url",
+        "binary" | "base64Binary" | "hexBinary" => "String",
+        "date" => "chrono::NaiveDate",
+        "dateTime" => "chrono::DateTime<chrono::Utc>",
+        "time" => "chrono::NaiveTime",
+        "duration" => "String",
+        _ => return None,
+    };
+    Some(ret)
+}
+
+/// Wrap a field type in a `Box`, keeping an outer `Option`
+fn boxed_type(param_type: &str) -> String {
+    if let Some(inner) = param_type
+        .strip_prefix("Option<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        format!("Option<Box<{}>>", inner)
+    } else {
+        format!("Box<{}>", param_type)
+    }
+}
+
+/// Return the type of a field that distinguishes `null` from a missing value, given its
+/// `Option` type
+fn nullable_type(param_type: &str, style: NullableFieldStyle) -> String {
+    let inner = param_type
+        .strip_prefix("Option<")
+        .and_then(|t| t.strip_suffix('>'))
+        .unwrap_or(param_type);
+    match style {
+        NullableFieldStyle::Option => param_type.to_string(),
+        NullableFieldStyle::DoubleOption => format!("Option<Option<{}>>", inner),
+        NullableFieldStyle::Patch => format!("wadl::Patch<{}>", inner),
+    }
+}
+
+/// Return the serde attributes of a field that distinguishes `null` from a missing value
+fn nullable_field_attrs(style: NullableFieldStyle) -> Vec<String> {
+    match style {
+        NullableFieldStyle::Option => vec![],
+        NullableFieldStyle::DoubleOption => vec![
+            "default".to_string(),
+            "skip_serializing_if = \"Option::is_none\"".to_string(),
+            "deserialize_with = \"wadl::patch::deserialize_some\"".to_string(),
+        ],
+        NullableFieldStyle::Patch => vec![
+            "default".to_string(),
+            "skip_serializing_if = \"wadl::Patch::is_unchanged\"".to_string(),
+        ],
+    }
+}
+
+/// Return an estimate of the size of a Rust type in bytes, on 64-bit platforms
+///
+/// Unknown types are assumed to be the size of a `String`.
+fn estimated_type_size(rust_type: &str) -> usize {
+    match rust_type {
+        "bool" | "i8" | "u8" => 1,
+        "i16" | "u16" => 2,
+        "i32" | "u32" | "f32" | "chrono::NaiveDate" | "time::Date" | "jiff::civil::Date" => 4,
+        "i64" | "u64" | "f64" | "chrono::NaiveTime" | "time::Time" | "jiff::civil::Time" => 8,
+        "chrono::DateTime<chrono::Utc>" => 12,
+        "time::OffsetDateTime"
+        | "jiff::Timestamp"
+        | "jiff::SignedDuration"
+        | "rust_decimal::Decimal" => 16,
+        "bigdecimal::BigDecimal" => 40,
+        "url::Url" | "reqwest::Url" => 80,
+        _ => 24,
+    }
+}
+
+fn param_rust_type(
+    container: &ParamContainer,
+    param: &Param,
+    config: &Config,
+    resource_type_rust_type: impl Fn(&ResourceTypeRef) -> String,
+    options_names: &HashMap<Options, String>,
+) -> (String, Vec<String>) {
+    let (mut param_type, annotations) = if !param.links.is_empty() {
+        if let Some(rt) = param.links[0].resource_type.as_ref() {
+            let name = resource_type_rust_type(rt);
+
+            if let Some(override_type_name) =
+                config.override_type(container, name.as_str(), param.name.as_str())
+            {
+                (override_type_name, vec![])
+            } else {
+                (name, vec![])
+            }
+        } else {
+            ("url::Url".to_string(), vec![])
+        }
+    } else if let Some(os) = param.options.as_ref() {
+        let options_name = options_names.get(os).unwrap_or_else(|| {
+            panic!("Unknown options {:?} for {}", os, param.name);
+        });
+        (options_name.clone(), vec![])
+    } else {
+        let simple_type = simple_type_rust_type(container, param.r#type.as_str(), param, config);
+        (simple_type.rust_type, simple_type.annotations)
+    };
+
+    if param.repeating {
+        param_type = format!("Vec<{}>", param_type);
+    }
+
+    if !param.required {
+        param_type = format!("Option<{}>", param_type);
+    }
+
+    (param_type, annotations)
+}
+
+fn readonly_rust_type(name: &str) -> String {
+    if name.starts_with("Option<") && name.ends_with('>') {
+        return format!(
+            "Option<{}>",
+            readonly_rust_type(name[7..name.len() - 1].trim())
+        );
+    }
+    match name {
+        "String" => "&str".to_string(),
+        x if x.starts_with("Vec<") && x.ends_with('>') => {
+            format!("&[{}]", x[4..x.len() - 1].trim())
+        }
+        x if x.starts_with('*') => x[1..].to_string(),
+        x => format!("&{}", x),
+    }
+}
+
+fn representation_rust_type(r: &RepresentationRef) -> String {
+    if let Some(id) = r.id() {
+        type_name(ItemKind::Representation, id)
+    } else {
+        "serde_json::Value".to_string()
+    }
+}
+
+/// Keywords of all Rust editions, including reserved ones, that can be used as raw identifiers
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords that can not be used as raw identifiers
+const NON_RAW_KEYWORDS: &[&str] = &["self", "Self", "super", "crate"];
+
+/// Escape a name that is a Rust keyword, so that it can be used as an identifier
+///
+/// Keywords become raw identifiers (e.g. `r#type`), except for those that can't be raw,
+/// which get an underscore appended (e.g. `self_`).
+fn escape_rust_reserved(name: &str) -> Cow<'_, str> {
+    if RUST_KEYWORDS.contains(&name) {
+        Cow::Owned(format!("r#{}", name))
+    } else if NON_RAW_KEYWORDS.contains(&name) {
+        Cow::Owned(format!("{}_", name))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+fn generate_representation_struct_json(
+    input: &RepresentationDef,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let name = input.id.as_ref().unwrap().as_str();
+    let name = type_name(ItemKind::Representation, name);
+
+    let container = ParamContainer::Representation(input);
+
+    for doc in &input.docs {
+        lines.extend(generate_doc(doc, 0, config));
+    }
+
+    if input.docs.is_empty() {
+        lines.push(format!(
+            "/// Representation of the `{}` resource\n",
+            input.id.as_ref().unwrap()
+        ));
+    }
+
+    // Nullable fields start out missing, which leaves the value unchanged
+    let defaults = input
+        .params
+        .iter()
+        .map(|param| {
+            let default = param
+                .default
+                .as_ref()
+                .filter(|_| !config.is_nullable_field(input, param))?;
+            let (param_type, _annotations) = param_rust_type(
+                &container,
+                param,
+                config,
+                |r| config.link_field_type(r),
+                options_names,
+            );
+            default_value_expr(param, param_type.as_str(), default)
+        })
+        .collect::<Vec<_>>();
+
+    let derive_default = input
+        .params
+        .iter()
+        .zip(&defaults)
+        .all(|(x, default)| !x.required || default.is_some());
+
+    // Fields that are left out of a merge patch are left unchanged, while null removes them
+    let merge_patch = is_merge_patch(input);
+
+    lines.extend(derive_line(
+        &config.representation_derives(input, name.as_str()),
+    ));
+
+    let rename_all = config
+        .serde_rename_all
+        .as_ref()
+        .and_then(|x| x(name.as_str()));
+
+    let mut serde_attrs = vec![];
+    if let Some(rename_all) = rename_all.as_ref() {
+        serde_attrs.push(format!("rename_all = \"{}\"", rename_all));
+    }
+    if config
+        .deny_unknown_fields
+        .as_ref()
+        .is_some_and(|x| x(name.as_str()))
+    {
+        // serde does not support deny_unknown_fields together with flatten
+        if config.capture_unknown_fields {
+            log::warn!(
+                "Not denying unknown fields for {}, since they are captured",
+                name
+            );
+        } else {
+            serde_attrs.push("deny_unknown_fields".to_string());
+        }
+    }
+    if !serde_attrs.is_empty() {
+        lines.push(format!("#[serde({})]\n", serde_attrs.join(", ")));
+    }
+
+    let visibility = config
+        .item_visibility(ItemKind::Representation, name.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    lines.push(format!(
+        "{}struct {} {{\n",
+        if visibility.is_empty() {
+            "".to_string()
+        } else {
+            format!("{} ", visibility)
+        },
+        name
+    ));
+
+    for param in &input.params {
+        let param_name = field_rust_name(input, param, config);
+        let renamed = param_name != snake_case_name(param.name.as_str());
+
+        let param_name = escape_rust_reserved(param_name.as_str());
+
+        let (mut param_type, annotations) = param_rust_type(
+            &container,
+            param,
+            config,
+            |r| config.link_field_type(r),
+            options_names,
+        );
+        let nullable = config.is_nullable_field(input, param);
+        if config.is_boxed_field(input, param) {
+            param_type = boxed_type(param_type.as_str());
+        } else if nullable {
+            param_type = nullable_type(param_type.as_str(), config.nullable_field_style);
+        }
+
+        // We provide accessors for resource types
+        let is_pub = true;
+
+        lines.push(format!("    // was: {}\n", param.r#type));
+        if let Some(doc) = param.doc.as_ref() {
+            lines.extend(generate_doc(doc, 1, config));
+        }
+
+        for ann in annotations {
+            lines.push(format!("    {}\n", ann));
+        }
+
+        let mut field_attrs = vec![];
+        // Explicitly renamed fields do not follow the rename_all strategy
+        if (rename_all.is_none() || renamed) && param_name.trim_start_matches("r#") != param.name {
+            field_attrs.push(format!("rename = \"{}\"", param.name));
+        }
+        if nullable {
+            field_attrs.extend(nullable_field_attrs(config.nullable_field_style));
+        } else if (config.skip_serializing_none || merge_patch) && param_type.starts_with("Option<")
+        {
+            field_attrs.push("skip_serializing_if = \"Option::is_none\"".to_string());
+        }
+        if !field_attrs.is_empty() {
+            lines.push(format!("    #[serde({})]\n", field_attrs.join(", ")));
+        }
+
+        lines.push(format!(
+            "    {}{}: {},\n",
+            if is_pub { "pub " } else { "" },
+            param_name,
+            param_type
+        ));
+        lines.push("\n".to_string());
+    }
+
+    let extra_field = extra_field_name(input, config);
+
+    if let Some(extra_field) = extra_field.as_ref() {
+        lines.push("    /// Fields not described in the WADL\n".to_string());
+        lines.push("    #[serde(flatten)]\n".to_string());
+        lines.push(format!(
+            "    pub {}: std::collections::HashMap<String, serde_json::Value>,\n",
+            extra_field
+        ));
+        lines.push("\n".to_string());
+    }
+
+    lines.push("}\n".to_string());
+
+    if derive_default {
+        lines.push(format!("impl Default for {} {{\n", name));
+        lines.push("    fn default() -> Self {\n".to_string());
+        lines.push("        Self {\n".to_string());
+        for (param, default) in input.params.iter().zip(defaults) {
+            let param_name = field_rust_name(input, param, config);
+
+            let param_name = escape_rust_reserved(param_name.as_str());
+
+            let value = if let Some(value) = default {
+                if !config.is_boxed_field(input, param) {
+                    value
+                } else if let Some(value) = value.strip_prefix("Some(") {
+                    format!("Some(Box::new({})", value)
+                } else {
+                    format!("Box::new({})", value)
+                }
+            } else {
+                "Default::default()".to_string()
+            };
+
+            lines.push(format!("            {}: {},\n", param_name, value));
+        }
+
+        if let Some(extra_field) = extra_field.as_ref() {
+            lines.push(format!(
+                "            {}: Default::default(),\n",
+                extra_field
+            ));
+        }
+
+        lines.push("        }\n".to_string());
+        lines.push("    }\n".to_string());
+        lines.push("}\n".to_string());
+        lines.push("\n".to_string());
+    }
+
+    lines.push("\n".to_string());
+
+    lines
+}
+
+/// Return the Rust name of the field for a param of a representation, without escaping
+///
+/// Names in [`Config::param_renames`] qualified with the id of the representation take
+/// precedence over unqualified ones.
+fn field_rust_name(input: &RepresentationDef, param: &Param, config: &Config) -> String {
+    input
+        .id
+        .as_ref()
+        .and_then(|id| {
+            config
+                .param_renames
+                .get(format!("{}.{}", id, param.name).as_str())
+        })
+        .or_else(|| config.param_renames.get(param.name.as_str()))
+        .cloned()
+        .unwrap_or_else(|| snake_case_name(param.name.as_str()))
+}
+
+/// Return the name of the field used to capture unknown fields, if enabled
+fn extra_field_name(input: &RepresentationDef, config: &Config) -> Option<String> {
+    if !config.capture_unknown_fields {
+        return None;
+    }
+    let mut name = "extra".to_string();
+    while input
+        .params
+        .iter()
+        .any(|p| field_rust_name(input, p, config) == name)
+    {
+        name.push('_');
+    }
+    Some(name)
+}
+
+lazy_static::lazy_static! {
+    static ref XSD_TIME: regex::Regex =
+        regex::Regex::new(r"^([01][0-9]|2[0-3]):[0-5][0-9]:[0-5][0-9](\.[0-9]{1,9})?$").unwrap();
+    static ref XSD_TIMEZONE: regex::Regex =
+        regex::Regex::new(r"(Z|[+-]([01][0-9]|2[0-3]):[0-5][0-9])$").unwrap();
+    // Durations of up to hours, as supported by jiff::SignedDuration
+    static ref XSD_DAY_TIME_DURATION: regex::Regex = regex::Regex::new(
+        r"^-?PT([0-9]{1,9}H)?([0-9]{1,9}M)?([0-9]{1,9}(\.[0-9]{1,9})?S)?$"
+    )
+    .unwrap();
+    static ref DECIMAL: regex::Regex = regex::Regex::new(r"^-?[0-9]{1,20}(\.[0-9]{1,8})?$").unwrap();
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Return whether a value is a date without a timezone, e.g. `2024-02-29`
+fn is_valid_date(value: &str) -> bool {
+    let parts = value.split('-').collect::<Vec<_>>();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (year.parse::<u32>(), month.parse(), day.parse::<u32>())
+    else {
+        return false;
+    };
+    let days = match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// Return whether a value is a date and time with a timezone, e.g. `2024-02-29T12:00:00Z`
+fn is_valid_date_time(value: &str) -> bool {
+    value.split_once('T').is_some_and(|(date, time)| {
+        XSD_TIMEZONE
+            .find(time)
+            .is_some_and(|tz| is_valid_date(date) && XSD_TIME.is_match(&time[..tz.start()]))
+    })
+}
+
+/// Return the expression that constructs a `time::Date`, given a valid date
+fn time_date_expr(value: &str) -> String {
+    let parts = value
+        .split('-')
+        .map(|p| p.parse::<u32>().unwrap())
+        .collect::<Vec<_>>();
+    format!(
+        "time::Date::from_calendar_date({}, time::Month::{}, {}).unwrap()",
+        parts[0],
+        MONTHS[parts[1] as usize - 1],
+        parts[2]
+    )
+}
+
+/// Return the expression that constructs a `time::Time`, given a valid time
+fn time_time_expr(value: &str) -> String {
+    let (hms, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let parts = hms
+        .split(':')
+        .map(|p| p.parse::<u32>().unwrap())
+        .collect::<Vec<_>>();
+    let nanos = format!("{:0<9}", fraction).parse::<u32>().unwrap();
+    format!(
+        "time::Time::from_hms_nano({}, {}, {}, {}).unwrap()",
+        parts[0], parts[1], parts[2], nanos
+    )
+}
+
+/// Return whether a default value can be converted to the given Rust type
+///
+/// This is stricter than the `FromStr` implementations of the types, so that the generated
+/// code never fails to parse a default that was accepted here.
+fn is_valid_default(rust_type: &str, default: &str) -> bool {
+    match rust_type {
+        "String" => true,
+        "bool" => matches!(default, "true" | "false" | "1" | "0"),
+        "i8" => default.parse::<i8>().is_ok(),
+        "i16" => default.parse::<i16>().is_ok(),
+        "i32" => default.parse::<i32>().is_ok(),
+        "i64" => default.parse::<i64>().is_ok(),
+        "u8" => default.parse::<u8>().is_ok(),
+        "u16" => default.parse::<u16>().is_ok(),
+        "u32" => default.parse::<u32>().is_ok(),
+        "u64" => default.parse::<u64>().is_ok(),
+        "f32" | "f64" => default.parse::<f64>().is_ok(),
+        "url::Url" => url::Url::parse(default).is_ok(),
+        "rust_decimal::Decimal" | "bigdecimal::BigDecimal" => DECIMAL.is_match(default),
+        "chrono::NaiveDate" | "time::Date" | "jiff::civil::Date" => is_valid_date(default),
+        "chrono::NaiveTime" | "time::Time" | "jiff::civil::Time" => XSD_TIME.is_match(default),
+        "chrono::DateTime<chrono::Utc>" | "time::OffsetDateTime" | "jiff::Timestamp" => {
+            is_valid_date_time(default)
+        }
+        "jiff::SignedDuration" => {
+            !default.ends_with("PT") && XSD_DAY_TIME_DURATION.is_match(default)
+        }
+        _ => false,
+    }
+}
+
+/// Generate a Rust expression for the default value of a parameter
+///
+/// Returns `None`, with a warning, if the default is not valid for the type, rather
+/// than generating code that fails when it runs.
+///
+/// # Arguments
+/// * `param` - The parameter
+/// * `param_type` - The Rust type of the parameter, as returned by `param_rust_type`
+/// * `default` - The default value, as declared in the WADL
+fn default_value_expr(param: &Param, param_type: &str, default: &str) -> Option<String> {
+    let mut inner_type = param_type;
+    if let Some(t) = inner_type
+        .strip_prefix("Option<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        inner_type = t;
+    }
+    if let Some(t) = inner_type
+        .strip_prefix("Vec<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        inner_type = t;
+    }
+
+    let mut value = if let Some(options) = param.options.as_ref() {
+        if options.get(default).is_none() {
+            log::warn!(
+                "Ignoring default {:?} for {}, since it is not one of its options",
+                default,
+                param.name
+            );
+            return None;
+        }
+        format!("{}::{}", inner_type, enum_rust_value(default))
+    } else if !is_valid_default(inner_type, default) {
+        log::warn!(
+            "Ignoring default {:?} for {}, since it is not a valid {}",
+            default,
+            param.name,
+            inner_type
+        );
+        return None;
+    } else {
+        match inner_type {
+            "String" => format!("{:?}.to_string()", default),
+            "bool" => matches!(default, "true" | "1").to_string(),
+            "f32" | "f64" => format!("{:?}", default.parse::<f64>().unwrap()),
+            "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" => {
+                default.parse::<i128>().unwrap().to_string()
+            }
+            // The time crate only parses with a format
+            "time::Date" => time_date_expr(default),
+            "time::Time" => time_time_expr(default),
+            "time::OffsetDateTime" => format!(
+                "time::OffsetDateTime::parse({:?}, &time::format_description::well_known::Rfc3339).unwrap()",
+                default
+            ),
+            // Checked by is_valid_default, so parsing can not fail
+            _ => format!("{:?}.parse().unwrap()", default),
+        }
+    };
+
+    if param.repeating {
+        value = format!("vec![{}]", value);
+    }
+
+    if !param.required {
+        value = format!("Some({})", value);
+    }
+
+    Some(value)
+}
+
+fn supported_representation_def(_d: &RepresentationDef) -> bool {
+    false
+}
+
+/// Generate the Rust type for a representation
+///
+/// # Arguments
+/// * `input` - The representation to generate the Rust type for
+/// * `name` - The name of the representation
+///
+/// # Returns
+///
+/// The Rust type for the representation
+fn rust_type_for_response(
+    method: &Method,
+    input: &Response,
+    name: &str,
+    options_names: &HashMap<Options, String>,
+) -> String {
+    let container = ParamContainer::Response(method, input);
+    if input.params.iter().any(is_etag_param) {
+        let untagged = Response {
+            params: input
+                .params
+                .iter()
+                .filter(|p| !is_etag_param(p))
+                .cloned()
+                .collect(),
+            ..input.clone()
+        };
+        return format!(
+            "wadl::Tagged<{}>",
+            rust_type_for_response(method, &untagged, name, options_names)
+        );
+    }
+    let representations = supported_representations(input);
+    if representations.len() == 1 {
+        assert!(input.params.is_empty());
+        match representations[0] {
+            Representation::Reference(ref r) => {
+                let id = r.id().unwrap().to_string();
+                type_name(ItemKind::Representation, id.as_str())
+            }
+            Representation::Definition(ref d) => {
+                assert!(d.params.iter().all(|p| p.style == ParamStyle::Header));
+
+                let mut ret = Vec::new();
+                for param in &input.params {
+                    let (param_type, _annotations) = param_rust_type(
+                        &container,
+                        param,
+                        &Config::default(),
+                        resource_type_rust_type,
+                        options_names,
+                    );
+                    ret.push(param_type);
+                }
+                if ret.len() == 1 {
+                    ret[0].clone()
+                } else {
+                    format!("({})", ret.join(", "))
+                }
+            }
+        }
+    } else if representations.is_empty() {
+        let mut ret = Vec::new();
+        for param in &input.params {
+            let (param_type, _annotations) = param_rust_type(
+                &container,
+                param,
+                &Config::default(),
+                resource_type_rust_type,
+                options_names,
+            );
+            ret.push(param_type);
+        }
+        if ret.len() == 1 {
+            ret[0].clone()
+        } else {
+            format!("({})", ret.join(", "))
+        }
+    } else if input.params.iter().all(is_etag_param)
+        && representations
+            .iter()
+            .all(|r| matches!(r, Representation::Reference(_)))
+    {
+        response_enum_name(method)
+    } else {
+        todo!(
+            "multiple representations for response: {}: {:?}",
+            name,
+            representations
+        );
+    }
+}
+
+fn format_arg_doc(name: &str, doc: Option<&crate::ast::Doc>, config: &Config) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(doc) = doc.as_ref() {
+        let doc = format_doc(doc, config);
+        let mut doc_lines = doc
+            .trim_start_matches('\n')
+            .split('\n')
+            .collect::<Vec<_>>()
+            .into_iter();
+        lines.push(format!(
+            "    /// * `{}`: {}\n",
+            name,
+            doc_lines.next().unwrap().trim_end_matches(' ')
+        ));
+        for doc_line in doc_lines {
+            if doc_line.is_empty() {
+                lines.push("    ///\n".to_string());
+            } else {
+                lines.push(format!("    ///     {}\n", doc_line.trim_end_matches(' ')));
+            }
+        }
+    } else {
+        lines.push(format!("    /// * `{}`\n", name));
+    }
+
+    lines
+}
+
+fn apply_map_fn(map_fn: Option<&str>, ret: &str, required: bool) -> String {
+    if let Some(map_fn) = map_fn {
+        if required {
+            if map_fn.starts_with('|') {
+                format!("({})({})", map_fn, ret)
+            } else {
+                format!("{}({})", map_fn, ret)
+            }
+        } else {
+            format!("{}.map({})", ret, map_fn)
+        }
+    } else {
+        ret.to_string()
+    }
+}
+
+/// Generate the code to send a value as a JSON request body, with the given media type
+fn serialize_json_body(value: &str, media_type: &str, config: &Config) -> Vec<String> {
+    if config.uses_http_crate() {
+        vec![
+            format!("let body_ = serde_json::to_vec(&{})?;\n", value),
+            format!(
+                "req = req.header({}::header::CONTENT_TYPE, \"{}\");\n",
+                config.http_module(),
+                media_type
+            ),
+        ]
+    } else if media_type == "application/json" {
+        vec![format!("req = req.json(&{});\n", value)]
+    } else {
+        vec![
+            format!(
+                "req = req.header({}::header::CONTENT_TYPE, \"{}\");\n",
+                config.http_module(),
+                media_type
+            ),
+            format!("req = req.body(serde_json::to_vec(&{})?);\n", value),
+        ]
+    }
+}
+
+/// Media type of JSON Patch documents (RFC 6902)
+const JSON_PATCH_MIME_TYPE: &str = "application/json-patch+json";
+
+/// Media type of JSON Merge Patch documents (RFC 7396)
+const MERGE_PATCH_MIME_TYPE: &str = "application/merge-patch+json";
+
+/// Return whether a representation is a JSON Patch or JSON Merge Patch document
+fn is_patch_representation(def: &RepresentationDef) -> bool {
+    def.media_type
+        .as_ref()
+        .is_some_and(|m| [JSON_PATCH_MIME_TYPE, MERGE_PATCH_MIME_TYPE].contains(&m.essence_str()))
+}
+
+/// Return the name of the struct generated for the merge patch request of a method
+fn patch_struct_name(method: &Method) -> String {
+    let name = camel_case_name(method.id.as_str());
+    if name.ends_with("Patch") {
+        name
+    } else {
+        format!("{}Patch", name)
+    }
+}
+
+/// Generate the structs for the JSON Merge Patch requests of the methods in an application
+///
+/// All fields of these structs are optional, and fields that are `None` are left out so
+/// that they are left unchanged.
+fn generate_patch_structs(
+    app: &Application,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+    for method in app.resource_types.iter().flat_map(|rt| rt.methods.iter()) {
+        for def in method
+            .request
+            .representations
+            .iter()
+            .filter_map(|r| r.as_def())
+            .filter(|d| {
+                d.media_type.as_ref().map(|m| m.essence_str()) == Some(MERGE_PATCH_MIME_TYPE)
+            })
+        {
+            let name = patch_struct_name(method);
+            let mut docs = def.docs.clone();
+            if docs.is_empty() {
+                docs.push(Doc::new(format!(
+                    "Changes to apply with the `{}` method",
+                    method.id
+                )));
+            }
+            let def = RepresentationDef {
+                id: Some(name),
+                docs,
+                params: def
+                    .params
+                    .iter()
+                    .filter(|p| p.fixed.is_none())
+                    .map(|p| Param {
+                        required: false,
+                        default: None,
+                        ..p.clone()
+                    })
+                    .collect(),
+                ..def.clone()
+            };
+            lines.extend(generate_representation_struct_json(
+                &def,
+                config,
+                options_names,
+            ));
+        }
+    }
+    lines
+}
+
+/// HTTP methods that are available as constants on `Method`
+const STANDARD_METHODS: &[&str] = &[
+    "GET", "POST", "PUT", "DELETE", "HEAD", "OPTIONS", "CONNECT", "PATCH", "TRACE",
+];
+
+/// Return whether a string is a token as defined in RFC 9110, like the names of HTTP methods
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Generate the expression for a HTTP method
+///
+/// Extension methods (e.g. `PROPFIND`) are created from their name at runtime. Methods whose
+/// name is not a token are dropped by [`filter_application`].
+fn method_expr(method: &str, config: &Config) -> String {
+    if STANDARD_METHODS.contains(&method) {
+        format!("{}::Method::{}", config.http_module(), method)
+    } else {
+        format!(
+            "{}::Method::from_bytes(b\"{}\").map_err(|_| wadl::Error::InvalidMethod({:?}.to_string()))?",
+            config.http_module(),
+            method,
+            method
+        )
+    }
+}
+
+/// Generate the code to create a request builder for a HTTP method and `url_`
+fn new_request(method: &str, config: &Config) -> String {
+    let method = method_expr(method, config);
+    if config.uses_http_crate() {
+        format!(
+            "let mut req = {}::Request::builder().method({}).uri(url_.as_str());\n",
+            config.http_module(),
+            method
+        )
+    } else if config.async_client() {
+        format!("let mut req = client.request({}, url_).await;\n", method)
+    } else {
+        format!("let mut req = client.request({}, url_);\n", method)
+    }
+}
+
+/// Generate the code to send `req`, storing the response in `resp`
+fn send_request(has_body: bool, policy: Option<&RequestPolicy>, config: &Config) -> Vec<String> {
+    // Request policies of clients that build `http::Request`s are rejected by `Config::validate`
+    if let Some(policy) = policy.filter(|_| !config.uses_http_crate()) {
+        let mut lines = vec![];
+        if let Some(timeout) = policy.timeout {
+            if config.async_client() {
+                // reqwest does not support timeouts in the browser
+                lines.push("#[cfg(not(target_arch = \"wasm32\"))]\n".to_string());
+            }
+            lines.push(format!(
+                "let req = req.timeout(std::time::Duration::from_millis({}));\n",
+                timeout.as_millis()
+            ));
+        }
+        if policy.retries > 0 {
+            lines.push(format!(
+                "let resp = wadl::{}::send_with_retry(client, req, &wadl::RetryPolicy {{ retries: {}, backoff: std::time::Duration::from_millis({}), ..Default::default() }}){}?;\n",
+                if config.async_client() { "r#async" } else { "blocking" },
+                policy.retries,
+                policy.backoff.as_millis(),
+                if config.async_client() { ".await" } else { "" },
+            ));
+            return lines;
+        }
+        lines.extend(send_request(has_body, None, config));
+        return lines;
+    }
+    if config.tower {
+        vec![
+            if has_body {
+                "let req = req.body(wadl::tower::Body::from(body_))?;\n".to_string()
+            } else {
+                "let req = req.body(wadl::tower::Body::default())?;\n".to_string()
+            },
+            "let resp = client.send(req).await?;\n".to_string(),
+        ]
+    } else if config.ureq {
+        vec![
+            if has_body {
+                "let req = req.body(body_)?;\n".to_string()
+            } else {
+                "let req = req.body(Vec::new())?;\n".to_string()
+            },
+            "let resp = client.send(req)?;\n".to_string(),
+        ]
+    } else if config.async_client() {
+        vec!["let resp = wadl::r#async::send(client, req).await?;\n".to_string()]
+    } else {
+        vec!["let resp = client.send(req)?;\n".to_string()]
+    }
+}
+
+/// Wrap the code to send a request in calls to the observer, if one is configured
+fn observe_request(mut lines: Vec<String>, method_id: &str, config: &Config) -> Vec<String> {
+    let Some(observer) = config.observer.as_ref() else {
+        return lines;
+    };
+    let send = lines.pop().unwrap();
+    let send = send
+        .strip_prefix("let resp = ")
+        .and_then(|s| s.strip_suffix("?;\n"))
+        .expect("unexpected request sending code");
+    let mut ret = vec![
+        format!("let observer_: &dyn wadl::Observer = {};\n", observer),
+        format!("observer_.on_request_start({:?});\n", method_id),
+        "let start_ = wadl::Stopwatch::start();\n".to_string(),
+    ];
+    ret.extend(lines);
+    ret.push(format!("let resp = {};\n", send));
+    ret.push(format!(
+        "observer_.on_request_finish({:?}, resp.as_ref().ok().map(|r| r.status()), start_.elapsed());\n",
+        method_id
+    ));
+    ret.push("let resp = resp?;\n".to_string());
+    ret
+}
+
+/// Names of the constants generated for fixed param values and media types
+#[derive(Default)]
+struct Constants(HashMap<(String, String), String>);
+
+impl Constants {
+    /// Collect the fixed params and media types used by the methods of an application
+    ///
+    /// # Returns
+    /// The constants, and the lines defining them
+    fn collect(app: &Application, config: &Config) -> (Self, Vec<String>) {
+        let mut constants = Constants::default();
+        let mut lines = vec![];
+        for method in app.resource_types.iter().flat_map(|rt| rt.methods.iter()) {
+            for param in method_request_params(method) {
+                if let Some(fixed) = param.fixed.as_ref() {
+                    lines.extend(constants.insert(param.name.as_str(), fixed));
+                }
+            }
+            if let Some(accept) = accept_header(method, config) {
+                lines.extend(constants.insert("", accept.as_str()));
+            }
+        }
+        // Media types that representation links are fetched as
+        for link in app.iter_all_params().flat_map(|p| p.links.iter()) {
+            if let Some(id) = link
+                .resource_type
+                .as_ref()
+                .and_then(|r| r.id())
+                .filter(|id| is_representation_link(id, &app.resource_types, &app.representations))
+            {
+                lines.extend(constants.insert("", representation_id_media_type(id).as_ref()));
+            }
+        }
+        if !lines.is_empty() {
+            lines.push("\n".to_string());
+        }
+        (constants, lines)
+    }
+
+    /// Add a constant for a value, returning its definition if it is new
+    ///
+    /// Values of fixed params are keyed by the param name; media types by an empty name.
+    fn insert(&mut self, param_name: &str, value: &str) -> Option<String> {
+        let key = (param_name.to_string(), value.to_string());
+        if self.0.contains_key(&key) {
+            return None;
+        }
+        let base = if param_name.is_empty() {
+            constant_name(&["media_type", value])
+        } else {
+            constant_name(&[param_name, value])
+        };
+        let mut name = base.clone();
+        let mut i = 1;
+        while self.0.values().any(|n| n == &name) {
+            i += 1;
+            name = format!("{}_{}", base, i);
+        }
+        self.0.insert(key, name.clone());
+        Some(format!("pub const {}: &str = {:?};\n", name, value))
+    }
+
+    /// Return the expression for the value of a fixed param
+    fn fixed(&self, param_name: &str, value: &str) -> String {
+        self.0
+            .get(&(param_name.to_string(), value.to_string()))
+            .cloned()
+            .unwrap_or_else(|| format!("\"{}\"", value))
+    }
+
+    /// Return the expression for a media type
+    fn media_type(&self, value: &str) -> String {
+        self.fixed("", value)
+    }
+}
+
+/// Return the name of a constant, given the parts of its name
+fn constant_name(parts: &[&str]) -> String {
+    let mut name = String::new();
+    for part in parts {
+        let mut prev_lower = false;
+        name.push('_');
+        for c in part.chars() {
+            if c.is_ascii_alphanumeric() {
+                if c.is_ascii_uppercase() && prev_lower {
+                    name.push('_');
+                }
+                name.push(c.to_ascii_uppercase());
+                prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            } else {
+                if !name.ends_with('_') {
+                    name.push('_');
+                }
+                prev_lower = false;
+            }
+        }
+    }
+    let name = name.trim_matches('_').to_string();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+fn serialize_representation_def(
+    def: &RepresentationDef,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+    constants: &Constants,
+) -> Vec<String> {
+    let mut lines = vec![];
+    fn process_param(
+        param: &Param,
+        container: &ParamContainer,
+        config: &Config,
+        cb: impl Fn(&str, &str, &str) -> String,
+        options_names: &HashMap<Options, String>,
+        constants: &Constants,
+    ) -> Vec<String> {
+        let param_name = escape_rust_reserved(param.name.as_str());
+
+        let (param_type, _annotations) = param_rust_type(
+            container,
+            param,
+            config,
+            resource_type_rust_type,
+            options_names,
+        );
+        let param_type = readonly_rust_type(&param_type);
+        let mut indent = 4;
+        let mut lines = vec![];
+
+        let needs_iter = param_type.starts_with("Vec<") || param_type.starts_with("Option<Vec<");
+        let is_optional = param_type.starts_with("Option<");
+
+        if is_optional && param.fixed.is_none() {
+            lines.push(format!(
+                "{:indent$}if let Some({}) = {} {{\n",
+                "",
+                param_name,
+                param_name,
+                indent = indent
+            ));
+            indent += 4;
+        }
+        if needs_iter && param.fixed.is_none() {
+            lines.push(format!(
+                "{:indent$}for {} in {} {{\n",
+                "", param_name, param_name
+            ));
+            indent += 4;
+        }
+
+        let value = if let Some(fixed) = param.fixed.as_ref() {
+            constants.fixed(param.name.as_str(), fixed)
+        } else if param.links.is_empty() {
+            format!("&{}.to_string()", param_name)
+        } else {
+            format!("&{}.url().to_string()", param_name)
+        };
+
+        lines.push(format!(
+            "{:indent$}{}\n",
+            "",
+            cb(param_type.as_str(), param.name.as_str(), value.as_str()),
+            indent = indent
+        ));
+
+        if needs_iter && param.fixed.is_none() {
+            indent -= 4;
+            lines.push(format!("{:indent$}}}\n", "", indent = indent));
+        }
+
+        if is_optional && param.fixed.is_none() {
+            indent -= 4;
+            lines.push(format!("{:indent$}}}\n", "", indent = indent));
+        }
+
+        lines
+    }
+
+    /// Insert a param into the JSON object `o`, keeping its native JSON type
+    ///
+    /// Repeating params are inserted as arrays, and links as the URL of the resource they
+    /// refer to.
+    fn json_param(
+        param: &Param,
+        container: &ParamContainer,
+        config: &Config,
+        options_names: &HashMap<Options, String>,
+        constants: &Constants,
+    ) -> Vec<String> {
+        let param_name = escape_rust_reserved(param.name.as_str());
+        let insert = |value: &str| {
+            format!(
+                "o.insert({:?}.to_string(), {});\n",
+                param.name.as_str(),
+                value
+            )
+        };
+
+        if let Some(fixed) = param.fixed.as_ref() {
+            return vec![format!(
+                "    {}",
+                insert(&format!(
+                    "serde_json::Value::String({}.to_string())",
+                    constants.fixed(param.name.as_str(), fixed)
+                ))
+            )];
+        }
+
+        let (param_type, _annotations) = param_rust_type(
+            container,
+            param,
+            config,
+            resource_type_rust_type,
+            options_names,
+        );
+        let is_optional = param_type.starts_with("Option<");
+        let is_array = param_type.starts_with("Vec<") || param_type.starts_with("Option<Vec<");
+
+        let value = match (param.links.is_empty(), is_array) {
+            (true, _) => format!("serde_json::to_value({})?", param_name),
+            (false, false) => format!(
+                "serde_json::Value::String({}.url().to_string())",
+                param_name
+            ),
+            (false, true) => format!(
+                "serde_json::Value::Array({}.iter().map(|x| serde_json::Value::String(x.url().to_string())).collect())",
+                param_name
+            ),
+        };
+
+        if is_optional {
+            vec![
+                format!("    if let Some({}) = {} {{\n", param_name, param_name),
+                format!("        {}", insert(&value)),
+                "    }\n".to_string(),
+            ]
+        } else {
+            vec![format!("    {}", insert(&value))]
+        }
+    }
+
+    /// Add a file upload param to a multipart form, streaming its contents
+    fn upload_part(param: &Param, config: &Config) -> Vec<String> {
+        let param_name = escape_rust_reserved(param.name.as_str());
+        let mut indent = 4;
+        let mut lines = vec![];
+        if !param.required {
+            lines.push(format!(
+                "{:indent$}if let Some({}) = {} {{\n",
+                "", param_name, param_name
+            ));
+            indent += 4;
+        }
+        if param.repeating {
+            lines.push(format!(
+                "{:indent$}for {} in {} {{\n",
+                "", param_name, param_name
+            ));
+            indent += 4;
+        }
+        lines.push(format!(
+            "{:indent$}form = form.part(\"{}\", {}.into_part(){}?);\n",
+            "",
+            param.name,
+            param_name,
+            if config.async_client() { ".await" } else { "" }
+        ));
+        while indent > 4 {
+            indent -= 4;
+            lines.push(format!("{:indent$}}}\n", ""));
+        }
+        lines
+    }
+
+    let container = ParamContainer::Representation(def);
+
+    match def.media_type.as_ref().map(|s| s.to_string()).as_deref() {
+        // Methods with these bodies are dropped by `filter_application` for other clients
+        Some("multipart/form-data") => {
+            let mp_mod = if !config.async_client() {
+                "reqwest::blocking"
+            } else {
+                "reqwest"
+            };
+            lines.push(format!(
+                "let mut form = {}::multipart::Form::new();\n",
+                mp_mod
+            ));
+            for param in def.params.iter() {
+                if is_upload_param(def, param, config) {
+                    lines.extend(upload_part(param, config));
+                    continue;
+                }
+                lines.extend(process_param(
+                    param,
+                    &container,
+                    config,
+                    |param_type, name, value| {
+                        format!(
+                            "form = form.part(\"{}\", {});",
+                            name,
+                            if let Some(convert_to_multipart) = config
+                                .convert_to_multipart
+                                .as_ref()
+                                .and_then(|x| x(param_type, value))
+                            {
+                                convert_to_multipart
+                            } else {
+                                format!(
+                                    "{}::multipart::Part::text({})",
+                                    mp_mod,
+                                    value.strip_prefix('&').unwrap_or(value)
+                                )
+                            }
+                        )
+                    },
+                    options_names,
+                    constants,
+                ));
+            }
+            lines.push("req = req.multipart(form);\n".to_string());
+        }
+        Some("application/x-www-form-urlencoded") => {
+            lines.push(
+                "let mut serializer = form_urlencoded::Serializer::new(String::new());\n"
+                    .to_string(),
+            );
+            for param in def.params.iter() {
+                lines.extend(process_param(param, &container, config, |r#type, name, value| {
+                    if r#type.contains("[") {
+                        format!("for value in {} {{ serializer.append_pair(\"{}\", &value.to_string()); }}", value.strip_prefix("&").unwrap().strip_suffix(".to_string()").unwrap(), name)
+                    } else {
+                        format!("serializer.append_pair(\"{}\", {});", name, value)
+                    }
+                }, options_names, constants));
+            }
+            lines.push(format!("req = req.header({}::header::CONTENT_TYPE, \"application/x-www-form-urlencoded\");\n", config.http_module()));
+            if config.uses_http_crate() {
+                lines.push("let body_ = serializer.finish().into_bytes();\n".to_string());
+            } else {
+                lines.push("req = req.body(serializer.finish());\n".to_string());
+            }
+        }
+        Some("application/json") => {
+            lines.push("let mut o = serde_json::Map::new();\n".to_string());
+
+            for param in def.params.iter() {
+                lines.extend(json_param(
+                    param,
+                    &container,
+                    config,
+                    options_names,
+                    constants,
+                ));
+            }
+
+            lines.extend(serialize_json_body("o", "application/json", config));
+        }
+        Some(media_type @ (JSON_PATCH_MIME_TYPE | MERGE_PATCH_MIME_TYPE)) => {
+            lines.extend(serialize_json_body("patch", media_type, config));
+        }
+        o => {
+            panic!("unsupported media type {:?}", o);
+        }
+    }
+    lines
+}
+
+fn generate_method(
+    input: &Method,
+    parent_id: &str,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+    constants: &Constants,
+    conditional: bool,
+) -> Vec<String> {
+    let lines =
+        generate_method_representation(input, parent_id, config, options_names, constants, false);
+    #[cfg(feature = "templates")]
+    let lines = templates::apply(
+        config.templates.as_ref(),
+        TemplateKind::Method,
+        lines,
+        |default| {
+            let name = method_rust_name(input, parent_id, config);
+            MethodContext {
+                ret_type: method_return_type(input, &name, config, options_names).0,
+                name,
+                id: input.id.clone(),
+                http_method: input.name.clone(),
+                resource_type: parent_id.to_string(),
+                default,
+            }
+        },
+    );
+    let mut lines = lines;
+
+    if conditional && ["PUT", "PATCH"].contains(&input.name.as_str()) {
+        lines.extend(generate_method_representation(
+            input,
+            parent_id,
+            config,
+            options_names,
+            constants,
+            true,
+        ));
+    }
+
+    for response in input.responses.iter() {
+        if response.representations.iter().any(|r| {
+            r.media_type().as_ref().map(|s| s.to_string()).as_deref() == Some(crate::WADL_MIME_TYPE)
+        }) {
+            lines.extend(generate_method_wadl(input, parent_id, config, constants))
+        }
+    }
+
+    lines.extend(generate_method_all(input, parent_id, config, options_names));
+    lines.extend(generate_method_refresh(
+        input,
+        parent_id,
+        config,
+        options_names,
+    ));
+
+    lines
+}
+
+/// Generate a variant of a `GET` method that takes the representation it returned before, and
+/// only fetches it again if it changed
+///
+/// This is only possible for methods without arguments that return a JSON representation
+/// along with its entity tag.
+fn generate_method_refresh(
+    input: &Method,
+    parent_id: &str,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+    if input.name != "GET"
+        || config.uses_http_crate()
+        || config.blocking_and_async
+        || !method_args(input, config, options_names).is_empty()
+    {
+        return lines;
+    }
+    let name = method_rust_name(input, parent_id, config);
+    let (ret_type, map_fn) = method_return_type(input, &name, config, options_names);
+    let representations = input
+        .responses
+        .iter()
+        .flat_map(supported_representations)
+        .collect::<Vec<_>>();
+    if map_fn.is_some()
+        || !ret_type.starts_with("wadl::Tagged<")
+        || representations.is_empty()
+        || representations
+            .iter()
+            .any(|r| representation_media_type(r).0 != mime::APPLICATION_JSON)
+    {
+        return lines;
+    }
+    let refresh_name = if name == "get" {
+        "refresh".to_string()
+    } else {
+        format!("{}_refresh", name)
+    };
+
+    lines.extend(cfg_line(&config.method_features(input, parent_id), 1));
+    lines.extend(config.deprecated_attr(&input.docs, 1));
+    lines.push(format!(
+        "    /// Fetch the representation returned by [`Self::{}`] again, if it changed\n",
+        name
+    ));
+    lines.push("    ///\n".to_string());
+    lines.push("    /// Returns whether it changed.\n".to_string());
+    let visibility = config
+        .item_visibility(ItemKind::Method, &name)
+        .unwrap_or_default()
+        .to_string();
+    let (async_prefix, module, await_suffix) = if config.is_async() {
+        ("async ", "wadl::r#async", ".await")
+    } else {
+        ("", "wadl::blocking", "")
+    };
+    lines.push(format!(
+        "    {}{}fn {}<'a>(&self, client: {}, representation: &mut {}) -> std::result::Result<bool, wadl::Error> {{\n",
+        if visibility.is_empty() {
+            "".to_string()
+        } else {
+            format!("{} ", visibility)
+        },
+        async_prefix,
+        refresh_name,
+        config.client_arg_type(),
+        ret_type
+    ));
+    lines.push(format!(
+        "        {}::refresh(client, self.url(), representation){}\n",
+        module, await_suffix
+    ));
+    lines.push("    }\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+/// Generate a variant of a `GET` method that returns a page of a collection, which iterates
+/// over the items of all pages, see [`Config::pagination`]
+///
+/// This takes a `dyn Client`, since the client is kept to fetch the following pages.
+fn generate_method_all(
+    input: &Method,
+    parent_id: &str,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+    if input.name != "GET" || !config.uses_dyn_client() || config.blocking_and_async {
+        return lines;
+    }
+    let name = method_rust_name(input, parent_id, config);
+    let (page_type, map_fn) = method_return_type(input, &name, config, options_names);
+    let Some(next_page) = NEXT_PAGE_EXPRS
+        .with(|p| p.borrow().get(&page_type).cloned())
+        .filter(|_| map_fn.is_none())
+    else {
+        return lines;
+    };
+    let pager_type = if config.is_async() {
+        format!("wadl::paginate::PageStream<'a, {}>", page_type)
+    } else {
+        format!("wadl::paginate::Pager<'a, {}>", page_type)
+    };
+    let args = method_args(input, config, options_names)
+        .into_iter()
+        .map(|(arg, _)| format!(", {}", arg))
+        .collect::<String>();
+
+    lines.extend(cfg_line(&config.method_features(input, parent_id), 1));
+    if config.is_async() {
+        lines.push("    #[cfg(not(target_arch = \"wasm32\"))]\n".to_string());
+    }
+    lines.extend(config.deprecated_attr(&input.docs, 1));
+    lines.push(format!(
+        "    /// Iterate over the items of all pages returned by [`Self::{}`]\n",
+        name
+    ));
+    let visibility = config
+        .item_visibility(ItemKind::Method, &name)
+        .unwrap_or_default()
+        .to_string();
+    lines.push(format!(
+        "    {}{} {{\n",
+        if visibility.is_empty() {
+            "".to_string()
+        } else {
+            format!("{} ", visibility)
+        },
+        method_signature(
+            input,
+            &format!("{}_all", name),
+            &pager_type,
+            &[],
+            config,
+            options_names
+        )
+    ));
+    if config.is_async() {
+        lines.push(format!(
+            "        let first = self.{}(client{}).await?;\n",
+            name, args
+        ));
+        lines.push(
+            "        Ok(wadl::paginate::PageStream::from_page(first, move |url| Box::pin(async move {\n"
+                .to_string(),
+        );
+        lines.push(
+            "            let mut req = client.request(reqwest::Method::GET, url).await;\n"
+                .to_string(),
+        );
+        lines.push(
+            "            req = req.header(reqwest::header::ACCEPT, \"application/json\");\n"
+                .to_string(),
+        );
+        lines.push("            let resp = wadl::r#async::send(client, req).await?;\n".to_string());
+        lines.push(format!(
+            "            wadl::r#async::parse_json_response::<{}>(resp, &[]).await\n",
+            page_type
+        ));
+        lines.push(format!("        }}), |page| {}))\n", next_page));
+    } else {
+        lines.push(format!(
+            "        let first = self.{}(client{})?;\n",
+            name, args
+        ));
+        lines.push("        Ok(wadl::paginate::Pager::from_page(first, move |url| {\n".to_string());
+        lines.push(
+            "            let mut req = client.request(reqwest::Method::GET, url.clone());\n"
+                .to_string(),
+        );
+        lines.push(
+            "            req = req.header(reqwest::header::ACCEPT, \"application/json\");\n"
+                .to_string(),
+        );
+        lines.push("            let resp = client.send(req)?;\n".to_string());
+        lines.push(format!(
+            "            wadl::blocking::parse_json_response::<{}>(resp, &[])\n",
+            page_type
+        ));
+        lines.push(format!("        }}, |page| {}))\n", next_page));
+    }
+    lines.push("    }\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+fn generate_method_wadl(
+    input: &Method,
+    parent_id: &str,
+    config: &Config,
+    constants: &Constants,
+) -> Vec<String> {
+    let mut lines = vec![];
+
+    let name = method_rust_name(input, parent_id, config);
+
+    let async_prefix = if config.is_async() { "async " } else { "" };
+
+    lines.extend(cfg_line(&config.method_features(input, parent_id), 1));
+    lines.extend(config.deprecated_attr(&input.docs, 1));
+    lines.push(format!("    pub {}fn {}_wadl<'a>(&self, client: {}) -> std::result::Result<wadl::ast::Resource, wadl::Error> {{\n", async_prefix, name, config.client_arg_type()));
+
+    lines.push("        let mut url_ = self.url().clone();\n".to_string());
+    for param in input
+        .request
+        .params
+        .iter()
+        .filter(|p| p.style == ParamStyle::Query)
+    {
+        if let Some(fixed) = param.fixed.as_ref() {
+            assert!(!param.repeating);
+            lines.push(format!(
+                "        url_.query_pairs_mut().append_pair(\"{}\", {});\n",
+                param.name,
+                constants.fixed(param.name.as_str(), fixed)
+            ));
+        }
+    }
+
+    lines.push("\n".to_string());
+
+    let method = input.name.as_str();
+    lines.push(format!("        {}", new_request(method, config)));
+
+    lines.push(format!(
+        "        req = req.header({}::header::ACCEPT, wadl::WADL_MIME_TYPE);\n",
+        config.http_module(),
+    ));
+
+    lines.push("\n".to_string());
+
+    if config.uses_http_crate() {
+        lines.extend(indent(2, send_request(false, None, config).into_iter()));
+        lines.push("        if !resp.status().is_success() {\n".to_string());
+        lines.push(
+            "            return Err(wadl::Error::UnhandledStatus(resp.status()));\n".to_string(),
+        );
+        lines.push("        }\n".to_string());
+        lines.push("        let wadl: wadl::ast::Application = String::from_utf8_lossy(resp.body()).parse()?;\n".to_string());
+    } else if config.async_client() {
+        lines.push("        let wadl: wadl::ast::Application = wadl::r#async::send(client, req).await?.error_for_status()?.text().await?.parse()?;\n".to_string());
+    } else {
+        lines.push("        let wadl: wadl::ast::Application = client.send(req)?.error_for_status()?.text()?.parse()?;\n".to_string());
+    }
+    lines.push("        let resource = wadl.get_resource_by_href(self.url()).ok_or_else(|| wadl::Error::ResourceNotFound(self.url().clone()))?;\n".to_string());
+
+    lines.push("        Ok(resource.clone())\n".to_string());
+
+    lines.push("    }\n".to_string());
+
+    lines.push("\n".to_string());
+
+    lines
+}
+
+/// Return the Rust name of the function for a method
+fn method_rust_name(input: &Method, parent_id: &str, config: &Config) -> String {
+    if let Some(name) = config.method_renames.get(input.id.as_str()) {
+        return name.clone();
+    }
+    let name = input.id.as_str();
+    let name = name
+        .strip_prefix(format!("{}-", parent_id).as_str())
+        .unwrap_or(name);
+    snake_case_name(name)
+}
+
+/// Return the Rust return type of a method, and the function used to map the response to it
+fn method_return_type(
+    input: &Method,
+    name: &str,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> (String, Option<String>) {
+    if let Some(return_type) = raw_return_type(input, config) {
+        let (return_type, map_fn) = match config
+            .map_type_for_response
+            .as_ref()
+            .and_then(|r| r(name, &return_type, config))
+        {
+            Some((map_type, map_fn)) => (map_type, Some(map_fn)),
+            None => (return_type, None),
+        };
+        (return_type, map_fn)
+    } else if input.responses.is_empty() {
+        ("()".to_string(), None)
+    } else {
+        assert_eq!(1, input.responses.len(), "expected 1 response for {}", name);
+        let mut return_type =
+            rust_type_for_response(input, &input.responses[0], input.id.as_str(), options_names);
+        let map_fn = if let Some((map_type, map_fn)) = config
+            .map_type_for_response
+            .as_ref()
+            .and_then(|r| r(name, &return_type, config))
+        {
+            return_type = map_type;
+            Some(map_fn)
+        } else {
+            None
+        };
+        (return_type, map_fn)
+    }
+}
+
+/// Return the header params of the responses of a `HEAD` method, deduplicated by name
+fn head_header_params(input: &Method) -> Vec<(&Response, &Param)> {
+    let mut ret: Vec<(&Response, &Param)> = vec![];
+    for response in &input.responses {
+        for param in response
+            .params
+            .iter()
+            .filter(|p| p.style == ParamStyle::Header)
+        {
+            if !ret
+                .iter()
+                .any(|(_, p)| p.name.eq_ignore_ascii_case(param.name.as_str()))
+            {
+                ret.push((response, param));
+            }
+        }
+    }
+    ret
+}
+
+/// Return the name of the struct with the response headers of a `HEAD` method
+fn head_headers_struct_name(input: &Method) -> String {
+    format!("{}Headers", camel_case_name(input.id.as_str()))
+}
+
+/// Return the name of the field for a header in a response headers struct
+fn header_field_name(param: &Param) -> String {
+    if is_etag_param(param) {
+        return "etag".to_string();
+    }
+    let name = snake_case_name(param.name.as_str())
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    escape_rust_reserved(name.as_str()).to_string()
+}
+
+/// Return the media types of the streamed response bodies of a method
+///
+/// Bodies are only streamed if the method has no other (JSON) response representations.
+fn streaming_media_types(input: &Method, config: &Config) -> Vec<String> {
+    let representations = input
+        .responses
+        .iter()
+        .flat_map(|r| r.representations.iter())
+        .collect::<Vec<_>>();
+    if representations
+        .iter()
+        .any(|r| matches!(r, Representation::Reference(_)))
+    {
+        return vec![];
+    }
+    let mut ret = vec![];
+    for media_type in representations.iter().filter_map(|r| r.media_type()) {
+        if config.is_streaming_media_type(media_type) && !ret.contains(&media_type.to_string()) {
+            ret.push(media_type.to_string());
+        }
+    }
+    ret
+}
+
+/// Return the return type of a method whose response body is not deserialized
+///
+/// `HEAD` methods return a struct with the response headers described in the WADL,
+/// `OPTIONS` methods the list of methods from the `Allow` header, and methods with binary
+/// response bodies a stream of the body.
+fn raw_return_type(input: &Method, config: &Config) -> Option<String> {
+    match input.name.as_str() {
+        "HEAD" if head_header_params(input).is_empty() => Some("()".to_string()),
+        "HEAD" => Some(head_headers_struct_name(input)),
+        "OPTIONS" => Some(format!("Vec<{}::Method>", config.http_module())),
+        _ if !streaming_media_types(input, config).is_empty() => {
+            Some(config.byte_stream_type().to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Generate the structs with the response headers of the `HEAD` methods in an application
+fn generate_head_header_structs(
+    app: &Application,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+    for method in app
+        .resource_types
+        .iter()
+        .flat_map(|rt| rt.methods.iter())
+        .filter(|m| m.name == "HEAD")
+    {
+        let params = head_header_params(method);
+        if params.is_empty() {
+            continue;
+        }
+        let name = head_headers_struct_name(method);
+        lines.push(format!(
+            "/// Response headers of the `{}` method\n",
+            method.id
+        ));
+        lines.extend(derive_line(&[
+            "Debug".to_string(),
+            "Clone".to_string(),
+            "PartialEq".to_string(),
+            "Default".to_string(),
+        ]));
+        lines.push(format!(
+            "{}struct {} {{\n",
+            config
+                .item_visibility(ItemKind::Representation, &name)
+                .unwrap_or_default()
+                .prefix(),
+            name
+        ));
+        for (response, param) in params {
+            let (param_type, _annotations) = param_rust_type(
+                &ParamContainer::Response(method, response),
+                param,
+                config,
+                |_x| "url::Url".to_string(),
+                options_names,
+            );
+            let param_type = param_type
+                .strip_prefix("Option<")
+                .and_then(|t| t.strip_suffix('>'))
+                .unwrap_or(param_type.as_str());
+            if let Some(doc) = param.doc.as_ref() {
+                lines.extend(generate_doc(doc, 1, config));
+            } else {
+                lines.push(format!("    /// The `{}` header\n", param.name));
+            }
+            lines.push(format!(
+                "    pub {}: Option<{}>,\n",
+                header_field_name(param),
+                param_type
+            ));
+        }
+        lines.push("}\n".to_string());
+        lines.push("\n".to_string());
+    }
+    lines
+}
+
+/// Check whether a param of a request representation is a file upload
+///
+/// Binary params of `multipart/form-data` representations are sent as streamed parts,
+/// rather than as text.
+fn is_upload_param(def: &RepresentationDef, param: &Param, config: &Config) -> bool {
+    if def.media_type.as_ref().map(|m| m.essence_str()) != Some("multipart/form-data")
+        || param.fixed.is_some()
+        || !param.links.is_empty()
+        || param.options.is_some()
+    {
+        return false;
+    }
+    let container = ParamContainer::Representation(def);
+    simple_type_rust_type(&container, param.r#type.as_str(), param, config).binary
+}
+
+/// Return the params of the request representations of a method that are file uploads
+fn method_upload_params<'a>(input: &'a Method, config: &Config) -> Vec<&'a Param> {
+    input
+        .request
+        .representations
+        .iter()
+        .filter_map(|r| match r {
+            Representation::Definition(d) => Some(
+                d.params
+                    .iter()
+                    .filter(move |p| is_upload_param(d, p, config)),
+            ),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Return the argument type for a file upload param
+fn upload_rust_type(param: &Param, config: &Config) -> String {
+    let mut ret = config.upload_type().to_string();
+    if param.repeating {
+        ret = format!("Vec<{}>", ret);
+    }
+    if !param.required {
+        ret = format!("Option<{}>", ret);
+    }
+    ret
+}
+
+/// Return whether a param directly on a request can be sent
+///
+/// Only header and query params are valid on requests; other styles are ignored.
+fn is_supported_request_param(param: &Param) -> bool {
+    [ParamStyle::Header, ParamStyle::Query].contains(&param.style)
+}
+
+/// Return all request params of a method, including those of request representations
+fn method_request_params(input: &Method) -> Vec<&Param> {
+    let mut params = input
+        .request
+        .params
+        .iter()
+        .filter(|p| is_supported_request_param(p))
+        .collect::<Vec<_>>();
+
+    params.extend(
+        input
+            .request
+            .representations
+            .iter()
+            .filter_map(|r| match r {
+                Representation::Definition(d) if !is_patch_representation(d) => Some(&d.params),
+                _ => None,
+            })
+            .flatten(),
+    );
+
+    params
+}
+
+/// Return the arguments of the function for a method, excluding `self` and the client
+fn method_args(
+    input: &Method,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<(String, String)> {
+    let mut args = vec![];
+    for representation in &input.request.representations {
+        match representation {
+            Representation::Definition(d) => match d.media_type.as_ref().map(|m| m.essence_str()) {
+                Some(JSON_PATCH_MIME_TYPE) => {
+                    args.push(("patch".to_string(), "&[wadl::PatchOperation]".to_string()));
+                }
+                Some(MERGE_PATCH_MIME_TYPE) => {
+                    args.push((
+                        "patch".to_string(),
+                        format!("&{}", patch_struct_name(input)),
+                    ));
+                }
+                _ => {}
+            },
+            Representation::Reference(r) => {
+                let id = type_name(ItemKind::Representation, r.id().unwrap());
+                args.push(("representation".to_string(), format!("&{}", id)));
+            }
+        }
+    }
+
+    let container = ParamContainer::Request(input, &input.request);
+    let uploads = method_upload_params(input, config);
+    for param in method_request_params(input) {
+        if param.fixed.is_some() {
+            continue;
+        }
+        // Params directly on the request are referred to by their snake case name when the
+        // request is built, those of representations by their own name
+        let param_name = if input.request.params.iter().any(|p| std::ptr::eq(p, param)) {
+            snake_case_name(param.name.as_str())
+        } else {
+            param.name.clone()
+        };
+        let param_name = escape_rust_reserved(param_name.as_str());
+        if uploads.iter().any(|u| std::ptr::eq(*u, param)) {
+            args.push((param_name.to_string(), upload_rust_type(param, config)));
+            continue;
+        }
+        let (param_type, _annotations) = param_rust_type(
+            &container,
+            param,
+            config,
+            resource_type_rust_type,
+            options_names,
+        );
+        let param_type = readonly_rust_type(param_type.as_str());
+
+        args.push((param_name.to_string(), param_type));
+    }
+    args
+}
+
+/// Return the signature of the function for a method, without visibility
+fn method_signature(
+    input: &Method,
+    name: &str,
+    ret_type: &str,
+    extra_args: &[(String, String)],
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> String {
+    let mut line = format!(
+        "{}fn {}<'a>(&self, client: {}",
+        if config.is_async() { "async " } else { "" },
+        name,
+        config.client_arg_type()
+    );
+
+    let mut args = method_args(input, config, options_names);
+    args.extend(extra_args.iter().cloned());
+    for (arg_name, arg_type) in args {
+        line.push_str(format!(", {}: {}", arg_name, arg_type).as_str());
+    }
+
+    line.push_str(format!(") -> std::result::Result<{}, wadl::Error>", ret_type).as_str());
+    line
+}
+
+/// Return the value of the `Accept` header for the responses of a method
+fn accept_header(input: &Method, config: &Config) -> Option<String> {
+    let streaming = streaming_media_types(input, config);
+    if !streaming.is_empty() {
+        return Some(streaming.join(", "));
+    }
+    let response_mime_types = input
+        .responses
+        .iter()
+        .flat_map(|x| {
+            x.representations.iter().filter_map(|x| match x {
+                Representation::Definition(ref d) if supported_representation_def(d) => {
+                    d.media_type.clone()
+                }
+                Representation::Reference(_) => Some(representation_media_type(x).0),
+                _ => None,
+            })
+        })
+        .map(|x| x.to_string())
+        .fold(vec![], |mut types, t| {
+            if !types.contains(&t) {
+                types.push(t);
+            }
+            types
+        });
+
+    if response_mime_types.is_empty() {
+        None
+    } else {
+        Some(response_mime_types.join(", "))
+    }
+}
+
+/// Return whether a param is the `ETag` response header
+fn is_etag_param(param: &Param) -> bool {
+    param.style == ParamStyle::Header
+        && param.links.is_empty()
+        && param.name.eq_ignore_ascii_case("etag")
+}
+
+/// Return whether any of the methods of a resource type return an entity tag
+fn returns_etag(input: &ResourceType) -> bool {
+    input
+        .methods
+        .iter()
+        .flat_map(|m| m.responses.iter())
+        .any(|r| r.params.iter().any(is_etag_param))
+}
+
+/// Generate the function for a method
+///
+/// If `if_match` is set, a variant is generated that takes an entity tag, which is sent
+/// in the `If-Match` header, and that fails with [`crate::Error::PreconditionFailed`] if
+/// the resource was modified.
+fn generate_method_representation(
+    input: &Method,
+    parent_id: &str,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+    constants: &Constants,
+    if_match: bool,
+) -> Vec<String> {
+    let mut lines = vec![];
+
+    let name = method_rust_name(input, parent_id, config);
+
+    let (ret_type, map_fn) = method_return_type(input, &name, config, options_names);
+
+    let (name, extra_args) = if if_match {
+        (
+            format!("{}_if_match", name),
+            vec![("etag".to_string(), "&str".to_string())],
+        )
+    } else {
+        (name, vec![])
+    };
+
+    let visibility = config
+        .item_visibility(ItemKind::Method, &name)
+        .unwrap_or_default()
+        .to_string();
+
+    let params = method_request_params(input);
+
+    lines.extend(cfg_line(&config.method_features(input, parent_id), 1));
+    for doc in &input.docs {
+        lines.extend(generate_doc(doc, 1, config));
+    }
+    lines.extend(config.deprecated_attr(&input.docs, 1));
+
+    if !params.is_empty() || if_match {
+        lines.push("    /// # Arguments\n".to_string());
+    }
+
+    for param in &params {
+        if param.fixed.is_some() {
+            continue;
+        }
+        let param_name = escape_rust_reserved(param.name.as_str());
+        lines.extend(format_arg_doc(&param_name, param.doc.as_ref(), config));
+    }
+
+    if if_match {
+        lines.push(
+            "    /// * `etag`: The entity tag of the resource, as returned when it was fetched\n"
+                .to_string(),
+        );
+    }
+
+    lines.push(format!(
+        "    {}{} {{\n",
+        if visibility.is_empty() {
+            "".to_string()
+        } else {
+            format!("{} ", visibility)
+        },
+        method_signature(input, &name, &ret_type, &extra_args, config, options_names)
+    ));
+
+    let container = ParamContainer::Request(input, &input.request);
+
+    for param in input
+        .request
+        .params
+        .iter()
+        .filter(|p| !is_supported_request_param(p))
+    {
+        log::warn!(
+            "Ignoring {:?} param {} on request of method {}",
+            param.style,
+            param.name,
+            input.id
+        );
+    }
+
+    lines.push("        let mut url_ = self.url().clone();\n".to_string());
+    for param in input
+        .request
+        .params
+        .iter()
+        .filter(|p| p.style == ParamStyle::Query)
+    {
+        if let Some(fixed) = param.fixed.as_ref() {
+            assert!(!param.repeating);
+            lines.push(format!(
+                "        url_.query_pairs_mut().append_pair(\"{}\", {});\n",
+                param.name,
+                constants.fixed(param.name.as_str(), fixed)
+            ));
+        } else {
+            let param_name = param.name.as_str();
+            let param_name = snake_case_name(param_name);
+            let param_name = escape_rust_reserved(param_name.as_str());
+            let (param_type, _annotations) = param_rust_type(
+                &container,
+                param,
+                config,
+                resource_type_rust_type,
+                options_names,
+            );
+            let value = if !param.links.is_empty() {
+                format!("&{}.url().to_string()", param_name)
+            } else {
+                format!("&{}.to_string()", param_name)
+            };
+
+            let mut indent = 0;
+
+            let needs_iter = param.repeating
+                || param_type.starts_with("Vec<")
+                || param_type.starts_with("Option<Vec<");
+
+            if param_type.starts_with("Option<") {
+                lines.push(format!(
+                    "        if let Some({}) = {} {{\n",
+                    param_name, param_name
+                ));
+                indent += 4;
+            }
+            let style = if needs_iter {
+                config.query_array_style_for(
+                    &method_rust_name(input, parent_id, config),
+                    param.name.as_str(),
+                )
+            } else {
+                QueryArrayStyle::Repeat
+            };
+            let key = match style {
+                QueryArrayStyle::Brackets => format!("{}[]", param.name),
+                _ => param.name.clone(),
+            };
+            if needs_iter && style == QueryArrayStyle::Comma {
+                lines.push(format!(
+                    "{:indent$}        if !{}.is_empty() {{\n",
+                    "", param_name
+                ));
+                indent += 4;
+                lines.push(format!(
+                    "{:indent$}        url_.query_pairs_mut().append_pair(\"{}\", &{}.iter().map(|{}| {}).collect::<Vec<_>>().join(\",\"));\n",
+                    "",
+                    key,
+                    param_name,
+                    param_name,
+                    value.strip_prefix('&').unwrap(),
+                ));
+            } else {
+                if needs_iter {
+                    lines.push(format!(
+                        "{:indent$}        for {} in {} {{\n",
+                        "", param_name, param_name
+                    ));
+                    indent += 4;
+                }
+                lines.push(format!(
+                    "{:indent$}        url_.query_pairs_mut().append_pair(\"{}\", {});\n",
+                    "",
+                    key,
+                    value,
+                    indent = indent
+                ));
+            }
+            while indent > 0 {
+                lines.push(format!("{:indent$}    }}\n", "", indent = indent));
+                indent -= 4;
+            }
+        }
+    }
+
+    lines.push("\n".to_string());
+
+    let method = input.name.as_str();
+    lines.push(format!("        {}", new_request(method, config)));
+
+    let has_body = !input.request.representations.is_empty();
+    for representation in &input.request.representations {
+        match representation {
+            Representation::Definition(ref d) => {
+                lines.extend(indent(
+                    2,
+                    serialize_representation_def(d, config, options_names, constants).into_iter(),
+                ));
+            }
+            Representation::Reference(_r) => {
+                // TODO(jelmer): Support non-JSON representations
+                lines.extend(indent(
+                    2,
+                    serialize_json_body("representation", "application/json", config).into_iter(),
+                ));
+            }
+        };
+    }
+
+    if let Some(accept) = accept_header(input, config) {
+        lines.push(format!(
+            "        req = req.header({}::header::ACCEPT, {});\n",
+            config.http_module(),
+            constants.media_type(accept.as_str())
+        ));
+    }
+
+    for param in params.iter().filter(|p| p.style == ParamStyle::Header) {
+        let value = if let Some(fixed) = param.fixed.as_ref() {
+            constants.fixed(param.name.as_str(), fixed)
+        } else {
+            let param_name = param.name.as_str();
+            let param_name = snake_case_name(param_name);
+            let param_name = escape_rust_reserved(param_name.as_str());
+
+            if !param.required {
+                lines.push(format!(
+                    "        if let Some({}) = {} {{\n",
+                    param_name, param_name
+                ));
+                lines.push(format!(
+                    "            req = req.header(\"{}\", &{}.to_string());\n",
+                    param.name, param_name
+                ));
+                lines.push("        }\n".to_string());
+                continue;
+            }
+            format!("&{}.to_string()", param_name)
+        };
+
+        lines.push(format!(
+            "        req = req.header(\"{}\", {});\n",
+            param.name, value
+        ));
+    }
+
+    if if_match {
+        lines.push(format!(
+            "        req = req.header({}::header::IF_MATCH, etag);\n",
+            config.http_module()
+        ));
+    }
+
+    lines.push("\n".to_string());
+    let policy = config.request_policy.as_ref().and_then(|f| f(&input.id));
+    let send = send_request(has_body, policy.as_ref(), config);
+    lines.extend(indent(
+        2,
+        observe_request(send, &input.id, config).into_iter(),
+    ));
+
+    // HEAD and OPTIONS responses have no body, so only their headers are returned
+    let raw_response = raw_return_type(input, config).is_some();
+    if !if_match && !raw_response && input.name != "HEAD" && input.name != "OPTIONS" {
+        if let Some(response_lines) = generate_simple_response(input, map_fn.as_deref(), config) {
+            lines.extend(indent(2, response_lines.into_iter()));
+            lines.push("    }\n".to_string());
+            lines.push("\n".to_string());
+            if let Some(extend_method) = config.extend_method.as_ref() {
+                lines.extend(extend_method(parent_id, &name, &ret_type, config));
+            }
+            for hook in &config.hooks {
+                lines.extend(hook.after_method(input, parent_id, &name, &ret_type, config));
+            }
+            return lines;
+        }
+    }
+
+    lines.push("        match resp.status() {\n".to_string());
+    if if_match {
+        lines.push(
+            "            s if s.as_u16() == 412 => Err(wadl::Error::PreconditionFailed),\n"
+                .to_string(),
+        );
+    }
+
+    let serialize_return_types = |return_types: Vec<(String, bool)>, etag: bool| {
+        if etag {
+            let v = match return_types.as_slice() {
+                [] => "()".to_string(),
+                [(v, _)] => v.clone(),
+                vs => format!(
+                    "({})",
+                    vs.iter()
+                        .map(|x| x.0.clone())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+            let v = format!("wadl::Tagged {{ value: {}, etag: etag_ }}", v);
+            format!("Ok({})", apply_map_fn(map_fn.as_deref(), &v, true))
+        } else if return_types.is_empty() {
+            "Ok(())".to_string()
+        } else if return_types.len() == 1 {
+            format!(
+                "Ok({})",
+                apply_map_fn(map_fn.as_deref(), &return_types[0].0, return_types[0].1)
+            )
+        } else {
+            let v = format!(
+                "({})",
+                return_types
+                    .iter()
+                    .map(|x| x.0.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            format!("Ok({})", apply_map_fn(map_fn.as_deref(), &v, true))
+        }
+    };
+
+    if input.name == "HEAD" {
+        let params = head_header_params(input);
+        let value = if params.is_empty() {
+            "()".to_string()
+        } else {
+            format!(
+                "{} {{ {} }}",
+                head_headers_struct_name(input),
+                params
+                    .iter()
+                    .map(|(_, p)| format!(
+                        "{}: resp.headers().get(\"{}\").and_then(|x| x.to_str().ok()).and_then(|x| x.parse().ok())",
+                        header_field_name(p),
+                        p.name
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        lines.push(format!(
+            "            s if s.is_success() => Ok({}),\n",
+            apply_map_fn(map_fn.as_deref(), &value, true)
+        ));
+    } else if input.name == "OPTIONS" {
+        let value = format!(
+            "resp.headers().get_all({0}::header::ALLOW).iter().filter_map(|x| x.to_str().ok()).flat_map(|x| x.split(',')).filter_map(|x| {0}::Method::from_bytes(x.trim().as_bytes()).ok()).collect::<Vec<_>>()",
+            config.http_module()
+        );
+        lines.push(format!(
+            "            s if s.is_success() => Ok({}),\n",
+            apply_map_fn(map_fn.as_deref(), &value, true)
+        ));
+    } else if raw_response {
+        let value = if config.uses_http_crate() {
+            "resp.into_body()".to_string()
+        } else {
+            format!("{}::new(resp)", config.byte_stream_type())
+        };
+        lines.push(format!(
+            "            s if s.is_success() => Ok({}),\n",
+            apply_map_fn(map_fn.as_deref(), &value, true)
+        ));
+    }
+
+    let responses = if raw_response {
+        &[][..]
+    } else {
+        input.responses.as_slice()
+    };
+
+    for response in responses {
+        let mut return_types = vec![];
+        // Header values are read before the body, since reading the body consumes the response
+        let mut header_lines = vec![];
+        let etag = response.params.iter().any(is_etag_param);
+
+        for param in response.params.iter() {
+            match &param.style {
+                ParamStyle::Header if is_etag_param(param) => {
+                    header_lines.push(format!(
+                        "let etag_ = resp.headers().get({}::header::ETAG).and_then(|x| x.to_str().ok()).map(|x| x.to_string());\n",
+                        config.http_module()
+                    ));
+                }
+                ParamStyle::Header => {
+                    if !param.links.is_empty() {
+                        let r = &param.links[0].resource_type.as_ref().unwrap();
+                        let var_name = format!(
+                            "{}_",
+                            snake_case_name(param.name.as_str())
+                                .chars()
+                                .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                                .collect::<String>()
+                        );
+                        let value = format!(
+                            "resp.headers().get(\"{}\").and_then(|x| x.to_str().ok()).and_then(|x| x.parse().ok())",
+                            param.name
+                        );
+                        if param.required {
+                            header_lines.push(format!(
+                                "let {} = {}({}.ok_or_else(|| wadl::Error::InvalidHeader({:?}.to_string()))?);\n",
+                                var_name,
+                                resource_type_rust_type(r),
+                                value,
+                                param.name
+                            ));
+                        } else {
+                            header_lines.push(format!(
+                                "let {} = {}.map({});\n",
+                                var_name,
+                                value,
+                                resource_type_rust_type(r)
+                            ));
+                        }
+                        return_types.push((var_name, param.required));
+                    } else {
+                        todo!(
+                            "header param type {:?} for {} in {:?}",
+                            param.r#type,
+                            param.name,
+                            input.id
+                        );
+                    }
+                }
+                t => todo!("param style {:?}", t),
+            }
+        }
+
+        // TODO(jelmer): match on media type
+        if let Some(status) = response.status {
+            lines.push(format!("            s if s.as_u16() == {} => {{\n", status));
+        } else {
+            lines.push("            s if s.is_success() => {\n".to_string());
+        }
+        lines.extend(indent(4, header_lines.into_iter()));
+
+        let representations = supported_representations(response);
+
+        if !representations.is_empty() {
+            lines.push(
+                "                let content_type = wadl::response::content_type(resp.headers());\n"
+                    .to_string(),
+            );
+            lines.push(
+                "                match content_type.as_ref().map(|x| x.essence_str()) {\n"
+                    .to_string(),
+            );
+            for (pattern, representation) in content_type_arms(&representations) {
+                lines.push(format!("                    {} => {{\n", pattern));
+                let t = match representation {
+                    Representation::Definition(_) => None,
+                    Representation::Reference(r) => {
+                        let rt = representation_rust_type(r);
+                        let value = if is_form_media_type(Some(
+                            &representation_media_type(representation).0,
+                        )) {
+                            if config.uses_http_crate() {
+                                format!("{}::from_form_urlencoded(resp.body())?", rt)
+                            } else {
+                                format!(
+                                    "{}::from_form_urlencoded(&resp.bytes(){}?)?",
+                                    rt,
+                                    if config.async_client() { ".await" } else { "" }
+                                )
+                            }
+                        } else if config.uses_http_crate() {
+                            format!("serde_json::from_slice::<{}>(resp.body())?", rt)
+                        } else {
+                            format!(
+                                "resp.json::<{}>(){}?",
+                                rt,
+                                if config.async_client() { ".await" } else { "" }
+                            )
+                        };
+                        // Responses with several representations return an enum of them
+                        if representations.len() > 1 {
+                            Some((
+                                format!("{}::{}({})", response_enum_name(input), rt, value),
+                                true,
+                            ))
+                        } else {
+                            Some((value, true))
+                        }
+                    }
+                };
+                if let Some(t) = t {
+                    let mut return_types = return_types.clone();
+                    return_types.insert(0, t);
+                    lines.push(format!(
+                        "                             {}\n",
+                        serialize_return_types(return_types, etag)
+                    ));
+                } else {
+                    // Inline representation definitions are not parsed
+                    lines.push(
+                        "                        Err(wadl::Error::UnhandledContentType(content_type))\n"
+                            .to_string(),
+                    );
+                }
+                lines.push("                        }\n".to_string());
+            }
+            lines.push(
+                "                    _ => { Err(wadl::Error::UnhandledContentType(content_type)) }\n"
+                    .to_string(),
+            );
+            lines.push("                }\n".to_string());
+        } else {
+            lines.push(format!(
+                "                {}\n",
+                serialize_return_types(return_types, etag)
+            ));
+        }
+
+        lines.push("            }\n".to_string());
+    }
+    if input.responses.is_empty() && !raw_response {
+        lines.push("            s if s.is_success() => Ok(()),\n".to_string());
+    }
+    lines.push("            s => Err(wadl::Error::UnhandledStatus(s))\n".to_string());
+    lines.push("        }\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("\n".to_string());
+
+    if let Some(extend_method) = config.extend_method.as_ref().filter(|_| !if_match) {
+        lines.extend(extend_method(parent_id, &name, &ret_type, config));
+    }
+    if !if_match {
+        for hook in &config.hooks {
+            lines.extend(hook.after_method(input, parent_id, &name, &ret_type, config));
+        }
+    }
+
+    lines
+}
+
+/// Return the representations of a response that code can be generated for
+fn supported_representations(response: &Response) -> Vec<&Representation> {
+    response
+        .representations
+        .iter()
+        .filter(|r| match r {
+            Representation::Definition(ref d) => supported_representation_def(d),
+            Representation::Reference(_) => true,
+        })
+        .collect()
+}
+
+/// Return the name of the enum with the representations a method can return
+fn response_enum_name(input: &Method) -> String {
+    format!("{}Response", camel_case_name(input.id.as_str()))
+}
+
+/// Return the arms that match the content type of a response to its representations
+///
+/// Representations are matched on the essence of their media type (e.g. `application/json`).
+/// If several representations share a media type, they are told apart by their media type
+/// parameters and profile, falling back to the first of them if none match.
+fn content_type_arms<'a>(
+    representations: &[&'a Representation],
+) -> Vec<(String, &'a Representation)> {
+    let media_types = representations
+        .iter()
+        .map(|r| representation_media_type(r))
+        .collect::<Vec<_>>();
+    let shared = |essence: &str| {
+        media_types
+            .iter()
+            .filter(|(m, _)| m.essence_str() == essence)
+            .count()
+            > 1
+    };
+    let mut arms = vec![];
+    let mut fallbacks: Vec<(String, &Representation)> = vec![];
+    for (representation, (media_type, profile)) in representations.iter().zip(media_types.iter()) {
+        let essence = media_type.essence_str();
+        if !shared(essence) {
+            arms.push((format!("Some({:?})", essence), *representation));
+            continue;
+        }
+        arms.push((
+            format!(
+                "Some({:?}) if wadl::response::media_type_matches(content_type.as_ref(), {:?}, {})",
+                essence,
+                media_type.to_string(),
+                profile
+                    .as_ref()
+                    .map_or("None".to_string(), |p| format!("Some({:?})", p))
+            ),
+            *representation,
+        ));
+        let fallback = format!("Some({:?})", essence);
+        if !fallbacks.iter().any(|(p, _)| p == &fallback) {
+            fallbacks.push((fallback, *representation));
+        }
+    }
+    arms.extend(fallbacks);
+    arms
+}
+
+/// Generate enums for the methods that can return one of several representations
+fn generate_response_enums(app: &Application, config: &Config) -> Vec<String> {
+    let mut lines = vec![];
+    for method in app.resource_types.iter().flat_map(|rt| rt.methods.iter()) {
+        let mut variants = vec![];
+        let mut clone = true;
+        for response in &method.responses {
+            let representations = supported_representations(response);
+            if representations.len() < 2 {
+                continue;
+            }
+            for representation in representations {
+                if let Representation::Reference(r) = representation {
+                    let variant = representation_rust_type(r);
+                    if let Some(def) = app
+                        .representations
+                        .iter()
+                        .find(|d| d.id.is_some() && d.id.as_deref() == r.id())
+                    {
+                        clone &= config
+                            .representation_derives(def, &variant)
+                            .iter()
+                            .any(|d| d == "Clone");
+                    }
+                    if !variants.contains(&variant) {
+                        variants.push(variant);
+                    }
+                }
+            }
+        }
+        if variants.is_empty() {
+            continue;
+        }
+        lines.push(format!(
+            "/// The representations returned by the `{}` method\n",
+            method.id
+        ));
+        let mut derives = vec![
+            "Debug".to_string(),
+            "Clone".to_string(),
+            "PartialEq".to_string(),
+        ];
+        if !clone {
+            derives.retain(|d| d != "Clone");
+        }
+        lines.extend(derive_line(&derives));
+        lines.push(format!("pub enum {} {{\n", response_enum_name(method)));
+        for variant in variants {
+            lines.push(format!("    /// A [`{}`] representation\n", variant));
+            lines.push(format!("    {}({}),\n", variant, variant));
+        }
+        lines.push("}\n".to_string());
+        lines.push("\n".to_string());
+    }
+    lines
+}
+
+/// Generate the expression that parses the JSON body of `resp` as `rt`
+fn parse_json_response_expr(rt: &str, statuses: &str, config: &Config) -> String {
+    if config.uses_http_crate() {
+        format!(
+            "wadl::response::parse_json_http_response::<{}, _>(&resp, {})",
+            rt, statuses
+        )
+    } else if config.async_client() {
+        format!(
+            "wadl::r#async::parse_json_response::<{}>(resp, {}).await",
+            rt, statuses
+        )
+    } else {
+        format!(
+            "wadl::blocking::parse_json_response::<{}>(resp, {})",
+            rt, statuses
+        )
+    }
+}
+
+/// Generate the code that handles the response of a method using the runtime helpers
+///
+/// This covers methods with at most one response, which has no parameters and is either empty
+/// or a single JSON representation. Returns `None` if the response has to be handled inline.
+fn generate_simple_response(
+    input: &Method,
+    map_fn: Option<&str>,
+    config: &Config,
+) -> Option<Vec<String>> {
+    let (statuses, representations) = match input.responses.as_slice() {
+        [] => (vec![], vec![]),
+        [response] if response.params.is_empty() => (
+            response.status.into_iter().collect::<Vec<_>>(),
+            supported_representations(response),
+        ),
+        _ => return None,
+    };
+    let statuses = format!(
+        "&[{}]",
+        statuses
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    match representations.as_slice() {
+        [] => Some(vec![
+            format!(
+                "wadl::response::check_status(resp.status(), {})?;\n",
+                statuses
+            ),
+            "Ok(())\n".to_string(),
+        ]),
+        [representation @ Representation::Reference(r)]
+            if representation_media_type(representation).0.essence_str()
+                == mime::APPLICATION_JSON.essence_str() =>
+        {
+            let value = parse_json_response_expr(&representation_rust_type(r), &statuses, config);
+            Some(vec![match map_fn {
+                Some(_) => format!(
+                    "Ok({})\n",
+                    apply_map_fn(map_fn, &format!("{}?", value), true)
+                ),
+                None => format!("{}\n", value),
+            }])
+        }
+        _ => None,
+    }
+}
+
+/// Collect the path templates of resources with a resource type, and the ids of their types
+fn collect_resource_paths<'a>(
+    app: &'a Application,
+    prefix: &str,
+    resources: &'a [Resource],
+    ret: &mut Vec<(String, &'a str)>,
+) {
+    for resource in resources {
+        let path = match resource.path.as_deref().map(|p| p.trim_matches('/')) {
+            Some(p) if !p.is_empty() && !prefix.is_empty() => format!("{}/{}", prefix, p),
+            Some(p) if !p.is_empty() => p.to_string(),
+            _ => prefix.to_string(),
+        };
+        if let Some(resource_type) = resource
+            .r#type
+            .iter()
+            .find_map(|t| t.id().and_then(|id| app.get_resource_type_by_id(id)))
+        {
+            ret.push((path.clone(), resource_type.id.as_str()));
+        }
+        collect_resource_paths(app, &path, &resource.subresources, ret);
+    }
+}
+
+/// The path templates of the resources in an application, grouped by their base URL
+type ResourcePaths<'a> = Vec<(Option<&'a url::Url>, Vec<(String, &'a str)>)>;
+
+/// Return the path templates of the resources in an application, grouped by their base URL
+///
+/// Within a group, the most specific templates come first, so that literal segments win over
+/// template parameters when matching.
+fn resource_paths(app: &Application) -> ResourcePaths<'_> {
+    let mut ret = vec![];
+    for resources in &app.resources {
+        let mut paths = vec![];
+        collect_resource_paths(app, "", &resources.resources, &mut paths);
+        paths.sort_by_key(|(path, _)| {
+            let segments = path
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>();
+            (
+                std::cmp::Reverse(segments.len()),
+                segments.iter().filter(|s| s.starts_with('{')).count(),
+            )
+        });
+        ret.push((resources.base.as_ref(), paths));
+    }
+    ret
+}
+
+/// Generate the code that matches `url` against the paths of resources
+///
+/// # Arguments
+/// * `paths` - The paths to match against
+/// * `id` - Only match against the paths of the resource type with this id
+/// * `on_match` - Generate the statement to run on a match, given the resource type name
+/// * `gate` - Whether to gate each match on the feature of its resource type
+fn generate_url_matching(
+    paths: &ResourcePaths,
+    id: Option<&str>,
+    on_match: impl Fn(&str) -> String,
+    gate: bool,
+    config: &Config,
+) -> Vec<String> {
+    let mut lines = vec![];
+    for (base, paths) in paths {
+        let paths = paths
+            .iter()
+            .filter(|(_, rt)| id.is_none_or(|id| id == *rt))
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            continue;
+        }
+        match base {
+            Some(base) => lines.push(format!(
+                "        let base: Option<url::Url> = {:?}.parse().ok();\n",
+                base.as_str()
+            )),
+            None => lines.push("        let base: Option<url::Url> = None;\n".to_string()),
+        }
+        for (path, rt) in paths {
+            let name = type_name(ItemKind::ResourceType, rt);
+            if gate {
+                lines.extend(cfg_line(
+                    &config
+                        .resource_type_feature(name.as_str())
+                        .into_iter()
+                        .collect::<Vec<_>>(),
+                    2,
+                ));
+            }
+            lines.push(format!(
+                "        if wadl::url_matches_template(base.as_ref(), {:?}, &url) {{\n",
+                path
+            ));
+            lines.push(format!("            {}\n", on_match(&name)));
+            lines.push("        }\n".to_string());
+        }
+    }
+    lines
+}
+
+/// Generate the `AnyResource` enum, see [`Config::generate_any_resource`]
+fn generate_any_resource(app: &Application, config: &Config) -> Vec<String> {
+    let paths = resource_paths(app);
+    let mut variants = vec![];
+    for (_, id) in paths.iter().flat_map(|(_, paths)| paths.iter()) {
+        let name = type_name(ItemKind::ResourceType, id);
+        if !variants.contains(&name) {
+            variants.push(name);
+        }
+    }
+    // Variants are listed in the order in which their resource types are defined
+    variants.sort_by_key(|name| {
+        app.resource_types
+            .iter()
+            .position(|rt| type_name(ItemKind::ResourceType, &rt.id) == *name)
+    });
+    let from_url = generate_url_matching(
+        &paths,
+        None,
+        |name| format!("return Some(AnyResource::{}({}(url)));", name, name),
+        true,
+        config,
+    );
+
+    if variants.is_empty() {
+        return vec![];
+    }
+
+    let cfgs = variants
+        .iter()
+        .map(|name| {
+            config
+                .resource_type_feature(name.as_str())
+                .into_iter()
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![];
+    lines.push("/// Any resource of the web service\n".to_string());
+    lines.push("pub enum AnyResource {\n".to_string());
+    for (name, cfg) in variants.iter().zip(cfgs.iter()) {
+        lines.extend(cfg_line(cfg, 1));
+        lines.push(format!("    /// A [`{}`] resource\n", name));
+        lines.push(format!("    {}({}),\n", name, name));
+    }
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+
+    lines.push("impl AnyResource {\n".to_string());
+    lines.push(
+        "    /// Return the resource that a URL refers to, based on the paths of the resources\n"
+            .to_string(),
+    );
+    lines.push("    ///\n".to_string());
+    lines.push(
+        "    /// Returns `None` if the URL does not match the path of any resource.\n".to_string(),
+    );
+    lines.push("    pub fn from_url(url: url::Url) -> Option<Self> {\n".to_string());
+    lines.extend(from_url);
+    lines.push("        None\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+
+    lines.push("impl wadl::Resource for AnyResource {\n".to_string());
+    lines.push("    fn url(&self) -> &reqwest::Url {\n".to_string());
+    lines.push("        match self {\n".to_string());
+    for (name, cfg) in variants.iter().zip(cfgs.iter()) {
+        lines.extend(cfg_line(cfg, 3));
+        lines.push(format!(
+            "            AnyResource::{}(r) => wadl::Resource::url(r),\n",
+            name
+        ));
+    }
+    lines.push("        }\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+fn generate_resource_type(
+    input: &ResourceType,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+    constants: &Constants,
+    paths: &ResourcePaths,
+) -> Vec<String> {
+    let mut lines = vec![];
+
+    let name = input.id.as_str();
+    let name = type_name(ItemKind::ResourceType, name);
+
+    let cfg = cfg_line(
+        &config
+            .resource_type_feature(name.as_str())
+            .into_iter()
+            .collect::<Vec<_>>(),
+        0,
+    );
+
+    lines.extend(cfg.clone());
+    for doc in &input.docs {
+        lines.extend(generate_doc(doc, 0, config));
+    }
+
+    let visibility = config
+        .item_visibility(ItemKind::ResourceType, name.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    lines.extend(derive_line(
+        &config.derives(ItemKind::ResourceType, name.as_str()),
+    ));
+    lines.push(format!(
+        "{}struct {} ({}reqwest::Url);\n",
+        if visibility.is_empty() {
+            "".to_string()
+        } else {
+            format!("{} ", visibility)
+        },
+        name,
+        // Resource types are constructed from their URL throughout the generated code, which
+        // may be spread over several modules
+        if config.module_path.is_some() {
+            "pub(crate) "
+        } else {
+            ""
+        }
+    ));
+
+    lines.push("\n".to_string());
+
+    if !config.blocking_and_async {
+        lines.extend(cfg.clone());
+        lines.push(format!("impl {} {{\n", name));
+        lines.extend(generate_resource_type_methods(
+            input,
+            config,
+            options_names,
+            constants,
+        ));
+        lines.push("}\n".to_string());
+        lines.push("\n".to_string());
+    }
+    lines.extend(cfg.clone());
+    lines.push(format!("impl wadl::Resource for {} {{\n", name));
+    lines.push("    fn url(&self) -> &reqwest::Url {\n".to_string());
+    lines.push("        &self.0\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+
+    lines.extend(generate_resource_type_conversions(
+        input, &name, &cfg, paths, config,
+    ));
+
+    if config.generates_fetch() {
+        lines.extend(generate_resource_type_fetch(
+            input,
+            &name,
+            &cfg,
+            config,
+            options_names,
+        ));
+    }
+
+    if config.generate_resource_traits && !config.blocking_and_async {
+        lines.extend(generate_resource_type_trait(
+            input,
+            name.as_str(),
+            visibility.as_str(),
+            &cfg,
+            config,
+            options_names,
+        ));
+    }
+    lines
+}
+
+/// Generate the `Fetch` implementation of a resource type, see [`Config::typed_links`]
+///
+/// This is only possible if the resource type has a `GET` method without arguments.
+fn generate_resource_type_fetch(
+    input: &ResourceType,
+    name: &str,
+    cfg: &[String],
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+    let Some(method) = input
+        .methods
+        .iter()
+        .find(|m| m.name == "GET" && method_args(m, config, options_names).is_empty())
+    else {
+        return lines;
+    };
+    let method_name = method_rust_name(method, &input.id, config);
+    let (ret_type, _map_fn) = method_return_type(method, &method_name, config, options_names);
+    let module = if config.is_async() {
+        "wadl::r#async"
+    } else {
+        "wadl::blocking"
+    };
+
+    lines.extend(cfg.iter().cloned());
+    lines.extend(cfg_line(&config.method_features(method, &input.id), 0));
+    if config.is_async() {
+        // Boxed futures must be `Send`, which reqwest futures are not on wasm
+        lines.push("#[cfg(not(target_arch = \"wasm32\"))]\n".to_string());
+    }
+    lines.push(format!("impl {}::Fetch for {} {{\n", module, name));
+    lines.push(format!("    type Representation = {};\n", ret_type));
+    lines.push("\n".to_string());
+    if config.is_async() {
+        lines.push(format!(
+            "    fn fetch<'a>(url: &'a url::Url, client: &'a dyn {}::Client) -> wadl::r#async::BoxFuture<'a, std::result::Result<Self::Representation, wadl::Error>> {{\n",
+            module
+        ));
+        lines.push(format!(
+            "        Box::pin(async move {{ {}(url.clone()).{}(client).await }})\n",
+            name, method_name
+        ));
+    } else {
+        lines.push(format!(
+            "    fn fetch(url: &url::Url, client: &dyn {}::Client) -> std::result::Result<Self::Representation, wadl::Error> {{\n",
+            module
+        ));
+        lines.push(format!(
+            "        {}(url.clone()).{}(client)\n",
+            name, method_name
+        ));
+    }
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+/// Generate the conversions between a resource type and URLs
+///
+/// URLs are validated against the paths of the resources of this type. If the resource type is
+/// not used by any resource with a known path, any URL is accepted.
+fn generate_resource_type_conversions(
+    input: &ResourceType,
+    name: &str,
+    cfg: &[String],
+    paths: &ResourcePaths,
+    config: &Config,
+) -> Vec<String> {
+    let mut lines = vec![];
+    let matching = generate_url_matching(
+        paths,
+        Some(input.id.as_str()),
+        |name| format!("return Ok({}(url));", name),
+        false,
+        config,
+    );
+
+    lines.extend(cfg.iter().cloned());
+    lines.push(format!(
+        "impl std::convert::TryFrom<url::Url> for {} {{\n",
+        name
+    ));
+    lines.push("    type Error = wadl::Error;\n".to_string());
+    lines.push("\n".to_string());
+    lines.push("    fn try_from(url: url::Url) -> Result<Self, Self::Error> {\n".to_string());
+    if matching.is_empty() {
+        lines.push(format!("        Ok({}(url))\n", name));
+    } else {
+        lines.extend(matching);
+        lines.push("        Err(wadl::Error::InvalidUrl)\n".to_string());
+    }
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+
+    lines.extend(cfg.iter().cloned());
+    lines.push(format!("impl std::str::FromStr for {} {{\n", name));
+    lines.push("    type Err = wadl::Error;\n".to_string());
+    lines.push("\n".to_string());
+    lines.push("    fn from_str(s: &str) -> Result<Self, Self::Err> {\n".to_string());
+    lines.push("        url::Url::parse(s)?.try_into()\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+
+    lines.extend(cfg.iter().cloned());
+    lines.push(format!("impl std::fmt::Display for {} {{\n", name));
+    lines.push(
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n".to_string(),
+    );
+    lines.push("        f.write_str(self.0.as_str())\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+/// Generate the methods for the operations of a resource type
+fn generate_resource_type_methods(
+    input: &ResourceType,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+    constants: &Constants,
+) -> Vec<String> {
+    let mut lines = vec![];
+    for method in &input.methods {
+        lines.extend(generate_method(
+            method,
+            input.id.as_str(),
+            config,
+            options_names,
+            constants,
+            returns_etag(input),
+        ));
+    }
+    lines
+}
+
+/// Generate a module with the operations of all resource types, for one kind of client
+///
+/// Used for [`Config::blocking_and_async`]; the operations of resource types are emitted as
+/// `Ops` traits, and navigation methods of representations as `Links` traits.
+fn generate_client_module(
+    app: &Application,
+    module: &str,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+    constants: &Constants,
+) -> Vec<String> {
+    let mut lines = vec![];
+    for representation in &app.representations {
+        let methods = generate_representation_link_navigation(
+            representation,
+            &app.resource_types,
+            &app.representations,
+            config,
+            options_names,
+            constants,
+        );
+        if !methods.is_empty() {
+            let name = type_name(
+                ItemKind::Representation,
+                representation.id.as_ref().unwrap(),
+            );
+            lines.extend(methods_to_trait(
+                &format!("{}Links", name),
+                &name,
+                "Navigation of the links in",
+                &[],
+                &methods,
+                config,
+            ));
+        }
+    }
+    for resource_type in &app.resource_types {
+        let name = type_name(ItemKind::ResourceType, &resource_type.id);
+        let cfg = cfg_line(
+            &config
+                .resource_type_feature(name.as_str())
+                .into_iter()
+                .collect::<Vec<_>>(),
+            0,
+        );
+        lines.extend(methods_to_trait(
+            &format!("{}Ops", name),
+            &name,
+            "Operations on",
+            &cfg,
+            &generate_resource_type_methods(resource_type, config, options_names, constants),
+            config,
+        ));
+    }
+
+    let mut ret = vec![
+        format!(
+            "/// Operations using {} client\n",
+            if module == "async" {
+                "an async"
+            } else {
+                "a blocking"
+            }
+        ),
+        format!(
+            "pub mod {} {{\n",
+            if module == "async" { "aio" } else { module }
+        ),
+        "    #![allow(unused_imports)]\n".to_string(),
+        "    use super::*;\n".to_string(),
+        "\n".to_string(),
+    ];
+    ret.extend(indent(1, lines.into_iter()));
+    ret.push("}\n".to_string());
+    ret.push("\n".to_string());
+    ret
+}
+
+/// Generate a trait with the operations of a resource type, implemented by the wrapper struct
+///
+/// This allows application code to depend on the trait, so that tests can provide fakes.
+fn generate_resource_type_trait(
+    input: &ResourceType,
+    name: &str,
+    visibility: &str,
+    cfg: &[String],
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+    let trait_name = format!("{}Ops", name);
+    let visibility = config
+        .item_visibility(ItemKind::Trait, &trait_name)
+        .map_or_else(|| visibility.to_string(), |v| v.to_string());
+
+    let methods = input
+        .methods
+        .iter()
+        .filter_map(|method| {
+            let method_name = method_rust_name(method, input.id.as_str(), config);
+            let (ret_type, _map_fn) =
+                method_return_type(method, &method_name, config, options_names);
+            // Only public methods are part of the trait
+            if config
+                .item_visibility(ItemKind::Method, &method_name)
+                .is_some_and(|v| v != Visibility::Public)
+            {
+                return None;
+            }
+            let signature =
+                method_signature(method, &method_name, &ret_type, &[], config, options_names);
+            let method_cfg = cfg_line(&config.method_features(method, input.id.as_str()), 1);
+            Some((method, method_name, signature, method_cfg))
+        })
+        .collect::<Vec<_>>();
+
+    lines.extend(cfg.iter().cloned());
+    lines.push(format!("/// Operations on [`{}`]\n", name));
+    let (trait_attrs, impl_attrs) = async_trait_attrs(config);
+    lines.extend(trait_attrs);
+    lines.push(format!(
+        "{}trait {} {{\n",
+        if visibility.is_empty() {
+            "".to_string()
+        } else {
+            format!("{} ", visibility)
+        },
+        trait_name
+    ));
+    for (method, _method_name, signature, method_cfg) in methods.iter() {
+        lines.extend(method_cfg.iter().cloned());
+        for doc in &method.docs {
+            lines.extend(generate_doc(doc, 1, config));
+        }
+        lines.extend(config.deprecated_attr(&method.docs, 1));
+        lines.push(format!("    {};\n", signature));
+        lines.push("\n".to_string());
+    }
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+
+    lines.extend(cfg.iter().cloned());
+    lines.extend(impl_attrs);
+    lines.push(format!("impl {} for {} {{\n", trait_name, name));
+    for (method, method_name, signature, method_cfg) in methods.iter() {
+        lines.extend(method_cfg.iter().cloned());
+        if config.deprecated_attr(&method.docs, 1).is_some() {
+            lines.push("    #[allow(deprecated)]\n".to_string());
+        }
+        let mut args = vec!["self".to_string(), "client".to_string()];
+        args.extend(
+            method_args(method, config, options_names)
+                .into_iter()
+                .map(|(arg_name, _arg_type)| arg_name),
+        );
+        lines.push(format!("    {} {{\n", signature));
+        lines.push(format!(
+            "        {}::{}({}){}\n",
+            name,
+            method_name,
+            args.join(", "),
+            if config.is_async() { ".await" } else { "" }
+        ));
+        lines.push("    }\n".to_string());
+        lines.push("\n".to_string());
+    }
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+/// The kind of a generated item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    /// A struct for a representation
+    Representation,
+
+    /// An enum for a set of options
+    OptionsEnum,
+
+    /// A wrapper struct for a resource type
+    ResourceType,
+
+    /// A method of a resource type or resource
+    Method,
+
+    /// An accessor of a representation field
+    Accessor,
+
+    /// A trait with the operations of a resource type
+    Trait,
+}
+
+fn cfg_line(features: &[String], indent: usize) -> Vec<String> {
+    let indent = " ".repeat(indent * 4);
+    match features {
+        [] => vec![],
+        [feature] => vec![format!("{}#[cfg(feature = \"{}\")]\n", indent, feature)],
+        features => vec![format!(
+            "{}#[cfg(all({}))]\n",
+            indent,
+            features
+                .iter()
+                .map(|f| format!("feature = \"{}\"", f))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )],
+    }
+}
+
+fn derive_line(derives: &[String]) -> Vec<String> {
+    if derives.is_empty() {
+        vec![]
+    } else {
+        vec![format!("#[derive({})]\n", derives.join(", "))]
+    }
+}
+
+/// Timeout and retry behaviour of the requests of a method
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestPolicy {
+    /// Timeout for the whole request, including reading the response body
+    pub timeout: Option<std::time::Duration>,
+
+    /// Number of times to retry a request, on the statuses and errors that the default
+    /// [`RetryPolicy`](crate::RetryPolicy) retries
+    pub retries: u32,
+
+    /// Delay before the first retry, doubled for every following retry
+    ///
+    /// The `Retry-After` header of the response takes precedence.
+    pub backoff: std::time::Duration,
+}
+
+/// A configuration that code can not be generated with, see [`Config::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A method has a request policy, but the client does not support them
+    RequestPolicyNotSupported(String),
+
+    /// A method has a `multipart/form-data` request body, but the client does not support them
+    MultipartNotSupported(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::RequestPolicyNotSupported(id) => write!(
+                f,
+                "method {:?} has a request policy, but request policies are only supported with reqwest-based clients",
+                id
+            ),
+            ConfigError::MultipartNotSupported(id) => write!(
+                f,
+                "method {:?} has a multipart/form-data request body, but these are only supported with reqwest-based clients",
+                id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// How the pages of a paginated collection are represented, see [`Config::pagination`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pagination {
+    /// The name of the repeating param with the items of a page
+    pub items: String,
+
+    /// The name of the link param with the URL of the next page
+    pub next: String,
+}
+
+impl Pagination {
+    /// The pages of Launchpad collections, with `entries` and `next_collection_link` params
+    pub fn launchpad() -> Self {
+        Self {
+            items: "entries".to_string(),
+            next: "next_collection_link".to_string(),
+        }
+    }
+}
+
+/// How repeating query params are serialized in request URLs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryArrayStyle {
+    /// Repeat the key for every value, e.g. `key=a&key=b`
+    #[default]
+    Repeat,
+
+    /// Join the values with commas, e.g. `key=a,b`
+    Comma,
+
+    /// Repeat the key with a `[]` suffix for every value, e.g. `key[]=a&key[]=b`
+    Brackets,
+}
+
+/// The visibility of a generated item
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Visibility {
+    /// `pub`
+    #[default]
+    Public,
+
+    /// `pub(crate)`
+    Crate,
+
+    /// `pub(super)`
+    Super,
+
+    /// Private to the module the item is generated in
+    Private,
+
+    /// Visible in a specific module, e.g. `pub(in crate::api)`
+    In(String),
+}
+
+impl std::fmt::Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "pub"),
+            Visibility::Crate => write!(f, "pub(crate)"),
+            Visibility::Super => write!(f, "pub(super)"),
+            Visibility::Private => Ok(()),
+            Visibility::In(path) => write!(f, "pub(in {})", path),
+        }
+    }
+}
+
+impl Visibility {
+    /// Return the visibility as a prefix of an item, e.g. `pub(crate) `
+    fn prefix(&self) -> String {
+        match self {
+            Visibility::Private => String::new(),
+            visibility => format!("{} ", visibility),
+        }
+    }
+}
+
+impl std::str::FromStr for Visibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "pub" => Ok(Visibility::Public),
+            "pub(crate)" => Ok(Visibility::Crate),
+            "pub(super)" => Ok(Visibility::Super),
+            "" | "private" => Ok(Visibility::Private),
+            s => s
+                .strip_prefix("pub(in ")
+                .and_then(|p| p.strip_suffix(')'))
+                .map(|p| Visibility::In(p.trim().to_string()))
+                .ok_or_else(|| format!("invalid visibility: {}", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Visibility {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Visibility> for String {
+    fn from(visibility: Visibility) -> Self {
+        match visibility {
+            Visibility::Private => "private".to_string(),
+            visibility => visibility.to_string(),
+        }
+    }
+}
+
+/// The crate whose types represent XSD date and time types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateTimeCrate {
+    /// [chrono](https://docs.rs/chrono), with the `serde` feature
+    #[default]
+    Chrono,
+
+    /// [time](https://docs.rs/time), with the `serde-human-readable` feature
+    ///
+    /// `xsd:duration` is mapped to `String`, since `time` can not parse ISO 8601 durations.
+    Time,
+
+    /// [jiff](https://docs.rs/jiff), with the `serde` feature
+    ///
+    /// `xsd:duration` is mapped to `jiff::SignedDuration`, which only supports units up to
+    /// hours.
+    Jiff,
+}
+
+impl DateTimeCrate {
+    /// Return the Rust type for an XSD date or time type
+    ///
+    /// # Arguments
+    /// * `name` - The local name of the XSD type, without namespace prefix
+    ///
+    /// # Returns
+    /// The Rust type, or `None` if the type is not a date or time type
+    pub fn rust_type(&self, name: &str) -> Option<&'static str> {
+        let ret = match (self, name) {
+            (DateTimeCrate::Chrono, _) => {
+                return xsd_rust_type(name).filter(|t| t.starts_with("chrono::"))
+            }
+            (DateTimeCrate::Time, "date") => "time::Date",
+            (DateTimeCrate::Time, "dateTime") => "time::OffsetDateTime",
+            (DateTimeCrate::Time, "time") => "time::Time",
+            (DateTimeCrate::Time, "duration") => "String",
+            (DateTimeCrate::Jiff, "date") => "jiff::civil::Date",
+            (DateTimeCrate::Jiff, "dateTime") => "jiff::Timestamp",
+            (DateTimeCrate::Jiff, "time") => "jiff::civil::Time",
+            (DateTimeCrate::Jiff, "duration") => "jiff::SignedDuration",
+            _ => return None,
+        };
+        Some(ret)
+    }
+
+    /// Return the module to (de)serialize an XSD date or time type with, if the default
+    /// serde implementation of its Rust type does not use the XSD format
+    fn serde_with(&self, name: &str) -> Option<&'static str> {
+        match (self, name) {
+            (DateTimeCrate::Time, "dateTime") => Some("time::serde::rfc3339"),
+            _ => None,
+        }
+    }
+
+    /// Return the modules to (de)serialize a required and an optional XSD `dateTime` with, as
+    /// a number since the Unix epoch
+    fn epoch_serde_with(&self, millis: bool) -> (&'static str, &'static str) {
+        match (self, millis) {
+            (DateTimeCrate::Chrono, false) => (
+                "chrono::serde::ts_seconds",
+                "chrono::serde::ts_seconds_option",
+            ),
+            (DateTimeCrate::Chrono, true) => (
+                "chrono::serde::ts_milliseconds",
+                "chrono::serde::ts_milliseconds_option",
+            ),
+            (DateTimeCrate::Time, false) => {
+                ("time::serde::timestamp", "time::serde::timestamp::option")
+            }
+            (DateTimeCrate::Time, true) => (
+                "time::serde::timestamp::milliseconds",
+                "time::serde::timestamp::milliseconds::option",
+            ),
+            (DateTimeCrate::Jiff, false) => (
+                "jiff::fmt::serde::timestamp::second::required",
+                "jiff::fmt::serde::timestamp::second::optional",
+            ),
+            (DateTimeCrate::Jiff, true) => (
+                "jiff::fmt::serde::timestamp::millisecond::required",
+                "jiff::fmt::serde::timestamp::millisecond::optional",
+            ),
+        }
+    }
+
+    /// Return the body of a function that formats `value` with the format string `FORMAT`,
+    /// and an expression that parses `s` with it
+    ///
+    /// Returns `None` if custom formats are not supported for the type.
+    fn custom_format_code(&self, name: &str) -> Option<(&'static str, &'static str)> {
+        const WRITE_FORMAT: &str = "    use std::fmt::Write;\n    let mut s = String::new();\n    write!(s, \"{}\", value.format(FORMAT)).map_err(|_| format!(\"invalid format {:?}\", FORMAT))?;\n    Ok(s)\n";
+        const WRITE_STRFTIME: &str = "    use std::fmt::Write;\n    let mut s = String::new();\n    write!(s, \"{}\", value.strftime(FORMAT)).map_err(|_| format!(\"invalid format {:?}\", FORMAT))?;\n    Ok(s)\n";
+        const FORMAT_DESCRIPTION: &str = "    let description = time::format_description::parse_borrowed::<2>(FORMAT).map_err(|e| e.to_string())?;\n    value.format(&description).map_err(|e| e.to_string())\n";
+        let ret = match (self, name) {
+            (DateTimeCrate::Chrono, "dateTime") => (
+                WRITE_FORMAT,
+                "chrono::DateTime::parse_from_str(s, FORMAT).map(|d| d.with_timezone(&chrono::Utc)).or_else(|_| chrono::NaiveDateTime::parse_from_str(s, FORMAT).map(|d| d.and_utc()))",
+            ),
+            (DateTimeCrate::Chrono, "date") => {
+                (WRITE_FORMAT, "chrono::NaiveDate::parse_from_str(s, FORMAT)")
+            }
+            (DateTimeCrate::Chrono, "time") => {
+                (WRITE_FORMAT, "chrono::NaiveTime::parse_from_str(s, FORMAT)")
+            }
+            (DateTimeCrate::Time, "dateTime") => (
+                FORMAT_DESCRIPTION,
+                "time::format_description::parse_borrowed::<2>(FORMAT).map_err(|e| e.to_string())\n        .and_then(|description| time::OffsetDateTime::parse(s, &description).or_else(|_| time::PrimitiveDateTime::parse(s, &description).map(|d| d.assume_utc())).map_err(|e| e.to_string()))",
+            ),
+            (DateTimeCrate::Time, "date") => (
+                FORMAT_DESCRIPTION,
+                "time::format_description::parse_borrowed::<2>(FORMAT).map_err(|e| e.to_string())\n        .and_then(|description| time::Date::parse(s, &description).map_err(|e| e.to_string()))",
+            ),
+            (DateTimeCrate::Time, "time") => (
+                FORMAT_DESCRIPTION,
+                "time::format_description::parse_borrowed::<2>(FORMAT).map_err(|e| e.to_string())\n        .and_then(|description| time::Time::parse(s, &description).map_err(|e| e.to_string()))",
+            ),
+            (DateTimeCrate::Jiff, "dateTime") => (
+                WRITE_STRFTIME,
+                "jiff::Timestamp::strptime(FORMAT, s).or_else(|_| jiff::civil::DateTime::strptime(FORMAT, s).and_then(|d| d.to_zoned(jiff::tz::TimeZone::UTC)).map(|d| d.timestamp()))",
+            ),
+            (DateTimeCrate::Jiff, "date") => {
+                (WRITE_STRFTIME, "jiff::civil::Date::strptime(FORMAT, s)")
+            }
+            (DateTimeCrate::Jiff, "time") => {
+                (WRITE_STRFTIME, "jiff::civil::Time::strptime(FORMAT, s)")
+            }
+            _ => return None,
+        };
+        Some(ret)
+    }
+}
+
+/// The Rust type of optional fields that distinguish `null` from a missing value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NullableFieldStyle {
+    /// `Option<T>`, where both `null` and a missing value are `None`
+    #[default]
+    Option,
+
+    /// `Option<Option<T>>`, where `Some(None)` is `null` and `None` is a missing value
+    DoubleOption,
+
+    /// [`wadl::Patch<T>`](crate::Patch)
+    Patch,
+}
+
+/// The format in which a date or time value is sent over the wire
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateTimeFormat {
+    /// The XSD format, which matches RFC 3339 for `xsd:dateTime`
+    #[default]
+    Rfc3339,
+
+    /// Seconds since the Unix epoch, as a number
+    ///
+    /// Only supported for `xsd:dateTime`.
+    EpochSeconds,
+
+    /// Milliseconds since the Unix epoch, as a number
+    ///
+    /// Only supported for `xsd:dateTime`.
+    EpochMillis,
+
+    /// A string in a custom format
+    ///
+    /// The format string uses the syntax of the [`DateTimeCrate`]: `strftime`-style for chrono
+    /// and jiff (e.g. `%Y-%m-%d %H:%M:%S`), and format descriptions for time (e.g.
+    /// `[year]-[month]-[day]`). Values of `xsd:dateTime` that are parsed without an offset are
+    /// assumed to be in UTC.
+    Custom(String),
+}
+
+/// The Rust type that represents `xsd:decimal`
+///
+/// With their `serde` feature, both decimal crates serialize values as strings so that no
+/// precision is lost, and accept both strings and numbers when deserializing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum DecimalType {
+    /// `f64`, which can not represent all decimal values exactly
+    #[default]
+    #[serde(rename = "f64")]
+    F64,
+
+    /// [rust_decimal](https://docs.rs/rust_decimal), with the `serde` feature
+    #[serde(rename = "rust_decimal")]
+    RustDecimal,
+
+    /// [bigdecimal](https://docs.rs/bigdecimal), with the `serde` feature
+    #[serde(rename = "bigdecimal")]
+    BigDecimal,
+}
+
+impl DecimalType {
+    /// Return the Rust type
+    pub fn rust_type(&self) -> &'static str {
+        match self {
+            DecimalType::F64 => "f64",
+            DecimalType::RustDecimal => "rust_decimal::Decimal",
+            DecimalType::BigDecimal => "bigdecimal::BigDecimal",
+        }
+    }
+}
+
+/// A version of Rust, e.g. `1.70`
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct RustVersion {
+    /// The major version
+    pub major: u32,
+
+    /// The minor version
+    pub minor: u32,
+}
+
+impl RustVersion {
+    /// Create a version from its major and minor version
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl std::fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl std::str::FromStr for RustVersion {
+    type Err = String;
+
+    /// Parse a version like `1.70` or `1.70.0`; the patch version is ignored
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('.').map(|p| p.parse::<u32>());
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor)), None | Some(Ok(_)), None) => {
+                Ok(Self::new(major, minor))
+            }
+            _ => Err(format!("invalid Rust version: {}", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for RustVersion {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<RustVersion> for String {
+    fn from(version: RustVersion) -> Self {
+        version.to_string()
+    }
+}
+
+/// Syntax that generated code only uses if [`Config::msrv`] allows it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Syntax {
+    /// `let ... else` statements
+    LetElse,
+
+    /// `async fn` and `-> impl Trait` in traits
+    ///
+    /// Without it, async traits are implemented with `async_trait`, which is re-exported by
+    /// the `async` feature of this crate.
+    ImplTraitInTrait,
+
+    /// Inline `const { ... }` blocks
+    InlineConst,
+}
+
+impl Syntax {
+    /// Return the Rust version in which the syntax was stabilized
+    pub fn stabilized_in(&self) -> RustVersion {
+        match self {
+            Syntax::LetElse => RustVersion::new(1, 65),
+            Syntax::ImplTraitInTrait => RustVersion::new(1, 75),
+            Syntax::InlineConst => RustVersion::new(1, 79),
+        }
+    }
+}
+
+#[derive(Default)]
+#[allow(clippy::type_complexity)]
+#[non_exhaustive]
+/// Configuration for code generation
+///
+/// Use [`Config::builder`] to construct a configuration, or modify the fields of
+/// [`Config::default`].
+pub struct Config {
+    /// Whether to generate async code
+    pub r#async: bool,
+
+    /// Whether to generate both blocking and async code
+    ///
+    /// Types are shared, while the operations are emitted as traits in a `blocking` and an
+    /// `aio` module, e.g. `blocking::PersonOps` and `aio::PersonOps`. Navigation methods for
+    /// links in representations are emitted as traits with a `Links` suffix. [`Config::r#async`]
+    /// is ignored.
+    pub blocking_and_async: bool,
+
+    /// Whether to send requests through a `tower::Service` rather than a reqwest-based client
+    ///
+    /// Generated methods are async and take a `wadl::tower::HttpService` as client, so that
+    /// layers from the tower ecosystem can be used. Requires the `tower`
+    /// feature of this crate in the generated code's dependencies.
+    pub tower: bool,
+
+    /// Whether to send requests with ureq rather than reqwest
+    ///
+    /// Generated methods are blocking and take a `wadl::ureq::Client` (implemented for
+    /// `ureq::Agent`) as client, so that no async runtime is needed. Requires the `ureq` feature of
+    /// this crate in the generated code's dependencies. Takes precedence over [`Config::r#async`].
+    pub ureq: bool,
+
+    /// Whether async code takes a `wadl::r#async::NativeClient` rather than a
+    /// `wadl::r#async::Client`
+    ///
+    /// `NativeClient` returns `impl Future` rather than a boxed future, so requests are created
+    /// without an allocation. Since it can't be used as a trait object, generated methods are
+    /// generic over the client. Requires Rust 1.75 and the `async-native` feature of this crate
+    /// in the generated code's dependencies, which does not depend on `async-trait`. Ignored if
+    /// [`Config::msrv`] is older than that.
+    pub native_async_client: bool,
+
+    /// The oldest Rust version that generated code has to compile with
+    ///
+    /// Syntax that is newer is avoided, see [`Syntax`]. If not set, any stable syntax may be
+    /// used.
+    pub msrv: Option<RustVersion>,
+
+    /// Based on the listed type and name of a parameter, determine the rust type
+    pub override_type_name:
+        Option<Box<dyn Fn(&ParamContainer, &str, &str, &Config) -> Option<String>>>,
+
+    /// Support renaming param accessor functions
+    ///
+    /// To rename both the field and the accessor of a param, use [`Config::param_renames`].
+    pub param_accessor_rename: Option<Box<dyn Fn(&str, &str) -> Option<String>>>,
+
+    /// Whether to strip code examples from the docstrings
+    ///
+    /// This is useful if the code examples are not valid rust code.
+    pub strip_code_examples: bool,
+
+    /// Whether to convert HTML tables in docstrings to markdown tables
+    ///
+    /// Tables that can not be represented in markdown (e.g. because they have merged cells)
+    /// are rendered as fenced text blocks instead.
+    pub markdown_tables: bool,
+
+    /// Generate custom trait implementations for representations
+    pub generate_representation_traits: Option<
+        Box<dyn Fn(&RepresentationDef, &str, &RepresentationDef, &Config) -> Option<Vec<String>>>,
+    >,
+
+    /// Return the visibility of a generated item
+    ///
+    /// Called with the kind of the item and its Rust name. Items for which `None` is returned
+    /// are public, except for the traits of resource types, which have the visibility of their
+    /// resource type. Methods that are not public are left out of the traits of resource types.
+    pub visibility: Option<Box<dyn Fn(ItemKind, &str) -> Option<Visibility>>>,
+
+    /// Map a method response type to a different type and a function to map the response
+    pub map_type_for_response: Option<Box<dyn Fn(&str, &str, &Config) -> Option<(String, String)>>>,
+
+    /// Map an accessor function name to a different type
+    pub map_type_for_accessor: Option<Box<dyn Fn(&str) -> Option<(String, String)>>>,
+
+    /// Extend the generated accessor
+    pub extend_accessor: Option<Box<dyn Fn(&Param, &'_ str, &'_ str, &Config) -> Vec<String>>>,
+
+    /// Extend the generated method
+    pub extend_method: Option<Box<dyn Fn(&str, &str, &str, &Config) -> Vec<String>>>,
+
+    /// Extend the generated representation
+    ///
+    /// Called with the representation and its Rust name. The returned lines are emitted after
+    /// the `impl` block of the representation, so they can contain further `impl` blocks and
+    /// trait implementations.
+    pub extend_representation:
+        Option<Box<dyn Fn(&RepresentationDef, &str, &Config) -> Vec<String>>>,
+
+    /// Return whether a param is deprecated
+    pub deprecated_param: Option<Box<dyn Fn(&Param) -> bool>>,
+
+    /// Detect whether documentation marks a method or accessor as deprecated
+    ///
+    /// Called with the documentation in markdown; returns the note for the generated
+    /// `#[deprecated]` attribute. If not set, [`default_deprecation_note`] is used.
+    pub deprecation_note: Option<Box<dyn Fn(&str) -> Option<String>>>,
+
+    /// Return the name for an enum representation a set of options
+    ///
+    /// The callback can be used to determine if the name is already taken.
+    pub options_enum_name: Option<Box<dyn Fn(&Param, Box<dyn Fn(&str) -> bool>) -> String>>,
+
+    /// Return the name for the type of a representation or resource type, given its kind and id
+    ///
+    /// If the name is already taken by another item, underscores are appended to it.
+    pub type_name: Option<Box<dyn Fn(ItemKind, &str) -> Option<String>>>,
+
+    /// Reformat a docstring; should already be in markdown
+    pub reformat_docstring: Option<Box<dyn Fn(&str) -> String>>,
+
+    /// Convert a string to a multipart Part, given a type name and value
+    pub convert_to_multipart: Option<Box<dyn Fn(&str, &str) -> Option<String>>>,
+
+    /// Return the list of traits to derive for a generated item, overriding the defaults
+    pub derive: Option<Box<dyn Fn(ItemKind, &str) -> Option<Vec<String>>>>,
+
+    /// Whether to skip serializing optional representation fields that are `None`
+    pub skip_serializing_none: bool,
+
+    /// How optional fields that distinguish `null` from a missing value are represented
+    ///
+    /// This applies to the fields of JSON merge patch structs, where `null` clears a field and
+    /// a missing field is left unchanged, and to the fields selected by
+    /// [`Config::nullable_field`]. Link fields and fields with a serde `with` annotation keep
+    /// their `Option` type.
+    pub nullable_field_style: NullableFieldStyle,
+
+    /// Return whether an optional field of a JSON representation distinguishes `null` from a
+    /// missing value, see [`Config::nullable_field_style`]
+    pub nullable_field: Option<Box<dyn Fn(&RepresentationDef, &Param) -> bool>>,
+
+    /// Whether to convert names to snake case the way older versions did
+    ///
+    /// Older versions did not treat runs of capitals as acronyms, e.g. `XMLParser` became
+    /// `x_mlparser` rather than `xml_parser`. Set this to keep the names of existing generated
+    /// code stable.
+    pub legacy_snake_case: bool,
+
+    /// Return whether a representation should reject unknown fields when deserializing
+    ///
+    /// Ignored if [`Config::capture_unknown_fields`] is set, since serde can not do both.
+    pub deny_unknown_fields: Option<Box<dyn Fn(&str) -> bool>>,
+
+    /// Return the serde `rename_all` strategy (e.g. `camelCase`) for a representation
+    ///
+    /// When set, no explicit `rename` attributes are emitted for the fields of the
+    /// representation.
+    pub serde_rename_all: Option<Box<dyn Fn(&str) -> Option<String>>>,
+
+    /// Whether to add a field to representations that captures JSON fields not described in
+    /// the WADL, so that they are preserved rather than silently dropped
+    pub capture_unknown_fields: bool,
+
+    /// Return whether to store a representation field in a `Box`
+    ///
+    /// Boxed fields have type `Box<T>` or `Option<Box<T>>`, which keeps large representations
+    /// cheap to move. Repeating fields and fields with a serde `with` annotation are never boxed.
+    pub box_field: Option<Box<dyn Fn(&RepresentationDef, &Param) -> bool>>,
+
+    /// Box all representation fields whose type is estimated to be larger than this many bytes
+    ///
+    /// For example, `Some(64)` boxes links, which are stored as `url::Url`.
+    pub box_fields_larger_than: Option<usize>,
+
+    /// Whether to leave `Clone` out of the derives of representations with boxed fields
+    ///
+    /// Response enums that contain such a representation do not derive `Clone` either.
+    pub omit_clone_for_boxed: bool,
+
+    /// Whether to generate a trait with the operations of each resource type
+    ///
+    /// The trait is named after the resource type with an `Ops` suffix (e.g. `PersonOps`)
+    /// and is implemented by the generated wrapper struct.
+    pub generate_resource_traits: bool,
+
+    /// Whether to generate an `AnyResource` enum with a variant for each resource type
+    ///
+    /// `AnyResource::from_url` matches a URL against the paths of the resources, so that
+    /// arbitrary URLs of the web service can be turned into typed resources.
+    pub generate_any_resource: bool,
+
+    /// Whether to use [`wadl::Link`](crate::Link) for links to resource types in representations
+    ///
+    /// Such fields are then typed by the resource type they link to, and can be followed with
+    /// `wadl::blocking::Follow` or `wadl::r#async::Follow`; resource types with a `GET` method
+    /// without arguments implement `Fetch` for this. Links to resource types that are gated by
+    /// a feature remain plain URLs.
+    pub typed_links: bool,
+
+    /// Return how the pages of a collection are represented by a representation, if it is one
+    ///
+    /// Page representations implement `IntoIterator` over their items, and `GET` methods that
+    /// return them get an `_all` variant that iterates over the items of all pages, using
+    /// [`wadl::paginate`](crate::paginate). Defaults to [`Pagination::launchpad`].
+    pub pagination: Option<Box<dyn Fn(&RepresentationDef) -> Option<Pagination>>>,
+
+    /// Whether to mark generated enums for options as `#[non_exhaustive]`
+    pub options_non_exhaustive: bool,
+
+    /// Map XSD simple type names to Rust types, overriding the built-in mapping
+    ///
+    /// Keys can either be the full type name (e.g. `xsd:decimal`) or the name without
+    /// namespace prefix (e.g. `decimal`).
+    pub xsd_type_map: HashMap<String, String>,
+
+    /// Base URL to resolve relative links in docs against
+    ///
+    /// If not set, the base URL of the `resources` element of the application is used.
+    pub doc_base_url: Option<url::Url>,
+
+    /// Media types of response bodies to stream, rather than to read into memory
+    ///
+    /// `application/octet-stream` is always streamed. Methods that return these media types
+    /// return a `ByteStream`, which implements `std::io::Read` for blocking clients and
+    /// `Stream` for async clients. With [`Config::tower`], the body is returned as `Bytes`.
+    pub streaming_media_types: Vec<String>,
+
+    /// How repeating query params are serialized in request URLs
+    pub query_array_style: QueryArrayStyle,
+
+    /// Override the serialization of a repeating query param
+    ///
+    /// Called with the Rust name of the method and the name of the param. If `None` is
+    /// returned, [`Config::query_array_style`] is used.
+    pub query_array_style_override: Option<Box<dyn Fn(&str, &str) -> Option<QueryArrayStyle>>>,
+
+    /// Return the timeout and retry policy for the requests of a method
+    ///
+    /// Called with the id of the method. The policy is compiled into the generated method.
+    /// Not supported with [`Config::tower`], where tower layers can be used instead, or with
+    /// [`Config::ureq`]; see [`Config::validate`].
+    pub request_policy: Option<Box<dyn Fn(&str) -> Option<RequestPolicy>>>,
+
+    /// The crate whose types represent XSD date and time types
+    ///
+    /// Used for `xsd:date`, `xsd:dateTime`, `xsd:time` and `xsd:duration`, unless overridden
+    /// by [`Config::xsd_type_map`].
+    pub date_time_crate: DateTimeCrate,
+
+    /// Return the wire format of a date or time param
+    ///
+    /// Called for the params whose Rust type comes from [`Config::date_time_crate`]. If `None`
+    /// is returned, [`DateTimeFormat::Rfc3339`] is used. Repeating params always use the
+    /// default format of their Rust type.
+    #[allow(clippy::type_complexity)]
+    pub date_time_format: Option<Box<dyn Fn(&ParamContainer, &Param) -> Option<DateTimeFormat>>>,
+
+    /// The Rust type that represents `xsd:decimal`, unless overridden by
+    /// [`Config::xsd_type_map`]
+    pub decimal_type: DecimalType,
+
+    /// Expression for the observer that generated methods report their requests to
+    ///
+    /// The expression (e.g. `&crate::METRICS`) must evaluate to a reference to a type that
+    /// implements `wadl::Observer`. It is called with the id of the method before each request
+    /// is sent and after its response has been received.
+    pub observer: Option<String>,
+
+    /// Return the cargo feature that gates a resource type or one of its methods
+    ///
+    /// Called with the Rust name of the resource type, and the Rust name of the method (or
+    /// `None` for the resource type itself). Items for which a feature is returned are wrapped
+    /// in `#[cfg(feature = "...")]`, so that downstream crates can prune large APIs at compile
+    /// time. Accessors and methods that refer to a gated resource type are gated as well.
+    pub feature_gate: Option<Box<dyn Fn(&str, Option<&str>) -> Option<String>>>,
+
+    /// Rust names of the fields of representations, keyed by the name of their param
+    ///
+    /// Keys are either the name of the param (e.g. `display_name`), or the id of the
+    /// representation and the name of the param separated by a dot (e.g.
+    /// `person-full.display_name`), which takes precedence. The accessors of renamed fields are
+    /// renamed as well, and the fields are still (de)serialized with the name of the param.
+    pub param_renames: HashMap<String, String>,
+
+    /// Rust names of methods, keyed by the id of the method
+    pub method_renames: HashMap<String, String>,
+
+    /// Rust names of representations and resource types, keyed by their id
+    ///
+    /// Takes precedence over [`Config::type_name`].
+    pub type_renames: HashMap<String, String>,
+
+    /// Return the module to place a representation or resource type in
+    ///
+    /// Called with the kind of the item and its id, and returns a module path relative to the
+    /// generated code (e.g. `bugs` or `bugs::tasks`), or `None` to keep the item at the top
+    /// level. Modules can refer to the items in all other modules, and the items in modules
+    /// are imported at the top level so that the remaining generated code can refer to them.
+    pub module_path: Option<Box<dyn Fn(ItemKind, &str) -> Option<String>>>,
+
+    /// Hooks that customize code generation, called in order
+    pub hooks: Vec<Box<dyn CodegenHook>>,
+
+    /// Header to emit at the top of the generated code, recording its provenance
+    pub header: Option<FileHeader>,
+
+    /// Return whether to generate code for an item
+    ///
+    /// Called with [`ItemKind::ResourceType`] and the id of each resource type, and with
+    /// [`ItemKind::Method`] and the id of each method. Excluded resource types are left out
+    /// entirely: resources of that type are skipped, and links to it are treated as plain URLs.
+    pub item_filter: Option<Box<dyn Fn(ItemKind, &str) -> bool>>,
+
+    #[cfg(feature = "templates")]
+    /// Templates that replace the generated code for some kinds of items
+    pub templates: Option<Templates>,
+}
+
+impl Config {
+    /// Return a builder for a configuration
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
+    /// Return whether to generate code for an item, see [`Config::item_filter`]
+    ///
+    /// # Arguments
+    /// * `kind` - The kind of item
+    /// * `id` - The id of the item in the WADL
+    pub fn include_item(&self, kind: ItemKind, id: &str) -> bool {
+        self.item_filter.as_ref().is_none_or(|f| f(kind, id))
+    }
+
+    /// Return the Rust type that overrides the type of a param, if any
+    ///
+    /// [`Config::override_type_name`] takes precedence over [`CodegenHook::override_type`].
+    fn override_type(
+        &self,
+        container: &ParamContainer,
+        type_name: &str,
+        param_name: &str,
+    ) -> Option<String> {
+        self.override_type_name
+            .as_ref()
+            .and_then(|f| f(container, type_name, param_name, self))
+            .or_else(|| {
+                self.hooks
+                    .iter()
+                    .find_map(|h| h.override_type(container, type_name, param_name, self))
+            })
+    }
+
+    /// Return identifier of the wadl client
+    pub fn client_trait_name(&self) -> &'static str {
+        if self.async_client() && self.uses_native_async_client() {
+            "wadl::r#async::NativeClient"
+        } else if self.async_client() {
+            "wadl::r#async::Client"
+        } else {
+            "wadl::blocking::Client"
+        }
+    }
+
+    /// Return whether generated code may use the given syntax, see [`Config::msrv`]
+    pub fn supports(&self, syntax: Syntax) -> bool {
+        self.msrv
+            .is_none_or(|version| version >= syntax.stabilized_in())
+    }
+
+    /// Return the type of the client argument of generated methods
+    pub fn client_arg_type(&self) -> String {
+        if self.tower {
+            "&'a mut impl wadl::tower::HttpService".to_string()
+        } else if self.ureq {
+            "&'a dyn wadl::ureq::Client".to_string()
+        } else if self.async_client() && self.uses_native_async_client() {
+            format!("&'a impl {}", self.client_trait_name())
+        } else {
+            format!("&'a dyn {}", self.client_trait_name())
+        }
+    }
+
+    /// Return whether async code takes a `NativeClient`, see [`Config::native_async_client`]
+    ///
+    /// `NativeClient` can't be used before [`Syntax::ImplTraitInTrait`] is supported.
+    fn uses_native_async_client(&self) -> bool {
+        self.native_async_client && self.supports(Syntax::ImplTraitInTrait)
+    }
+
+    /// Return whether to generate code for the async client, rather than the blocking one
+    fn async_client(&self) -> bool {
+        ASYNC_CLIENT_OVERRIDE
+            .with(|o| o.get())
+            .unwrap_or(self.r#async)
+    }
+
+    /// Return whether generated methods are async
+    pub fn is_async(&self) -> bool {
+        (self.async_client() && !self.ureq) || self.tower
+    }
+
+    /// Return whether generated code builds `http::Request`s, rather than using reqwest
+    fn uses_http_crate(&self) -> bool {
+        self.tower || self.ureq
+    }
+
+    /// Check that code can be generated for an application with this configuration
+    ///
+    /// Request policies and `multipart/form-data` request bodies are only supported with
+    /// reqwest-based clients. Generating code without checking drops such policies and methods.
+    pub fn validate(&self, app: &Application) -> Result<(), ConfigError> {
+        if !self.uses_http_crate() {
+            return Ok(());
+        }
+        for method in all_methods(app) {
+            if self
+                .request_policy
+                .as_ref()
+                .is_some_and(|f| f(&method.id).is_some())
+            {
+                return Err(ConfigError::RequestPolicyNotSupported(method.id.clone()));
+            }
+            if has_multipart_body(method) {
+                return Err(ConfigError::MultipartNotSupported(method.id.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return whether response bodies with a media type are streamed
+    pub fn is_streaming_media_type(&self, media_type: &mime::Mime) -> bool {
+        media_type.essence_str() == mime::APPLICATION_OCTET_STREAM.essence_str()
+            || self
+                .streaming_media_types
+                .iter()
+                .any(|m| m == media_type.essence_str())
+    }
+
+    /// Return how a repeating query param of a method is serialized
+    pub fn query_array_style_for(&self, method: &str, param: &str) -> QueryArrayStyle {
+        self.query_array_style_override
+            .as_ref()
+            .and_then(|f| f(method, param))
+            .unwrap_or(self.query_array_style)
+    }
+
+    /// Return the type of streamed response bodies
+    fn byte_stream_type(&self) -> &'static str {
+        if self.tower {
+            "wadl::tower::Bytes"
+        } else if self.ureq {
+            "Vec<u8>"
+        } else if self.async_client() {
+            "wadl::r#async::ByteStream"
         } else {
-            format!("({})", ret.join(", "))
+            "wadl::blocking::ByteStream"
         }
-    } else {
-        todo!(
-            "multiple representations for response: {}: {:?}",
-            name,
-            representations
-        );
     }
-}
 
-fn format_arg_doc(name: &str, doc: Option<&crate::ast::Doc>, config: &Config) -> Vec<String> {
-    let mut lines = Vec::new();
-    if let Some(doc) = doc.as_ref() {
-        let doc = format_doc(doc, config);
-        let mut doc_lines = doc
-            .trim_start_matches('\n')
-            .split('\n')
-            .collect::<Vec<_>>()
-            .into_iter();
-        lines.push(format!(
-            "    /// * `{}`: {}\n",
-            name,
-            doc_lines.next().unwrap().trim_end_matches(' ')
-        ));
-        for doc_line in doc_lines {
-            if doc_line.is_empty() {
-                lines.push("    ///\n".to_string());
-            } else {
-                lines.push(format!("    ///     {}\n", doc_line.trim_end_matches(' ')));
-            }
+    /// Return the type of files uploaded in multipart request bodies
+    fn upload_type(&self) -> &'static str {
+        if self.async_client() {
+            "wadl::r#async::Upload"
+        } else {
+            "wadl::blocking::Upload"
         }
-    } else {
-        lines.push(format!("    /// * `{}`\n", name));
     }
 
-    lines
-}
+    /// Return the path of the module that provides HTTP types like `header` and `Method`
+    fn http_module(&self) -> &'static str {
+        if self.tower {
+            "wadl::tower::http"
+        } else if self.ureq {
+            "wadl::ureq::http"
+        } else {
+            "reqwest"
+        }
+    }
 
-fn apply_map_fn(map_fn: Option<&str>, ret: &str, required: bool) -> String {
-    if let Some(map_fn) = map_fn {
-        if required {
-            if map_fn.starts_with('|') {
-                format!("({})({})", map_fn, ret)
-            } else {
-                format!("{}({})", map_fn, ret)
+    /// Return the base URL to resolve relative links in docs against
+    fn doc_base_url<'a>(&'a self, app: &'a Application) -> Option<&'a url::Url> {
+        self.doc_base_url
+            .as_ref()
+            .or_else(|| app.resources.iter().find_map(|r| r.base.as_ref()))
+    }
+
+    /// Return the cargo feature that gates a resource type
+    ///
+    /// # Arguments
+    /// * `name` - The Rust name of the resource type
+    pub fn resource_type_feature(&self, name: &str) -> Option<String> {
+        self.feature_gate.as_ref().and_then(|x| x(name, None))
+    }
+
+    /// Return whether representation fields that link to a resource type use `wadl::Link`
+    ///
+    /// See [`Config::typed_links`].
+    fn is_typed_link(&self, r: &ResourceTypeRef) -> bool {
+        self.typed_links
+            && r.id().is_some_and(|id| {
+                TYPE_NAMES.with(|n| {
+                    n.borrow()
+                        .contains_key(&(ItemKind::ResourceType, id.to_string()))
+                }) && self
+                    .resource_type_feature(type_name(ItemKind::ResourceType, id).as_str())
+                    .is_none()
+            })
+    }
+
+    /// Return the type of representation fields that link to a resource type
+    fn link_field_type(&self, r: &ResourceTypeRef) -> String {
+        match r.id() {
+            Some(id) if self.is_typed_link(r) => {
+                format!("wadl::Link<{}>", type_name(ItemKind::ResourceType, id))
             }
-        } else {
-            format!("{}.map({})", ret, map_fn)
+            _ => "url::Url".to_string(),
         }
-    } else {
-        ret.to_string()
     }
-}
 
-fn serialize_representation_def(
-    def: &RepresentationDef,
-    config: &Config,
-    options_names: &HashMap<Options, String>,
-) -> Vec<String> {
-    let mut lines = vec![];
-    fn process_param(
-        param: &Param,
-        container: &ParamContainer,
-        config: &Config,
-        cb: impl Fn(&str, &str, &str) -> String,
-        options_names: &HashMap<Options, String>,
-    ) -> Vec<String> {
-        let param_name = escape_rust_reserved(param.name.as_str());
+    /// Return whether resource types implement `Fetch`, so that typed links can be followed
+    ///
+    /// `Fetch` takes a `dyn Client`, so this is not possible for other kinds of clients.
+    fn generates_fetch(&self) -> bool {
+        self.typed_links && !self.blocking_and_async && self.uses_dyn_client()
+    }
 
-        let (param_type, _annotations) = param_rust_type(
-            container,
-            param,
-            config,
-            resource_type_rust_type,
-            options_names,
-        );
-        let param_type = readonly_rust_type(&param_type);
-        let mut indent = 4;
-        let mut lines = vec![];
+    /// Return whether generated methods take a `dyn Client` of reqwest
+    fn uses_dyn_client(&self) -> bool {
+        matches!(
+            self.client_arg_type().as_str(),
+            "&'a dyn wadl::blocking::Client" | "&'a dyn wadl::r#async::Client"
+        )
+    }
 
-        let needs_iter = param_type.starts_with("Vec<") || param_type.starts_with("Option<Vec<");
-        let is_optional = param_type.starts_with("Option<");
+    /// Return how the pages of a collection are represented by a representation, if it is one
+    ///
+    /// See [`Config::pagination`]. The items param must be repeating, and the next param must
+    /// be an optional link; JSON is the only supported media type.
+    pub fn pagination_for(&self, input: &RepresentationDef) -> Option<Pagination> {
+        let pagination = match self.pagination.as_ref() {
+            Some(f) => f(input)?,
+            None => Pagination::launchpad(),
+        };
+        let items = input.params.iter().find(|p| p.name == pagination.items)?;
+        let next = input.params.iter().find(|p| p.name == pagination.next)?;
+        let usable = input.id.is_some()
+            && input.media_type == Some(mime::APPLICATION_JSON)
+            && items.repeating
+            && !self.is_nullable_field(input, items)
+            && !next.links.is_empty()
+            && !next.repeating
+            && !next.required
+            && !self.is_nullable_field(input, next);
+        usable.then_some(pagination)
+    }
 
-        if is_optional && param.fixed.is_none() {
-            lines.push(format!(
-                "{:indent$}if let Some({}) = {} {{\n",
-                "",
-                param_name,
-                param_name,
-                indent = indent
-            ));
-            indent += 4;
+    /// Return the cargo features that gate a method, excluding the feature of its resource type
+    fn method_features(&self, input: &Method, parent_id: &str) -> Vec<String> {
+        let name = method_rust_name(input, parent_id, self);
+        let parent = type_name(ItemKind::ResourceType, parent_id);
+        let mut features = vec![];
+        if let Some(feature) = self
+            .feature_gate
+            .as_ref()
+            .and_then(|x| x(parent.as_str(), Some(name.as_str())))
+        {
+            features.push(feature);
         }
-        if needs_iter && param.fixed.is_none() {
-            lines.push(format!(
-                "{:indent$}for {} in {} {{\n",
-                "", param_name, param_name
-            ));
-            indent += 4;
+        // The method can only be generated if the resource types it refers to are
+        let params = method_request_params(input)
+            .into_iter()
+            .chain(input.responses.iter().flat_map(|r| r.params.iter()));
+        for param in params {
+            for link in &param.links {
+                if let Some(id) = link.resource_type.as_ref().and_then(|r| r.id()) {
+                    let referenced = type_name(ItemKind::ResourceType, id);
+                    if referenced == parent {
+                        continue;
+                    }
+                    if let Some(feature) = self.resource_type_feature(referenced.as_str()) {
+                        if !features.contains(&feature) {
+                            features.push(feature);
+                        }
+                    }
+                }
+            }
         }
+        features
+    }
 
-        let value = if let Some(fixed) = param.fixed.as_ref() {
-            format!("\"{}\"", fixed)
-        } else if param.links.is_empty() {
-            format!("&{}.to_string()", param_name)
+    /// Return the `#[deprecated]` attribute for an item, if its documentation marks it
+    /// as deprecated
+    fn deprecated_attr<'a>(
+        &self,
+        docs: impl IntoIterator<Item = &'a Doc>,
+        indent: usize,
+    ) -> Option<String> {
+        let note = docs.into_iter().find_map(|doc| {
+            let text = format_doc(doc, self);
+            match self.deprecation_note.as_ref() {
+                Some(deprecation_note) => deprecation_note(&text),
+                None => default_deprecation_note(&text),
+            }
+        })?;
+        Some(format!(
+            "{}#[deprecated(note = {:?})]\n",
+            " ".repeat(indent * 4),
+            note
+        ))
+    }
+
+    /// Return the visibility of a generated item, see [`Config::visibility`]
+    ///
+    /// # Arguments
+    /// * `kind` - The kind of item
+    /// * `name` - The Rust name of the item
+    pub fn item_visibility(&self, kind: ItemKind, name: &str) -> Option<Visibility> {
+        self.visibility.as_ref().and_then(|f| f(kind, name))
+    }
+
+    /// Return the segments of the module path of an item, see [`Config::module_path`]
+    ///
+    /// # Arguments
+    /// * `kind` - The kind of item
+    /// * `id` - The id of the item in the WADL
+    pub fn module_path_segments(&self, kind: ItemKind, id: Option<&str>) -> Vec<String> {
+        let Some(path) = self
+            .module_path
+            .as_ref()
+            .zip(id)
+            .and_then(|(f, id)| f(kind, id))
+        else {
+            return vec![];
+        };
+        path.split("::")
+            .map(|segment| segment.trim())
+            .filter(|segment| !segment.is_empty() && *segment != "self")
+            .map(|segment| segment.to_string())
+            .collect()
+    }
+
+    /// Return whether a representation field is stored in a `Box`, see [`Config::box_field`]
+    pub fn is_boxed_field(&self, input: &RepresentationDef, param: &Param) -> bool {
+        if param.repeating
+            || (self.box_field.is_none() && self.box_fields_larger_than.is_none())
+            || self.is_nullable_field(input, param)
+        {
+            return false;
+        }
+        let rust_type = if !param.links.is_empty() {
+            "url::Url".to_string()
+        } else if param.options.is_some() {
+            // Options enums are fieldless
+            return self.box_field.as_ref().is_some_and(|f| f(input, param));
         } else {
-            format!("&{}.url().to_string()", param_name)
+            let simple_type = simple_type_rust_type(
+                &ParamContainer::Representation(input),
+                &param.r#type,
+                param,
+                self,
+            );
+            if !simple_type.annotations.is_empty() {
+                return false;
+            }
+            simple_type.rust_type
         };
+        self.box_field.as_ref().is_some_and(|f| f(input, param))
+            || self
+                .box_fields_larger_than
+                .is_some_and(|limit| estimated_type_size(&rust_type) > limit)
+    }
 
-        lines.push(format!(
-            "{:indent$}{}\n",
-            "",
-            cb(param_type.as_str(), param.name.as_str(), value.as_str()),
-            indent = indent
-        ));
+    /// Return whether a field distinguishes `null` from a missing value
+    ///
+    /// See [`Config::nullable_field_style`] and [`Config::nullable_field`].
+    pub fn is_nullable_field(&self, input: &RepresentationDef, param: &Param) -> bool {
+        if self.nullable_field_style == NullableFieldStyle::Option
+            || param.required
+            || !param.links.is_empty()
+            || is_form_media_type(input.media_type.as_ref())
+        {
+            return false;
+        }
+        if param.options.is_none() {
+            let simple_type = simple_type_rust_type(
+                &ParamContainer::Representation(input),
+                &param.r#type,
+                param,
+                self,
+            );
+            if !simple_type.annotations.is_empty() {
+                return false;
+            }
+        }
+        is_merge_patch(input)
+            || self
+                .nullable_field
+                .as_ref()
+                .is_some_and(|f| f(input, param))
+    }
 
-        if needs_iter && param.fixed.is_none() {
-            indent -= 4;
-            lines.push(format!("{:indent$}}}\n", "", indent = indent));
+    /// Return the traits to derive for a representation
+    ///
+    /// This is [`Config::derives`], without `Clone` if [`Config::omit_clone_for_boxed`] is set
+    /// and the representation has boxed fields.
+    pub fn representation_derives(&self, input: &RepresentationDef, name: &str) -> Vec<String> {
+        let mut derives = self.derives(ItemKind::Representation, name);
+        if self.omit_clone_for_boxed && input.params.iter().any(|p| self.is_boxed_field(input, p)) {
+            derives.retain(|d| d != "Clone");
         }
+        derives
+    }
 
-        if is_optional && param.fixed.is_none() {
-            indent -= 4;
-            lines.push(format!("{:indent$}}}\n", "", indent = indent));
+    /// Return the traits to derive for a generated item
+    ///
+    /// # Arguments
+    /// * `kind` - The kind of item
+    /// * `name` - The Rust name of the item
+    pub fn derives(&self, kind: ItemKind, name: &str) -> Vec<String> {
+        if let Some(derives) = self.derive.as_ref().and_then(|x| x(kind, name)) {
+            return derives;
         }
+        let defaults: &[&str] = match kind {
+            ItemKind::Representation => &[
+                "Debug",
+                "Clone",
+                "PartialEq",
+                "serde::Serialize",
+                "serde::Deserialize",
+            ],
+            ItemKind::OptionsEnum => &[
+                "Debug",
+                "Clone",
+                "Copy",
+                "PartialEq",
+                "Eq",
+                "std::hash::Hash",
+                "serde::Serialize",
+                "serde::Deserialize",
+            ],
+            ItemKind::ResourceType | ItemKind::Method | ItemKind::Accessor | ItemKind::Trait => &[],
+        };
+        defaults.iter().map(|x| x.to_string()).collect()
+    }
+}
 
-        lines
+fn enum_rust_value(option: &str) -> String {
+    let name = camel_case_name(option.replace(' ', "-").as_str());
+
+    // Now, strip all characters not allowed in rust identifiers
+    let name = name
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_')
+        .collect::<String>();
+
+    // If the identifier starts with a digit, prefix it with '_' to make it a valid identifier
+    if name.chars().next().unwrap().is_numeric() {
+        format!("_{}", name)
+    } else {
+        name
     }
+}
 
-    let container = ParamContainer::Representation(def);
+fn generate_options(
+    name: &str,
+    options: &crate::ast::Options,
+    default: Option<&str>,
+    config: &Config,
+) -> Vec<String> {
+    let mut lines = vec![];
 
-    match def.media_type.as_ref().map(|s| s.to_string()).as_deref() {
-        Some("multipart/form-data") => {
-            let mp_mod = if !config.r#async {
-                "reqwest::blocking"
-            } else {
-                "reqwest"
-            };
-            lines.push(format!(
-                "let mut form = {}::multipart::Form::new();\n",
-                mp_mod
-            ));
-            for param in def.params.iter() {
-                lines.extend(process_param(
-                    param,
-                    &container,
-                    config,
-                    |param_type, name, value| {
-                        format!(
-                            "form = form.part(\"{}\", {});",
-                            name,
-                            if let Some(convert_to_multipart) = config
-                                .convert_to_multipart
-                                .as_ref()
-                                .and_then(|x| x(param_type, value))
-                            {
-                                convert_to_multipart
-                            } else {
-                                format!(
-                                    "{}::multipart::Part::text({})",
-                                    mp_mod,
-                                    value.strip_prefix('&').unwrap_or(value)
-                                )
-                            }
-                        )
-                    },
-                    options_names,
-                ));
-            }
-            lines.push("req = req.multipart(form);\n".to_string());
-        }
-        Some("application/x-www-form-urlencoded") => {
-            lines.push(
-                "let mut serializer = form_urlencoded::Serializer::new(String::new());\n"
-                    .to_string(),
-            );
-            for param in def.params.iter() {
-                lines.extend(process_param(param, &container, config, |r#type, name, value| {
-                    if r#type.contains("[") {
-                        format!("for value in {} {{ serializer.append_pair(\"{}\", &value.to_string()); }}", value.strip_prefix("&").unwrap().strip_suffix(".to_string()").unwrap(), name)
-                    } else {
-                        format!("serializer.append_pair(\"{}\", {});", name, value)
-                    }
-                }, options_names));
+    lines.extend(derive_line(&config.derives(ItemKind::OptionsEnum, name)));
+    if config.options_non_exhaustive {
+        lines.push("#[non_exhaustive]\n".to_string());
+    }
+    lines.push(format!(
+        "{}enum {} {{\n",
+        config
+            .item_visibility(ItemKind::OptionsEnum, name)
+            .unwrap_or_default()
+            .prefix(),
+        name
+    ));
+
+    let mut option_map = vec![];
+
+    for (option, media_type) in options.iter() {
+        let rust_name = enum_rust_value(option);
+        if let Some(doc) = options.get_doc(option) {
+            lines.extend(generate_doc(doc, 1, config));
+        }
+        if let Some(media_type) = media_type {
+            if options.get_doc(option).is_some() {
+                lines.push("    ///\n".to_string());
             }
-            lines.push("req = req.header(reqwest::header::CONTENT_TYPE, \"application/x-www-form-urlencoded\");\n".to_string());
-            lines.push("req = req.body(serializer.finish());\n".to_string());
+            lines.push(format!("    /// Media type: `{}`\n", media_type));
         }
-        Some("application/json") => {
-            lines.push("let mut o = serde_json::Value::Object::new();".to_string());
+        lines.push(format!("    #[serde(rename = \"{}\")]\n", option));
+        lines.push(format!("    {},\n", rust_name));
+        option_map.push((option, rust_name));
+    }
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
 
-            for param in def.params.iter() {
-                lines.extend(process_param(
-                    param,
-                    &container,
-                    config,
-                    |_type, name, value| format!("o.insert(\"{}\", {});", name, value),
-                    options_names,
-                ));
-            }
+    lines.push(format!("impl std::fmt::Display for {} {{\n", name));
+    lines.push(
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n".to_string(),
+    );
+    lines.push("        match self {\n".to_string());
+    for (option, rust_name) in option_map.iter() {
+        lines.push(format!(
+            "            {}::{} => write!(f, \"{}\"),\n",
+            name, rust_name, option
+        ));
+    }
+    lines.push("        }\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
 
-            lines.push("req = req.json(&o);\n".to_string());
-        }
-        o => {
-            panic!("unsupported media type {:?}", o);
+    lines.push(format!("impl std::str::FromStr for {} {{\n", name));
+    lines.push("    type Err = wadl::Error;\n".to_string());
+    lines.push("\n".to_string());
+    lines.push("    fn from_str(s: &str) -> Result<Self, Self::Err> {\n".to_string());
+    lines.push("        match s {\n".to_string());
+    for (option, rust_name) in option_map.iter() {
+        lines.push(format!(
+            "            {:?} => Ok({}::{}),\n",
+            option, name, rust_name
+        ));
+    }
+    lines.push(format!(
+        "            s => Err(wadl::Error::InvalidOption({:?}, s.to_string())),\n",
+        name
+    ));
+    lines.push("        }\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+
+    lines.push(format!(
+        "impl std::convert::TryFrom<&str> for {} {{\n",
+        name
+    ));
+    lines.push("    type Error = wadl::Error;\n".to_string());
+    lines.push("\n".to_string());
+    lines.push("    fn try_from(s: &str) -> Result<Self, Self::Error> {\n".to_string());
+    lines.push("        s.parse()\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+
+    if let Some(default) = default {
+        if let Some((_, rust_name)) = option_map.iter().find(|(o, _)| *o == default) {
+            lines.push("\n".to_string());
+            lines.push(format!("impl Default for {} {{\n", name));
+            lines.push("    fn default() -> Self {\n".to_string());
+            lines.push(format!("        {}::{}\n", name, rust_name));
+            lines.push("    }\n".to_string());
+            lines.push("}\n".to_string());
+        } else {
+            log::warn!("Default {} for {} is not a valid option", default, name);
         }
     }
+
+    #[cfg(feature = "templates")]
+    let lines = templates::apply(
+        config.templates.as_ref(),
+        TemplateKind::OptionsEnum,
+        lines,
+        |code| OptionsEnumContext {
+            name: name.to_string(),
+            derives: config.derives(ItemKind::OptionsEnum, name),
+            variants: option_map
+                .iter()
+                .map(|(option, rust_name)| VariantContext {
+                    name: rust_name.clone(),
+                    value: option.to_string(),
+                })
+                .collect(),
+            default_variant: default.and_then(|default| {
+                option_map
+                    .iter()
+                    .find(|(o, _)| *o == default)
+                    .map(|(_, rust_name)| rust_name.clone())
+            }),
+            default: code,
+        },
+    );
+
     lines
 }
 
-fn generate_method(
-    input: &Method,
-    parent_id: &str,
+fn options_rust_enum_name(param: &Param, options: &HashMap<Options, String>) -> String {
+    let mut name = camel_case_name(param.name.as_str());
+    while options.values().any(|v| v == &name) || type_name_taken(&name) {
+        name = format!("{}_", name);
+    }
+    name
+}
+
+/// Generate enums for all sets of options in the application
+///
+/// Returns the names of the generated enums, keyed by their options, and the generated code.
+fn generate_all_options(
+    app: &Application,
     config: &Config,
-    options_names: &HashMap<Options, String>,
-) -> Vec<String> {
-    let mut lines = generate_method_representation(input, parent_id, config, options_names);
+) -> (HashMap<Options, String>, Vec<String>) {
+    let mut options = HashMap::new();
+    let mut lines = vec![];
 
-    for response in input.responses.iter() {
-        if response.representations.iter().any(|r| {
-            r.media_type().as_ref().map(|s| s.to_string()).as_deref() == Some(crate::WADL_MIME_TYPE)
-        }) {
-            lines.extend(generate_method_wadl(input, parent_id, config))
+    for param in app.iter_all_params() {
+        if let Some(os) = &param.options {
+            if options.contains_key(os) {
+                continue;
+            }
+            let name = if let Some(enum_name_fn) = config.options_enum_name.as_ref() {
+                let cb_options = options.clone();
+                let name = enum_name_fn(
+                    param,
+                    Box::new(move |name: &str| -> bool {
+                        cb_options.values().any(|v| v == name) || type_name_taken(name)
+                    }),
+                );
+                let taken = options
+                    .iter()
+                    .filter_map(|(k, v)| if v == &name { Some(k) } else { None })
+                    .collect::<Vec<_>>();
+                if !taken.is_empty() {
+                    panic!(
+                        "Enum name {} is already taken by {:?} ({:?})",
+                        name, taken, options
+                    );
+                }
+                name
+            } else {
+                options_rust_enum_name(param, &options)
+            };
+            let enum_lines = generate_options(name.as_str(), os, param.default.as_deref(), config);
+            options.insert(os.clone(), name);
+            lines.extend(enum_lines);
         }
     }
 
-    lines
+    (options, lines)
 }
 
-fn generate_method_wadl(input: &Method, parent_id: &str, config: &Config) -> Vec<String> {
-    let mut lines = vec![];
+/// Generate code from a WADL application definition.
+///
+/// This function generates Rust code from a WADL application definition.
+/// The generated code includes Rust types for the representations and
+/// resource types defined in the WADL application, as well as methods
+/// for interacting with the resources.
+///
+/// # Arguments
+/// * `app` - The WADL application definition.
+/// * `config` - Configuration for the code generation.
+pub fn generate(app: &Application, config: &Config) -> String {
+    let app = filter_application(app, config);
+    with_application(&app, config, || generate_in_context(&app, config, None))
+}
 
-    let name = input.id.as_str();
-    let name = name
-        .strip_prefix(format!("{}-", parent_id).as_str())
-        .unwrap_or(name);
-    let name = snake_case_name(name);
+/// Generate code from a WADL application definition, split over several files.
+///
+/// The modules of [`Config::module_path`] are placed in files of their own rather than
+/// inline, following the layout of `mod.rs` modules: the returned map has a `mod.rs` with
+/// the top-level code, a `bugs.rs` for a module `bugs`, a `bugs/tasks.rs` for a module
+/// `bugs::tasks`, and so on. If [`Config::header`] is set, every file starts with it.
+///
+/// # Arguments
+/// * `app` - The WADL application definition.
+/// * `config` - Configuration for the code generation.
+///
+/// # Returns
+/// The contents of the files, keyed by their path relative to the output directory
+pub fn generate_files(app: &Application, config: &Config) -> BTreeMap<PathBuf, String> {
+    let app = filter_application(app, config);
+    let mut files = BTreeMap::new();
+    let code = with_application(&app, config, || {
+        generate_in_context(&app, config, Some(&mut files))
+    });
+    files.insert(PathBuf::from("mod.rs"), code);
+    files
+}
 
-    let async_prefix = if config.r#async { "async " } else { "" };
+/// Return why code can not be generated for a method with a configuration, if it can't
+fn unsupported_method(method: &Method, config: &Config) -> Option<&'static str> {
+    if !is_token(&method.name) {
+        Some("its name is not a valid HTTP method")
+    } else if config.uses_http_crate() && has_multipart_body(method) {
+        Some("multipart/form-data request bodies are only supported with reqwest-based clients")
+    } else {
+        None
+    }
+}
 
-    lines.push(format!("    pub {}fn {}_wadl<'a>(&self, client: &'a dyn {}) -> std::result::Result<wadl::ast::Resource, wadl::Error> {{\n", async_prefix, name, config.client_trait_name()));
+/// Return whether a method has a `multipart/form-data` request body
+fn has_multipart_body(method: &Method) -> bool {
+    method.request.representations.iter().any(|r| {
+        r.as_def()
+            .and_then(|d| d.media_type.as_ref())
+            .is_some_and(|m| m.essence_str() == "multipart/form-data")
+    })
+}
 
-    lines.push("        let mut url_ = self.url().clone();\n".to_string());
-    for param in input
-        .request
-        .params
-        .iter()
-        .filter(|p| p.style == ParamStyle::Query)
-    {
-        if let Some(fixed) = param.fixed.as_ref() {
-            assert!(!param.repeating);
-            lines.push(format!(
-                "        url_.query_pairs_mut().append_pair(\"{}\", \"{}\");\n",
-                param.name, fixed
-            ));
+/// Return all methods of an application, including those of nested resources
+fn all_methods(app: &Application) -> Vec<&Method> {
+    fn collect<'a>(resources: &'a [Resource], methods: &mut Vec<&'a Method>) {
+        for resource in resources {
+            methods.extend(resource.methods.iter());
+            collect(&resource.subresources, methods);
         }
     }
 
-    lines.push("\n".to_string());
-
-    let method = input.name.as_str();
-    if config.r#async {
-        lines.push(format!(
-            "        let mut req = client.request(reqwest::Method::{}, url_).await;\n",
-            method
-        ));
-    } else {
-        lines.push(format!(
-            "        let mut req = client.request(reqwest::Method::{}, url_);\n",
-            method
-        ));
+    let mut methods = vec![];
+    for resource_type in &app.resource_types {
+        methods.extend(resource_type.methods.iter());
+        collect(&resource_type.subresources, &mut methods);
     }
+    for resources in &app.resources {
+        collect(&resources.resources, &mut methods);
+    }
+    methods
+}
 
-    lines.push(format!(
-        "        req = req.header(reqwest::header::ACCEPT, \"{}\");\n",
-        crate::WADL_MIME_TYPE
-    ));
-
-    lines.push("\n".to_string());
-
-    if config.r#async {
-        lines.push("        let wadl: wadl::ast::Application = req.send().await?.error_for_status()?.text().await?.parse()?;\n".to_string());
-    } else {
-        lines.push("        let wadl: wadl::ast::Application = req.send()?.error_for_status()?.text()?.parse()?;\n".to_string());
+/// Return the application without the items excluded by [`Config::include_item`]
+///
+/// Methods that code can not be generated for are dropped as well, with a warning.
+fn filter_application<'a>(app: &'a Application, config: &Config) -> Cow<'a, Application> {
+    if config.item_filter.is_none()
+        && all_methods(app)
+            .iter()
+            .all(|m| unsupported_method(m, config).is_none())
+    {
+        return Cow::Borrowed(app);
     }
-    lines.push(
-        "        let resource = wadl.get_resource_by_href(self.url()).unwrap();\n".to_string(),
-    );
 
-    lines.push("        Ok(resource.clone())\n".to_string());
+    let excluded = app
+        .resource_types
+        .iter()
+        .filter(|rt| !config.include_item(ItemKind::ResourceType, &rt.id))
+        .map(|rt| rt.id.to_string())
+        .collect::<HashSet<_>>();
+    let is_excluded = |r: &ResourceTypeRef| r.id().is_some_and(|id| excluded.contains(id));
+
+    let filter_params = |params: &mut Vec<Param>| {
+        for param in params {
+            for link in param.links.iter_mut() {
+                if link.resource_type.as_ref().is_some_and(is_excluded) {
+                    link.resource_type = None;
+                }
+            }
+        }
+    };
 
-    lines.push("    }\n".to_string());
+    let filter_representations = |representations: &mut Vec<Representation>| {
+        for representation in representations {
+            if let Representation::Definition(def) = representation {
+                filter_params(&mut def.params);
+            }
+        }
+    };
 
-    lines.push("\n".to_string());
+    let filter_methods = |methods: &mut Vec<Method>| {
+        methods.retain(|m| {
+            if let Some(reason) = unsupported_method(m, config) {
+                log::warn!("Skipping {} method {:?}, since {}", m.name, m.id, reason);
+                return false;
+            }
+            config.include_item(ItemKind::Method, &m.id)
+        });
+        for method in methods {
+            filter_params(&mut method.request.params);
+            filter_representations(&mut method.request.representations);
+            for response in &mut method.responses {
+                filter_params(&mut response.params);
+                filter_representations(&mut response.representations);
+            }
+        }
+    };
 
-    lines
+    fn filter_resources(
+        resources: &mut Vec<Resource>,
+        is_excluded: &dyn Fn(&ResourceTypeRef) -> bool,
+        filter_params: &dyn Fn(&mut Vec<Param>),
+        filter_methods: &dyn Fn(&mut Vec<Method>),
+    ) {
+        resources.retain(|r| !r.r#type.iter().any(is_excluded));
+        for resource in resources {
+            filter_params(&mut resource.params);
+            filter_methods(&mut resource.methods);
+            filter_resources(
+                &mut resource.subresources,
+                is_excluded,
+                filter_params,
+                filter_methods,
+            );
+        }
+    }
+
+    let mut app = app.clone();
+    app.resource_types
+        .retain(|rt| !excluded.contains(rt.id.as_str()));
+    for resource_type in &mut app.resource_types {
+        filter_params(&mut resource_type.params);
+        filter_methods(&mut resource_type.methods);
+        filter_resources(
+            &mut resource_type.subresources,
+            &is_excluded,
+            &filter_params,
+            &filter_methods,
+        );
+    }
+    for resources in &mut app.resources {
+        filter_resources(
+            &mut resources.resources,
+            &is_excluded,
+            &filter_params,
+            &filter_methods,
+        );
+    }
+    for representation in &mut app.representations {
+        filter_params(&mut representation.params);
+    }
+    Cow::Owned(app)
 }
 
-fn generate_method_representation(
-    input: &Method,
-    parent_id: &str,
+/// Generate the code for an application
+///
+/// If `files` is given, the modules of [`Config::module_path`] are added to it as separate
+/// files rather than generated inline, see [`generate_files`].
+fn generate_in_context(
+    app: &Application,
     config: &Config,
-    options_names: &HashMap<Options, String>,
-) -> Vec<String> {
+    files: Option<&mut BTreeMap<PathBuf, String>>,
+) -> String {
     let mut lines = vec![];
 
-    let name = input.id.as_str();
-    let name = name
-        .strip_prefix(format!("{}-", parent_id).as_str())
-        .unwrap_or(name);
-    let name = snake_case_name(name);
+    if let Some(header) = config.header.as_ref() {
+        lines.extend(header.comment_lines());
+        lines.extend(header.attribute_lines());
+        lines.push("\n".to_string());
+    }
 
-    let (ret_type, map_fn) = if input.responses.is_empty() {
-        ("()".to_string(), None)
-    } else {
-        assert_eq!(1, input.responses.len(), "expected 1 response for {}", name);
-        let mut return_type =
-            rust_type_for_response(input, &input.responses[0], input.id.as_str(), options_names);
-        let map_fn = if let Some((map_type, map_fn)) = config
-            .map_type_for_response
-            .as_ref()
-            .and_then(|r| r(&name, &return_type, config))
-        {
-            return_type = map_type;
-            Some(map_fn)
-        } else {
-            None
-        };
-        (return_type, map_fn)
-    };
+    // Application docs are emitted as regular comments, since there is no item to attach
+    // them to and inner doc comments are rejected by include!().
+    for doc in &app.docs {
+        lines.extend(
+            generate_doc(doc, 0, config)
+                .into_iter()
+                .map(|line| line.replacen("///", "//", 1)),
+        );
+    }
 
-    let visibility = config
-        .method_visibility
-        .as_ref()
-        .and_then(|x| x(&name, &ret_type))
-        .unwrap_or("pub".to_string());
+    // Code that is include!()d relies on the includer to import the traits it uses, but
+    // the files of generate_files() are modules of their own
+    if files.is_some() {
+        lines.push("#[allow(unused_imports)]\n".to_string());
+        lines.push("use wadl::Resource;\n".to_string());
+        lines.push("\n".to_string());
+    }
 
-    let mut line = format!(
-        "    {}{}fn {}<'a>(&self, client: &'a dyn {}",
-        if visibility.is_empty() {
-            "".to_string()
-        } else {
-            format!("{} ", visibility)
-        },
-        if config.r#async { "async " } else { "" },
-        name,
-        config.client_trait_name()
-    );
+    let (options, options_lines) = generate_all_options(app, config);
+    lines.extend(options_lines);
 
-    let mut params = input.request.params.iter().collect::<Vec<_>>();
+    let (constants, constants_lines) = Constants::collect(app, config);
+    lines.extend(constants_lines);
 
-    params.extend(
-        input
-            .request
-            .representations
-            .iter()
-            .filter_map(|r| match r {
-                Representation::Definition(d) => Some(&d.params),
-                Representation::Reference(_) => None,
-            })
-            .flatten(),
-    );
+    lines.extend(generate_patch_structs(app, config, &options));
+    lines.extend(generate_head_header_structs(app, config, &options));
+    lines.extend(generate_response_enums(app, config));
 
-    for doc in &input.docs {
-        lines.extend(generate_doc(doc, 1, config));
+    let mut modules = ModuleTree::default();
+    for representation in &app.representations {
+        modules.insert(
+            &config.module_path_segments(ItemKind::Representation, representation.id.as_deref()),
+            generate_representation(
+                representation,
+                &app.resource_types,
+                &app.representations,
+                config,
+                &options,
+                &constants,
+            ),
+        );
     }
 
-    if !params.is_empty() {
-        lines.push("    /// # Arguments\n".to_string());
+    let paths = resource_paths(app);
+    for resource_type in &app.resource_types {
+        modules.insert(
+            &config.module_path_segments(ItemKind::ResourceType, Some(&resource_type.id)),
+            generate_resource_type(resource_type, config, &options, &constants, &paths),
+        );
     }
 
-    for representation in &input.request.representations {
-        match representation {
-            Representation::Definition(_) => {}
-            Representation::Reference(r) => {
-                let id = camel_case_name(r.id().unwrap());
-                line.push_str(format!(", representation: &{}", id).as_str());
-            }
+    lines.extend(modules.imports(&[]));
+    match files {
+        Some(files) => {
+            lines.extend(modules.declarations());
+            lines.extend(modules.into_files(Path::new(""), app, config, files));
         }
+        None => lines.extend(modules.into_lines()),
     }
 
-    let container = ParamContainer::Request(input, &input.request);
-    for param in &params {
-        if param.fixed.is_some() {
-            continue;
-        }
-        let (param_type, _annotations) = param_rust_type(
-            &container,
-            param,
-            config,
-            resource_type_rust_type,
-            options_names,
-        );
-        let param_type = readonly_rust_type(param_type.as_str());
-        let param_name = param.name.clone();
-        let param_name = escape_rust_reserved(param_name.as_str());
-
-        line.push_str(format!(", {}: {}", param_name, param_type).as_str());
-
-        lines.extend(format_arg_doc(param_name, param.doc.as_ref(), config));
+    if config.generate_any_resource {
+        lines.extend(generate_any_resource(app, config));
     }
-    line.push_str(") -> std::result::Result<");
-    line.push_str(ret_type.as_str());
 
-    line.push_str(", wadl::Error> {\n");
-    lines.push(line);
+    if config.blocking_and_async {
+        for (module, r#async) in [("blocking", false), ("async", true)] {
+            lines.extend(with_async_client(r#async, || {
+                generate_client_module(app, module, config, &options, &constants)
+            }));
+        }
+    }
 
-    assert!(input
-        .request
-        .params
-        .iter()
-        .all(|p| [ParamStyle::Header, ParamStyle::Query].contains(&p.style)));
+    lines.extend(generate_date_time_format_modules(config));
 
-    lines.push("        let mut url_ = self.url().clone();\n".to_string());
-    for param in input
-        .request
-        .params
-        .iter()
-        .filter(|p| p.style == ParamStyle::Query)
-    {
-        if let Some(fixed) = param.fixed.as_ref() {
-            assert!(!param.repeating);
-            lines.push(format!(
-                "        url_.query_pairs_mut().append_pair(\"{}\", \"{}\");\n",
-                param.name, fixed
-            ));
-        } else {
-            let param_name = param.name.as_str();
-            let param_name = snake_case_name(param_name);
-            let param_name = escape_rust_reserved(param_name.as_str());
-            let (param_type, _annotations) = param_rust_type(
-                &container,
-                param,
-                config,
-                resource_type_rust_type,
-                options_names,
-            );
-            let value = if !param.links.is_empty() {
-                format!("&{}.url().to_string()", param_name)
-            } else {
-                format!("&{}.to_string()", param_name)
-            };
+    for hook in &config.hooks {
+        lines.extend(hook.extend_module(app, config));
+    }
 
-            let mut indent = 0;
+    resolve_generated_doc_links(lines, config.doc_base_url(app)).concat()
+}
 
-            let needs_iter = param.repeating
-                || param_type.starts_with("Vec<")
-                || param_type.starts_with("Option<Vec<");
+/// Generated items, grouped by the module they are placed in
+#[derive(Default)]
+struct ModuleTree {
+    lines: Vec<String>,
+    children: BTreeMap<String, ModuleTree>,
+}
 
-            if param_type.starts_with("Option<") {
-                lines.push(format!(
-                    "        if let Some({}) = {} {{\n",
-                    param_name, param_name
-                ));
-                indent += 4;
-            }
-            if needs_iter {
-                lines.push(format!(
-                    "{:indent$}        for {} in {} {{\n",
-                    "", param_name, param_name
-                ));
-                indent += 4;
-            }
-            lines.push(format!(
-                "{:indent$}        url_.query_pairs_mut().append_pair(\"{}\", {});\n",
-                "",
-                param.name,
-                value,
-                indent = indent
-            ));
-            while indent > 0 {
-                lines.push(format!("{:indent$}    }}\n", "", indent = indent));
-                indent -= 4;
-            }
+impl ModuleTree {
+    /// Add the lines of an item to the module with the given path
+    fn insert(&mut self, path: &[String], lines: Vec<String>) {
+        match path.split_first() {
+            None => self.lines.extend(lines),
+            Some((first, rest)) => self
+                .children
+                .entry(first.clone())
+                .or_default()
+                .insert(rest, lines),
         }
     }
 
-    lines.push("\n".to_string());
+    /// Return the imports of the items in all submodules
+    fn imports(&self, prefix: &[String]) -> Vec<String> {
+        let mut lines = vec![];
+        for (name, child) in &self.children {
+            let mut path = prefix.to_vec();
+            path.push(escape_rust_reserved(name).to_string());
+            lines.push("#[allow(unused_imports)]\n".to_string());
+            lines.push(format!("use self::{}::*;\n", path.join("::")));
+            lines.extend(child.imports(&path));
+        }
+        if prefix.is_empty() && !lines.is_empty() {
+            lines.push("\n".to_string());
+        }
+        lines
+    }
 
-    let method = input.name.as_str();
-    if config.r#async {
-        lines.push(format!(
-            "        let mut req = client.request(reqwest::Method::{}, url_).await;\n",
-            method
-        ));
-    } else {
-        lines.push(format!(
-            "        let mut req = client.request(reqwest::Method::{}, url_);\n",
-            method
-        ));
+    /// Return the declarations of the submodules, for when they are in files of their own
+    fn declarations(&self) -> Vec<String> {
+        let mut lines = vec![];
+        for name in self.children.keys() {
+            lines.push(format!("pub mod {};\n", escape_rust_reserved(name)));
+        }
+        if !lines.is_empty() {
+            lines.push("\n".to_string());
+        }
+        lines
     }
 
-    for representation in &input.request.representations {
-        match representation {
-            Representation::Definition(ref d) => {
-                lines.extend(indent(
-                    2,
-                    serialize_representation_def(d, config, options_names).into_iter(),
-                ));
-            }
-            Representation::Reference(_r) => {
-                // TODO(jelmer): Support non-JSON representations
-                lines.push("        req = req.json(&representation);\n".to_string());
+    /// Add a file for each submodule, see [`generate_files`], and return the lines of the
+    /// items in this module
+    ///
+    /// # Arguments
+    /// * `dir` - The directory of the files of the submodules, relative to the output directory
+    fn into_files(
+        self,
+        dir: &Path,
+        app: &Application,
+        config: &Config,
+        files: &mut BTreeMap<PathBuf, String>,
+    ) -> Vec<String> {
+        for (name, child) in self.children {
+            let mut lines = vec![];
+            if let Some(header) = config.header.as_ref() {
+                // Lints allowed by the header in `mod.rs` also apply to its submodules
+                lines.extend(header.comment_lines());
+                lines.push("\n".to_string());
             }
-        };
+            lines.push("#![allow(unused_imports)]\n".to_string());
+            lines.push("use super::*;\n".to_string());
+            lines.push("\n".to_string());
+            lines.extend(child.declarations());
+            lines.extend(child.into_files(&dir.join(&name), app, config, files));
+            let code = resolve_generated_doc_links(lines, config.doc_base_url(app)).concat();
+            files.insert(dir.join(format!("{}.rs", name)), code);
+        }
+        self.lines
     }
 
-    let response_mime_types = input
-        .responses
-        .iter()
-        .flat_map(|x| {
-            x.representations.iter().filter_map(|x| match x {
-                Representation::Definition(ref d) if supported_representation_def(d) => {
-                    d.media_type.clone()
-                }
-                Representation::Reference(_) => {
-                    // TODO: Look up media type of reference
-                    Some(mime::APPLICATION_JSON)
-                }
-                _ => None,
-            })
-        })
-        .collect::<Vec<_>>();
-
-    if !response_mime_types.is_empty() {
-        lines.push(format!(
-            "        req = req.header(reqwest::header::ACCEPT, \"{}\");\n",
-            response_mime_types
-                .into_iter()
-                .map(|x| x.to_string())
-                .collect::<Vec<_>>()
-                .join(", ")
-        ));
+    /// Return the lines of the items in this module, followed by its submodules
+    fn into_lines(self) -> Vec<String> {
+        let mut lines = self.lines;
+        for (name, child) in self.children {
+            lines.push(format!("pub mod {} {{\n", escape_rust_reserved(&name)));
+            lines.push("    #![allow(unused_imports)]\n".to_string());
+            lines.push("    use super::*;\n".to_string());
+            lines.push("\n".to_string());
+            lines.extend(indent(1, child.into_lines().into_iter()));
+            lines.push("}\n".to_string());
+            lines.push("\n".to_string());
+        }
+        lines
     }
+}
 
-    for param in params.iter().filter(|p| p.style == ParamStyle::Header) {
-        let value = if let Some(fixed) = param.fixed.as_ref() {
-            format!("\"{}\"", fixed)
-        } else {
-            let param_name = param.name.as_str();
-            let param_name = snake_case_name(param_name);
-            let param_name = escape_rust_reserved(param_name.as_str());
-
-            format!("&{}.to_string()", param_name)
-        };
-
-        lines.push(format!(
-            "        req = req.header(\"{}\", {});\n",
-            param.name, value
-        ));
-    }
+/// Format Rust code using prettyplease.
+///
+/// # Arguments
+/// * `code` - The code to format
+///
+/// # Returns
+/// The formatted code, or an error if the code could not be parsed
+pub fn format_code(code: &str) -> Result<String, syn::Error> {
+    let file = syn::parse_file(code)?;
+    Ok(prettyplease::unparse(&file))
+}
 
-    lines.push("\n".to_string());
-    if config.r#async {
-        lines.push("        let resp = req.send().await?;\n".to_string());
-    } else {
-        lines.push("        let resp = req.send()?;\n".to_string());
-    }
+/// Generate formatted code from a WADL application definition.
+///
+/// This is equivalent to calling [`generate`] followed by [`format_code`], except that the
+/// comments of [`Config::header`] are kept.
+///
+/// # Arguments
+/// * `app` - The WADL application definition.
+/// * `config` - Configuration for the code generation.
+pub fn generate_formatted(app: &Application, config: &Config) -> Result<String, syn::Error> {
+    format_generated(&generate(app, config), config)
+}
 
-    lines.push("        match resp.status() {\n".to_string());
+/// Format generated code using prettyplease, keeping the comments of [`Config::header`]
+///
+/// Unlike [`generate_formatted`], this can be used for the files of [`generate_files`].
+///
+/// # Arguments
+/// * `code` - The code generated with `config`
+/// * `config` - The configuration the code was generated with
+pub fn format_generated(code: &str, config: &Config) -> Result<String, syn::Error> {
+    let code = format_code(code)?;
+    Ok(match config.header.as_ref() {
+        Some(header) => header.comment_lines().concat() + &code,
+        None => code,
+    })
+}
 
-    let serialize_return_types = |return_types: Vec<(String, bool)>| {
-        if return_types.is_empty() {
-            "Ok(())".to_string()
-        } else if return_types.len() == 1 {
-            format!(
-                "Ok({})",
-                apply_map_fn(map_fn.as_deref(), &return_types[0].0, return_types[0].1)
-            )
-        } else {
-            let v = format!(
-                "({})",
-                return_types
-                    .iter()
-                    .map(|x| x.0.clone())
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            );
-            format!("Ok({})", apply_map_fn(map_fn.as_deref(), &v, true))
-        }
-    };
+fn indent(indent: usize, lines: impl Iterator<Item = String>) -> impl Iterator<Item = String> {
+    lines.map(move |line| format!("{}{}", " ".repeat(indent * 4), line))
+}
 
-    for response in input.responses.iter() {
-        let mut return_types = vec![];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_camel_case_name() {
+        assert_eq!(camel_case_name("foo-bar"), "FooBar");
+        assert_eq!(camel_case_name("foo-bar-baz"), "FooBarBaz");
+        assert_eq!(camel_case_name("foo-bar-baz-quux"), "FooBarBazQuux");
+        assert_eq!(camel_case_name("_foo-bar"), "_fooBar");
+        assert_eq!(camel_case_name("service-root-json"), "ServiceRootJson");
+        assert_eq!(camel_case_name("get-some-URL"), "GetSomeURL");
+    }
 
-        for param in response.params.iter() {
-            match &param.style {
-                ParamStyle::Header => {
-                    if !param.links.is_empty() {
-                        let r = &param.links[0].resource_type.as_ref().unwrap();
-                        if param.required {
-                            return_types.push((
-                                format!(
-                                    "{}(resp.headers().get(\"{}\")?.to_str()?.parse().unwrap())",
-                                    resource_type_rust_type(r),
-                                    param.name
-                                ),
-                                true,
-                            ));
-                        } else {
-                            return_types.push((format!(
-                                "resp.headers().get(\"{}\").map(|x| {}(x.to_str().unwrap().parse().unwrap()))",
-                                param.name,
-                                resource_type_rust_type(r),
-                            ), false));
-                        }
-                    } else {
-                        todo!(
-                            "header param type {:?} for {} in {:?}",
-                            param.r#type,
-                            param.name,
-                            input.id
-                        );
-                    }
-                }
-                t => todo!("param style {:?}", t),
-            }
-        }
+    #[test]
+    fn test_generate_empty() {
+        let input = crate::ast::Application {
+            docs: vec![],
+            representations: vec![],
+            resource_types: vec![],
+            resources: vec![],
+            grammars: vec![],
+        };
+        let config = Config::default();
+        let lines = generate(&input, &config);
+        assert_eq!(lines, "".to_string());
+    }
 
-        // TODO(jelmer): match on media type
-        if let Some(status) = response.status {
-            lines.push(format!(
-                "            s if s.as_u16() == reqwest::StatusCode::{} => {{\n",
-                status
-            ));
-        } else {
-            lines.push("            s if s.is_success() => {\n".to_string());
+    #[test]
+    fn test_enum_rust_value() {
+        assert_eq!(enum_rust_value("foo"), "Foo");
+        assert_eq!(enum_rust_value("foo bar"), "FooBar");
+        assert_eq!(enum_rust_value("foo bar blah"), "FooBarBlah");
+        assert_eq!(enum_rust_value("foo-bar"), "FooBar");
+    }
+
+    #[test]
+    fn test_generate_options() {
+        let mut options = Options::new();
+        options.insert("in progress".to_string(), None);
+        options.set_doc("in progress", Doc::new("Work has started".to_string()));
+        let mut config = Config::default();
+        let code = generate_options("Status", &options, Some("in progress"), &config).concat();
+        assert_eq!(
+            code,
+            r#"#[derive(Debug, Clone, Copy, PartialEq, Eq, std::hash::Hash, serde::Serialize, serde::Deserialize)]
+pub enum Status {
+    /// Work has started
+    #[serde(rename = "in progress")]
+    InProgress,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::InProgress => write!(f, "in progress"),
         }
+    }
+}
 
-        if !response.representations.is_empty() {
-            lines.push("                let content_type: Option<mime::Mime> = resp.headers().get(reqwest::header::CONTENT_TYPE).map(|x| x.to_str().unwrap()).map(|x| x.parse().unwrap());\n".to_string());
-            lines.push(
-                "                match content_type.as_ref().map(|x| x.essence_str()) {\n"
-                    .to_string(),
-            );
-            for representation in response.representations.iter() {
-                let media_type = representation
-                    .media_type()
-                    .unwrap_or(&mime::APPLICATION_JSON);
-                lines.push(format!(
-                    "                    Some(\"{}\") => {{\n",
-                    media_type
-                ));
-                let t = match representation {
-                    Representation::Definition(_) => None,
-                    Representation::Reference(r) => {
-                        let rt = representation_rust_type(r);
+impl std::str::FromStr for Status {
+    type Err = wadl::Error;
 
-                        Some((
-                            format!(
-                                "resp.json::<{}>(){}?",
-                                rt,
-                                if config.r#async { ".await" } else { "" }
-                            ),
-                            true,
-                        ))
-                    }
-                };
-                if let Some(t) = t {
-                    let mut return_types = return_types.clone();
-                    return_types.insert(0, t);
-                    lines.push(format!(
-                        "                             {}\n",
-                        serialize_return_types(return_types)
-                    ));
-                } else {
-                    lines.push("                        unimplemented!();\n".to_string());
-                }
-                lines.push("                        }\n".to_string());
-            }
-            lines.push(
-                "                    _ => { Err(wadl::Error::UnhandledContentType(content_type)) }\n"
-                    .to_string(),
-            );
-            lines.push("                }\n".to_string());
-        } else {
-            lines.push(format!(
-                "                {}\n",
-                serialize_return_types(return_types)
-            ));
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "in progress" => Ok(Status::InProgress),
+            s => Err(wadl::Error::InvalidOption("Status", s.to_string())),
         }
+    }
+}
 
-        lines.push("            }\n".to_string());
+impl std::convert::TryFrom<&str> for Status {
+    type Error = wadl::Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
     }
-    if input.responses.is_empty() {
-        lines.push("            s if s.is_success() => Ok(()),\n".to_string());
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Status::InProgress
     }
-    lines.push("            s => Err(wadl::Error::UnhandledStatus(s))\n".to_string());
-    lines.push("        }\n".to_string());
-    lines.push("    }\n".to_string());
-    lines.push("\n".to_string());
+}
+"#
+        );
 
-    if let Some(extend_method) = config.extend_method.as_ref() {
-        lines.extend(extend_method(parent_id, &name, &ret_type, config));
+        let mut options = Options::new();
+        options.insert("json".to_string(), Some(mime::APPLICATION_JSON));
+        config.options_non_exhaustive = true;
+        let code = generate_options("Format", &options, None, &config).concat();
+        assert!(code.contains(
+            "#[non_exhaustive]\npub enum Format {\n    /// Media type: `application/json`\n"
+        ));
+        assert!(!code.contains("impl Default"));
     }
 
-    lines
-}
+    #[test]
+    fn test_snake_case_name() {
+        assert_eq!(snake_case_name("F"), "f");
+        assert_eq!(snake_case_name("FooBar"), "foo_bar");
+        assert_eq!(snake_case_name("FooBarBaz"), "foo_bar_baz");
+        assert_eq!(snake_case_name("FooBarBazQuux"), "foo_bar_baz_quux");
+        assert_eq!(snake_case_name("_FooBar"), "_foo_bar");
+        assert_eq!(snake_case_name("ServiceRootJson"), "service_root_json");
+        assert_eq!(snake_case_name("GetSomeURL"), "get_some_url");
+        assert_eq!(snake_case_name("XMLParser"), "xml_parser");
+        assert_eq!(snake_case_name("HTTPSProxyURL"), "https_proxy_url");
+        assert_eq!(snake_case_name("getHTTP2Proxy"), "get_http2_proxy");
+        assert_eq!(snake_case_name("bug-task_URL"), "bug_task_url");
+        assert_eq!(snake_case_name("ws.size"), "ws_size");
+    }
 
-fn generate_resource_type(
-    input: &ResourceType,
-    config: &Config,
-    options_names: &HashMap<Options, String>,
-) -> Vec<String> {
-    let mut lines = vec![];
+    #[test]
+    fn test_legacy_snake_case_name() {
+        assert_eq!(legacy_snake_case_name("GetSomeURL"), "get_some_url");
+        assert_eq!(legacy_snake_case_name("XMLParser"), "x_mlparser");
+
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <representation id="item" mediaType="application/json">
+                    <param name="XMLParser" style="plain" type="xsd:string" required="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(code.contains("pub xml_parser: String,"), "{}", code);
+        let config = Config::builder().legacy_snake_case(true).build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("pub x_mlparser: String,"), "{}", code);
+    }
 
-    for doc in &input.docs {
-        lines.extend(generate_doc(doc, 0, config));
+    #[test]
+    fn test_with_application_restores_state_on_panic() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <representation id="item" mediaType="application/json"/>
+            </application>"#,
+        )
+        .unwrap();
+        let config = Config::builder().legacy_snake_case(true).build();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            with_application(&app, &config, || {
+                assert_eq!(snake_case_name("XMLParser"), "x_mlparser");
+                panic!("generating failed");
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(snake_case_name("XMLParser"), "xml_parser");
+        assert!(REPRESENTATION_MEDIA_TYPES.with(|m| m.borrow().is_empty()));
     }
 
-    let name = input.id.as_str();
-    let name = camel_case_name(name);
+    #[test]
+    fn test_strip_code_examples() {
+        let input = r#"This is a test
+```python
+def foo():
+    pass
+```
 
-    let visibility = config
-        .resource_type_visibility
-        .as_ref()
-        .and_then(|x| x(name.as_str()))
-        .unwrap_or("pub".to_string());
+This is another test
+```python
+def bar():
+    pass
+```
+"#;
+        let expected = r#"This is a test
 
-    lines.push(format!(
-        "{}struct {} (reqwest::Url);\n",
-        if visibility.is_empty() {
-            "".to_string()
-        } else {
-            format!("{} ", visibility)
-        },
-        name
-    ));
+This is another test"#;
+        assert_eq!(strip_code_examples(input.to_string()), expected);
+    }
 
-    lines.push("\n".to_string());
+    #[test]
+    fn test_format_doc_plain() {
+        let doc = Doc {
+            title: None,
+            lang: None,
+            content: "This is a test".to_string(),
+            xmlns: None,
+        };
 
-    lines.push(format!("impl {} {{\n", name));
+        assert_eq!(
+            format_doc(&doc, &Config::default()),
+            "This is a test".to_string()
+        );
+    }
 
-    for method in &input.methods {
-        lines.extend(generate_method(
-            method,
-            input.id.as_str(),
-            config,
-            options_names,
-        ));
+    #[test]
+    fn test_format_doc_html() {
+        let doc = Doc {
+            title: None,
+            lang: None,
+            content: "<p>This is a test</p>".to_string(),
+            xmlns: Some("http://www.w3.org/1999/xhtml".parse().unwrap()),
+        };
+
+        assert_eq!(
+            format_doc(&doc, &Config::default()),
+            "This is a test".to_string()
+        );
     }
 
-    lines.push("}\n".to_string());
-    lines.push("\n".to_string());
-    lines.push(format!("impl wadl::Resource for {} {{\n", name));
-    lines.push("    fn url(&self) -> &reqwest::Url {\n".to_string());
-    lines.push("        &self.0\n".to_string());
-    lines.push("    }\n".to_string());
-    lines.push("}\n".to_string());
-    lines.push("\n".to_string());
-    lines
-}
+    #[test]
+    fn test_format_doc_html_link() {
+        let doc = Doc {
+            title: None,
+            lang: None,
+            content: "<p>This is a <a href=\"https://example.com\">test</a></p>".to_string(),
+            xmlns: Some("http://www.w3.org/1999/xhtml".parse().unwrap()),
+        };
 
-#[derive(Default)]
-#[allow(clippy::type_complexity)]
-/// Configuration for code generation
-pub struct Config {
-    /// Whether to generate async code
-    pub r#async: bool,
+        assert_eq!(
+            format_doc(&doc, &Config::default()),
+            "This is a [test](https://example.com)".to_string()
+        );
+    }
 
-    /// Based on the listed type and name of a parameter, determine the rust type
-    pub override_type_name:
-        Option<Box<dyn Fn(&ParamContainer, &str, &str, &Config) -> Option<String>>>,
+    #[test]
+    fn test_resolve_doc_links() {
+        let base: url::Url = "https://api.example.com/1.0/".parse().unwrap();
+        assert_eq!(
+            resolve_doc_links("/// See [people](people) and [root](/)", &base),
+            "/// See [people](https://api.example.com/1.0/people) and [root](https://api.example.com/)"
+        );
+        assert_eq!(
+            resolve_doc_links("/// See [test](https://example.com)", &base),
+            "/// See [test](https://example.com)"
+        );
+        assert_eq!(
+            resolve_doc_links("/// Unterminated [link](foo", &base),
+            "/// Unterminated [link](foo"
+        );
+    }
 
-    /// Support renaming param accessor functions
-    pub param_accessor_rename: Option<Box<dyn Fn(&str, &str) -> Option<String>>>,
+    #[test]
+    fn test_generate_resolves_doc_links() {
+        let mut app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10"
+>
+                <resources base="https://api.example.com/1.0/"/>
+                <resource_type id="people">
+                    <doc xmlns="http://www.w3.org/1999/xhtml">See <a href="/docs/people">the docs</a></doc>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = generate(&app, &Config::default());
+        assert!(
+            code.contains("/// See [the docs](https://api.example.com/docs/people)"),
+            "{}",
+            code
+        );
 
-    /// Whether to strip code examples from the docstrings
-    ///
-    /// This is useful if the code examples are not valid rust code.
-    pub strip_code_examples: bool,
+        let code = generate(
+            &app,
+            &Config {
+                doc_base_url: Some("https://docs.example.com/".parse().unwrap()),
+                ..Default::default()
+            },
+        );
+        assert!(code.contains("/// See [the docs](https://docs.example.com/docs/people)"));
 
-    /// Generate custom trait implementations for representations
-    pub generate_representation_traits: Option<
-        Box<dyn Fn(&RepresentationDef, &str, &RepresentationDef, &Config) -> Option<Vec<String>>>,
-    >,
+        app.resources.clear();
+        let code = generate(&app, &Config::default());
+        assert!(code.contains("/// See [the docs](/docs/people)"));
+    }
 
-    /// Return the visibility of a representation
-    pub representation_visibility: Option<Box<dyn Fn(&str) -> Option<String>>>,
+    #[test]
+    fn test_generate_patch_methods() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10"
+                             xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="person">
+                    <method name="PATCH" id="person-merge">
+                        <request>
+                            <representation mediaType="application/merge-patch+json">
+                                <param name="display_name" style="plain" type="xsd:string" required="true"/>
+                                <param name="karma" style="plain" type="xsd:int"/>
+                            </representation>
+                        </request>
+                    </method>
+                    <method name="PATCH" id="person-json-patch">
+                        <request>
+                            <representation mediaType="application/json-patch+json"/>
+                        </request>
+                    </method>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        for expected in [
+            "/// Changes to apply with the `person-merge` method\n",
+            "pub struct PersonMergePatch {\n",
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub display_name: Option<String>,\n",
+            "        patch: &PersonMergePatch,\n",
+            "        req = req.header(reqwest::header::CONTENT_TYPE, \"application/merge-patch+json\");\n        req = req.body(serde_json::to_vec(&patch)?);\n",
+            "        patch: &[wadl::PatchOperation],\n",
+            "\"application/json-patch+json\"",
+        ] {
+            assert!(code.contains(expected), "missing {:?} in:\n{}", expected, code);
+        }
+        assert!(!code.contains("display_name: &str"));
+    }
 
-    /// Return the visibility of a representation accessor
-    pub accessor_visibility: Option<Box<dyn Fn(&str, &str) -> Option<String>>>,
+    #[test]
+    fn test_generate_nullable_fields() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                             xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <representation id="person-full" mediaType="application/json">
+                    <param name="nickname" style="plain" type="xsd:string" default="none"/>
+                    <param name="karma" style="plain" type="xsd:int"/>
+                    <param name="team_link" style="plain" type="xsd:anyURI">
+                        <link resource_type="#team"/>
+                    </param>
+                </representation>
+                <resource_type id="team"/>
+                <resource_type id="person">
+                    <method name="PATCH" id="person-merge">
+                        <request>
+                            <representation mediaType="application/merge-patch+json">
+                                <param name="display_name" style="plain" type="xsd:string" required="true"/>
+                                <param name="karma" style="plain" type="xsd:int"/>
+                            </representation>
+                        </request>
+                    </method>
+                </resource_type>
+            </application>"##,
+        )
+        .unwrap();
+
+        let config = Config::builder()
+            .nullable_field_style(NullableFieldStyle::Patch)
+            .nullable_field(|_, param| param.name == "nickname")
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        for expected in [
+            "    #[serde(default, skip_serializing_if = \"wadl::Patch::is_unchanged\")]\n    pub display_name: wadl::Patch<String>,\n",
+            "    #[serde(default, skip_serializing_if = \"wadl::Patch::is_unchanged\")]\n    pub karma: wadl::Patch<i32>,\n",
+            "    pub nickname: wadl::Patch<String>,\n",
+            "    pub karma: Option<i32>,\n",
+            "    pub team_link: Option<url::Url>,\n",
+            "            nickname: Default::default(),\n",
+        ] {
+            assert!(code.contains(expected), "missing {:?} in:\n{}", expected, code);
+        }
 
-    /// Return the visibility of a resource type
-    pub resource_type_visibility: Option<Box<dyn Fn(&str) -> Option<String>>>,
+        let config = Config::builder()
+            .nullable_field_style(NullableFieldStyle::DoubleOption)
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        for expected in [
+            "    #[serde(\n        default,\n        skip_serializing_if = \"Option::is_none\",\n        deserialize_with = \"wadl::patch::deserialize_some\"\n    )]\n    pub display_name: Option<Option<String>>,\n",
+            "    pub nickname: Option<String>,\n",
+            "            nickname: Some(\"none\".to_string()),\n",
+        ] {
+            assert!(code.contains(expected), "missing {:?} in:\n{}", expected, code);
+        }
 
-    /// Map a method response type to a different type and a function to map the response
-    pub map_type_for_response: Option<Box<dyn Fn(&str, &str, &Config) -> Option<(String, String)>>>,
+        // Without a style, merge patches keep their Option fields
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(
+            code.contains("    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub display_name: Option<String>,\n"),
+            "{}",
+            code
+        );
+    }
 
-    /// Map an accessor function name to a different type
-    pub map_type_for_accessor: Option<Box<dyn Fn(&str) -> Option<(String, String)>>>,
+    #[test]
+    fn test_generate_head_and_options() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                             xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="file">
+                    <method name="HEAD" id="file-head">
+                        <response>
+                            <param name="ETag" style="header"/>
+                            <param name="Content-Length" style="header" type="xsd:long"/>
+                            <representation href="#file-json"/>
+                        </response>
+                    </method>
+                    <method name="HEAD" id="file-exists"/>
+                    <method name="OPTIONS" id="file-options"/>
+                </resource_type>
+            </application>"##,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        for expected in [
+            "pub struct FileHeadHeaders {\n    /// The `ETag` header\n    pub etag: Option<String>,\n    /// The `Content-Length` header\n    pub content_length: Option<i64>,\n}",
+            ") -> std::result::Result<FileHeadHeaders, wadl::Error> {",
+            "            s if s.is_success() => {\n                Ok(FileHeadHeaders {\n                    etag: resp\n",
+            "    pub fn exists<'a>(\n        &self,\n        client: &'a dyn wadl::blocking::Client,\n    ) -> std::result::Result<(), wadl::Error> {",
+            ") -> std::result::Result<Vec<reqwest::Method>, wadl::Error> {",
+            ".get_all(reqwest::header::ALLOW)",
+        ] {
+            assert!(code.contains(expected), "missing {:?} in:\n{}", expected, code);
+        }
+        assert!(!code.contains("resp.json"));
+    }
 
-    /// Extend the generated accessor
-    pub extend_accessor: Option<Box<dyn Fn(&Param, &'_ str, &'_ str, &Config) -> Vec<String>>>,
+    #[test]
+    fn test_generate_streaming_download() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <resource_type id="file">
+                    <method name="GET" id="file-download">
+                        <response>
+                            <representation mediaType="application/octet-stream"/>
+                        </response>
+                    </method>
+                    <method name="GET" id="file-thumbnail">
+                        <response>
+                            <representation mediaType="image/png"/>
+                        </response>
+                    </method>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(
+            code.contains(") -> std::result::Result<wadl::blocking::ByteStream, wadl::Error> {"),
+            "{}",
+            code
+        );
+        assert!(code.contains("s if s.is_success() => Ok(wadl::blocking::ByteStream::new(resp)),"));
+        assert!(code.contains("\"application/octet-stream\""));
+        assert_eq!(code.matches("ByteStream::new").count(), 1);
 
-    /// Extend the generated method
-    pub extend_method: Option<Box<dyn Fn(&str, &str, &str, &Config) -> Vec<String>>>,
+        let config = Config {
+            r#async: true,
+            streaming_media_types: vec!["image/png".to_string()],
+            ..Default::default()
+        };
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert_eq!(
+            code.matches("wadl::r#async::ByteStream::new(resp)").count(),
+            2
+        );
+        assert!(code.contains("\"image/png\""));
+    }
+
+    #[test]
+    fn test_generate_request_policy() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <resource_type id="items">
+                    <method name="GET" id="items-list"/>
+                    <method name="POST" id="items-add"/>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let config = Config {
+            request_policy: Some(Box::new(|id| {
+                (id == "items-list").then(|| RequestPolicy {
+                    timeout: Some(std::time::Duration::from_secs(5)),
+                    retries: 3,
+                    backoff: std::time::Duration::from_millis(500),
+                })
+            })),
+            ..Default::default()
+        };
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("let req = req.timeout(std::time::Duration::from_millis(5000));"),
+            "{}",
+            code
+        );
+        assert!(code.contains("let resp = wadl::blocking::send_with_retry("));
+        assert!(code.contains("retries: 3,"));
+        assert!(code.contains("backoff: std::time::Duration::from_millis(500),"));
+        assert_eq!(code.matches("client.send(req)?").count(), 1);
+
+        let config = Config {
+            r#async: true,
+            ..config
+        };
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("let resp = wadl::r#async::send_with_retry("));
+        assert_eq!(config.validate(&app), Ok(()));
+
+        let config = Config {
+            tower: true,
+            ..config
+        };
+        assert_eq!(
+            config.validate(&app),
+            Err(ConfigError::RequestPolicyNotSupported(
+                "items-list".to_string()
+            ))
+        );
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(!code.contains("send_with_retry"), "{}", code);
+    }
+
+    #[test]
+    fn test_generate_representation_link_media_type() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <representation id="person" mediaType="application/json">
+                    <param name="name" style="plain" type="xsd:string"/>
+                    <param name="team_link" style="plain" type="xsd:anyURI">
+                        <link resource_type="#team"/>
+                    </param>
+                </representation>
+                <representation id="team" mediaType="application/json"/>
+            </application>"##,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(
+            code.contains("pub const MEDIA_TYPE_APPLICATION_JSON: &str = \"application/json\";"),
+            "{}",
+            code
+        );
+        assert!(code
+            .contains("req = req.header(reqwest::header::ACCEPT, MEDIA_TYPE_APPLICATION_JSON);"));
+    }
+
+    #[test]
+    fn test_generate_observer() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <resource_type id="items">
+                    <method name="GET" id="items-list"/>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let config = Config {
+            observer: Some("&crate::METRICS".to_string()),
+            ..Default::default()
+        };
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains(
+                r#"        let observer_: &dyn wadl::Observer = &crate::METRICS;
+        observer_.on_request_start("items-list");
+        let start_ = wadl::Stopwatch::start();
+        let resp = client.send(req);
+        observer_
+            .on_request_finish(
+                "items-list",
+                resp.as_ref().ok().map(|r| r.status()),
+                start_.elapsed(),
+            );
+        let resp = resp?;
+"#
+            ),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_generate_colliding_type_names() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <resource_type id="team_member">
+                    <method name="GET" id="team-member-get">
+                        <response>
+                            <representation href="#team-member"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="team-member" mediaType="application/json">
+                    <param name="team_member" style="plain">
+                        <option value="yes"/>
+                        <option value="no"/>
+                    </param>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(code.contains("pub struct TeamMember {"), "{}", code);
+        assert!(
+            code.contains("pub struct TeamMember_(reqwest::Url);"),
+            "{}",
+            code
+        );
+        assert!(code.contains("pub enum TeamMember__ {"), "{}", code);
+        assert!(
+            code.contains("wadl::blocking::parse_json_response::<TeamMember>(resp, &[])"),
+            "{}",
+            code
+        );
+
+        let config = Config {
+            type_name: Some(Box::new(|kind, _id| match kind {
+                ItemKind::ResourceType => Some("TeamMemberResource".to_string()),
+                _ => None,
+            })),
+            ..Default::default()
+        };
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("pub struct TeamMemberResource(reqwest::Url);"),
+            "{}",
+            code
+        );
+        assert!(code.contains("pub enum TeamMember_ {"), "{}", code);
+    }
 
-    /// Retrieve visibility for a method
-    pub method_visibility: Option<Box<dyn Fn(&str, &str) -> Option<String>>>,
+    #[test]
+    fn test_generate_json_response_helper() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <resource_type id="items">
+                    <method name="POST" id="items-new">
+                        <response status="201">
+                            <representation href="#item"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="item" mediaType="application/json"/>
+            </application>"##,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(
+            code.contains("wadl::blocking::parse_json_response::<Item>(resp, &[201])"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("match resp.status()"), "{}", code);
 
-    /// Return whether a param is deprecated
-    pub deprecated_param: Option<Box<dyn Fn(&Param) -> bool>>,
+        let config = Config {
+            r#async: true,
+            ..Default::default()
+        };
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("wadl::r#async::parse_json_response::<Item>(resp, &[201]).await"),
+            "{}",
+            code
+        );
+    }
 
-    /// Return the name for an enum representation a set of options
-    ///
-    /// The callback can be used to determine if the name is already taken.
-    pub options_enum_name: Option<Box<dyn Fn(&Param, Box<dyn Fn(&str) -> bool>) -> String>>,
+    #[cfg(feature = "templates")]
+    #[test]
+    fn test_generate_templates() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="items">
+                    <method name="GET" id="items-get">
+                        <response status="200">
+                            <representation href="#item"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="item" mediaType="application/json">
+                    <param name="count" style="plain" type="xsd:int" required="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let templates = Templates::new()
+            .with_template(
+                TemplateKind::Representation,
+                "pub struct {{ name }} {\n{% for field in fields %}    pub {{ field.name }}: {{ field.rust_type }},\n{% endfor %}}\n",
+            )
+            .unwrap()
+            .with_template(
+                TemplateKind::Method,
+                "    // {{ http_method }} {{ id }} -> {{ ret_type }}\n{{ default }}",
+            )
+            .unwrap();
+        let config = Config::builder().templates(templates).build();
+        let code = generate(&app, &config);
+        assert!(
+            code.contains("pub struct Item {\n    pub count: i32,\n}\n"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("#[derive"), "{}", code);
+        assert!(code.contains("    // GET items-get -> Item\n"), "{}", code);
+        assert!(code.contains("    pub fn get<'a>("), "{}", code);
+    }
 
-    /// Reformat a docstring; should already be in markdown
-    pub reformat_docstring: Option<Box<dyn Fn(&str) -> String>>,
+    #[test]
+    fn test_generate_renames() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="items">
+                    <method name="GET" id="items-get">
+                        <response status="200">
+                            <representation href="#item"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="item" mediaType="application/json">
+                    <param name="count" style="plain" type="xsd:int" required="true"/>
+                    <param name="owner_link" style="plain" required="true">
+                        <link resource_type="#items"/>
+                    </param>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let config = Config::builder()
+            .param_rename("count", "total")
+            .param_rename("item.owner_link", "owner")
+            .param_rename("other.owner_link", "ignored")
+            .method_rename("items-get", "fetch")
+            .type_rename("item", "Thing")
+            .serde_rename_all(|_| Some("camelCase".to_string()))
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("pub struct Thing {"), "{}", code);
+        assert!(
+            code.contains("    #[serde(rename = \"count\")]\n    pub total: i32,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    #[serde(rename = \"owner_link\")]\n    pub owner: url::Url,"),
+            "{}",
+            code
+        );
+        assert!(code.contains("pub fn owner(&self) -> Items {"), "{}", code);
+        assert!(code.contains("pub fn fetch<'a>("), "{}", code);
+        assert!(!code.contains("ignored"), "{}", code);
+    }
 
-    /// Convert a string to a multipart Part, given a type name and value
-    pub convert_to_multipart: Option<Box<dyn Fn(&str, &str) -> Option<String>>>,
-}
+    #[test]
+    fn test_generate_item_filter() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resources base="https://api.example.com/">
+                    <resource path="items" type="#items"/>
+                    <resource path="admin" type="#admin"/>
+                </resources>
+                <resource_type id="items">
+                    <method name="GET" id="items-get">
+                        <response status="200">
+                            <representation href="#item"/>
+                        </response>
+                    </method>
+                    <method name="DELETE" id="items-purge"/>
+                </resource_type>
+                <resource_type id="admin">
+                    <method name="POST" id="admin-reset"/>
+                </resource_type>
+                <representation id="item" mediaType="application/json">
+                    <param name="admin_link" style="plain" required="true">
+                        <link resource_type="#admin"/>
+                    </param>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let config = Config::builder()
+            .item_filter(|kind, id| match kind {
+                ItemKind::ResourceType => id != "admin",
+                ItemKind::Method => !id.ends_with("-purge"),
+                _ => true,
+            })
+            .build();
+        assert!(config.include_item(ItemKind::ResourceType, "items"));
+        assert!(!config.include_item(ItemKind::ResourceType, "admin"));
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("pub struct Items("), "{}", code);
+        assert!(code.contains("pub fn get<'a>("), "{}", code);
+        assert!(!code.contains("Admin"), "{}", code);
+        assert!(!code.contains("purge"), "{}", code);
+        assert!(!code.contains("reset"), "{}", code);
+        assert!(code.contains("pub admin_link: url::Url,"), "{}", code);
+
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(
+            code.contains("pub fn admin_link(&self) -> Admin {"),
+            "{}",
+            code
+        );
+    }
 
-impl Config {
-    /// Return identifier of the wadl client
-    pub fn client_trait_name(&self) -> &'static str {
-        if self.r#async {
-            "wadl::r#async::Client"
-        } else {
-            "wadl::blocking::Client"
-        }
+    #[test]
+    fn test_generate_module_path() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="bug"/>
+                <resource_type id="bug-task"/>
+                <representation id="bug-full" mediaType="application/json">
+                    <param name="task_link" style="plain" required="true">
+                        <link resource_type="#bug-task"/>
+                    </param>
+                </representation>
+                <representation id="person" mediaType="application/json">
+                    <param name="name" style="plain" type="xsd:string" required="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let config = Config::builder()
+            .module_path(|_kind, id| match id {
+                "bug-task" => Some("bugs::tasks".to_string()),
+                id if id.starts_with("bug") => Some("bugs".to_string()),
+                _ => None,
+            })
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("#[allow(unused_imports)]\nuse self::bugs::*;\n#[allow(unused_imports)]\nuse self::bugs::tasks::*;\n"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("pub mod bugs {\n    #![allow(unused_imports)]\n    use super::*;\n"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains(
+                "    pub mod tasks {\n        #![allow(unused_imports)]\n        use super::*;\n"
+            ),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("        pub struct BugTask(pub(crate) reqwest::Url);"),
+            "{}",
+            code
+        );
+        assert!(code.contains("    pub struct BugFull {"), "{}", code);
+        assert!(code.contains("\npub struct Person {"), "{}", code);
+
+        // Without a module path, all items stay at the top level
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(!code.contains("mod bugs"), "{}", code);
+        assert!(
+            code.contains("\npub struct BugTask(reqwest::Url);"),
+            "{}",
+            code
+        );
     }
-}
 
-fn enum_rust_value(option: &str) -> String {
-    let name = camel_case_name(option.replace(' ', "-").as_str());
+    #[test]
+    fn test_generate_files() {
+        let wadl = r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="bug"/>
+                <resource_type id="bug-task"/>
+                <representation id="person" mediaType="application/json">
+                    <param name="name" style="plain" type="xsd:string" required="true"/>
+                </representation>
+            </application>"##;
+        let app = crate::parse_string(wadl).unwrap();
+        let config = Config::builder()
+            .module_path(|_kind, id| match id {
+                "bug-task" => Some("bugs::tasks".to_string()),
+                "bug" => Some("bugs".to_string()),
+                _ => None,
+            })
+            .header(FileHeader::for_source("bugs.wadl", wadl.as_bytes()))
+            .build();
+        let files = generate_files(&app, &config);
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            [
+                Path::new("bugs/tasks.rs"),
+                Path::new("bugs.rs"),
+                Path::new("mod.rs")
+            ]
+        );
+        for code in files.values() {
+            assert!(FileHeader::parse(code).is_some(), "{}", code);
+            syn::parse_file(code).unwrap();
+        }
 
-    // Now, strip all characters not allowed in rust identifiers
-    let name = name
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '_')
-        .collect::<String>();
+        let code = &files[Path::new("mod.rs")];
+        assert!(code.contains("use self::bugs::tasks::*;\n"), "{}", code);
+        assert!(code.contains("\npub mod bugs;\n"), "{}", code);
+        assert!(code.contains("pub struct Person {"), "{}", code);
+        assert!(!code.contains("pub struct Bug ("), "{}", code);
+
+        let code = &files[Path::new("bugs.rs")];
+        assert!(
+            code.contains("#![allow(unused_imports)]\nuse super::*;\n\npub mod tasks;\n"),
+            "{}",
+            code
+        );
+        assert!(code.contains("pub struct Bug ("), "{}", code);
+        assert!(
+            files[Path::new("bugs/tasks.rs")].contains("pub struct BugTask ("),
+            "{}",
+            files[Path::new("bugs/tasks.rs")]
+        );
 
-    // If the identifier starts with a digit, prefix it with '_' to make it a valid identifier
-    if name.chars().next().unwrap().is_numeric() {
-        format!("_{}", name)
-    } else {
-        name
+        // Without a module path, all code is in mod.rs
+        let files = generate_files(&app, &Config::default());
+        assert_eq!(files.keys().collect::<Vec<_>>(), [Path::new("mod.rs")]);
+        assert_eq!(
+            files[Path::new("mod.rs")],
+            format!(
+                "#[allow(unused_imports)]\nuse wadl::Resource;\n\n{}",
+                generate(&app, &Config::default())
+            )
+        );
     }
-}
 
-fn generate_options(name: &str, options: &crate::ast::Options) -> Vec<String> {
-    let mut lines = vec![];
+    #[test]
+    fn test_generate_header() {
+        let wadl = r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <representation id="person" mediaType="application/json"/>
+            </application>"#;
+        let app = crate::parse_string(wadl).unwrap();
+        let config = Config::builder()
+            .header(FileHeader::for_source("people.wadl", wadl.as_bytes()).allow("dead_code"))
+            .build();
+
+        let code = generate(&app, &config);
+        assert!(code.starts_with("// @generated by wadl "), "{}", code);
+        assert!(code.contains("\n#![allow(dead_code)]\n"), "{}", code);
+
+        let code = generate_formatted(&app, &config).unwrap();
+        let header = FileHeader::parse(&code).unwrap();
+        assert_eq!(header.source.as_deref(), Some("people.wadl"));
+        assert_eq!(header.allow, vec!["dead_code".to_string()]);
+        assert!(header.matches_source(wadl.as_bytes()));
+        assert!(code.contains("pub struct Person {"), "{}", code);
+
+        assert!(!generate(&app, &Config::default()).contains("@generated"));
+    }
 
-    lines.push("#[derive(Debug, Clone, Copy, PartialEq, Eq, std::hash::Hash, serde::Serialize, serde::Deserialize)]\n".to_string());
-    lines.push(format!("pub enum {} {{\n", name));
+    #[test]
+    fn test_generate_visibility() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="items">
+                    <method name="GET" id="items-get">
+                        <response status="200">
+                            <representation href="#item"/>
+                        </response>
+                    </method>
+                    <method name="DELETE" id="items-purge"/>
+                </resource_type>
+                <representation id="item" mediaType="application/json">
+                    <param name="owner_link" style="plain" required="true">
+                        <link resource_type="#items"/>
+                    </param>
+                    <param name="state" style="plain" type="xsd:string" required="true">
+                        <option value="open"/>
+                        <option value="closed"/>
+                    </param>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let config = Config::builder()
+            .generate_resource_traits(true)
+            .visibility(|kind, name| match (kind, name) {
+                (ItemKind::Representation, "Item") => Some(Visibility::Crate),
+                (ItemKind::Accessor, "owner_link") => Some(Visibility::Private),
+                (ItemKind::Method, "purge") => Some(Visibility::Super),
+                (ItemKind::OptionsEnum, "State") => Some(Visibility::In("crate::api".to_string())),
+                (ItemKind::Trait, "ItemsOps") => Some(Visibility::Crate),
+                _ => None,
+            })
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("\npub(crate) struct Item {"), "{}", code);
+        assert!(
+            code.contains("\n    fn owner_link(&self) -> Items {"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("\npub struct Items(reqwest::Url);"),
+            "{}",
+            code
+        );
+        assert!(code.contains("\n    pub(super) fn purge<'a>("), "{}", code);
+        assert!(
+            code.contains("\npub(in crate::api) enum State {"),
+            "{}",
+            code
+        );
+        assert!(code.contains("\npub(crate) trait ItemsOps {"), "{}", code);
+        // Methods that are not public are left out of the trait
+        assert!(code.contains("\n    fn get<'a>("), "{}", code);
+        assert_eq!(code.matches("fn purge<'a>(").count(), 1, "{}", code);
+    }
 
-    let mut option_map = HashMap::new();
+    #[test]
+    fn test_visibility_from_str() {
+        for (text, visibility) in [
+            ("pub", Visibility::Public),
+            ("pub(crate)", Visibility::Crate),
+            ("pub(super)", Visibility::Super),
+            ("private", Visibility::Private),
+            (
+                "pub(in crate::api)",
+                Visibility::In("crate::api".to_string()),
+            ),
+        ] {
+            assert_eq!(text.parse::<Visibility>(), Ok(visibility.clone()));
+            assert_eq!(String::from(visibility), text);
+        }
+        assert_eq!("".parse::<Visibility>(), Ok(Visibility::Private));
+        assert!("public".parse::<Visibility>().is_err());
+    }
 
-    for option in options.keys() {
-        let rust_name = enum_rust_value(option);
-        lines.push(format!("    #[serde(rename = \"{}\")]\n", option));
-        lines.push(format!("    {},\n", rust_name));
-        option_map.insert(option, rust_name);
+    #[test]
+    fn test_generate_extend_representation() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <representation id="item" mediaType="application/json">
+                    <param name="name" style="plain" type="xsd:string" required="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let config = Config::builder()
+            .extend_representation(|input, name, _config| {
+                vec![
+                    format!("impl std::fmt::Display for {} {{\n", name),
+                    "    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {\n"
+                        .to_string(),
+                    format!("        write!(f, {:?})\n", input.id.as_deref().unwrap()),
+                    "    }\n".to_string(),
+                    "}\n".to_string(),
+                ]
+            })
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("impl std::fmt::Display for Item {\n    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {\n        write!(f, \"item\")\n    }\n}"),
+            "{}",
+            code
+        );
+        assert!(
+            code.find("impl Item {").unwrap() < code.find("impl std::fmt::Display").unwrap(),
+            "{}",
+            code
+        );
     }
-    lines.push("}\n".to_string());
-    lines.push("\n".to_string());
 
-    lines.push(format!("impl std::fmt::Display for {} {{\n", name));
-    lines.push(
-        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n".to_string(),
-    );
-    lines.push("        match self {\n".to_string());
-    for (option, rust_name) in option_map {
-        lines.push(format!(
-            "            {}::{} => write!(f, \"{}\"),\n",
-            name, rust_name, option
-        ));
+    #[test]
+    fn test_generate_boxed_fields() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="person">
+                    <method name="GET" id="person-get">
+                        <response>
+                            <representation href="#person-full"/>
+                            <representation href="#team"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="person-full" mediaType="application/json">
+                    <param name="name" style="plain" type="xsd:string" required="true"/>
+                    <param name="age" style="plain" type="xsd:int"/>
+                    <param name="self_link" style="plain" required="true">
+                        <link resource_type="#person"/>
+                    </param>
+                    <param name="boss_link" style="plain">
+                        <link resource_type="#person" rel="boss"/>
+                    </param>
+                </representation>
+                <representation id="team" mediaType="application/json">
+                    <param name="name" style="plain" type="xsd:string" required="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let config = Config::builder()
+            .box_fields_larger_than(64)
+            .box_field(|_input, param| param.name == "name")
+            .omit_clone_for_boxed(true)
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]\npub struct PersonFull {"),
+            "{}",
+            code
+        );
+        assert!(code.contains("pub name: Box<String>,"), "{}", code);
+        assert!(code.contains("pub age: Option<i32>,"), "{}", code);
+        assert!(code.contains("pub self_link: Box<url::Url>,"), "{}", code);
+        assert!(
+            code.contains("pub boss_link: Option<Box<url::Url>>,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("Person((*self.self_link).clone())"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("self.boss_link.as_deref().map(|x| Person(x.clone()))"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("self.self_link = Box::new(value.url().clone());"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("match self.boss_link.as_deref() {"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("#[derive(Debug, PartialEq)]\npub enum PersonGetResponse {"),
+            "{}",
+            code
+        );
+
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(code.contains("pub self_link: url::Url,"), "{}", code);
+        assert!(
+            code.contains("#[derive(Debug, Clone, PartialEq)]\npub enum PersonGetResponse {"),
+            "{}",
+            code
+        );
     }
-    lines.push("        }\n".to_string());
-    lines.push("    }\n".to_string());
-    lines.push("}\n".to_string());
-    lines
-}
 
-fn options_rust_enum_name(param: &Param, options: &HashMap<Options, String>) -> String {
-    let mut name = camel_case_name(param.name.as_str());
-    while options.values().any(|v| v == &name) {
-        name = format!("{}_", name);
+    #[test]
+    fn test_rust_version() {
+        assert_eq!("1.70".parse(), Ok(RustVersion::new(1, 70)));
+        assert_eq!("1.65.0".parse(), Ok(RustVersion::new(1, 65)));
+        assert!("1".parse::<RustVersion>().is_err());
+        assert!("1.x".parse::<RustVersion>().is_err());
+        assert_eq!(RustVersion::new(1, 70).to_string(), "1.70");
+        assert!(RustVersion::new(1, 9) < RustVersion::new(1, 10));
+
+        let config = Config::builder().msrv(RustVersion::new(1, 70)).build();
+        assert!(config.supports(Syntax::LetElse));
+        assert!(!config.supports(Syntax::ImplTraitInTrait));
+        assert!(Config::default().supports(Syntax::ImplTraitInTrait));
     }
-    name
-}
 
-/// Generate code from a WADL application definition.
-///
-/// This function generates Rust code from a WADL application definition.
-/// The generated code includes Rust types for the representations and
-/// resource types defined in the WADL application, as well as methods
-/// for interacting with the resources.
-///
-/// # Arguments
-/// * `app` - The WADL application definition.
-/// * `config` - Configuration for the code generation.
-pub fn generate(app: &Application, config: &Config) -> String {
-    let mut lines = vec![];
+    #[test]
+    fn test_generate_msrv() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="item">
+                    <method name="GET" id="item-get">
+                        <response>
+                            <representation href="#item-full"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="item-full" mediaType="application/json">
+                    <param name="parent_link" style="plain">
+                        <link resource_type="#item-full"/>
+                    </param>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let config = Config::builder()
+            .r#async(true)
+            .generate_resource_traits(true)
+            .native_async_client(true)
+            .msrv(RustVersion::new(1, 60))
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("let url_ = match self.parent_link.clone() {\n            Some(url_) => url_,\n            None => return Ok(None),\n        };"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("#[wadl::r#async::async_trait(?Send)]\npub trait ItemOps {"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("#[wadl::r#async::async_trait(?Send)]\nimpl ItemOps for Item {"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("client: &'a dyn wadl::r#async::Client"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("async_fn_in_trait"), "{}", code);
+
+        let config = Config::builder()
+            .r#async(true)
+            .generate_resource_traits(true)
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("let Some(url_) = self.parent_link.clone() else {"),
+            "{}",
+            code
+        );
+        assert!(code.contains("#[allow(async_fn_in_trait)]"), "{}", code);
+    }
 
-    let mut options = HashMap::new();
+    #[test]
+    fn test_codegen_hooks() {
+        struct Marker;
+
+        impl CodegenHook for Marker {
+            fn before_representation(
+                &self,
+                _input: &RepresentationDef,
+                name: &str,
+                _config: &Config,
+            ) -> Vec<String> {
+                vec![format!("// representation {}\n", name)]
+            }
 
-    for param in app.iter_all_params() {
-        if let Some(os) = &param.options {
-            if options.contains_key(os) {
-                continue;
+            fn after_method(
+                &self,
+                input: &Method,
+                _parent_id: &str,
+                name: &str,
+                _ret_type: &str,
+                _config: &Config,
+            ) -> Vec<String> {
+                vec![format!(
+                    "    pub fn {}_id(&self) -> &'static str {{ {:?} }}\n",
+                    name, input.id
+                )]
+            }
+
+            fn extend_module(&self, app: &Application, _config: &Config) -> Vec<String> {
+                vec![format!(
+                    "pub const RESOURCE_TYPES: usize = {};\n",
+                    app.resource_types.len()
+                )]
+            }
+
+            fn override_type(
+                &self,
+                _container: &ParamContainer,
+                type_name: &str,
+                _param_name: &str,
+                _config: &Config,
+            ) -> Option<String> {
+                (type_name == "xsd:int").then(|| "u64".to_string())
             }
-            let name = if let Some(enum_name_fn) = config.options_enum_name.as_ref() {
-                let cb_options = options.clone();
-                let name = enum_name_fn(
-                    param,
-                    Box::new(move |name: &str| -> bool { cb_options.values().any(|v| v == name) }),
-                );
-                let taken = options
-                    .iter()
-                    .filter_map(|(k, v)| if v == &name { Some(k) } else { None })
-                    .collect::<Vec<_>>();
-                if !taken.is_empty() {
-                    panic!(
-                        "Enum name {} is already taken by {:?} ({:?})",
-                        name, taken, options
-                    );
-                }
-                name
-            } else {
-                options_rust_enum_name(param, &options)
-            };
-            let enum_lines = generate_options(name.as_str(), os);
-            options.insert(os.clone(), name);
-            lines.extend(enum_lines);
         }
-    }
 
-    for doc in &app.docs {
-        lines.extend(generate_doc(doc, 0, config));
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="items">
+                    <method name="GET" id="items-get">
+                        <response status="200">
+                            <representation href="#item"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="item" mediaType="application/json">
+                    <param name="count" style="plain" type="xsd:int" required="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let config = Config::builder().hook(Marker).build();
+        let code = generate(&app, &config);
+        assert!(code.contains("\n// representation Item\n"), "{}", code);
+        assert!(code.contains("pub count: u64,"), "{}", code);
+        assert!(
+            code.contains("    pub fn get_id(&self) -> &'static str { \"items-get\" }\n"),
+            "{}",
+            code
+        );
+        assert!(
+            code.ends_with("pub const RESOURCE_TYPES: usize = 1;\n"),
+            "{}",
+            code
+        );
     }
 
-    for representation in &app.representations {
-        lines.extend(generate_representation(representation, config, &options));
+    #[test]
+    fn test_generate_response_profiles() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <resource_type id="items">
+                    <method name="GET" id="items-get">
+                        <response status="200">
+                            <representation href="#item-summary"/>
+                            <representation href="#item-full"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="item-summary" mediaType="application/json" profile="http://example.com/summary"/>
+                <representation id="item-full" mediaType="application/json" profile="http://example.com/full"/>
+            </application>"##,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(code.contains("pub enum ItemsGetResponse {"), "{}", code);
+        assert!(
+            code.contains("-> std::result::Result<ItemsGetResponse, wadl::Error>"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("Some(\"http://example.com/full\"),\n                    ) =>"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("Ok(ItemsGetResponse::ItemFull(resp.json::<ItemFull>()?))"),
+            "{}",
+            code
+        );
+        assert!(code.contains("\"application/json\""), "{}", code);
+        assert!(
+            !code.contains("application/json, application/json"),
+            "{}",
+            code
+        );
     }
 
-    for resource_type in &app.resource_types {
-        lines.extend(generate_resource_type(resource_type, config, &options));
+    #[test]
+    fn test_generate_form_urlencoded_response() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="token">
+                    <method name="POST" id="token-new">
+                        <response status="200">
+                            <representation href="#token-reply"/>
+                        </response>
+                    </method>
+                </resource_type>
+                <representation id="token-reply" mediaType="application/x-www-form-urlencoded">
+                    <param name="oauth_token" style="plain" type="xsd:string" required="true"/>
+                    <param name="expires" style="plain" type="xsd:int"/>
+                    <param name="scope" style="plain" type="xsd:string" repeating="true" required="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(
+            code.contains(
+                "pub fn from_form_urlencoded(body: &[u8]) -> Result<Self, wadl::Error> {"
+            ),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("oauth_token: fields.required(\"oauth_token\")?,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("expires: fields.value(\"expires\")?,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("scope: fields.values(\"scope\")?,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("Some(\"application/x-www-form-urlencoded\") => {"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("TokenReply::from_form_urlencoded(&resp.bytes()?)?"),
+            "{}",
+            code
+        );
     }
 
-    lines.concat()
-}
-
-fn indent(indent: usize, lines: impl Iterator<Item = String>) -> impl Iterator<Item = String> {
-    lines.map(move |line| format!("{}{}", " ".repeat(indent * 4), line))
-}
+    #[test]
+    fn test_generate_unsupported_request_params() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="items">
+                    <method name="GET" id="items-search">
+                        <request>
+                            <param name="q" style="query" type="xsd:string" required="true"/>
+                            <param name="id" style="template" type="xsd:string"/>
+                            <param name="rev" style="matrix" type="xsd:string"/>
+                            <param name="path" style="plain" type="xsd:string"/>
+                        </request>
+                    </method>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(code.contains("q: &str"), "{}", code);
+        assert!(!code.contains("id: "), "{}", code);
+        assert!(!code.contains("rev: "), "{}", code);
+        assert!(!code.contains("path: "), "{}", code);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
     #[test]
-    fn test_camel_case_name() {
-        assert_eq!(camel_case_name("foo-bar"), "FooBar");
-        assert_eq!(camel_case_name("foo-bar-baz"), "FooBarBaz");
-        assert_eq!(camel_case_name("foo-bar-baz-quux"), "FooBarBazQuux");
-        assert_eq!(camel_case_name("_foo-bar"), "_fooBar");
-        assert_eq!(camel_case_name("service-root-json"), "ServiceRootJson");
-        assert_eq!(camel_case_name("get-some-URL"), "GetSomeURL");
+    fn test_generate_query_array_styles() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="items">
+                    <method name="GET" id="items-search">
+                        <request>
+                            <param name="tag" style="query" type="xsd:string" repeating="true" required="true"/>
+                            <param name="status" style="query" type="xsd:string" repeating="true"/>
+                        </request>
+                    </method>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(code.contains("url_.query_pairs_mut().append_pair(\"tag\", &tag.to_string());"));
+
+        let config = Config {
+            query_array_style: QueryArrayStyle::Comma,
+            query_array_style_override: Some(Box::new(|method, param| {
+                assert_eq!(method, "search");
+                (param == "status").then_some(QueryArrayStyle::Brackets)
+            })),
+            ..Default::default()
+        };
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains(
+                "&tag.iter().map(|tag| tag.to_string()).collect::<Vec<_>>().join(\",\"),"
+            ),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("url_.query_pairs_mut().append_pair(\"status[]\", &status.to_string());")
+        );
     }
 
     #[test]
-    fn test_generate_empty() {
-        let input = crate::ast::Application {
-            docs: vec![],
-            representations: vec![],
-            resource_types: vec![],
-            resources: vec![],
-            grammars: vec![],
+    fn test_generate_multipart_upload() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="files">
+                    <method name="POST" id="upload">
+                        <request>
+                            <representation mediaType="multipart/form-data">
+                                <param name="description" style="query" type="xsd:string" required="true"/>
+                                <param name="file" style="query" type="xsd:base64Binary" required="true"/>
+                                <param name="thumbnail" style="query" type="xsd:binary"/>
+                            </representation>
+                        </request>
+                    </method>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(
+            code.contains("thumbnail: Option<wadl::blocking::Upload>,"),
+            "{}",
+            code
+        );
+        assert!(code.contains("file: wadl::blocking::Upload,"));
+        assert!(code.contains("form = form.part(\"file\", file.into_part()?);"));
+        assert!(code.contains("Part::text(description.to_string())"));
+
+        let config = Config {
+            r#async: true,
+            ..Default::default()
         };
-        let config = Config::default();
-        let lines = generate(&input, &config);
-        assert_eq!(lines, "".to_string());
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("thumbnail: Option<wadl::r#async::Upload>"));
+        assert!(code.contains("form = form.part(\"thumbnail\", thumbnail.into_part().await?);"));
+        assert_eq!(config.validate(&app), Ok(()));
+
+        let config = Config {
+            ureq: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(&app),
+            Err(ConfigError::MultipartNotSupported("upload".to_string()))
+        );
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(!code.contains("multipart"), "{}", code);
     }
 
     #[test]
-    fn test_enum_rust_value() {
-        assert_eq!(enum_rust_value("foo"), "Foo");
-        assert_eq!(enum_rust_value("foo bar"), "FooBar");
-        assert_eq!(enum_rust_value("foo bar blah"), "FooBarBlah");
-        assert_eq!(enum_rust_value("foo-bar"), "FooBar");
+    fn test_generate_request_param_names() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <resource_type id="pets">
+                    <method name="GET" id="list">
+                        <request>
+                            <param name="X-Request-Id" style="header" type="xsd:string"/>
+                            <param name="pageSize" style="query" type="xsd:int"/>
+                        </request>
+                    </method>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(code.contains("x_request_id: Option<&str>,"), "{}", code);
+        assert!(code.contains("page_size: Option<&i32>,"), "{}", code);
+        assert!(code.contains("req = req.header(\"X-Request-Id\", &x_request_id.to_string());"));
+        assert!(code.contains("if let Some(x_request_id) = x_request_id {"));
     }
 
     #[test]
-    fn test_snake_case_name() {
-        assert_eq!(snake_case_name("F"), "f");
-        assert_eq!(snake_case_name("FooBar"), "foo_bar");
-        assert_eq!(snake_case_name("FooBarBaz"), "foo_bar_baz");
-        assert_eq!(snake_case_name("FooBarBazQuux"), "foo_bar_baz_quux");
-        assert_eq!(snake_case_name("_FooBar"), "_foo_bar");
-        assert_eq!(snake_case_name("ServiceRootJson"), "service_root_json");
-        assert_eq!(snake_case_name("GetSomeURL"), "get_some_url");
+    fn test_method_expr() {
+        let config = Config::default();
+        assert_eq!(method_expr("GET", &config), "reqwest::Method::GET");
+        assert_eq!(
+            method_expr("PROPFIND", &config),
+            "reqwest::Method::from_bytes(b\"PROPFIND\").map_err(|_| wadl::Error::InvalidMethod(\"PROPFIND\".to_string()))?"
+        );
+        let config = Config {
+            tower: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            method_expr("PURGE", &config),
+            "wadl::tower::http::Method::from_bytes(b\"PURGE\").map_err(|_| wadl::Error::InvalidMethod(\"PURGE\".to_string()))?"
+        );
+    }
+
+    #[test]
+    fn test_generate_invalid_method_name() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <resource_type id="items">
+                    <method name="GET" id="items-list"/>
+                    <method name="NOT VALID" id="items-bogus"/>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = format_code(&generate(&app, &Config::default())).unwrap();
+        assert!(code.contains("pub fn list<"), "{}", code);
+        assert!(!code.contains("bogus"), "{}", code);
+        assert!(!code.contains("NOT VALID"), "{}", code);
     }
 
     #[test]
-    fn test_strip_code_examples() {
-        let input = r#"This is a test
-```python
-def foo():
-    pass
-```
-
-This is another test
-```python
-def bar():
-    pass
-```
-"#;
-        let expected = r#"This is a test
-
-This is another test"#;
-        assert_eq!(strip_code_examples(input.to_string()), expected);
+    fn test_constant_name() {
+        assert_eq!(
+            constant_name(&["ws.op", "searchTasks"]),
+            "WS_OP_SEARCH_TASKS"
+        );
+        assert_eq!(
+            constant_name(&["media_type", "application/vnd.sun.wadl+xml"]),
+            "MEDIA_TYPE_APPLICATION_VND_SUN_WADL_XML"
+        );
+        assert_eq!(constant_name(&["1.0", "x--y"]), "_1_0_X_Y");
     }
 
     #[test]
-    fn test_format_doc_plain() {
-        let doc = Doc {
-            title: None,
-            lang: None,
-            content: "This is a test".to_string(),
-            xmlns: None,
-        };
+    fn test_constants() {
+        let mut constants = Constants::default();
+        assert_eq!(
+            constants.insert("ws.op", "new"),
+            Some("pub const WS_OP_NEW: &str = \"new\";\n".to_string())
+        );
+        assert_eq!(constants.insert("ws.op", "new"), None);
+        assert_eq!(
+            constants.insert("ws-op", "new"),
+            Some("pub const WS_OP_NEW_2: &str = \"new\";\n".to_string())
+        );
+        assert_eq!(constants.fixed("ws-op", "new"), "WS_OP_NEW_2");
+        assert_eq!(constants.fixed("ws.op", "delete"), "\"delete\"");
+        assert_eq!(constants.media_type("text/plain"), "\"text/plain\"");
+    }
 
+    #[test]
+    fn test_default_deprecation_note() {
         assert_eq!(
-            format_doc(&doc, &Config::default()),
-            "This is a test".to_string()
+            default_deprecation_note("Get a bug.\n\nDEPRECATED: use get_bug instead."),
+            Some("DEPRECATED: use get_bug instead.".to_string())
+        );
+        assert_eq!(
+            default_deprecation_note("  This operation is obsolete. "),
+            Some("This operation is obsolete.".to_string())
+        );
+        assert_eq!(
+            default_deprecation_note("Replaces the deprecated foo parameter."),
+            None
         );
+        assert_eq!(default_deprecation_note("Get a bug."), None);
     }
 
     #[test]
-    fn test_format_doc_html() {
+    fn test_generate_deprecated_from_docs() {
+        let app = crate::parse_string(
+            r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+                <resource_type id="bug">
+                    <method name="GET" id="bug-get">
+                        <doc>Deprecated: use "get_task" instead.</doc>
+                    </method>
+                    <method name="DELETE" id="bug-delete">
+                        <doc>Delete the bug.</doc>
+                    </method>
+                </resource_type>
+            </application>"#,
+        )
+        .unwrap();
+        let code = generate(&app, &Config::default());
+        assert!(
+            code.contains(
+                "    #[deprecated(note = \"Deprecated: use \\\"get_task\\\" instead.\")]\n    pub fn get<'a>"
+            ),
+            "{}",
+            code
+        );
+        assert_eq!(code.matches("#[deprecated").count(), 1);
+
+        let code = generate(
+            &app,
+            &Config {
+                deprecation_note: Some(Box::new(|text| {
+                    text.starts_with("Delete").then(|| "going away".to_string())
+                })),
+                ..Default::default()
+            },
+        );
+        assert!(code.contains("    #[deprecated(note = \"going away\")]\n    pub fn delete<'a>"));
+        assert_eq!(code.matches("#[deprecated").count(), 1);
+    }
+
+    #[test]
+    fn test_format_doc_markdown_table() {
         let doc = Doc {
             title: None,
             lang: None,
-            content: "<p>This is a test</p>".to_string(),
+            content: "<p>Values:</p><table><tr><th>Name</th><th>Meaning</th></tr><tr><td>active</td><td><p>Is</p> <b>active</b></td></tr><tr><td>a|b</td><td>pipe</td></tr></table><p>Done.</p>".to_string(),
             xmlns: Some("http://www.w3.org/1999/xhtml".parse().unwrap()),
         };
 
         assert_eq!(
-            format_doc(&doc, &Config::default()),
-            "This is a test".to_string()
+            format_doc(
+                &doc,
+                &Config {
+                    markdown_tables: true,
+                    ..Default::default()
+                }
+            ),
+            r#"Values:
+
+| Name | Meaning |
+|---|---|
+| active | Is active |
+| a\|b | pipe |
+
+Done."#
         );
     }
 
     #[test]
-    fn test_format_doc_html_link() {
+    fn test_format_doc_markdown_table_merged_cells() {
         let doc = Doc {
             title: None,
             lang: None,
-            content: "<p>This is a <a href=\"https://example.com\">test</a></p>".to_string(),
+            content: "<table><tbody><tr><td>a</td><td>bb</td></tr><tr><td colspan=\"2\">merged</td></tr></tbody></table>".to_string(),
             xmlns: Some("http://www.w3.org/1999/xhtml".parse().unwrap()),
         };
 
         assert_eq!(
-            format_doc(&doc, &Config::default()),
-            "This is a [test](https://example.com)".to_string()
+            format_doc(
+                &doc,
+                &Config {
+                    markdown_tables: true,
+                    ..Default::default()
+                }
+            ),
+            "```text\na       bb\nmerged\n```"
         );
     }
 
@@ -1739,6 +8679,7 @@ This is another test"#;
             required: true,
             repeating: false,
             fixed: None,
+            default: None,
             doc: None,
             options: None,
             id: None,
@@ -1821,7 +8762,7 @@ This is another test"#;
             resource_type_rust_type,
             &HashMap::new(),
         );
-        assert_eq!(param_type, "Vec<u8>");
+        assert_eq!(param_type, "String");
 
         param.r#type = "xsd:date".to_string();
         let (param_type, _) = param_rust_type(
@@ -1847,6 +8788,234 @@ This is another test"#;
         assert_eq!(param_type, "MyOptions");
     }
 
+    #[test]
+    fn test_simple_type_rust_type() {
+        let mut param = Param {
+            name: "foo".to_string(),
+            r#type: "xsd:long".to_string(),
+            required: true,
+            repeating: false,
+            fixed: None,
+            default: None,
+            doc: None,
+            options: None,
+            id: None,
+            style: ParamStyle::Plain,
+            path: None,
+            links: vec![],
+        };
+        let def = RepresentationDef::default();
+        let container = ParamContainer::Representation(&def);
+        let mut config = Config::default();
+
+        let rust_type = |param: &Param, config: &Config| {
+            simple_type_rust_type(&container, &param.r#type, param, config).rust_type
+        };
+
+        assert_eq!(rust_type(&param, &config), "i64");
+        param.r#type = "xs:time".to_string();
+        assert_eq!(rust_type(&param, &config), "chrono::NaiveTime");
+        param.r#type = "anyURI".to_string();
+        assert_eq!(rust_type(&param, &config), "url::Url");
+        param.r#type = "xsd:duration".to_string();
+        assert_eq!(rust_type(&param, &config), "String");
+        param.r#type = "xsd:hexBinary".to_string();
+        assert_eq!(rust_type(&param, &config), "String");
+        param.r#type = "xsd:unknownType".to_string();
+        assert_eq!(rust_type(&param, &config), "String");
+
+        param.r#type = "xsd:decimal".to_string();
+        assert_eq!(rust_type(&param, &config), "f64");
+        config.decimal_type = DecimalType::BigDecimal;
+        assert_eq!(rust_type(&param, &config), "bigdecimal::BigDecimal");
+        config.decimal_type = DecimalType::RustDecimal;
+        assert_eq!(rust_type(&param, &config), "rust_decimal::Decimal");
+        param.r#type = "xsd:double".to_string();
+        assert_eq!(rust_type(&param, &config), "f64");
+        param.r#type = "xsd:decimal".to_string();
+        config
+            .xsd_type_map
+            .insert("decimal".to_string(), "rust_decimal::Decimal".to_string());
+        assert_eq!(rust_type(&param, &config), "rust_decimal::Decimal");
+        config.xsd_type_map.insert(
+            "xsd:decimal".to_string(),
+            "bigdecimal::BigDecimal".to_string(),
+        );
+        assert_eq!(rust_type(&param, &config), "bigdecimal::BigDecimal");
+
+        let binary = |param: &Param, config: &Config| {
+            simple_type_rust_type(&container, &param.r#type, param, config).binary
+        };
+        assert!(!binary(&param, &config));
+        param.r#type = "xsd:base64Binary".to_string();
+        assert!(binary(&param, &config));
+        config
+            .xsd_type_map
+            .insert("base64Binary".to_string(), "bytes::Bytes".to_string());
+        assert!(binary(&param, &config));
+        param.r#type = "xsd:string".to_string();
+        assert!(!binary(&param, &config));
+        config
+            .xsd_type_map
+            .insert("string".to_string(), "Vec<u8>".to_string());
+        assert!(binary(&param, &config));
+    }
+
+    #[test]
+    fn test_date_time_crate() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <representation id="event" mediaType="application/json">
+                    <param name="day" style="plain" type="xsd:date" required="true"/>
+                    <param name="starts" style="plain" type="xsd:dateTime" required="true"/>
+                    <param name="ends" style="plain" type="xsd:dateTime"/>
+                    <param name="at" style="plain" type="xsd:time" required="true"/>
+                    <param name="length" style="plain" type="xsd:duration" required="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+
+        let config = Config::builder()
+            .date_time_crate(DateTimeCrate::Jiff)
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("pub day: jiff::civil::Date,"), "{}", code);
+        assert!(code.contains("pub starts: jiff::Timestamp,"), "{}", code);
+        assert!(
+            code.contains("pub ends: Option<jiff::Timestamp>,"),
+            "{}",
+            code
+        );
+        assert!(code.contains("pub at: jiff::civil::Time,"), "{}", code);
+        assert!(
+            code.contains("pub length: jiff::SignedDuration,"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("serde(with"), "{}", code);
+
+        let config = Config::builder()
+            .date_time_crate(DateTimeCrate::Time)
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("pub day: time::Date,"), "{}", code);
+        assert!(
+            code.contains(
+                "    #[serde(with = \"time::serde::rfc3339\")]\n    pub starts: time::OffsetDateTime,"
+            ),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    #[serde(default, with = \"time::serde::rfc3339::option\")]\n    pub ends: Option<time::OffsetDateTime>,"),
+            "{}",
+            code
+        );
+        assert!(code.contains("pub length: String,"), "{}", code);
+
+        // The XSD type map still takes precedence
+        let config = Config::builder()
+            .date_time_crate(DateTimeCrate::Time)
+            .xsd_type("dateTime", "String")
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("pub starts: String,"), "{}", code);
+        assert!(!code.contains("serde(with"), "{}", code);
+
+        assert_eq!(
+            DateTimeCrate::Chrono.rust_type("dateTime"),
+            Some("chrono::DateTime<chrono::Utc>")
+        );
+        assert_eq!(DateTimeCrate::Chrono.rust_type("string"), None);
+    }
+
+    #[test]
+    fn test_date_time_format() {
+        let app = crate::parse_string(
+            r##"<application xmlns="http://research.sun.com/wadl/2006/10"
+                            xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+                <representation id="event" mediaType="application/json">
+                    <param name="day" style="plain" type="xsd:date" required="true"/>
+                    <param name="starts" style="plain" type="xsd:dateTime" required="true"/>
+                    <param name="ends" style="plain" type="xsd:dateTime"/>
+                    <param name="updated" style="plain" type="xsd:dateTime"/>
+                    <param name="reminders" style="plain" type="xsd:dateTime" repeating="true"/>
+                </representation>
+            </application>"##,
+        )
+        .unwrap();
+
+        let config = Config::builder()
+            .date_time_format(|_, param| match param.name.as_str() {
+                "day" => Some(DateTimeFormat::Custom("%d/%m/%Y".to_string())),
+                "starts" => Some(DateTimeFormat::EpochSeconds),
+                "ends" | "reminders" => Some(DateTimeFormat::EpochMillis),
+                "updated" => Some(DateTimeFormat::Custom("%Y-%m-%d %H:%M:%S".to_string())),
+                _ => None,
+            })
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains(
+                "    #[serde(with = \"date_time_format_0\")]\n    pub day: chrono::NaiveDate,"
+            ),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    #[serde(with = \"chrono::serde::ts_seconds\")]\n    pub starts: chrono::DateTime<chrono::Utc>,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    #[serde(default, with = \"chrono::serde::ts_milliseconds_option\")]\n    pub ends: Option<chrono::DateTime<chrono::Utc>>,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    #[serde(default, with = \"date_time_format_1::option\")]\n    pub updated: Option<chrono::DateTime<chrono::Utc>>,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    pub reminders: Option<Vec<chrono::DateTime<chrono::Utc>>>,"),
+            "{}",
+            code
+        );
+        assert!(code.contains("mod date_time_format_0 {\n    type Value = chrono::NaiveDate;\n    const FORMAT: &str = \"%d/%m/%Y\";"), "{}", code);
+        assert!(code.contains("mod date_time_format_1 {"), "{}", code);
+        assert!(!code.contains("mod date_time_format_2"), "{}", code);
+
+        let config = Config::builder()
+            .date_time_crate(DateTimeCrate::Jiff)
+            .date_time_format(|_, param| {
+                (param.name == "starts").then_some(DateTimeFormat::EpochMillis)
+            })
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(
+            code.contains("    #[serde(with = \"jiff::fmt::serde::timestamp::millisecond::required\")]\n    pub starts: jiff::Timestamp,"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("date_time_format_"), "{}", code);
+
+        // Epoch formats only apply to xsd:dateTime, so the default is used for dates
+        let config = Config::builder()
+            .date_time_crate(DateTimeCrate::Time)
+            .date_time_format(|_, _| Some(DateTimeFormat::EpochSeconds))
+            .build();
+        let code = format_code(&generate(&app, &config)).unwrap();
+        assert!(code.contains("    pub day: time::Date,"), "{}", code);
+        assert!(
+            code.contains("    #[serde(default, with = \"time::serde::timestamp::option\")]\n    pub ends: Option<time::OffsetDateTime>,"),
+            "{}",
+            code
+        );
+    }
+
     #[test]
     fn test_readonly_rust_type() {
         assert_eq!(readonly_rust_type("String"), "&str");
@@ -1877,6 +9046,18 @@ This is another test"#;
         assert_eq!(escape_rust_reserved("as"), "r#as");
         assert_eq!(escape_rust_reserved("const"), "r#const");
         assert_eq!(escape_rust_reserved("let"), "r#let");
+        assert_eq!(escape_rust_reserved("async"), "r#async");
+        assert_eq!(escape_rust_reserved("await"), "r#await");
+        assert_eq!(escape_rust_reserved("dyn"), "r#dyn");
+        assert_eq!(escape_rust_reserved("ref"), "r#ref");
+        assert_eq!(escape_rust_reserved("where"), "r#where");
+        assert_eq!(escape_rust_reserved("yield"), "r#yield");
+        assert_eq!(escape_rust_reserved("gen"), "r#gen");
+        assert_eq!(escape_rust_reserved("self"), "self_");
+        assert_eq!(escape_rust_reserved("super"), "super_");
+        assert_eq!(escape_rust_reserved("crate"), "crate_");
+        assert_eq!(escape_rust_reserved("Self"), "Self_");
+        assert_eq!(escape_rust_reserved("union"), "union");
         assert_eq!(escape_rust_reserved("foo"), "foo");
     }
 
@@ -1905,6 +9086,7 @@ This is another test"#;
                     id: None,
                     repeating: false,
                     fixed: None,
+                    default: None,
                     links: vec![],
                     options: None,
                 },
@@ -1918,6 +9100,7 @@ This is another test"#;
                     id: None,
                     repeating: false,
                     fixed: None,
+                    default: None,
                     links: vec![],
                     options: None,
                 },
@@ -1949,6 +9132,372 @@ This is another test"#;
         );
     }
 
+    #[test]
+    fn test_generate_representation_default() {
+        let param = |name: &str, r#type: &str, required: bool, default: Option<&str>| Param {
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+            style: ParamStyle::Plain,
+            required,
+            doc: None,
+            path: None,
+            id: None,
+            repeating: false,
+            fixed: None,
+            default: default.map(|x| x.to_string()),
+            links: vec![],
+            options: None,
+        };
+        let input = RepresentationDef {
+            media_type: Some("application/json".parse().unwrap()),
+            id: Some("settings".to_string()),
+            params: vec![
+                param("name", "string", true, Some("anonymous")),
+                param("count", "xs:int", false, Some("10")),
+                param("ratio", "xs:double", true, Some("1")),
+                param("enabled", "xs:boolean", true, Some("true")),
+                param("comment", "string", false, None),
+            ],
+            ..Default::default()
+        };
+
+        let lines =
+            generate_representation_struct_json(&input, &Config::default(), &HashMap::new());
+        let code = lines.concat();
+        assert!(code.contains(
+            r#"impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            name: "anonymous".to_string(),
+            count: Some(10),
+            ratio: 1.0,
+            enabled: true,
+            comment: Default::default(),
+        }
+    }
+}
+"#
+        ));
+
+        assert_eq!(
+            default_value_expr(
+                &Param {
+                    options: Some(Options::from(vec!["in progress", "done"])),
+                    repeating: true,
+                    ..param("status", "string", true, Some("in progress"))
+                },
+                "Vec<Status>",
+                "in progress"
+            ),
+            Some("vec![Status::InProgress]".to_string())
+        );
+        assert_eq!(
+            default_value_expr(
+                &param("when", "xs:date", true, None),
+                "chrono::NaiveDate",
+                "2024-02-29"
+            ),
+            Some(r#""2024-02-29".parse().unwrap()"#.to_string())
+        );
+        assert_eq!(
+            default_value_expr(&param("size", "xs:byte", true, None), "i8", "+7"),
+            Some("7".to_string())
+        );
+
+        // Defaults that would fail to parse at runtime are skipped
+        for (rust_type, default) in [
+            ("bool", "yes"),
+            ("u8", "256"),
+            ("f64", "many"),
+            ("url::Url", "not a url"),
+            ("chrono::NaiveDate", "2023-02-29"),
+            ("chrono::NaiveTime", "24:00:00"),
+            ("chrono::DateTime<chrono::Utc>", "2024-01-01T00:00:00"),
+            ("Person", "bob"),
+        ] {
+            assert_eq!(
+                default_value_expr(&param("x", "string", true, None), rust_type, default),
+                None,
+                "{} {}",
+                rust_type,
+                default
+            );
+        }
+        assert!(is_valid_default(
+            "chrono::DateTime<chrono::Utc>",
+            "2024-01-01T12:30:00.5+02:00"
+        ));
+        assert_eq!(
+            default_value_expr(
+                &param("when", "xs:date", true, None),
+                "time::Date",
+                "2024-02-29"
+            ),
+            Some(
+                "time::Date::from_calendar_date(2024, time::Month::February, 29).unwrap()"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            default_value_expr(
+                &param("at", "xs:time", true, None),
+                "time::Time",
+                "09:30:00.25"
+            ),
+            Some("time::Time::from_hms_nano(9, 30, 0, 250000000).unwrap()".to_string())
+        );
+        assert_eq!(
+            default_value_expr(
+                &param("timeout", "xs:duration", true, None),
+                "jiff::SignedDuration",
+                "PT1M30S"
+            ),
+            Some(r#""PT1M30S".parse().unwrap()"#.to_string())
+        );
+        for (rust_type, default) in [
+            ("time::OffsetDateTime", "2024-01-01T00:00:00Z"),
+            ("jiff::Timestamp", "2024-01-01T00:00:00-05:00"),
+            ("jiff::civil::Time", "23:59:59.999999999"),
+            ("rust_decimal::Decimal", "-12.50"),
+            ("bigdecimal::BigDecimal", "3"),
+        ] {
+            assert!(
+                is_valid_default(rust_type, default),
+                "{} {}",
+                rust_type,
+                default
+            );
+        }
+        for (rust_type, default) in [
+            ("time::Date", "2024-13-01"),
+            ("jiff::SignedDuration", "PT"),
+            ("jiff::SignedDuration", "P1D"),
+            ("rust_decimal::Decimal", "1e5"),
+            ("chrono::NaiveTime", "12:00:00.0000000001"),
+        ] {
+            assert!(
+                !is_valid_default(rust_type, default),
+                "{} {}",
+                rust_type,
+                default
+            );
+        }
+
+        // Options enums only have variants for the declared options
+        assert_eq!(
+            default_value_expr(
+                &Param {
+                    options: Some(Options::from(vec!["in progress", "done"])),
+                    ..param("status", "string", true, None)
+                },
+                "Status",
+                "cancelled"
+            ),
+            None
+        );
+
+        // A required param with an invalid default prevents the Default impl
+        let bad = RepresentationDef {
+            params: vec![param("count", "xs:int", true, Some("lots"))],
+            ..input.clone()
+        };
+        let code =
+            generate_representation_struct_json(&bad, &Config::default(), &HashMap::new()).concat();
+        assert!(!code.contains("impl Default"), "{}", code);
+        assert!(!code.contains("unwrap"), "{}", code);
+
+        // Required params without a default prevent the Default impl
+        let input = RepresentationDef {
+            params: vec![param("name", "string", true, None)],
+            ..input
+        };
+        let code = generate_representation_struct_json(&input, &Config::default(), &HashMap::new())
+            .concat();
+        assert!(!code.contains("impl Default"));
+    }
+
+    #[test]
+    fn test_config_derives() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.derives(ItemKind::Representation, "Person"),
+            vec![
+                "Debug",
+                "Clone",
+                "PartialEq",
+                "serde::Serialize",
+                "serde::Deserialize"
+            ]
+        );
+        assert!(config.derives(ItemKind::ResourceType, "Person").is_empty());
+
+        config.derive = Some(Box::new(|kind, name| match (kind, name) {
+            (ItemKind::Representation, "Person") => Some(vec![
+                "Debug".to_string(),
+                "Eq".to_string(),
+                "Hash".to_string(),
+                "schemars::JsonSchema".to_string(),
+            ]),
+            (ItemKind::ResourceType, _) => Some(vec!["Debug".to_string(), "Clone".to_string()]),
+            _ => None,
+        }));
+
+        let input = RepresentationDef {
+            media_type: Some(mime::APPLICATION_JSON),
+            id: Some("person".to_string()),
+            ..Default::default()
+        };
+        let code = generate_representation_struct_json(&input, &config, &HashMap::new()).concat();
+        assert!(
+            code.contains("#[derive(Debug, Eq, Hash, schemars::JsonSchema)]\npub struct Person {")
+        );
+
+        let input = ResourceType {
+            id: "person".to_string(),
+            docs: vec![],
+            methods: vec![],
+            query_type: mime::APPLICATION_JSON,
+            params: vec![],
+            subresources: vec![],
+        };
+        let code = generate_resource_type(
+            &input,
+            &config,
+            &HashMap::new(),
+            &Constants::default(),
+            &vec![],
+        )
+        .concat();
+        assert!(code.starts_with("#[derive(Debug, Clone)]\npub struct Person (reqwest::Url);"));
+    }
+
+    #[test]
+    fn test_generate_representation_serde_attributes() {
+        let param = |name: &str, required: bool| Param {
+            name: name.to_string(),
+            r#type: "string".to_string(),
+            style: ParamStyle::Plain,
+            required,
+            doc: None,
+            path: None,
+            id: None,
+            repeating: false,
+            fixed: None,
+            default: None,
+            links: vec![],
+            options: None,
+        };
+        let input = RepresentationDef {
+            media_type: Some(mime::APPLICATION_JSON),
+            id: Some("person".to_string()),
+            params: vec![
+                param("displayName", true),
+                param("type", true),
+                param("nick", false),
+            ],
+            ..Default::default()
+        };
+
+        let mut config = Config::default();
+        let code = generate_representation_struct_json(&input, &config, &HashMap::new()).concat();
+        assert!(code
+            .contains("    #[serde(rename = \"displayName\")]\n    pub display_name: String,\n"));
+        assert!(code.contains("    // was: string\n    pub r#type: String,\n"));
+        assert!(code.contains("    // was: string\n    pub nick: Option<String>,\n"));
+
+        config.skip_serializing_none = true;
+        config.deny_unknown_fields = Some(Box::new(|name| name == "Person"));
+        config.serde_rename_all = Some(Box::new(|_| Some("camelCase".to_string())));
+        let code = generate_representation_struct_json(&input, &config, &HashMap::new()).concat();
+        assert!(code.contains(
+            "#[serde(rename_all = \"camelCase\", deny_unknown_fields)]\npub struct Person {\n"
+        ));
+        assert!(code.contains("    // was: string\n    pub display_name: String,\n"));
+        assert!(code.contains(
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub nick: Option<String>,\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_representation_capture_unknown_fields() {
+        let input = RepresentationDef {
+            media_type: Some(mime::APPLICATION_JSON),
+            id: Some("person".to_string()),
+            params: vec![Param {
+                name: "extra".to_string(),
+                r#type: "string".to_string(),
+                style: ParamStyle::Plain,
+                required: false,
+                doc: None,
+                path: None,
+                id: None,
+                repeating: false,
+                fixed: None,
+                default: None,
+                links: vec![],
+                options: None,
+            }],
+            ..Default::default()
+        };
+        let config = Config {
+            capture_unknown_fields: true,
+            ..Default::default()
+        };
+        let code = generate_representation_struct_json(&input, &config, &HashMap::new()).concat();
+        assert!(code.contains(
+            r#"    /// Fields not described in the WADL
+    #[serde(flatten)]
+    pub extra_: std::collections::HashMap<String, serde_json::Value>,
+"#
+        ));
+        assert!(code.contains("            extra_: Default::default(),\n"));
+    }
+
+    #[test]
+    fn test_generate_representation_capture_and_deny_unknown_fields() {
+        let input = RepresentationDef {
+            media_type: Some(mime::APPLICATION_JSON),
+            id: Some("person".to_string()),
+            params: vec![Param {
+                name: "details".to_string(),
+                r#type: "string".to_string(),
+                style: ParamStyle::Plain,
+                required: false,
+                doc: None,
+                path: None,
+                id: None,
+                repeating: false,
+                fixed: None,
+                default: None,
+                links: vec![],
+                options: None,
+            }],
+            ..Default::default()
+        };
+        let config = Config {
+            capture_unknown_fields: true,
+            deny_unknown_fields: Some(Box::new(|_| true)),
+            param_renames: maplit::hashmap! {
+                "person.details".to_string() => "extra".to_string(),
+            },
+            ..Default::default()
+        };
+        let code = generate_representation_struct_json(&input, &config, &HashMap::new()).concat();
+        assert!(!code.contains("deny_unknown_fields"), "{}", code);
+        assert!(
+            code.contains("    pub extra: Option<String>,\n"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains(
+                "    pub extra_: std::collections::HashMap<String, serde_json::Value>,\n"
+            ),
+            "{}",
+            code
+        );
+    }
+
     #[test]
     fn test_supported_representation_def() {
         let mut d = RepresentationDef {
@@ -1976,6 +9525,7 @@ This is another test"#;
                 required: true,
                 repeating: false,
                 fixed: None,
+                default: None,
                 path: None,
                 links: Vec::new(),
                 options: None,
@@ -2006,6 +9556,7 @@ This is another test"#;
                 required: true,
                 repeating: false,
                 fixed: None,
+                default: None,
                 path: None,
                 links: Vec::new(),
                 options: None,
@@ -2019,6 +9570,7 @@ This is another test"#;
                 required: true,
                 repeating: false,
                 fixed: None,
+                default: None,
                 path: None,
                 links: Vec::new(),
                 options: None,
@@ -2038,6 +9590,7 @@ This is another test"#;
             required: true,
             repeating: false,
             fixed: None,
+            default: None,
             path: None,
             links: vec![Link {
                 relation: None,
@@ -2061,6 +9614,7 @@ This is another test"#;
             required: true,
             repeating: false,
             fixed: None,
+            default: None,
             path: None,
             links: vec![Link {
                 relation: None,
@@ -2084,6 +9638,7 @@ This is another test"#;
             required: true,
             repeating: false,
             fixed: None,
+            default: None,
             options: None,
             path: None,
             links: vec![Link {
@@ -2159,18 +9714,23 @@ This is another test"#;
             responses: vec![],
         };
         let config = Config::default();
-        let lines = generate_method(&input, "bar", &config, &HashMap::new());
+        let lines = generate_method(
+            &input,
+            "bar",
+            &config,
+            &HashMap::new(),
+            &Constants::default(),
+            false,
+        );
         assert_eq!(lines, vec![
         "    pub fn foo<'a>(&self, client: &'a dyn wadl::blocking::Client) -> std::result::Result<(), wadl::Error> {\n".to_string(),
         "        let mut url_ = self.url().clone();\n".to_string(),
         "\n".to_string(),
         "        let mut req = client.request(reqwest::Method::GET, url_);\n".to_string(),
         "\n".to_string(),
-        "        let resp = req.send()?;\n".to_string(),
-        "        match resp.status() {\n".to_string(),
-        "            s if s.is_success() => Ok(()),\n".to_string(),
-        "            s => Err(wadl::Error::UnhandledStatus(s))\n".to_string(),
-        "        }\n".to_string(),
+        "        let resp = client.send(req)?;\n".to_string(),
+        "        wadl::response::check_status(resp.status(), &[])?;\n".to_string(),
+        "        Ok(())\n".to_string(),
         "    }\n".to_string(),
         "\n".to_string(),
     ]);
@@ -2187,7 +9747,13 @@ This is another test"#;
             subresources: vec![],
         };
         let config = Config::default();
-        let lines = generate_resource_type(&input, &config, &HashMap::new());
+        let lines = generate_resource_type(
+            &input,
+            &config,
+            &HashMap::new(),
+            &Constants::default(),
+            &vec![],
+        );
         assert_eq!(
             lines,
             vec![
@@ -2202,6 +9768,29 @@ This is another test"#;
                 "    }\n".to_string(),
                 "}\n".to_string(),
                 "\n".to_string(),
+                "impl std::convert::TryFrom<url::Url> for Foo {\n".to_string(),
+                "    type Error = wadl::Error;\n".to_string(),
+                "\n".to_string(),
+                "    fn try_from(url: url::Url) -> Result<Self, Self::Error> {\n".to_string(),
+                "        Ok(Foo(url))\n".to_string(),
+                "    }\n".to_string(),
+                "}\n".to_string(),
+                "\n".to_string(),
+                "impl std::str::FromStr for Foo {\n".to_string(),
+                "    type Err = wadl::Error;\n".to_string(),
+                "\n".to_string(),
+                "    fn from_str(s: &str) -> Result<Self, Self::Err> {\n".to_string(),
+                "        url::Url::parse(s)?.try_into()\n".to_string(),
+                "    }\n".to_string(),
+                "}\n".to_string(),
+                "\n".to_string(),
+                "impl std::fmt::Display for Foo {\n".to_string(),
+                "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n"
+                    .to_string(),
+                "        f.write_str(self.0.as_str())\n".to_string(),
+                "    }\n".to_string(),
+                "}\n".to_string(),
+                "\n".to_string(),
             ]
         );
     }
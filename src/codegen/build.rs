@@ -0,0 +1,215 @@
+//! Helper for generating code from build scripts
+
+use super::{format_generated, generate, Config, ConfigError};
+use std::path::{Path, PathBuf};
+
+/// An error that occurred while generating code from a build script
+#[derive(Debug)]
+pub enum BuildError {
+    /// An I/O error occurred
+    Io(std::io::Error),
+
+    /// A WADL file could not be parsed
+    Wadl(PathBuf, crate::ParseError),
+
+    /// The generated code could not be formatted
+    Format(PathBuf, syn::Error),
+
+    /// Code can not be generated for a WADL file with the configuration
+    Config(PathBuf, ConfigError),
+
+    /// The output directory is not set, and `OUT_DIR` is not set in the environment
+    NoOutDir,
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BuildError::Io(err) => write!(f, "IO error: {}", err),
+            BuildError::Wadl(path, err) => write!(f, "Unable to parse {}: {}", path.display(), err),
+            BuildError::Format(path, err) => write!(
+                f,
+                "Unable to format code generated from {}: {}",
+                path.display(),
+                err
+            ),
+            BuildError::Config(path, err) => {
+                write!(f, "Unable to generate code for {}: {}", path.display(), err)
+            }
+            BuildError::NoOutDir => write!(f, "OUT_DIR is not set"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl From<std::io::Error> for BuildError {
+    fn from(err: std::io::Error) -> Self {
+        BuildError::Io(err)
+    }
+}
+
+/// Generate code from WADL files in a build script
+///
+/// Every WADL file is written to a module file in `OUT_DIR`, named after the WADL file
+/// (e.g. `launchpad.wadl` becomes `launchpad.rs`), and cargo is told to rerun the build
+/// script when the WADL file changes.
+///
+/// # Example
+///
+/// ```no_run
+/// // build.rs
+/// wadl::codegen::BuildHelper::new(wadl::codegen::Config::default())
+///     .wadl("api/launchpad.wadl")
+///     .run()
+///     .unwrap();
+/// ```
+///
+/// The generated code can then be included with
+/// `include!(concat!(env!("OUT_DIR"), "/launchpad.rs"));`.
+///
+/// If [`Config::header`] is set, the source of the header is set to each WADL file.
+pub struct BuildHelper {
+    config: Config,
+    inputs: Vec<(PathBuf, String)>,
+    out_dir: Option<PathBuf>,
+    format: bool,
+}
+
+impl BuildHelper {
+    /// Create a new build helper, generating code with the given configuration
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            inputs: vec![],
+            out_dir: None,
+            format: true,
+        }
+    }
+
+    /// Add a WADL file, writing the generated code to a file named after it
+    pub fn wadl(self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        self.wadl_as(path, name.as_str())
+    }
+
+    /// Add a WADL file, writing the generated code to `{name}.rs`
+    pub fn wadl_as(mut self, path: impl AsRef<Path>, name: &str) -> Self {
+        self.inputs
+            .push((path.as_ref().to_path_buf(), name.to_string()));
+        self
+    }
+
+    /// Set the directory to write generated code to, rather than `OUT_DIR`
+    pub fn out_dir(mut self, out_dir: impl AsRef<Path>) -> Self {
+        self.out_dir = Some(out_dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set whether to format the generated code (the default)
+    ///
+    /// Note that formatting drops regular comments, such as the application docs.
+    pub fn format(mut self, format: bool) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Generate the code for all WADL files
+    ///
+    /// # Returns
+    /// The paths of the generated files
+    pub fn run(mut self) -> Result<Vec<PathBuf>, BuildError> {
+        let out_dir = match self.out_dir {
+            Some(out_dir) => out_dir,
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(BuildError::NoOutDir)?,
+        };
+        std::fs::create_dir_all(&out_dir)?;
+
+        let mut ret = vec![];
+        for (path, name) in self.inputs {
+            println!("cargo:rerun-if-changed={}", path.display());
+            let contents = std::fs::read(&path)
+                .map_err(|e| BuildError::Wadl(path.clone(), crate::ParseError::Io(e)))?;
+            let app =
+                crate::parse_bytes(&contents).map_err(|e| BuildError::Wadl(path.clone(), e))?;
+            if let Some(header) = self.config.header.take() {
+                self.config.header = Some(header.source(&path.display().to_string(), &contents));
+            }
+            self.config
+                .validate(&app)
+                .map_err(|e| BuildError::Config(path.clone(), e))?;
+            let mut code = generate(&app, &self.config);
+            if self.format {
+                code = format_generated(&code, &self.config)
+                    .map_err(|e| BuildError::Format(path.clone(), e))?;
+            }
+            let output = out_dir.join(format!("{}.rs", name));
+            std::fs::write(&output, code)?;
+            ret.push(output);
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::FileHeader;
+
+    #[test]
+    fn test_build_helper() {
+        let out_dir = std::env::temp_dir().join(format!("wadl-build-{}", std::process::id()));
+        let paths = BuildHelper::new(Config::default())
+            .wadl(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/people-wadl.xml"
+            ))
+            .out_dir(&out_dir)
+            .run()
+            .unwrap();
+        assert_eq!(paths, vec![out_dir.join("people_wadl.rs")]);
+        let code = std::fs::read_to_string(&paths[0]).unwrap();
+        assert!(code.contains("pub struct PersonFull"));
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn test_build_helper_header() {
+        let out_dir =
+            std::env::temp_dir().join(format!("wadl-build-header-{}", std::process::id()));
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/people-wadl.xml");
+        let paths = BuildHelper::new(Config::builder().header(FileHeader::new()).build())
+            .wadl(path)
+            .out_dir(&out_dir)
+            .run()
+            .unwrap();
+        let code = std::fs::read_to_string(&paths[0]).unwrap();
+        let header = FileHeader::parse(&code).unwrap();
+        assert_eq!(header.source.as_deref(), Some(path));
+        assert!(header.matches_source(&std::fs::read(path).unwrap()));
+        assert!(code.contains("pub struct PersonFull"));
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn test_build_helper_missing_file() {
+        let out_dir =
+            std::env::temp_dir().join(format!("wadl-build-missing-{}", std::process::id()));
+        let err = BuildHelper::new(Config::default())
+            .wadl("nonexistent.wadl")
+            .out_dir(&out_dir)
+            .run()
+            .unwrap_err();
+        assert!(matches!(err, BuildError::Wadl(..)));
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
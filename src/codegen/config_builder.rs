@@ -0,0 +1,449 @@
+//! Fluent construction of [`Config`]
+
+use super::{
+    CodegenHook, Config, DateTimeCrate, DateTimeFormat, DecimalType, FileHeader, ItemKind,
+    NullableFieldStyle, Pagination, ParamContainer, QueryArrayStyle, RequestPolicy, RustVersion,
+    Visibility,
+};
+use crate::ast::{Param, RepresentationDef};
+
+/// Builder for [`Config`]
+///
+/// # Example
+///
+/// ```
+/// use wadl::codegen::{Config, ItemKind, Visibility};
+///
+/// let config = Config::builder()
+///     .r#async(true)
+///     .xsd_type("decimal", "rust_decimal::Decimal")
+///     .visibility(|kind, name| {
+///         (kind == ItemKind::Method && name.starts_with("internal_")).then_some(Visibility::Crate)
+///     })
+///     .build();
+/// assert!(config.r#async);
+/// ```
+#[derive(Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Create a builder with the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the configuration
+    pub fn build(self) -> Config {
+        self.config
+    }
+
+    /// Set [`Config::r#async`]
+    pub fn r#async(mut self, value: bool) -> Self {
+        self.config.r#async = value;
+        self
+    }
+
+    /// Set [`Config::blocking_and_async`]
+    pub fn blocking_and_async(mut self, value: bool) -> Self {
+        self.config.blocking_and_async = value;
+        self
+    }
+
+    /// Set [`Config::tower`]
+    pub fn tower(mut self, value: bool) -> Self {
+        self.config.tower = value;
+        self
+    }
+
+    /// Set [`Config::ureq`]
+    pub fn ureq(mut self, value: bool) -> Self {
+        self.config.ureq = value;
+        self
+    }
+
+    /// Set [`Config::native_async_client`]
+    pub fn native_async_client(mut self, value: bool) -> Self {
+        self.config.native_async_client = value;
+        self
+    }
+
+    /// Set [`Config::msrv`]
+    pub fn msrv(mut self, version: RustVersion) -> Self {
+        self.config.msrv = Some(version);
+        self
+    }
+
+    /// Set [`Config::override_type_name`]
+    pub fn override_type_name(
+        mut self,
+        f: impl Fn(&ParamContainer, &str, &str, &Config) -> Option<String> + 'static,
+    ) -> Self {
+        self.config.override_type_name = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::param_accessor_rename`]
+    pub fn param_accessor_rename(
+        mut self,
+        f: impl Fn(&str, &str) -> Option<String> + 'static,
+    ) -> Self {
+        self.config.param_accessor_rename = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::strip_code_examples`]
+    pub fn strip_code_examples(mut self, value: bool) -> Self {
+        self.config.strip_code_examples = value;
+        self
+    }
+
+    /// Set [`Config::markdown_tables`]
+    pub fn markdown_tables(mut self, value: bool) -> Self {
+        self.config.markdown_tables = value;
+        self
+    }
+
+    /// Set [`Config::generate_representation_traits`]
+    pub fn generate_representation_traits(
+        mut self,
+        f: impl Fn(&RepresentationDef, &str, &RepresentationDef, &Config) -> Option<Vec<String>>
+            + 'static,
+    ) -> Self {
+        self.config.generate_representation_traits = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::visibility`]
+    pub fn visibility(
+        mut self,
+        f: impl Fn(ItemKind, &str) -> Option<Visibility> + 'static,
+    ) -> Self {
+        self.config.visibility = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::map_type_for_response`]
+    pub fn map_type_for_response(
+        mut self,
+        f: impl Fn(&str, &str, &Config) -> Option<(String, String)> + 'static,
+    ) -> Self {
+        self.config.map_type_for_response = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::map_type_for_accessor`]
+    pub fn map_type_for_accessor(
+        mut self,
+        f: impl Fn(&str) -> Option<(String, String)> + 'static,
+    ) -> Self {
+        self.config.map_type_for_accessor = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::extend_accessor`]
+    pub fn extend_accessor(
+        mut self,
+        f: impl Fn(&Param, &'_ str, &'_ str, &Config) -> Vec<String> + 'static,
+    ) -> Self {
+        self.config.extend_accessor = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::extend_method`]
+    pub fn extend_method(
+        mut self,
+        f: impl Fn(&str, &str, &str, &Config) -> Vec<String> + 'static,
+    ) -> Self {
+        self.config.extend_method = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::extend_representation`]
+    pub fn extend_representation(
+        mut self,
+        f: impl Fn(&RepresentationDef, &str, &Config) -> Vec<String> + 'static,
+    ) -> Self {
+        self.config.extend_representation = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::deprecated_param`]
+    pub fn deprecated_param(mut self, f: impl Fn(&Param) -> bool + 'static) -> Self {
+        self.config.deprecated_param = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::deprecation_note`]
+    pub fn deprecation_note(mut self, f: impl Fn(&str) -> Option<String> + 'static) -> Self {
+        self.config.deprecation_note = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::options_enum_name`]
+    pub fn options_enum_name(
+        mut self,
+        f: impl Fn(&Param, Box<dyn Fn(&str) -> bool>) -> String + 'static,
+    ) -> Self {
+        self.config.options_enum_name = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::type_name`]
+    pub fn type_name(mut self, f: impl Fn(ItemKind, &str) -> Option<String> + 'static) -> Self {
+        self.config.type_name = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::reformat_docstring`]
+    pub fn reformat_docstring(mut self, f: impl Fn(&str) -> String + 'static) -> Self {
+        self.config.reformat_docstring = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::convert_to_multipart`]
+    pub fn convert_to_multipart(
+        mut self,
+        f: impl Fn(&str, &str) -> Option<String> + 'static,
+    ) -> Self {
+        self.config.convert_to_multipart = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::derive`]
+    pub fn derive(mut self, f: impl Fn(ItemKind, &str) -> Option<Vec<String>> + 'static) -> Self {
+        self.config.derive = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::skip_serializing_none`]
+    pub fn skip_serializing_none(mut self, value: bool) -> Self {
+        self.config.skip_serializing_none = value;
+        self
+    }
+
+    /// Set [`Config::nullable_field_style`]
+    pub fn nullable_field_style(mut self, value: NullableFieldStyle) -> Self {
+        self.config.nullable_field_style = value;
+        self
+    }
+
+    /// Set [`Config::nullable_field`]
+    pub fn nullable_field(
+        mut self,
+        f: impl Fn(&RepresentationDef, &Param) -> bool + 'static,
+    ) -> Self {
+        self.config.nullable_field = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::legacy_snake_case`]
+    pub fn legacy_snake_case(mut self, value: bool) -> Self {
+        self.config.legacy_snake_case = value;
+        self
+    }
+
+    /// Set [`Config::deny_unknown_fields`]
+    pub fn deny_unknown_fields(mut self, f: impl Fn(&str) -> bool + 'static) -> Self {
+        self.config.deny_unknown_fields = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::serde_rename_all`]
+    pub fn serde_rename_all(mut self, f: impl Fn(&str) -> Option<String> + 'static) -> Self {
+        self.config.serde_rename_all = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::capture_unknown_fields`]
+    pub fn capture_unknown_fields(mut self, value: bool) -> Self {
+        self.config.capture_unknown_fields = value;
+        self
+    }
+
+    /// Set [`Config::box_field`]
+    pub fn box_field(mut self, f: impl Fn(&RepresentationDef, &Param) -> bool + 'static) -> Self {
+        self.config.box_field = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::box_fields_larger_than`]
+    pub fn box_fields_larger_than(mut self, size: usize) -> Self {
+        self.config.box_fields_larger_than = Some(size);
+        self
+    }
+
+    /// Set [`Config::omit_clone_for_boxed`]
+    pub fn omit_clone_for_boxed(mut self, value: bool) -> Self {
+        self.config.omit_clone_for_boxed = value;
+        self
+    }
+
+    /// Set [`Config::generate_resource_traits`]
+    pub fn generate_resource_traits(mut self, value: bool) -> Self {
+        self.config.generate_resource_traits = value;
+        self
+    }
+
+    /// Set [`Config::generate_any_resource`]
+    pub fn generate_any_resource(mut self, value: bool) -> Self {
+        self.config.generate_any_resource = value;
+        self
+    }
+
+    /// Set [`Config::typed_links`]
+    pub fn typed_links(mut self, value: bool) -> Self {
+        self.config.typed_links = value;
+        self
+    }
+
+    /// Set [`Config::pagination`]
+    pub fn pagination(
+        mut self,
+        f: impl Fn(&RepresentationDef) -> Option<Pagination> + 'static,
+    ) -> Self {
+        self.config.pagination = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::options_non_exhaustive`]
+    pub fn options_non_exhaustive(mut self, value: bool) -> Self {
+        self.config.options_non_exhaustive = value;
+        self
+    }
+
+    /// Map an XSD simple type to a Rust type, see [`Config::xsd_type_map`]
+    pub fn xsd_type(mut self, type_name: &str, rust_type: &str) -> Self {
+        self.config
+            .xsd_type_map
+            .insert(type_name.to_string(), rust_type.to_string());
+        self
+    }
+
+    /// Set [`Config::doc_base_url`]
+    pub fn doc_base_url(mut self, url: url::Url) -> Self {
+        self.config.doc_base_url = Some(url);
+        self
+    }
+
+    /// Stream response bodies with a media type, see [`Config::streaming_media_types`]
+    pub fn streaming_media_type(mut self, media_type: &str) -> Self {
+        self.config
+            .streaming_media_types
+            .push(media_type.to_string());
+        self
+    }
+
+    /// Set [`Config::query_array_style`]
+    pub fn query_array_style(mut self, value: QueryArrayStyle) -> Self {
+        self.config.query_array_style = value;
+        self
+    }
+
+    /// Set [`Config::query_array_style_override`]
+    pub fn query_array_style_override(
+        mut self,
+        f: impl Fn(&str, &str) -> Option<QueryArrayStyle> + 'static,
+    ) -> Self {
+        self.config.query_array_style_override = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::request_policy`]
+    pub fn request_policy(mut self, f: impl Fn(&str) -> Option<RequestPolicy> + 'static) -> Self {
+        self.config.request_policy = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::date_time_crate`]
+    pub fn date_time_crate(mut self, value: DateTimeCrate) -> Self {
+        self.config.date_time_crate = value;
+        self
+    }
+
+    /// Set [`Config::date_time_format`]
+    pub fn date_time_format(
+        mut self,
+        f: impl Fn(&ParamContainer, &Param) -> Option<DateTimeFormat> + 'static,
+    ) -> Self {
+        self.config.date_time_format = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::decimal_type`]
+    pub fn decimal_type(mut self, value: DecimalType) -> Self {
+        self.config.decimal_type = value;
+        self
+    }
+
+    /// Set [`Config::observer`]
+    pub fn observer(mut self, expr: &str) -> Self {
+        self.config.observer = Some(expr.to_string());
+        self
+    }
+
+    /// Set [`Config::feature_gate`]
+    pub fn feature_gate(
+        mut self,
+        f: impl Fn(&str, Option<&str>) -> Option<String> + 'static,
+    ) -> Self {
+        self.config.feature_gate = Some(Box::new(f));
+        self
+    }
+
+    /// Rename the field of a param, see [`Config::param_renames`]
+    pub fn param_rename(mut self, param: &str, rust_name: &str) -> Self {
+        self.config
+            .param_renames
+            .insert(param.to_string(), rust_name.to_string());
+        self
+    }
+
+    /// Rename a method, see [`Config::method_renames`]
+    pub fn method_rename(mut self, id: &str, rust_name: &str) -> Self {
+        self.config
+            .method_renames
+            .insert(id.to_string(), rust_name.to_string());
+        self
+    }
+
+    /// Rename a representation or resource type, see [`Config::type_renames`]
+    pub fn type_rename(mut self, id: &str, rust_name: &str) -> Self {
+        self.config
+            .type_renames
+            .insert(id.to_string(), rust_name.to_string());
+        self
+    }
+
+    /// Set [`Config::module_path`]
+    pub fn module_path(mut self, f: impl Fn(ItemKind, &str) -> Option<String> + 'static) -> Self {
+        self.config.module_path = Some(Box::new(f));
+        self
+    }
+
+    /// Set [`Config::header`]
+    pub fn header(mut self, header: FileHeader) -> Self {
+        self.config.header = Some(header);
+        self
+    }
+
+    /// Register a hook, see [`Config::hooks`]
+    pub fn hook(mut self, hook: impl CodegenHook + 'static) -> Self {
+        self.config.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Set [`Config::item_filter`]
+    pub fn item_filter(mut self, f: impl Fn(ItemKind, &str) -> bool + 'static) -> Self {
+        self.config.item_filter = Some(Box::new(f));
+        self
+    }
+
+    #[cfg(feature = "templates")]
+    /// Set [`Config::templates`]
+    pub fn templates(mut self, templates: super::Templates) -> Self {
+        self.config.templates = Some(templates);
+        self
+    }
+}
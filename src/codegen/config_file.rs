@@ -0,0 +1,495 @@
+//! Declarative code generation configuration
+//!
+//! Most of [`Config`] consists of callbacks, which can only be set from Rust code. A
+//! [`ConfigFile`] covers the common cases with plain tables, so that code generation can be
+//! configured from a `wadl-codegen.toml` (or JSON) file:
+//!
+//! ```toml
+//! async = true
+//! skip_serializing_none = true
+//!
+//! [type_names]
+//! person-full = "Person"
+//!
+//! [derives]
+//! Person = ["Debug", "Clone", "serde::Serialize", "serde::Deserialize"]
+//!
+//! [visibility.representations]
+//! PersonPage = "pub(crate)"
+//! ```
+//!
+//! The resulting [`Config`] can still be extended with callbacks for anything the file can not
+//! express.
+
+use super::{
+    Config, DateTimeCrate, DateTimeFormat, DecimalType, ItemKind, NullableFieldStyle,
+    QueryArrayStyle, RustVersion, Visibility,
+};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// An error that occurred while loading a configuration file
+#[derive(Debug)]
+pub enum ConfigFileError {
+    /// An I/O error occurred
+    Io(std::io::Error),
+
+    /// The file is not valid TOML, or does not match [`ConfigFile`]
+    Toml(toml::de::Error),
+
+    /// The file is not valid JSON, or does not match [`ConfigFile`]
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigFileError::Io(err) => write!(f, "IO error: {}", err),
+            ConfigFileError::Toml(err) => write!(f, "TOML error: {}", err),
+            ConfigFileError::Json(err) => write!(f, "JSON error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigFileError {}
+
+impl From<std::io::Error> for ConfigFileError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigFileError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for ConfigFileError {
+    fn from(err: toml::de::Error) -> Self {
+        ConfigFileError::Toml(err)
+    }
+}
+
+impl From<serde_json::Error> for ConfigFileError {
+    fn from(err: serde_json::Error) -> Self {
+        ConfigFileError::Json(err)
+    }
+}
+
+/// Visibility overrides, keyed by the Rust name of the item
+///
+/// Values are `pub`, `pub(crate)`, `pub(super)`, `pub(in path)` or `private`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VisibilityTable {
+    /// Visibility of representation structs, e.g. `PersonFull = "pub(crate)"`
+    pub representations: BTreeMap<String, Visibility>,
+
+    /// Visibility of representation accessors
+    pub accessors: BTreeMap<String, Visibility>,
+
+    /// Visibility of resource type structs
+    pub resource_types: BTreeMap<String, Visibility>,
+
+    /// Visibility of methods
+    pub methods: BTreeMap<String, Visibility>,
+
+    /// Visibility of options enums
+    pub options_enums: BTreeMap<String, Visibility>,
+
+    /// Visibility of traits
+    pub traits: BTreeMap<String, Visibility>,
+}
+
+impl VisibilityTable {
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Return the visibility of an item
+    pub fn get(&self, kind: ItemKind, name: &str) -> Option<&Visibility> {
+        let table = match kind {
+            ItemKind::Representation => &self.representations,
+            ItemKind::Accessor => &self.accessors,
+            ItemKind::ResourceType => &self.resource_types,
+            ItemKind::Method => &self.methods,
+            ItemKind::OptionsEnum => &self.options_enums,
+            ItemKind::Trait => &self.traits,
+        };
+        table.get(name)
+    }
+}
+
+/// Code generation configuration that can be loaded from a file
+///
+/// See the fields of [`Config`] with the same names for their meaning.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Whether to generate async code
+    #[serde(rename = "async")]
+    pub r#async: bool,
+
+    /// Whether to generate both blocking and async code
+    pub blocking_and_async: bool,
+
+    /// Whether to send requests through a `tower::Service`
+    pub tower: bool,
+
+    /// Whether to send requests with ureq
+    pub ureq: bool,
+
+    /// Whether async code takes a `wadl::r#async::NativeClient`
+    pub native_async_client: bool,
+
+    /// The oldest Rust version that generated code has to compile with, e.g. `"1.70"`
+    pub msrv: Option<RustVersion>,
+
+    /// Whether to strip code examples from the docstrings
+    pub strip_code_examples: bool,
+
+    /// Whether to convert HTML tables in docstrings to markdown tables
+    pub markdown_tables: bool,
+
+    /// Whether to skip serializing optional representation fields that are `None`
+    pub skip_serializing_none: bool,
+
+    /// How optional fields that distinguish `null` from a missing value are represented
+    pub nullable_field_style: NullableFieldStyle,
+
+    /// Whether to convert names to snake case the way older versions did
+    pub legacy_snake_case: bool,
+
+    /// Whether to capture JSON fields not described in the WADL
+    pub capture_unknown_fields: bool,
+
+    /// Box representation fields whose type is estimated to be larger than this many bytes
+    pub box_fields_larger_than: Option<usize>,
+
+    /// Whether to leave `Clone` out of the derives of representations with boxed fields
+    pub omit_clone_for_boxed: bool,
+
+    /// Whether to generate a trait with the operations of each resource type
+    pub generate_resource_traits: bool,
+
+    /// Whether to generate an `AnyResource` enum
+    pub generate_any_resource: bool,
+
+    /// Whether to use `wadl::Link` for links to resource types in representations
+    pub typed_links: bool,
+
+    /// Whether to mark generated enums for options as `#[non_exhaustive]`
+    pub options_non_exhaustive: bool,
+
+    /// Map XSD simple type names to Rust types
+    pub xsd_type_map: BTreeMap<String, String>,
+
+    /// Base URL to resolve relative links in docs against
+    pub doc_base_url: Option<url::Url>,
+
+    /// Media types of response bodies to stream
+    pub streaming_media_types: Vec<String>,
+
+    /// How repeating query params are serialized in request URLs
+    pub query_array_style: QueryArrayStyle,
+
+    /// The crate whose types represent XSD date and time types
+    pub date_time_crate: DateTimeCrate,
+
+    /// Wire formats of date and time params, keyed by the name of the param
+    ///
+    /// Values are `rfc3339`, `epoch_seconds`, `epoch_millis` or `{ custom = "format" }`.
+    pub date_time_formats: BTreeMap<String, DateTimeFormat>,
+
+    /// The Rust type that represents `xsd:decimal`
+    pub decimal_type: DecimalType,
+
+    /// Expression for the observer that generated methods report their requests to
+    pub observer: Option<String>,
+
+    /// Rust names of representations and resource types, keyed by their WADL id
+    pub type_names: BTreeMap<String, String>,
+
+    /// Modules to place representations and resource types in, keyed by their id
+    ///
+    /// Keys ending in `*` match all ids that start with the rest of the key, e.g.
+    /// `"bug-*" = "bugs"`. Exact keys take precedence, then the longest matching prefix.
+    pub modules: BTreeMap<String, String>,
+
+    /// Rust names of representation fields, keyed by the name of their param
+    pub param_renames: BTreeMap<String, String>,
+
+    /// Rust names of methods, keyed by the id of the method
+    pub method_renames: BTreeMap<String, String>,
+
+    /// Traits to derive, keyed by the Rust name of the item
+    pub derives: BTreeMap<String, Vec<String>>,
+
+    /// Visibility overrides
+    pub visibility: VisibilityTable,
+
+    /// The serde `rename_all` strategy, keyed by the Rust name of the representation
+    pub serde_rename_all: BTreeMap<String, String>,
+
+    /// Rust names of the representations that reject unknown fields
+    pub deny_unknown_fields: Vec<String>,
+
+    /// Cargo features that gate resource types, keyed by the Rust name of the resource type
+    pub feature_gates: BTreeMap<String, String>,
+
+    /// Ids of the resource types to leave out of the generated code
+    pub exclude_resource_types: Vec<String>,
+
+    /// Ids of the methods to leave out of the generated code
+    pub exclude_methods: Vec<String>,
+}
+
+impl ConfigFile {
+    /// Parse a configuration in TOML format
+    pub fn from_toml(text: &str) -> Result<Self, ConfigFileError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Parse a configuration in JSON format
+    pub fn from_json(text: &str) -> Result<Self, ConfigFileError> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Load a configuration file
+    ///
+    /// Files with a `.json` extension are parsed as JSON, all others as TOML.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigFileError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|e| e == "json") {
+            Self::from_json(&text)
+        } else {
+            Self::from_toml(&text)
+        }
+    }
+}
+
+/// Return a callback that looks up the value for an item by its name
+#[allow(clippy::type_complexity)]
+fn table_callback(table: BTreeMap<String, String>) -> Option<Box<dyn Fn(&str) -> Option<String>>> {
+    if table.is_empty() {
+        None
+    } else {
+        Some(Box::new(move |name| table.get(name).cloned()))
+    }
+}
+
+/// Return the module for an id from a table of ids and id prefixes
+fn module_for_id(modules: &BTreeMap<String, String>, id: &str) -> Option<String> {
+    if let Some(module) = modules.get(id) {
+        return Some(module.clone());
+    }
+    modules
+        .iter()
+        .filter_map(|(key, module)| Some((key.strip_suffix('*')?, module)))
+        .filter(|(prefix, _)| id.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, module)| module.clone())
+}
+
+impl From<ConfigFile> for Config {
+    fn from(file: ConfigFile) -> Self {
+        let mut config = Config {
+            r#async: file.r#async,
+            blocking_and_async: file.blocking_and_async,
+            tower: file.tower,
+            ureq: file.ureq,
+            native_async_client: file.native_async_client,
+            msrv: file.msrv,
+            strip_code_examples: file.strip_code_examples,
+            markdown_tables: file.markdown_tables,
+            skip_serializing_none: file.skip_serializing_none,
+            nullable_field_style: file.nullable_field_style,
+            legacy_snake_case: file.legacy_snake_case,
+            capture_unknown_fields: file.capture_unknown_fields,
+            box_fields_larger_than: file.box_fields_larger_than,
+            omit_clone_for_boxed: file.omit_clone_for_boxed,
+            generate_resource_traits: file.generate_resource_traits,
+            generate_any_resource: file.generate_any_resource,
+            typed_links: file.typed_links,
+            options_non_exhaustive: file.options_non_exhaustive,
+            xsd_type_map: file.xsd_type_map.into_iter().collect(),
+            doc_base_url: file.doc_base_url,
+            streaming_media_types: file.streaming_media_types,
+            query_array_style: file.query_array_style,
+            date_time_crate: file.date_time_crate,
+            decimal_type: file.decimal_type,
+            observer: file.observer,
+            type_renames: file.type_names.into_iter().collect(),
+            param_renames: file.param_renames.into_iter().collect(),
+            method_renames: file.method_renames.into_iter().collect(),
+            serde_rename_all: table_callback(file.serde_rename_all),
+            ..Default::default()
+        };
+        if !file.modules.is_empty() {
+            let modules = file.modules;
+            config.module_path = Some(Box::new(move |_kind, id| module_for_id(&modules, id)));
+        }
+        if !file.date_time_formats.is_empty() {
+            let formats = file.date_time_formats;
+            config.date_time_format = Some(Box::new(move |_container, param| {
+                formats.get(&param.name).cloned()
+            }));
+        }
+        if !file.derives.is_empty() {
+            let derives = file.derives;
+            config.derive = Some(Box::new(move |_kind: ItemKind, name: &str| {
+                derives.get(name).cloned()
+            }));
+        }
+        if !file.visibility.is_empty() {
+            let visibility = file.visibility;
+            config.visibility = Some(Box::new(move |kind, name| {
+                visibility.get(kind, name).cloned()
+            }));
+        }
+        if !file.deny_unknown_fields.is_empty() {
+            let names = file.deny_unknown_fields;
+            config.deny_unknown_fields =
+                Some(Box::new(move |name| names.iter().any(|n| n == name)));
+        }
+        if !file.feature_gates.is_empty() {
+            let features = file.feature_gates;
+            config.feature_gate = Some(Box::new(move |name, method| match method {
+                None => features.get(name).cloned(),
+                Some(_) => None,
+            }));
+        }
+        if !file.exclude_resource_types.is_empty() || !file.exclude_methods.is_empty() {
+            let resource_types = file.exclude_resource_types;
+            let methods = file.exclude_methods;
+            config.item_filter = Some(Box::new(move |kind, id| {
+                let excluded = match kind {
+                    ItemKind::ResourceType => &resource_types,
+                    ItemKind::Method => &methods,
+                    _ => return true,
+                };
+                !excluded.iter().any(|x| x == id)
+            }));
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let file = ConfigFile::from_toml(
+            r#"
+async = true
+query_array_style = "comma"
+date_time_crate = "jiff"
+decimal_type = "rust_decimal"
+box_fields_larger_than = 64
+msrv = "1.70"
+
+[type_names]
+person-full = "Person"
+
+[derives]
+Person = ["Debug"]
+
+[visibility.methods]
+get = "pub(crate)"
+
+[date_time_formats]
+created = "epoch_seconds"
+updated = { custom = "%Y-%m-%d %H:%M" }
+"#,
+        )
+        .unwrap();
+        assert!(file.r#async);
+        assert_eq!(file.query_array_style, QueryArrayStyle::Comma);
+        assert_eq!(file.date_time_crate, DateTimeCrate::Jiff);
+        assert_eq!(file.decimal_type, DecimalType::RustDecimal);
+        assert_eq!(file.box_fields_larger_than, Some(64));
+        assert!(!file.omit_clone_for_boxed);
+        assert_eq!(file.msrv, Some(RustVersion::new(1, 70)));
+
+        assert_eq!(
+            file.date_time_formats.get("updated"),
+            Some(&DateTimeFormat::Custom("%Y-%m-%d %H:%M".to_string()))
+        );
+
+        let config = Config::from(file);
+        assert!(config.r#async);
+        assert!(config.date_time_format.is_some());
+        assert_eq!(
+            config.type_renames.get("person-full"),
+            Some(&"Person".to_string())
+        );
+        assert_eq!(
+            (config.derive.as_ref().unwrap())(ItemKind::Representation, "Person"),
+            Some(vec!["Debug".to_string()])
+        );
+        assert_eq!(
+            config.item_visibility(ItemKind::Method, "get"),
+            Some(Visibility::Crate)
+        );
+        assert_eq!(
+            config.item_visibility(ItemKind::Representation, "get"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_json() {
+        let file = ConfigFile::from_json(
+            r#"{"ureq": true, "deny_unknown_fields": ["Person"],
+                "nullable_field_style": "double_option"}"#,
+        )
+        .unwrap();
+        assert!(file.ureq);
+        assert_eq!(file.nullable_field_style, NullableFieldStyle::DoubleOption);
+        let config = Config::from(file);
+        assert!((config.deny_unknown_fields.as_ref().unwrap())("Person"));
+        assert!(!(config.deny_unknown_fields.as_ref().unwrap())("Team"));
+    }
+
+    #[test]
+    fn test_exclude() {
+        let file = ConfigFile::from_toml(
+            r#"
+exclude_resource_types = ["admin"]
+exclude_methods = ["items-purge"]
+"#,
+        )
+        .unwrap();
+        let config = Config::from(file);
+        assert!(!config.include_item(ItemKind::ResourceType, "admin"));
+        assert!(config.include_item(ItemKind::ResourceType, "items"));
+        assert!(!config.include_item(ItemKind::Method, "items-purge"));
+        assert!(config.include_item(ItemKind::Method, "admin"));
+        assert!(Config::default().include_item(ItemKind::Method, "items-purge"));
+    }
+
+    #[test]
+    fn test_modules() {
+        let file = ConfigFile::from_toml(
+            r#"
+[modules]
+"bug-*" = "bugs"
+"bug-task-*" = "bugs::tasks"
+"bug-tracker" = "trackers"
+"#,
+        )
+        .unwrap();
+        let config = Config::from(file);
+        let module = |id| (config.module_path.as_ref().unwrap())(ItemKind::ResourceType, id);
+        assert_eq!(module("bug-full"), Some("bugs".to_string()));
+        assert_eq!(module("bug-task-list"), Some("bugs::tasks".to_string()));
+        assert_eq!(module("bug-tracker"), Some("trackers".to_string()));
+        assert_eq!(module("person"), None);
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        assert!(matches!(
+            ConfigFile::from_toml("asynchronous = true"),
+            Err(ConfigFileError::Toml(_))
+        ));
+    }
+}
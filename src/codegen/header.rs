@@ -0,0 +1,189 @@
+//! Provenance header for generated files
+//!
+//! A [`FileHeader`] records which version of this crate generated a file and from which WADL,
+//! including a hash of the WADL, so that checked-in code can be verified against its source:
+//!
+//! ```
+//! use wadl::codegen::FileHeader;
+//!
+//! let wadl = b"<application xmlns=\"http://research.sun.com/wadl/2006/10\"/>";
+//! let header = FileHeader::for_source("api.wadl", wadl).allow("clippy::all");
+//! let code = format!("{}pub struct Api;\n", header.render());
+//!
+//! let parsed = FileHeader::parse(&code).unwrap();
+//! assert_eq!(parsed.source.as_deref(), Some("api.wadl"));
+//! assert!(parsed.matches_source(wadl));
+//! ```
+
+use sha2::{Digest, Sha256};
+
+const GENERATED_PREFIX: &str = "// @generated by wadl ";
+const SOURCE_PREFIX: &str = "// Source: ";
+const HASH_PREFIX: &str = "// Source SHA-256: ";
+
+/// The header emitted at the top of generated code
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileHeader {
+    /// The version of this crate that generated the code
+    ///
+    /// Defaults to the current version; set when parsing the header of existing code.
+    pub version: Option<String>,
+
+    /// The path or URL of the WADL that the code was generated from
+    pub source: Option<String>,
+
+    /// The SHA-256 hash of the WADL, as lowercase hex
+    pub source_hash: Option<String>,
+
+    /// Lints to allow in the whole file, e.g. `clippy::all`
+    ///
+    /// These are emitted as inner attributes, which `include!()` rejects, so only set them for
+    /// code that is used as a module file (e.g. `mod api;`).
+    pub allow: Vec<String>,
+}
+
+/// Return the SHA-256 hash of the contents of a WADL, as lowercase hex
+pub fn source_hash(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl FileHeader {
+    /// Create a header that only records the version of this crate
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a header for code generated from a WADL
+    ///
+    /// # Arguments
+    /// * `source` - The path or URL of the WADL
+    /// * `contents` - The contents of the WADL, which are hashed
+    pub fn for_source(source: &str, contents: &[u8]) -> Self {
+        Self::new().source(source, contents)
+    }
+
+    /// Set the source of the code, see [`FileHeader::for_source`]
+    pub fn source(mut self, source: &str, contents: &[u8]) -> Self {
+        self.source = Some(source.to_string());
+        self.source_hash = Some(source_hash(contents));
+        self
+    }
+
+    /// Allow a lint in the whole file, see [`FileHeader::allow`](#structfield.allow)
+    pub fn allow(mut self, lint: &str) -> Self {
+        self.allow.push(lint.to_string());
+        self
+    }
+
+    /// Return the comment lines of the header
+    ///
+    /// These are dropped by [`format_code`](super::format_code), so they are kept separate
+    /// from the attributes.
+    pub(super) fn comment_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{}{}. Do not edit.\n",
+            GENERATED_PREFIX,
+            self.version.as_deref().unwrap_or(env!("CARGO_PKG_VERSION"))
+        )];
+        if let Some(source) = self.source.as_ref() {
+            lines.push(format!("{}{}\n", SOURCE_PREFIX, source));
+        }
+        if let Some(hash) = self.source_hash.as_ref() {
+            lines.push(format!("{}{}\n", HASH_PREFIX, hash));
+        }
+        lines
+    }
+
+    /// Return the attribute lines of the header
+    pub(super) fn attribute_lines(&self) -> Vec<String> {
+        if self.allow.is_empty() {
+            vec![]
+        } else {
+            vec![format!("#![allow({})]\n", self.allow.join(", "))]
+        }
+    }
+
+    /// Render the header
+    pub fn render(&self) -> String {
+        let mut lines = self.comment_lines();
+        lines.extend(self.attribute_lines());
+        lines.push("\n".to_string());
+        lines.concat()
+    }
+
+    /// Parse the header at the top of generated code
+    ///
+    /// Returns `None` if the code does not start with a header.
+    pub fn parse(code: &str) -> Option<Self> {
+        let mut lines = code.lines();
+        let version = lines.next()?.strip_prefix(GENERATED_PREFIX)?;
+        let mut header = Self {
+            version: Some(version.trim_end_matches(". Do not edit.").to_string()),
+            ..Default::default()
+        };
+        for line in lines {
+            if let Some(source) = line.strip_prefix(SOURCE_PREFIX) {
+                header.source = Some(source.to_string());
+            } else if let Some(hash) = line.strip_prefix(HASH_PREFIX) {
+                header.source_hash = Some(hash.to_string());
+            } else if let Some(lints) = line
+                .strip_prefix("#![allow(")
+                .and_then(|l| l.strip_suffix(")]"))
+            {
+                header
+                    .allow
+                    .extend(lints.split(',').map(|l| l.trim().to_string()));
+            } else {
+                break;
+            }
+        }
+        Some(header)
+    }
+
+    /// Return whether the header was generated from a WADL with the given contents
+    pub fn matches_source(&self, contents: &[u8]) -> bool {
+        self.source_hash.as_deref() == Some(source_hash(contents).as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let header = FileHeader {
+            version: Some("1.2.3".to_string()),
+            ..FileHeader::for_source("api.wadl", b"")
+        }
+        .allow("clippy::all")
+        .allow("dead_code");
+        assert_eq!(
+            header.render(),
+            "// @generated by wadl 1.2.3. Do not edit.\n\
+             // Source: api.wadl\n\
+             // Source SHA-256: e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855\n\
+             #![allow(clippy::all, dead_code)]\n\
+             \n"
+        );
+        assert_eq!(FileHeader::parse(&header.render()), Some(header));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(FileHeader::parse("pub struct Api;\n"), None);
+
+        let code = format!("{}pub struct Api;\n", FileHeader::new().render());
+        let header = FileHeader::parse(&code).unwrap();
+        assert_eq!(header.version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+        assert_eq!(header.source, None);
+        assert!(!header.matches_source(b""));
+
+        let header = FileHeader::for_source("api.wadl", b"<application/>");
+        assert!(header.matches_source(b"<application/>"));
+        assert!(!header.matches_source(b"<application />"));
+    }
+}
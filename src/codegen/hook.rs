@@ -0,0 +1,101 @@
+//! Reusable code generation policies
+//!
+//! The callbacks on [`Config`] each customize a single aspect of code generation. A
+//! [`CodegenHook`] bundles related customizations, so that a policy (e.g. the conventions of a
+//! particular web service) can be packaged as a type and registered with
+//! [`ConfigBuilder::hook`](super::ConfigBuilder::hook).
+
+use super::{Config, ParamContainer};
+use crate::ast::{Application, Method, RepresentationDef};
+
+/// Hook into the generation of code
+///
+/// All methods have default implementations that do nothing, so implementations only need to
+/// override the ones they are interested in. Hooks are called in the order in which they were
+/// registered, after the equivalent callbacks on [`Config`].
+///
+/// # Example
+///
+/// ```
+/// use wadl::codegen::{CodegenHook, Config, ParamContainer};
+///
+/// /// Use the `chrono` types Launchpad clients expect, and mark representations as
+/// /// generated.
+/// struct LaunchpadConventions;
+///
+/// impl CodegenHook for LaunchpadConventions {
+///     fn before_representation(
+///         &self,
+///         _input: &wadl::ast::RepresentationDef,
+///         _name: &str,
+///         _config: &Config,
+///     ) -> Vec<String> {
+///         vec!["#[doc(alias = \"generated\")]\n".to_string()]
+///     }
+///
+///     fn override_type(
+///         &self,
+///         _container: &ParamContainer,
+///         type_name: &str,
+///         _param_name: &str,
+///         _config: &Config,
+///     ) -> Option<String> {
+///         (type_name == "xsd:dateTime")
+///             .then(|| "chrono::DateTime<chrono::Utc>".to_string())
+///     }
+/// }
+///
+/// let config = Config::builder().hook(LaunchpadConventions).build();
+/// ```
+pub trait CodegenHook {
+    /// Return lines to emit before the struct of a representation
+    ///
+    /// # Arguments
+    /// * `input` - The representation
+    /// * `name` - The Rust name of the representation
+    fn before_representation(
+        &self,
+        _input: &RepresentationDef,
+        _name: &str,
+        _config: &Config,
+    ) -> Vec<String> {
+        vec![]
+    }
+
+    /// Return lines to emit after a method, in the `impl` block of its resource type
+    ///
+    /// # Arguments
+    /// * `input` - The method
+    /// * `parent_id` - The id of the resource type of the method
+    /// * `name` - The Rust name of the method
+    /// * `ret_type` - The return type of the method
+    fn after_method(
+        &self,
+        _input: &Method,
+        _parent_id: &str,
+        _name: &str,
+        _ret_type: &str,
+        _config: &Config,
+    ) -> Vec<String> {
+        vec![]
+    }
+
+    /// Return lines to append to the generated code
+    fn extend_module(&self, _app: &Application, _config: &Config) -> Vec<String> {
+        vec![]
+    }
+
+    /// Override the Rust type of a param, given its WADL type and name
+    ///
+    /// For params that link to a resource type, `type_name` is the Rust name of the resource
+    /// type.
+    fn override_type(
+        &self,
+        _container: &ParamContainer,
+        _type_name: &str,
+        _param_name: &str,
+        _config: &Config,
+    ) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,829 @@
+//! Generate server stubs for the axum web framework
+//!
+//! The generated code refers to the representation and options types generated by
+//! [`super::generate`], so both should end up in the same module. Servers that don't need the
+//! client code can use [`generate_server_types`] for those types instead, or
+//! [`generate_server_files`] for both.
+
+use super::{
+    camel_case_name, escape_rust_reserved, filter_application, generate_all_options,
+    generate_date_time_format_modules, generate_doc, generate_representation, method_rust_name,
+    param_rust_type, representation_rust_type, resolve_generated_doc_links,
+    resource_type_rust_type, snake_case_name, type_name, with_application, Config, Constants,
+    ItemKind, ParamContainer, Syntax,
+};
+use crate::ast::*;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// A resource with a known path, and the methods it supports
+struct ServerResource<'a> {
+    /// The route of the resource, in axum syntax
+    path: String,
+
+    /// The Rust name of the resource, used as prefix for the generated items
+    name: String,
+
+    /// The docs for the resource
+    docs: &'a [Doc],
+
+    /// The template parameters of the resource, including those of its parents
+    template_params: Vec<(&'a Resource, &'a Param)>,
+
+    /// The methods of the resource, with the id of the resource (type) they were defined in
+    methods: Vec<(&'a Method, String)>,
+}
+
+/// The body of a request, as understood by the server stubs
+enum RequestBody<'a> {
+    /// A JSON representation, deserialized into the given type
+    Json(String),
+
+    /// A form-urlencoded representation with the given params
+    Form(Vec<&'a Param>),
+}
+
+/// A method of a resource, as exposed by the handler trait
+struct ServerMethod<'a> {
+    method: &'a Method,
+
+    /// The Rust name of the method
+    name: String,
+
+    /// Query parameters that are passed to the handler
+    query_params: Vec<&'a Param>,
+
+    /// Query parameters with a fixed value, used to dispatch between methods
+    fixed_params: Vec<&'a Param>,
+
+    body: Option<RequestBody<'a>>,
+
+    /// The Rust type of the response representation, if any
+    ret: Option<String>,
+}
+
+fn join_path(prefix: &str, path: Option<&str>) -> String {
+    let path = path.unwrap_or("").trim_matches('/');
+    let prefix = prefix.trim_end_matches('/');
+    if path.is_empty() {
+        if prefix.is_empty() {
+            "/".to_string()
+        } else {
+            prefix.to_string()
+        }
+    } else {
+        format!("{}/{}", prefix, path)
+    }
+}
+
+fn resource_rust_name(app: &Application, resource: &Resource, path: &str) -> String {
+    if let Some(id) = resource.id.as_ref() {
+        return camel_case_name(id);
+    }
+    if let Some(resource_type) = resource
+        .r#type
+        .iter()
+        .find_map(|t| t.id().and_then(|id| app.get_resource_type_by_id(id)))
+    {
+        return type_name(ItemKind::ResourceType, resource_type.id.as_str());
+    }
+    let name = path
+        .split('/')
+        .filter(|s| !s.is_empty() && !s.starts_with('{'))
+        .map(|s| {
+            camel_case_name(
+                s.chars()
+                    .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                    .collect::<String>()
+                    .as_str(),
+            )
+        })
+        .collect::<String>();
+    if name.is_empty() {
+        "Root".to_string()
+    } else {
+        name
+    }
+}
+
+fn collect_resources<'a>(
+    app: &'a Application,
+    prefix: &str,
+    parent_params: &[(&'a Resource, &'a Param)],
+    resources: &'a [Resource],
+    ret: &mut Vec<ServerResource<'a>>,
+) {
+    for resource in resources {
+        let path = join_path(prefix, resource.path.as_deref());
+
+        let mut template_params = parent_params.to_vec();
+        template_params.extend(
+            resource
+                .params
+                .iter()
+                .filter(|p| p.style == ParamStyle::Template)
+                .map(|p| (resource, p)),
+        );
+        // Path extractors are positional, so order the parameters as they appear in the path
+        template_params.sort_by_key(|(_, p)| path.find(&format!("{{{}}}", p.name)));
+
+        let mut methods = resource
+            .methods
+            .iter()
+            .map(|m| (m, resource.id.clone().unwrap_or_default()))
+            .collect::<Vec<_>>();
+        let mut docs = resource.docs.as_slice();
+        for resource_type in resource
+            .r#type
+            .iter()
+            .filter_map(|t| t.id().and_then(|id| app.get_resource_type_by_id(id)))
+        {
+            methods.extend(
+                resource_type
+                    .methods
+                    .iter()
+                    .map(|m| (m, resource_type.id.clone())),
+            );
+            if docs.is_empty() {
+                docs = resource_type.docs.as_slice();
+            }
+        }
+
+        if !methods.is_empty() {
+            let mut name = resource_rust_name(app, resource, &path);
+            while ret.iter().any(|r| r.name == name) {
+                name = format!("{}_", name);
+            }
+            ret.push(ServerResource {
+                name,
+                path: path.clone(),
+                docs,
+                template_params: template_params.clone(),
+                methods,
+            });
+        }
+
+        collect_resources(app, &path, &template_params, &resource.subresources, ret);
+    }
+}
+
+fn resolve_representation<'a>(
+    app: &'a Application,
+    representation: &'a Representation,
+) -> Option<&'a RepresentationDef> {
+    match representation {
+        Representation::Definition(d) => Some(d),
+        Representation::Reference(r) => {
+            let id = r.id()?;
+            app.representations
+                .iter()
+                .find(|d| d.id.as_deref() == Some(id))
+        }
+    }
+}
+
+fn has_media_type(def: Option<&RepresentationDef>, media_type: &str) -> bool {
+    def.and_then(|d| d.media_type.as_ref())
+        .is_some_and(|m| m.essence_str() == media_type)
+}
+
+fn server_method<'a>(
+    app: &'a Application,
+    method: &'a Method,
+    parent_id: &str,
+    config: &Config,
+) -> ServerMethod<'a> {
+    let name = method_rust_name(method, parent_id, config);
+
+    let query_params = method
+        .request
+        .params
+        .iter()
+        .filter(|p| p.style == ParamStyle::Query)
+        .collect::<Vec<_>>();
+
+    let mut body = None;
+    for representation in &method.request.representations {
+        let def = resolve_representation(app, representation);
+        match representation {
+            Representation::Reference(r)
+                if def.is_none() || has_media_type(def, "application/json") =>
+            {
+                body = Some(RequestBody::Json(representation_rust_type(r)));
+                break;
+            }
+            Representation::Definition(d)
+                if has_media_type(def, "application/x-www-form-urlencoded") =>
+            {
+                body = Some(RequestBody::Form(
+                    d.params.iter().filter(|p| p.fixed.is_none()).collect(),
+                ));
+                break;
+            }
+            _ => {}
+        }
+    }
+    if body.is_none() && !method.request.representations.is_empty() {
+        log::warn!(
+            "No supported request representation for {}, ignoring request body",
+            method.id
+        );
+    }
+
+    let ret = method
+        .responses
+        .iter()
+        .flat_map(|r| r.representations.iter())
+        .find_map(|representation| match representation {
+            Representation::Reference(r)
+                if has_media_type(
+                    resolve_representation(app, representation),
+                    "application/json",
+                ) =>
+            {
+                Some(representation_rust_type(r))
+            }
+            _ => None,
+        });
+
+    ServerMethod {
+        method,
+        name,
+        query_params: query_params
+            .iter()
+            .copied()
+            .filter(|p| p.fixed.is_none())
+            .collect(),
+        fixed_params: query_params
+            .iter()
+            .copied()
+            .filter(|p| p.fixed.is_some())
+            .collect(),
+        body,
+        ret,
+    }
+}
+
+/// Return a valid Rust identifier for a parameter name
+fn param_field_name(name: &str) -> String {
+    let name = snake_case_name(name)
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    escape_rust_reserved(name.as_str()).to_string()
+}
+
+fn generate_params_struct(
+    name: &str,
+    method: &Method,
+    params: &[&Param],
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+    let container = ParamContainer::Request(method, &method.request);
+
+    lines.push("#[derive(Debug, Clone, serde::Deserialize)]\n".to_string());
+    lines.push(format!(
+        "{}struct {} {{\n",
+        config
+            .item_visibility(ItemKind::Representation, name)
+            .unwrap_or_default()
+            .prefix(),
+        name
+    ));
+    for param in params {
+        if let Some(doc) = param.doc.as_ref() {
+            lines.extend(generate_doc(doc, 1, config));
+        }
+        let (param_type, _annotations) = param_rust_type(
+            &container,
+            param,
+            config,
+            resource_type_rust_type,
+            options_names,
+        );
+        let field_name = param_field_name(param.name.as_str());
+        if field_name != param.name {
+            lines.push(format!("    #[serde(rename = \"{}\")]\n", param.name));
+        }
+        lines.push(format!("    pub {}: {},\n", field_name, param_type));
+    }
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+fn query_struct_name(resource: &ServerResource, method: &ServerMethod) -> String {
+    format!("{}{}Query", resource.name, camel_case_name(&method.name))
+}
+
+fn form_struct_name(resource: &ServerResource, method: &ServerMethod) -> String {
+    format!("{}{}Form", resource.name, camel_case_name(&method.name))
+}
+
+fn handler_fn_name(resource: &ServerResource, method: &ServerMethod) -> String {
+    format!("{}_{}", snake_case_name(&resource.name), method.name)
+}
+
+/// Return the template parameters of a resource, with their Rust names and types
+fn template_params(
+    resource: &ServerResource,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<(String, String)> {
+    resource
+        .template_params
+        .iter()
+        .map(|(r, p)| {
+            // Template parameters are always present in the path
+            let param = Param {
+                required: true,
+                ..(*p).clone()
+            };
+            let (param_type, _annotations) = param_rust_type(
+                &ParamContainer::Resource(r),
+                &param,
+                config,
+                resource_type_rust_type,
+                options_names,
+            );
+            (param_field_name(p.name.as_str()), param_type)
+        })
+        .collect()
+}
+
+/// Return the arguments of the handler trait method, excluding `self`
+fn handler_args(
+    resource: &ServerResource,
+    method: &ServerMethod,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<(String, String)> {
+    let mut args = template_params(resource, config, options_names);
+    if !method.query_params.is_empty() {
+        args.push(("query".to_string(), query_struct_name(resource, method)));
+    }
+    match method.body {
+        Some(RequestBody::Json(ref t)) => args.push(("representation".to_string(), t.clone())),
+        Some(RequestBody::Form(_)) => {
+            args.push(("form".to_string(), form_struct_name(resource, method)))
+        }
+        None => {}
+    }
+    args
+}
+
+fn generate_handler_trait(
+    resource: &ServerResource,
+    methods: &[ServerMethod],
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+
+    if resource.docs.is_empty() {
+        lines.push(format!("/// Handler for `{}`\n", resource.path));
+    } else {
+        for doc in resource.docs {
+            lines.extend(generate_doc(doc, 0, config));
+        }
+    }
+    let trait_name = format!("{}Handler", resource.name);
+    lines.push(format!(
+        "{}trait {}: Send + Sync + 'static {{\n",
+        config
+            .item_visibility(ItemKind::Trait, &trait_name)
+            .unwrap_or_default()
+            .prefix(),
+        trait_name
+    ));
+    for method in methods {
+        for doc in &method.method.docs {
+            lines.extend(generate_doc(doc, 1, config));
+        }
+        let mut line = format!("    fn {}(&self", method.name);
+        for (arg_name, arg_type) in handler_args(resource, method, config, options_names) {
+            line.push_str(format!(", {}: {}", arg_name, arg_type).as_str());
+        }
+        line.push_str(
+            format!(
+                ") -> impl std::future::Future<Output = std::result::Result<{}, axum::response::Response>> + Send;\n",
+                method.ret.as_deref().unwrap_or("()")
+            )
+            .as_str(),
+        );
+        lines.push(line);
+        lines.push("\n".to_string());
+    }
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+fn generate_handler_fn(
+    resource: &ServerResource,
+    method: &ServerMethod,
+    config: &Config,
+    options_names: &HashMap<Options, String>,
+) -> Vec<String> {
+    let mut lines = vec![];
+
+    lines.push(format!(
+        "async fn {}<H: {}Handler>(\n",
+        handler_fn_name(resource, method),
+        resource.name
+    ));
+    lines.push(
+        "    axum::extract::State(handler): axum::extract::State<std::sync::Arc<H>>,\n".to_string(),
+    );
+
+    let path_params = template_params(resource, config, options_names);
+    match path_params.as_slice() {
+        [] => {}
+        [(name, t)] => lines.push(format!(
+            "    axum::extract::Path({}): axum::extract::Path<{}>,\n",
+            name, t
+        )),
+        params => lines.push(format!(
+            "    axum::extract::Path(({})): axum::extract::Path<({})>,\n",
+            params
+                .iter()
+                .map(|(n, _)| n.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            params
+                .iter()
+                .map(|(_, t)| t.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+
+    if !method.query_params.is_empty() {
+        lines.push(format!(
+            "    axum::extract::Query(query): axum::extract::Query<{}>,\n",
+            query_struct_name(resource, method)
+        ));
+    }
+
+    // The body extractor has to come last
+    match method.body {
+        Some(RequestBody::Json(ref t)) => lines.push(format!(
+            "    axum::Json(representation): axum::Json<{}>,\n",
+            t
+        )),
+        Some(RequestBody::Form(_)) => lines.push(format!(
+            "    axum::Form(form): axum::Form<{}>,\n",
+            form_struct_name(resource, method)
+        )),
+        None => {}
+    }
+
+    lines.push(") -> axum::response::Response {\n".to_string());
+    let args = handler_args(resource, method, config, options_names)
+        .into_iter()
+        .map(|(n, _)| n)
+        .collect::<Vec<_>>();
+    lines.push(format!(
+        "    match handler.{}({}).await {{\n",
+        method.name,
+        args.join(", ")
+    ));
+    if method.ret.is_some() {
+        lines.push(
+            "        Ok(ret) => axum::response::IntoResponse::into_response(axum::Json(ret)),\n"
+                .to_string(),
+        );
+    } else {
+        lines.push(
+            "        Ok(ret) => axum::response::IntoResponse::into_response(ret),\n".to_string(),
+        );
+    }
+    lines.push("        Err(response) => response,\n".to_string());
+    lines.push("    }\n".to_string());
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+/// Generate a function that dispatches between methods with the same HTTP method, based on
+/// their fixed query parameters
+fn generate_dispatch_fn(
+    resource: &ServerResource,
+    http_method: &str,
+    methods: &[&ServerMethod],
+) -> Vec<String> {
+    let mut lines = vec![];
+
+    lines.push(format!(
+        "async fn {}_{}_dispatch<H: {}Handler>(\n",
+        snake_case_name(&resource.name),
+        http_method.to_lowercase(),
+        resource.name
+    ));
+    lines.push(
+        "    axum::extract::State(handler): axum::extract::State<std::sync::Arc<H>>,\n".to_string(),
+    );
+    lines.push("    request: axum::extract::Request,\n".to_string());
+    lines.push(") -> axum::response::Response {\n".to_string());
+    lines.push("    let query: std::collections::HashMap<String, String> =\n".to_string());
+    lines.push(
+        "        axum::extract::Query::try_from_uri(request.uri()).map(|q| q.0).unwrap_or_default();\n"
+            .to_string(),
+    );
+
+    let mut fallback = None;
+    for method in methods {
+        if method.fixed_params.is_empty() {
+            if fallback.is_some() {
+                log::warn!(
+                    "Multiple {} methods without fixed parameters for {}, ignoring {}",
+                    http_method,
+                    resource.path,
+                    method.method.id
+                );
+            } else {
+                fallback = Some(method);
+            }
+            continue;
+        }
+        let conditions = method
+            .fixed_params
+            .iter()
+            .map(|p| {
+                format!(
+                    "query.get(\"{}\").map(String::as_str) == Some(\"{}\")",
+                    p.name,
+                    p.fixed.as_ref().unwrap()
+                )
+            })
+            .collect::<Vec<_>>();
+        lines.push(format!("    if {} {{\n", conditions.join(" && ")));
+        lines.push(format!(
+            "        return axum::handler::Handler::call({}::<H>, request, handler).await;\n",
+            handler_fn_name(resource, method)
+        ));
+        lines.push("    }\n".to_string());
+    }
+
+    if let Some(method) = fallback {
+        lines.push(format!(
+            "    axum::handler::Handler::call({}::<H>, request, handler).await\n",
+            handler_fn_name(resource, method)
+        ));
+    } else {
+        lines.push(
+            "    axum::response::IntoResponse::into_response(axum::http::StatusCode::BAD_REQUEST)\n"
+                .to_string(),
+        );
+    }
+    lines.push("}\n".to_string());
+    lines.push("\n".to_string());
+    lines
+}
+
+/// Return the axum routing function for a HTTP method, if it is supported
+fn axum_method_router(http_method: &str) -> Option<&'static str> {
+    Some(match http_method {
+        "GET" => "get",
+        "POST" => "post",
+        "PUT" => "put",
+        "PATCH" => "patch",
+        "DELETE" => "delete",
+        "HEAD" => "head",
+        "OPTIONS" => "options",
+        "TRACE" => "trace",
+        _ => return None,
+    })
+}
+
+/// Generate server stubs for the axum web framework from a WADL application definition.
+///
+/// For every resource with a known path this generates a handler trait (named after the
+/// resource with a `Handler` suffix) with a method per WADL method, and a `router` function
+/// that creates an [`axum::Router`](https://docs.rs/axum) dispatching to an implementation
+/// of all handler traits.
+///
+/// Template parameters are passed as typed path parameters, query parameters as a struct
+/// deserialized from the query string, and JSON or form-urlencoded request representations
+/// as typed request bodies. The generated code refers to the representation and options types
+/// generated by [`generate`](super::generate), so both should be included in the same module.
+///
+/// # Arguments
+/// * `app` - The WADL application definition.
+/// * `config` - Configuration for the code generation.
+pub fn generate_server(app: &Application, config: &Config) -> String {
+    if !config.supports(Syntax::ImplTraitInTrait) {
+        log::warn!(
+            "Handler traits return impl Future, which requires Rust {}",
+            Syntax::ImplTraitInTrait.stabilized_in()
+        );
+    }
+    let app = filter_application(app, config);
+    with_application(&app, config, || generate_server_in_context(&app, config))
+}
+
+fn generate_server_in_context(app: &Application, config: &Config) -> String {
+    let mut lines = vec![];
+
+    let (options_names, _options_lines) = generate_all_options(app, config);
+
+    let mut resources = vec![];
+    for resources_element in &app.resources {
+        collect_resources(app, "", &[], &resources_element.resources, &mut resources);
+    }
+
+    let mut routes = vec![];
+    let mut handler_traits = vec![];
+
+    for resource in &resources {
+        let methods = resource
+            .methods
+            .iter()
+            .map(|(m, parent_id)| server_method(app, m, parent_id, config))
+            .collect::<Vec<_>>();
+
+        for method in &methods {
+            if !method.query_params.is_empty() {
+                lines.extend(generate_params_struct(
+                    query_struct_name(resource, method).as_str(),
+                    method.method,
+                    &method.query_params,
+                    config,
+                    &options_names,
+                ));
+            }
+            if let Some(RequestBody::Form(ref params)) = method.body {
+                lines.extend(generate_params_struct(
+                    form_struct_name(resource, method).as_str(),
+                    method.method,
+                    params,
+                    config,
+                    &options_names,
+                ));
+            }
+        }
+
+        lines.extend(generate_handler_trait(
+            resource,
+            &methods,
+            config,
+            &options_names,
+        ));
+        handler_traits.push(format!("{}Handler", resource.name));
+
+        for method in &methods {
+            lines.extend(generate_handler_fn(
+                resource,
+                method,
+                config,
+                &options_names,
+            ));
+        }
+
+        let mut by_http_method: Vec<(&str, Vec<&ServerMethod>)> = vec![];
+        for method in &methods {
+            let http_method = method.method.name.as_str();
+            if let Some((_, ms)) = by_http_method.iter_mut().find(|(n, _)| *n == http_method) {
+                ms.push(method);
+            } else {
+                by_http_method.push((http_method, vec![method]));
+            }
+        }
+
+        let mut method_routers = vec![];
+        for (http_method, ms) in by_http_method {
+            let Some(router_fn) = axum_method_router(http_method) else {
+                log::warn!(
+                    "Unsupported HTTP method {} for {}, skipping",
+                    http_method,
+                    resource.path
+                );
+                continue;
+            };
+            let target = if ms.len() == 1 && ms[0].fixed_params.is_empty() {
+                handler_fn_name(resource, ms[0])
+            } else {
+                lines.extend(generate_dispatch_fn(resource, http_method, &ms));
+                format!(
+                    "{}_{}_dispatch",
+                    snake_case_name(&resource.name),
+                    http_method.to_lowercase()
+                )
+            };
+            method_routers.push((router_fn, target));
+        }
+
+        if routes.iter().any(|(p, _)| p == &resource.path) {
+            log::warn!("Duplicate route {}, skipping", resource.path);
+            continue;
+        }
+        routes.push((resource.path.clone(), method_routers));
+    }
+
+    lines.push("/// Create an axum router that dispatches requests to `handler`\n".to_string());
+    lines.push("///\n".to_string());
+    lines.push(
+        "/// Paths are relative to the base URL of the web service; use `axum::Router::nest` to\n"
+            .to_string(),
+    );
+    lines.push("/// serve them below a prefix.\n".to_string());
+    lines.push("pub fn router<H>(handler: H) -> axum::Router\n".to_string());
+    lines.push("where\n".to_string());
+    if handler_traits.is_empty() {
+        lines.push("    H: Send + Sync + 'static,\n".to_string());
+    } else {
+        lines.push(format!("    H: {},\n", handler_traits.join(" + ")));
+    }
+    lines.push("{\n".to_string());
+    lines.push("    axum::Router::new()\n".to_string());
+    for (path, method_routers) in routes {
+        if method_routers.is_empty() {
+            continue;
+        }
+        let method_router = method_routers
+            .iter()
+            .enumerate()
+            .map(|(i, (router_fn, target))| {
+                if i == 0 {
+                    format!("axum::routing::{}({}::<H>)", router_fn, target)
+                } else {
+                    format!(".{}({}::<H>)", router_fn, target)
+                }
+            })
+            .collect::<String>();
+        lines.push(format!("        .route(\"{}\", {})\n", path, method_router));
+    }
+    lines.push("        .with_state(std::sync::Arc::new(handler))\n".to_string());
+    lines.push("}\n".to_string());
+
+    resolve_generated_doc_links(lines, config.doc_base_url(app)).concat()
+}
+
+/// Generate the representation and options types that the stubs of [`generate_server`] refer
+/// to, without the client code that [`generate`](super::generate) generates along with them.
+///
+/// Links are plain URLs, since there are no resource types to navigate them to.
+///
+/// # Arguments
+/// * `app` - The WADL application definition.
+/// * `config` - Configuration for the code generation.
+pub fn generate_server_types(app: &Application, config: &Config) -> String {
+    let mut app = filter_application(app, config).into_owned();
+    app.for_each_reference(&mut |r| {
+        if let Reference::LinkTarget(rt) = r {
+            *rt = ResourceTypeRef::Empty;
+        }
+    });
+    with_application(&app, config, || {
+        let mut lines = vec![];
+        if let Some(header) = config.header.as_ref() {
+            lines.push(header.render());
+        }
+        let (options, options_lines) = generate_all_options(&app, config);
+        lines.extend(options_lines);
+        for representation in &app.representations {
+            lines.extend(generate_representation(
+                representation,
+                &app.resource_types,
+                &app.representations,
+                config,
+                &options,
+                &Constants::default(),
+            ));
+        }
+        lines.extend(generate_date_time_format_modules(config));
+        resolve_generated_doc_links(lines, config.doc_base_url(&app)).concat()
+    })
+}
+
+/// Generate server stubs for the axum web framework, split over files for inclusion in a
+/// server crate.
+///
+/// The returned map has a `mod.rs` with the router and handler traits of [`generate_server`],
+/// and a `types.rs` with the types of [`generate_server_types`], which `mod.rs` re-exports. If
+/// [`Config::header`] is set, both files start with it.
+///
+/// # Arguments
+/// * `app` - The WADL application definition.
+/// * `config` - Configuration for the code generation.
+///
+/// # Returns
+/// The contents of the files, keyed by their path relative to the output directory
+pub fn generate_server_files(app: &Application, config: &Config) -> BTreeMap<PathBuf, String> {
+    let mut server = config
+        .header
+        .as_ref()
+        .map(|header| header.render())
+        .unwrap_or_default();
+    server.push_str("pub mod types;\npub use types::*;\n\n");
+    server.push_str(&generate_server(app, config));
+    BTreeMap::from([
+        (PathBuf::from("mod.rs"), server),
+        (
+            PathBuf::from("types.rs"),
+            generate_server_types(app, config),
+        ),
+    ])
+}
@@ -0,0 +1,327 @@
+//! Template-based overrides of generated code
+//!
+//! The code for some kinds of items can be replaced by rendering a
+//! [minijinja](https://docs.rs/minijinja) template. Templates are rendered with a context that
+//! describes the item; every context has a `default` field with the code that would have been
+//! generated without the template, so that templates can also wrap or amend it.
+//!
+//! For example, to add an attribute to every representation struct:
+//!
+//! ```
+//! use wadl::codegen::{Config, TemplateKind, Templates};
+//!
+//! let templates = Templates::new()
+//!     .with_template(
+//!         TemplateKind::Representation,
+//!         "#[allow(clippy::large_enum_variant)]\n{{ default }}",
+//!     )
+//!     .unwrap();
+//! let config = Config::builder().templates(templates).build();
+//! ```
+
+use std::path::Path;
+
+/// A kind of item whose code can be generated by a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemplateKind {
+    /// The struct of a representation, rendered with a [`RepresentationContext`]
+    Representation,
+
+    /// A method of a resource type, rendered with a [`MethodContext`]
+    Method,
+
+    /// The enum for a set of options, rendered with an [`OptionsEnumContext`]
+    OptionsEnum,
+}
+
+impl TemplateKind {
+    /// All kinds of items that can be generated by a template
+    pub const ALL: [TemplateKind; 3] = [
+        TemplateKind::Representation,
+        TemplateKind::Method,
+        TemplateKind::OptionsEnum,
+    ];
+
+    /// Return the name of the template, which is also the stem of its file name
+    pub fn name(&self) -> &'static str {
+        match self {
+            TemplateKind::Representation => "representation",
+            TemplateKind::Method => "method",
+            TemplateKind::OptionsEnum => "options_enum",
+        }
+    }
+}
+
+/// An error that occurred while loading templates
+#[derive(Debug)]
+pub enum TemplateError {
+    /// An I/O error occurred
+    Io(std::io::Error),
+
+    /// A template could not be parsed
+    Template(minijinja::Error),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TemplateError::Io(err) => write!(f, "IO error: {}", err),
+            TemplateError::Template(err) => write!(f, "Template error: {:#}", err),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl From<std::io::Error> for TemplateError {
+    fn from(err: std::io::Error) -> Self {
+        TemplateError::Io(err)
+    }
+}
+
+impl From<minijinja::Error> for TemplateError {
+    fn from(err: minijinja::Error) -> Self {
+        TemplateError::Template(err)
+    }
+}
+
+/// A field of a representation
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FieldContext {
+    /// The Rust name of the field
+    pub name: String,
+
+    /// The name of the param in the WADL
+    pub wadl_name: String,
+
+    /// The Rust type of the field
+    pub rust_type: String,
+
+    /// The documentation of the field, in markdown
+    pub doc: Option<String>,
+}
+
+/// The context that representation templates are rendered with
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RepresentationContext {
+    /// The Rust name of the representation
+    pub name: String,
+
+    /// The id of the representation in the WADL
+    pub id: String,
+
+    /// The documentation of the representation, in markdown
+    pub doc: Option<String>,
+
+    /// The traits to derive
+    pub derives: Vec<String>,
+
+    /// The fields of the representation
+    pub fields: Vec<FieldContext>,
+
+    /// The code that is generated without a template
+    pub default: String,
+}
+
+/// The context that method templates are rendered with
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MethodContext {
+    /// The Rust name of the method
+    pub name: String,
+
+    /// The id of the method in the WADL
+    pub id: String,
+
+    /// The HTTP method, e.g. `GET`
+    pub http_method: String,
+
+    /// The id of the resource type of the method
+    pub resource_type: String,
+
+    /// The return type of the method
+    pub ret_type: String,
+
+    /// The code that is generated without a template
+    pub default: String,
+}
+
+/// A variant of an options enum
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VariantContext {
+    /// The Rust name of the variant
+    pub name: String,
+
+    /// The value of the option in the WADL
+    pub value: String,
+}
+
+/// The context that options enum templates are rendered with
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OptionsEnumContext {
+    /// The Rust name of the enum
+    pub name: String,
+
+    /// The traits to derive
+    pub derives: Vec<String>,
+
+    /// The variants of the enum
+    pub variants: Vec<VariantContext>,
+
+    /// The Rust name of the default variant, if any
+    pub default_variant: Option<String>,
+
+    /// The code that is generated without a template
+    pub default: String,
+}
+
+/// Templates that override the generated code for kinds of items
+pub struct Templates {
+    env: minijinja::Environment<'static>,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Templates {
+    /// Create an empty set of templates, which leaves all generated code unchanged
+    pub fn new() -> Self {
+        let mut env = minijinja::Environment::new();
+        env.set_keep_trailing_newline(true);
+        Self { env }
+    }
+
+    /// Set the template for a kind of item
+    ///
+    /// Returns an error if the template can not be parsed.
+    pub fn with_template(
+        mut self,
+        kind: TemplateKind,
+        source: &str,
+    ) -> Result<Self, TemplateError> {
+        self.env
+            .add_template_owned(kind.name(), source.to_string())?;
+        Ok(self)
+    }
+
+    /// Load templates from a directory
+    ///
+    /// The template for each kind of item is read from a file named after
+    /// [`TemplateKind::name`] with a `.rs.jinja` extension, e.g. `representation.rs.jinja`.
+    /// Kinds without a file keep the default code.
+    pub fn from_dir(path: impl AsRef<Path>) -> Result<Self, TemplateError> {
+        let mut templates = Self::new();
+        for kind in TemplateKind::ALL {
+            let file = path.as_ref().join(format!("{}.rs.jinja", kind.name()));
+            if file.exists() {
+                templates = templates.with_template(kind, &std::fs::read_to_string(file)?)?;
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Return whether there is a template for a kind of item
+    pub fn has_template(&self, kind: TemplateKind) -> bool {
+        self.env.get_template(kind.name()).is_ok()
+    }
+
+    /// Render the template for a kind of item, if there is one
+    ///
+    /// # Panics
+    ///
+    /// If the template fails to render.
+    pub fn render(&self, kind: TemplateKind, context: &impl serde::Serialize) -> Option<String> {
+        let template = self.env.get_template(kind.name()).ok()?;
+        Some(
+            template
+                .render(context)
+                .unwrap_or_else(|e| panic!("Unable to render {} template: {:#}", kind.name(), e)),
+        )
+    }
+}
+
+/// Replace the lines generated for an item with the rendered template for its kind
+///
+/// The context is only built if there is a template, and is passed the default code.
+pub(super) fn apply<C: serde::Serialize>(
+    templates: Option<&Templates>,
+    kind: TemplateKind,
+    lines: Vec<String>,
+    context: impl FnOnce(String) -> C,
+) -> Vec<String> {
+    let Some(templates) = templates.filter(|t| t.has_template(kind)) else {
+        return lines;
+    };
+    let mut rendered = templates
+        .render(kind, &context(lines.concat()))
+        .unwrap_or_default();
+    if !rendered.is_empty() && !rendered.ends_with('\n') {
+        rendered.push('\n');
+    }
+    rendered
+        .split_inclusive('\n')
+        .map(|l| l.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let lines = vec!["pub enum Foo {}\n".to_string()];
+        assert_eq!(
+            apply(None, TemplateKind::OptionsEnum, lines.clone(), |_| ()),
+            lines
+        );
+
+        let templates = Templates::new()
+            .with_template(
+                TemplateKind::OptionsEnum,
+                "// {{ name }}\n{{ default }}// end",
+            )
+            .unwrap();
+        let rendered = apply(
+            Some(&templates),
+            TemplateKind::OptionsEnum,
+            lines.clone(),
+            |default| OptionsEnumContext {
+                name: "Foo".to_string(),
+                derives: vec![],
+                variants: vec![],
+                default_variant: None,
+                default,
+            },
+        );
+        assert_eq!(
+            rendered,
+            vec![
+                "// Foo\n".to_string(),
+                "pub enum Foo {}\n".to_string(),
+                "// end\n".to_string()
+            ]
+        );
+
+        // Other kinds are left alone
+        assert_eq!(
+            apply(
+                Some(&templates),
+                TemplateKind::Method,
+                lines.clone(),
+                |_| ()
+            ),
+            lines
+        );
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        assert!(matches!(
+            Templates::new().with_template(TemplateKind::Method, "{% if %}"),
+            Err(TemplateError::Template(_))
+        ));
+    }
+}
@@ -0,0 +1,261 @@
+//! Check that generated code compiles
+//!
+//! The generated code is placed in a temporary crate that depends on this crate and the other
+//! crates that generated code refers to, which is then checked with `cargo check`.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A problem found while checking generated code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    /// The message of the error
+    pub message: String,
+
+    /// The line of the generated code that the error refers to, starting at 1
+    pub line: Option<usize>,
+
+    /// The error as rendered by the compiler, including the offending code
+    pub rendered: Option<String>,
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (&self.rendered, self.line) {
+            (Some(rendered), _) => write!(f, "{}", rendered.trim_end()),
+            (None, Some(line)) => write!(f, "line {}: {}", line, self.message),
+            (None, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl CompileError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            line: None,
+            rendered: None,
+        }
+    }
+}
+
+/// The dependencies of the crate that generated code is checked in, besides this crate
+const DEFAULT_DEPENDENCIES: &[(&str, &str)] = &[
+    (
+        "reqwest",
+        r#"{ version = ">=0.11", features = ["json", "multipart"] }"#,
+    ),
+    ("serde", r#"{ version = "1", features = ["derive"] }"#),
+    ("serde_json", r#""1""#),
+    ("url", r#"{ version = "2", features = ["serde"] }"#),
+    ("chrono", r#"{ version = "0.4", features = ["serde"] }"#),
+    ("form_urlencoded", r#""1""#),
+    ("mime", r#""0.3""#),
+];
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Check that generated code compiles
+///
+/// # Example
+///
+/// ```no_run
+/// let app = wadl::parse_file("api.wadl").unwrap();
+/// let config = wadl::codegen::Config::builder().r#async(true).build();
+/// let code = wadl::codegen::generate(&app, &config);
+/// wadl::codegen::Verifier::new()
+///     .feature("async")
+///     .verify(&code)
+///     .unwrap();
+/// ```
+pub struct Verifier {
+    features: Vec<String>,
+    dependencies: Vec<(String, String)>,
+    target_dir: PathBuf,
+    offline: bool,
+}
+
+impl Default for Verifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Verifier {
+    /// Create a verifier for code generated for the default blocking client
+    pub fn new() -> Self {
+        Self {
+            features: vec!["blocking".to_string()],
+            dependencies: DEFAULT_DEPENDENCIES
+                .iter()
+                .map(|(name, spec)| (name.to_string(), spec.to_string()))
+                .collect(),
+            target_dir: std::env::temp_dir().join("wadl-verify-target"),
+            offline: false,
+        }
+    }
+
+    /// Enable a feature of this crate, e.g. `async` or `tower`
+    pub fn feature(mut self, feature: &str) -> Self {
+        if !self.features.iter().any(|f| f == feature) {
+            self.features.push(feature.to_string());
+        }
+        self
+    }
+
+    /// Add a dependency, given its name and its specification in `Cargo.toml`
+    ///
+    /// For example, `.dependency("axum", "\"0.8\"")` for code generated by
+    /// [`generate_server`](super::generate_server).
+    pub fn dependency(mut self, name: &str, spec: &str) -> Self {
+        self.dependencies.retain(|(n, _)| n != name);
+        self.dependencies.push((name.to_string(), spec.to_string()));
+        self
+    }
+
+    /// Set the target directory to build in
+    ///
+    /// Defaults to a directory in the system temporary directory, which is shared between
+    /// checks so that dependencies are only built once.
+    pub fn target_dir(mut self, target_dir: impl AsRef<Path>) -> Self {
+        self.target_dir = target_dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Do not access the network to resolve dependencies
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    fn manifest(&self) -> String {
+        let mut manifest = String::new();
+        manifest.push_str("[package]\n");
+        manifest.push_str("name = \"wadl-verify\"\n");
+        manifest.push_str("version = \"0.0.0\"\n");
+        manifest.push_str("edition = \"2021\"\n");
+        manifest.push_str("publish = false\n");
+        manifest.push('\n');
+        manifest.push_str("[workspace]\n");
+        manifest.push('\n');
+        manifest.push_str("[dependencies]\n");
+        manifest.push_str(&format!(
+            "wadl = {{ path = {:?}, default-features = false, features = {:?} }}\n",
+            env!("CARGO_MANIFEST_DIR"),
+            self.features
+        ));
+        for (name, spec) in &self.dependencies {
+            manifest.push_str(&format!("{} = {}\n", name, spec));
+        }
+        manifest
+    }
+
+    /// Check that the generated code compiles
+    ///
+    /// Returns the errors reported by the compiler if it does not. Warnings are ignored.
+    pub fn verify(&self, code: &str) -> Result<(), Vec<CompileError>> {
+        let dir = std::env::temp_dir().join(format!(
+            "wadl-verify-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let ret = self.verify_in(&dir, code);
+        let _ = std::fs::remove_dir_all(&dir);
+        ret
+    }
+
+    fn verify_in(&self, dir: &Path, code: &str) -> Result<(), Vec<CompileError>> {
+        let io_error = |e: std::io::Error| vec![CompileError::new(e.to_string())];
+        std::fs::create_dir_all(dir.join("src")).map_err(io_error)?;
+        std::fs::write(dir.join("Cargo.toml"), self.manifest()).map_err(io_error)?;
+        std::fs::write(dir.join("src/generated.rs"), code).map_err(io_error)?;
+        std::fs::write(
+            dir.join("src/lib.rs"),
+            "#[allow(unused_imports)]\nuse wadl::Resource;\ninclude!(\"generated.rs\");\n",
+        )
+        .map_err(io_error)?;
+
+        let mut cmd =
+            std::process::Command::new(std::env::var("CARGO").unwrap_or("cargo".to_string()));
+        cmd.arg("check")
+            .arg("--quiet")
+            .arg("--message-format=json")
+            .arg("--manifest-path")
+            .arg(dir.join("Cargo.toml"))
+            .env("CARGO_TARGET_DIR", &self.target_dir);
+        if self.offline {
+            cmd.arg("--offline");
+        }
+        let output = cmd.output().map_err(io_error)?;
+
+        let errors = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|msg| msg["reason"] == "compiler-message")
+            .filter(|msg| msg["message"]["level"] == "error")
+            .map(|msg| compile_error(&msg["message"]))
+            .collect::<Vec<_>>();
+
+        if !errors.is_empty() {
+            Err(errors)
+        } else if !output.status.success() {
+            Err(vec![CompileError::new(
+                String::from_utf8_lossy(&output.stderr).trim(),
+            )])
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Convert a diagnostic emitted by the compiler into an error
+fn compile_error(message: &serde_json::Value) -> CompileError {
+    let line = message["spans"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|span| {
+            span["is_primary"] == true
+                && span["file_name"]
+                    .as_str()
+                    .is_some_and(|f| f.ends_with("generated.rs"))
+        })
+        .and_then(|span| span["line_start"].as_u64())
+        .map(|line| line as usize);
+    CompileError {
+        message: message["message"].as_str().unwrap_or_default().to_string(),
+        line,
+        rendered: message["rendered"].as_str().map(|s| s.to_string()),
+    }
+}
+
+/// Check that generated code compiles, with the default settings of [`Verifier`]
+pub fn verify(code: &str) -> Result<(), Vec<CompileError>> {
+    Verifier::new().verify(code)
+}
+
+/// Assert that generated code matches the contents of a snapshot file
+///
+/// If the `WADL_UPDATE_GOLDEN` environment variable is set, the snapshot file is updated
+/// instead.
+///
+/// # Panics
+///
+/// If the code does not match the snapshot, or the snapshot can not be read.
+pub fn assert_golden(path: impl AsRef<Path>, code: &str) {
+    let path = path.as_ref();
+    if std::env::var_os("WADL_UPDATE_GOLDEN").is_some() {
+        std::fs::write(path, code)
+            .unwrap_or_else(|e| panic!("unable to write {}: {}", path.display(), e));
+    }
+    let expected = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("unable to read {}: {}", path.display(), e));
+    assert_eq!(
+        code,
+        expected,
+        "generated code does not match {}; set WADL_UPDATE_GOLDEN=1 to update",
+        path.display()
+    );
+}
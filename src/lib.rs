@@ -4,14 +4,43 @@
 //! A crate for parsing WADL files and generating Rust code from them.
 
 pub mod ast;
+#[cfg(feature = "http")]
+pub mod cache;
 #[cfg(feature = "codegen")]
 pub mod codegen;
+pub mod link;
+pub mod lint;
+#[cfg(feature = "oauth1")]
+pub mod oauth1;
+#[cfg(feature = "oauth2")]
+pub mod oauth2;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod paginate;
 mod parse;
+pub mod patch;
+#[cfg(feature = "http")]
+pub mod response;
+pub mod stats;
+#[cfg(all(
+    feature = "testing",
+    any(feature = "blocking", feature = "async-native", feature = "ureq"),
+    not(target_arch = "wasm32")
+))]
+pub mod testing;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(feature = "ureq")]
+pub mod ureq;
+mod write;
 
 /// The MIME type of WADL files.
 pub const WADL_MIME_TYPE: &str = "application/vnd.sun.wadl+xml";
 
+pub use link::Link;
 pub use parse::{parse, parse_bytes, parse_file, parse_string, Error as ParseError};
+pub use patch::{Patch, PatchOperation};
+pub use write::{write, write_string};
 
 use url::Url;
 
@@ -21,118 +50,2465 @@ pub trait Resource {
     fn url(&self) -> &Url;
 }
 
-#[cfg(feature = "async")]
+/// Check whether a URL matches the path template of a resource
+///
+/// The template is relative to `base`; if there is no base URL, it is matched against the
+/// full path of the URL. Template parameters like `{name}` match a single path segment.
+pub fn url_matches_template(base: Option<&Url>, template: &str, url: &Url) -> bool {
+    let path = match base {
+        Some(base) => {
+            if base.origin() != url.origin() {
+                return false;
+            }
+            match url.path().strip_prefix(base.path().trim_end_matches('/')) {
+                Some(path) if path.is_empty() || path.starts_with('/') => path,
+                _ => return false,
+            }
+        }
+        None => url.path(),
+    };
+    let segments = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    let template = template
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    segments.len() == template.len()
+        && segments
+            .iter()
+            .zip(template.iter())
+            .all(|(segment, t)| (t.starts_with('{') && t.ends_with('}')) || segment == t)
+}
+
+/// A representation, along with the entity tag of the resource it was fetched from
+///
+/// The entity tag can be passed to the generated `_if_match` variants of methods, so that
+/// changes are only applied if the resource has not been modified in the meantime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tagged<T> {
+    /// The representation
+    pub value: T,
+
+    /// The value of the `ETag` header, if any
+    pub etag: Option<String>,
+}
+
+impl<T> Tagged<T> {
+    /// Return the representation, discarding the entity tag
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> std::ops::Deref for Tagged<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> std::ops::DerefMut for Tagged<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(feature = "http")]
+/// How to retry requests that failed because the server is unavailable or overloaded
+///
+/// By default, requests are retried up to three times if the server responds with
+/// `429 Too Many Requests`, `500 Internal Server Error`, `502 Bad Gateway`,
+/// `503 Service Unavailable` or `504 Gateway Timeout`, or if no connection could be made.
+/// Delays grow exponentially, unless the server sends a `Retry-After` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of retries
+    pub retries: u32,
+
+    /// The delay before the first retry, doubled for every following retry
+    ///
+    /// Not used if the server sends a `Retry-After` header.
+    pub backoff: std::time::Duration,
+
+    /// The longest delay to wait before a retry
+    ///
+    /// Exponential delays are capped at this; if the server asks to wait longer with a
+    /// `Retry-After` header, the request is not retried.
+    pub max_backoff: std::time::Duration,
+
+    /// Whether to randomize exponential delays, so that clients that failed at the same time
+    /// do not all retry at the same time
+    ///
+    /// Delays are picked between half and all of the exponential delay.
+    pub jitter: bool,
+
+    /// The response statuses on which to retry
+    pub statuses: Vec<u16>,
+
+    /// Whether to retry requests for which no connection could be made
+    pub connection_errors: bool,
+}
+
+#[cfg(feature = "http")]
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff: std::time::Duration::from_millis(500),
+            max_backoff: std::time::Duration::from_secs(30),
+            jitter: true,
+            statuses: vec![429, 500, 502, 503, 504],
+            connection_errors: true,
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl RetryPolicy {
+    /// Create a policy with the default settings and a maximum number of retries
+    pub fn new(retries: u32) -> Self {
+        Self {
+            retries,
+            ..Default::default()
+        }
+    }
+
+    /// Set the delay before the first retry
+    pub fn with_backoff(mut self, backoff: std::time::Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set the longest delay to wait before a retry
+    pub fn with_max_backoff(mut self, max_backoff: std::time::Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set whether to randomize delays
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the response statuses on which to retry
+    pub fn with_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Set whether to retry requests for which no connection could be made
+    pub fn with_connection_errors(mut self, connection_errors: bool) -> Self {
+        self.connection_errors = connection_errors;
+        self
+    }
+
+    /// Return how long to wait before retrying a response, or `None` if it should not be retried
+    ///
+    /// # Arguments
+    /// * `attempt` - The number of retries so far
+    /// * `status` - The status of the response
+    /// * `headers` - The headers of the response
+    pub fn delay(
+        &self,
+        attempt: u32,
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Option<std::time::Duration> {
+        if attempt >= self.retries || !self.statuses.contains(&status.as_u16()) {
+            return None;
+        }
+        match retry_after(headers) {
+            Some(delay) if delay > self.max_backoff => None,
+            Some(delay) => Some(delay),
+            None => Some(self.backoff_delay(attempt)),
+        }
+    }
+
+    /// Return how long to wait before retrying a request that failed, or `None` if it should
+    /// not be retried
+    ///
+    /// # Arguments
+    /// * `attempt` - The number of retries so far
+    /// * `error` - The error sending the request failed with
+    pub fn error_delay(&self, attempt: u32, error: &Error) -> Option<std::time::Duration> {
+        if self.connection_errors && error.is_connect() && attempt < self.retries {
+            Some(self.backoff_delay(attempt))
+        } else {
+            None
+        }
+    }
+
+    fn backoff_delay(&self, attempt: u32) -> std::time::Duration {
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        if self.jitter {
+            use std::hash::{BuildHasher, Hasher};
+            let random = std::collections::hash_map::RandomState::new()
+                .build_hasher()
+                .finish();
+            delay / 2 + delay.mul_f64(random as f64 / u64::MAX as f64 / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+/// Parse the `Retry-After` header of a response, as a delay in seconds or an HTTP date
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<std::time::Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(std::time::Duration::from_secs(seconds));
+    }
+    let date = parse_http_date(value)?;
+    Some(
+        date.duration_since(std::time::SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(feature = "http")]
+/// Parse an HTTP date in the preferred format, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+fn parse_http_date(value: &str) -> Option<std::time::SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (_, date) = value.split_once(", ")?;
+    let [day, month, year, time, "GMT"] = date.split_whitespace().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let day = day.parse::<i64>().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let year = year.parse::<i64>().ok()?;
+    let mut time = time.split(':').map(|t| t.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+
+    // Days since the epoch of a date in the proleptic Gregorian calendar
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds = days * 86400 + hours * 3600 + minutes * 60 + seconds;
+    std::time::UNIX_EPOCH.checked_add(std::time::Duration::from_secs(seconds.try_into().ok()?))
+}
+
+#[cfg(feature = "http")]
+/// Measures the latency of requests made by generated code, for [`Observer`]s
+///
+/// In the browser, where `std::time::Instant` is not available, the latency is always zero.
+#[derive(Debug, Clone, Copy)]
+pub struct Stopwatch(#[cfg(not(target_arch = "wasm32"))] std::time::Instant);
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+impl Stopwatch {
+    /// Start measuring
+    pub fn start() -> Self {
+        Self(std::time::Instant::now())
+    }
+
+    /// Return the time since the stopwatch was started
+    pub fn elapsed(&self) -> std::time::Duration {
+        self.0.elapsed()
+    }
+}
+
+#[cfg(all(feature = "http", target_arch = "wasm32"))]
+impl Stopwatch {
+    /// Start measuring
+    pub fn start() -> Self {
+        Self()
+    }
+
+    /// Return the time since the stopwatch was started
+    pub fn elapsed(&self) -> std::time::Duration {
+        std::time::Duration::ZERO
+    }
+}
+
+#[cfg(feature = "http")]
+/// Observe the requests made by generated code, e.g. to collect metrics
+///
+/// Generated code reports to an observer if the `observer` option of the code generator is set.
+pub trait Observer: Send + Sync {
+    /// Called before a request for a method is sent
+    fn on_request_start(&self, _method_id: &str) {}
+
+    /// Called when the response of a request has been received, or sending it failed
+    ///
+    /// # Arguments
+    /// * `method_id` - The id of the method in the WADL
+    /// * `status` - The status of the response, or `None` if no response was received
+    /// * `latency` - The time between sending the request and receiving the response
+    fn on_request_finish(
+        &self,
+        _method_id: &str,
+        _status: Option<reqwest::StatusCode>,
+        _latency: std::time::Duration,
+    ) {
+    }
+}
+
+#[cfg(feature = "http")]
+/// Credentials that a `WadlClient` sends with every request
+#[derive(Clone, PartialEq, Eq)]
+pub enum Auth {
+    /// A bearer token, e.g. for OAuth 2.0
+    Bearer(String),
+
+    /// A user name and password, for HTTP basic authentication
+    Basic {
+        /// The user name
+        username: String,
+
+        /// The password, if any
+        password: Option<String>,
+    },
+}
+
+#[cfg(feature = "http")]
+// Keep credentials out of logs
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Auth::Bearer(_) => f.debug_tuple("Bearer").field(&"..").finish(),
+            Auth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+/// The `User-Agent` that clients created with `client_builder` identify themselves with
+pub const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+#[cfg(feature = "http")]
+/// How long clients created with `client_builder` wait for a connection to be established
+pub const CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[cfg(feature = "http")]
+/// How long clients created with `client_builder` wait for the server
+///
+/// For blocking clients this covers the whole request. Async clients apply it to every read
+/// instead, so that long downloads are not cut off.
+pub const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+#[cfg(feature = "http")]
+/// Name of the environment variable with a PEM bundle of additional root certificates
+///
+/// This is the variable OpenSSL uses, so that the certificates are also trusted when reqwest
+/// uses a TLS backend that does not read it, like rustls.
+pub const CA_BUNDLE_ENV: &str = "SSL_CERT_FILE";
+
+/// Load the root certificates in the PEM bundle at `path`, if any
+#[cfg(all(
+    any(feature = "blocking", feature = "async-native"),
+    not(target_arch = "wasm32")
+))]
+fn root_certificates(path: Option<&std::path::Path>) -> Result<Vec<reqwest::Certificate>, Error> {
+    match path {
+        None => Ok(vec![]),
+        Some(path) => {
+            let bundle = std::fs::read(path)?;
+            Ok(reqwest::Certificate::from_pem_bundle(&bundle)?)
+        }
+    }
+}
+
+#[cfg(all(
+    any(feature = "blocking", feature = "async-native"),
+    not(target_arch = "wasm32")
+))]
+fn env_root_certificates() -> Result<Vec<reqwest::Certificate>, Error> {
+    let path = std::env::var_os(CA_BUNDLE_ENV).filter(|path| !path.is_empty());
+    root_certificates(path.as_deref().map(std::path::Path::new))
+}
+
+#[cfg(feature = "async-native")]
 /// Asynchronous features
 pub mod r#async {
     use super::*;
 
-    /// A client for a WADL API
-    #[async_trait::async_trait]
-    pub trait Client: Sync + Send {
-        /// Create a new request builder
-        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder;
+    /// A client for a WADL API
+    #[cfg(feature = "async")]
+    #[async_trait::async_trait]
+    pub trait Client: Sync + Send {
+        /// Create a new request builder
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder;
+
+        /// Send a request built with [`Client::request`]
+        ///
+        /// Generated code sends all requests through this, so that implementations can wrap
+        /// them, e.g. with [`LayeredClient`].
+        #[cfg(not(target_arch = "wasm32"))]
+        async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+            Ok(req.send().await?)
+        }
+
+        /// Return the WADL of the service, if the client has it cached
+        ///
+        /// [`get_wadl_resource_by_href`] looks resources up in it before fetching their WADL.
+        fn application(&self) -> Option<&crate::ast::Application> {
+            None
+        }
+
+        /// Return the cache that WADL documents fetched with this client are kept in, if any
+        ///
+        /// [`get_wadl_resource_by_href`] fetches WADL documents through it.
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            None
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[async_trait::async_trait]
+    impl Client for reqwest::Client {
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            self.request(method, url)
+        }
+    }
+
+    /// A client for a WADL API, without boxing the futures of its methods
+    ///
+    /// Code generated with the `native_async_client` option takes this rather than [`Client`].
+    /// It is implemented for all implementations of [`Client`], so that they can be used with
+    /// such code as well; [`reqwest::Client`] only avoids the boxing if the `async` feature
+    /// is disabled, leaving just `async-native`.
+    pub trait NativeClient: Sync + Send {
+        /// Create a new request builder
+        fn request(
+            &self,
+            method: reqwest::Method,
+            url: url::Url,
+        ) -> impl std::future::Future<Output = reqwest::RequestBuilder> + Send;
+
+        /// Send a request built with [`NativeClient::request`]
+        ///
+        /// Generated code sends all requests through this, so that implementations can wrap
+        /// them, e.g. with [`LayeredClient`].
+        #[cfg(not(target_arch = "wasm32"))]
+        fn send(
+            &self,
+            req: reqwest::RequestBuilder,
+        ) -> impl std::future::Future<Output = Result<reqwest::Response, Error>> + Send {
+            async move { Ok(req.send().await?) }
+        }
+
+        /// Return the WADL of the service, if the client has it cached
+        ///
+        /// [`get_wadl_resource_by_href`] looks resources up in it before fetching their WADL.
+        fn application(&self) -> Option<&crate::ast::Application> {
+            None
+        }
+
+        /// Return the cache that WADL documents fetched with this client are kept in, if any
+        ///
+        /// [`get_wadl_resource_by_href`] fetches WADL documents through it.
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            None
+        }
+    }
+
+    // Used by generated code for async traits, if it has to support Rust versions without
+    // async fn in traits
+    #[cfg(feature = "async")]
+    pub use async_trait::async_trait;
+
+    #[cfg(feature = "async")]
+    impl<T: Client + ?Sized> NativeClient for T {
+        fn request(
+            &self,
+            method: reqwest::Method,
+            url: url::Url,
+        ) -> impl std::future::Future<Output = reqwest::RequestBuilder> + Send {
+            Client::request(self, method, url)
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        fn send(
+            &self,
+            req: reqwest::RequestBuilder,
+        ) -> impl std::future::Future<Output = Result<reqwest::Response, Error>> + Send {
+            Client::send(self, req)
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            Client::application(self)
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            Client::application_cache(self)
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    impl NativeClient for reqwest::Client {
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            self.request(method, url)
+        }
+    }
+
+    /// Create a reqwest client builder, configured for talking to WADL services
+    ///
+    /// The client identifies itself with [`USER_AGENT`], gives up after [`CONNECT_TIMEOUT`] and
+    /// [`TIMEOUT`], and trusts the root certificates in the PEM bundle named by
+    /// [`CA_BUNDLE_ENV`] in addition to the default ones. Proxies are taken from the
+    /// environment (`HTTPS_PROXY`, `HTTP_PROXY`, `NO_PROXY`) as usual. All of these can be
+    /// overridden on the returned builder.
+    ///
+    /// ```no_run
+    /// let client = wadl::r#async::WadlClient::new(wadl::r#async::client_builder()?.build()?);
+    /// # Ok::<(), wadl::Error>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn client_builder() -> Result<reqwest::ClientBuilder, Error> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .read_timeout(TIMEOUT);
+        for certificate in env_root_certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        Ok(builder)
+    }
+
+    /// A client for a WADL API, with a base URL, default headers and credentials
+    ///
+    /// It can be passed to generated code wherever that takes a client. The headers and
+    /// credentials are sent with every request.
+    #[derive(Debug, Clone)]
+    pub struct WadlClient {
+        client: reqwest::Client,
+        base_url: Option<url::Url>,
+        headers: reqwest::header::HeaderMap,
+        auth: Option<Auth>,
+        application: Option<std::sync::Arc<crate::ast::Application>>,
+        cache: Option<std::sync::Arc<crate::cache::ApplicationCache>>,
+        #[cfg(not(target_arch = "wasm32"))]
+        observer: Option<SharedObserver>,
+    }
+
+    impl Default for WadlClient {
+        fn default() -> Self {
+            Self::new(reqwest::Client::new())
+        }
+    }
+
+    impl WadlClient {
+        /// Wrap a reqwest client
+        pub fn new(client: reqwest::Client) -> Self {
+            Self {
+                client,
+                base_url: None,
+                headers: reqwest::header::HeaderMap::new(),
+                auth: None,
+                application: None,
+                cache: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                observer: None,
+            }
+        }
+
+        /// Create a client for the service described by the WADL at a URL
+        ///
+        /// See [`WadlClient::with_wadl_url`].
+        pub async fn from_wadl_url(url: &url::Url) -> Result<Self, Error> {
+            Self::default().with_wadl_url(url).await
+        }
+
+        /// Fetch and cache the WADL of the service
+        ///
+        /// The WADL is fetched with the headers and credentials of this client. Unless a base
+        /// URL was set, the base of the first resources in the WADL is used.
+        pub async fn with_wadl_url(mut self, url: &url::Url) -> Result<Self, Error> {
+            let application = if let Some(cache) = self.cache.as_ref() {
+                get_wadl_application(&self, url, cache).await?
+            } else {
+                let res = self
+                    .send_request(
+                        self.build_request(reqwest::Method::GET, url.clone())
+                            .header(reqwest::header::ACCEPT, WADL_MIME_TYPE),
+                    )
+                    .await?;
+                response::check_status(res.status(), &[])?;
+                std::sync::Arc::new(parse_string(&res.text().await?)?)
+            };
+            if self.base_url.is_none() {
+                self.base_url = application.resources.iter().find_map(|r| r.base.clone());
+            }
+            self.application = Some(application);
+            Ok(self)
+        }
+
+        /// Set the URL that relative paths are resolved against, see [`WadlClient::join`]
+        pub fn with_base_url(mut self, base_url: url::Url) -> Self {
+            self.base_url = Some(base_url);
+            self
+        }
+
+        /// Add a header to send with every request
+        pub fn with_header(
+            mut self,
+            name: reqwest::header::HeaderName,
+            value: reqwest::header::HeaderValue,
+        ) -> Self {
+            self.headers.insert(name, value);
+            self
+        }
+
+        /// Set the credentials to send with every request
+        pub fn with_auth(mut self, auth: Auth) -> Self {
+            self.auth = Some(auth);
+            self
+        }
+
+        /// Keep the WADL documents fetched with this client in a cache
+        ///
+        /// Set this before [`WadlClient::with_wadl_url`] to fetch the WADL of the service
+        /// through the cache as well.
+        pub fn with_cache(mut self, cache: std::sync::Arc<crate::cache::ApplicationCache>) -> Self {
+            self.cache = Some(cache);
+            self
+        }
+
+        /// Report every request sent with this client, and its response, to an observer
+        #[cfg(not(target_arch = "wasm32"))]
+        pub fn with_observer(mut self, observer: impl RequestObserver + 'static) -> Self {
+            self.observer = Some(SharedObserver(std::sync::Arc::new(observer)));
+            self
+        }
+
+        /// Return the wrapped reqwest client
+        pub fn client(&self) -> &reqwest::Client {
+            &self.client
+        }
+
+        /// Return the base URL of the service
+        pub fn base_url(&self) -> Option<&url::Url> {
+            self.base_url.as_ref()
+        }
+
+        /// Return the cached WADL of the service
+        pub fn application(&self) -> Option<&crate::ast::Application> {
+            self.application.as_deref()
+        }
+
+        /// Resolve a path relative to the base URL
+        pub fn join(&self, path: &str) -> Result<url::Url, Error> {
+            Ok(self
+                .base_url
+                .as_ref()
+                .ok_or(Error::InvalidUrl)?
+                .join(path)?)
+        }
+
+        fn build_request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            let req = self
+                .client
+                .request(method, url)
+                .headers(self.headers.clone());
+            match &self.auth {
+                None => req,
+                Some(Auth::Bearer(token)) => req.bearer_auth(token),
+                Some(Auth::Basic { username, password }) => {
+                    req.basic_auth(username, password.as_ref())
+                }
+            }
+        }
+
+        async fn send_request(
+            &self,
+            req: reqwest::RequestBuilder,
+        ) -> Result<reqwest::Response, Error> {
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(observer) = &self.observer {
+                let (client, req) = req.build_split();
+                return send_observed(&*observer.0, req?, |req| async move {
+                    Ok(client.execute(req).await?)
+                })
+                .await;
+            }
+            Ok(req.send().await?)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[async_trait::async_trait]
+    impl Client for WadlClient {
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            self.build_request(method, url)
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+            self.send_request(req).await
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.application.as_deref()
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.cache.as_deref()
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    impl NativeClient for WadlClient {
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            self.build_request(method, url)
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+            self.send_request(req).await
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.application.as_deref()
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.cache.as_deref()
+        }
+    }
+
+    /// A response body that is streamed, rather than read into memory
+    ///
+    /// Generated methods return this for binary response bodies, like those with media type
+    /// `application/octet-stream`.
+    pub struct ByteStream(std::pin::Pin<Box<BodyStream>>);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    type BodyStream = dyn futures_core::Stream<Item = reqwest::Result<bytes::Bytes>> + Send;
+
+    // Response bodies are backed by JavaScript objects in the browser, which are not `Send`
+    #[cfg(target_arch = "wasm32")]
+    type BodyStream = dyn futures_core::Stream<Item = reqwest::Result<bytes::Bytes>>;
+
+    impl ByteStream {
+        /// Stream the body of a response
+        pub fn new(response: reqwest::Response) -> Self {
+            Self(Box::pin(response.bytes_stream()))
+        }
+    }
+
+    impl futures_core::Stream for ByteStream {
+        type Item = Result<bytes::Bytes, Error>;
+
+        fn poll_next(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Self::Item>> {
+            self.0
+                .as_mut()
+                .poll_next(cx)
+                .map(|chunk| chunk.map(|chunk| chunk.map_err(Error::from)))
+        }
+    }
+
+    impl std::fmt::Debug for ByteStream {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.debug_struct("ByteStream").finish_non_exhaustive()
+        }
+    }
+
+    /// Data to upload as part of a multipart request
+    ///
+    /// Files and streams are sent without reading them into memory first.
+    pub enum Upload {
+        /// Data in memory
+        Bytes(Vec<u8>),
+
+        #[cfg(not(target_arch = "wasm32"))]
+        /// The contents of a file, with its name as file name
+        Path(std::path::PathBuf),
+
+        /// A stream of data
+        Stream(reqwest::Body),
+    }
+
+    impl Upload {
+        /// Create the part of a multipart form for this upload
+        pub async fn into_part(self) -> std::io::Result<reqwest::multipart::Part> {
+            Ok(match self {
+                Upload::Bytes(data) => reqwest::multipart::Part::bytes(data),
+                #[cfg(not(target_arch = "wasm32"))]
+                Upload::Path(path) => reqwest::multipart::Part::file(path).await?,
+                Upload::Stream(body) => reqwest::multipart::Part::stream(body),
+            })
+        }
+    }
+
+    impl From<Vec<u8>> for Upload {
+        fn from(data: Vec<u8>) -> Self {
+            Upload::Bytes(data)
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl From<std::path::PathBuf> for Upload {
+        fn from(path: std::path::PathBuf) -> Self {
+            Upload::Path(path)
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl From<&std::path::Path> for Upload {
+        fn from(path: &std::path::Path) -> Self {
+            Upload::Path(path.to_path_buf())
+        }
+    }
+
+    /// Middleware that wraps the requests sent by a [`LayeredClient`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wadl::r#async::{BoxFuture, Middleware, Next};
+    ///
+    /// struct Logger;
+    ///
+    /// impl Middleware for Logger {
+    ///     fn handle<'a>(
+    ///         &'a self,
+    ///         req: reqwest::Request,
+    ///         next: Next<'a>,
+    ///     ) -> BoxFuture<'a, Result<reqwest::Response, wadl::Error>> {
+    ///         Box::pin(async move {
+    ///             let url = req.url().clone();
+    ///             let resp = next.run(req).await?;
+    ///             log::debug!("{}: {}", url, resp.status());
+    ///             Ok(resp)
+    ///         })
+    ///     }
+    /// }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub trait Middleware: Send + Sync {
+        /// Handle a request, usually by passing it on to `next`
+        fn handle<'a>(
+            &'a self,
+            req: reqwest::Request,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<reqwest::Response, Error>>;
+    }
+
+    /// A boxed future, as returned by [`Middleware::handle`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+    /// The rest of the middleware chain, ending in the HTTP client
+    #[cfg(not(target_arch = "wasm32"))]
+    #[derive(Clone, Copy)]
+    pub struct Next<'a> {
+        client: &'a reqwest::Client,
+        middleware: &'a [Box<dyn Middleware>],
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl<'a> Next<'a> {
+        /// Pass a request on to the next middleware, or send it if there is none
+        pub fn run(self, req: reqwest::Request) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+            match self.middleware.split_first() {
+                Some((first, rest)) => first.handle(
+                    req,
+                    Next {
+                        client: self.client,
+                        middleware: rest,
+                    },
+                ),
+                None => Box::pin(async move { Ok(self.client.execute(req).await?) }),
+            }
+        }
+    }
+
+    /// Observe the requests sent by a [`WadlClient`] or [`LayeredClient`]
+    ///
+    /// Register one with [`WadlClient::with_observer`] or [`LayeredClient::with_observer`], e.g.
+    /// to log requests, to assert on them in tests or to record them as fixtures.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub trait RequestObserver: Send + Sync {
+        /// Called before a request is sent
+        fn on_request(&self, _req: &reqwest::Request) {}
+
+        /// Called when the response to a request has been received
+        ///
+        /// `elapsed` is the time between sending the request and receiving the headers of the
+        /// response; the body may not have been read yet.
+        fn on_response(&self, _resp: &reqwest::Response, _elapsed: std::time::Duration) {}
+
+        /// Called when sending a request failed, without a response
+        fn on_error(&self, _err: &Error, _elapsed: std::time::Duration) {}
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl<O: RequestObserver + ?Sized> RequestObserver for std::sync::Arc<O> {
+        fn on_request(&self, req: &reqwest::Request) {
+            (**self).on_request(req)
+        }
+
+        fn on_response(&self, resp: &reqwest::Response, elapsed: std::time::Duration) {
+            (**self).on_response(resp, elapsed)
+        }
+
+        fn on_error(&self, err: &Error, elapsed: std::time::Duration) {
+            (**self).on_error(err, elapsed)
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[derive(Clone)]
+    struct SharedObserver(std::sync::Arc<dyn RequestObserver>);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl std::fmt::Debug for SharedObserver {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.debug_struct("RequestObserver").finish_non_exhaustive()
+        }
+    }
+
+    /// Send a request with `execute`, reporting it and its outcome to an observer
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn send_observed<F>(
+        observer: &(impl RequestObserver + ?Sized),
+        req: reqwest::Request,
+        execute: impl FnOnce(reqwest::Request) -> F,
+    ) -> Result<reqwest::Response, Error>
+    where
+        F: std::future::Future<Output = Result<reqwest::Response, Error>>,
+    {
+        observer.on_request(&req);
+        let stopwatch = Stopwatch::start();
+        let result = execute(req).await;
+        match &result {
+            Ok(resp) => observer.on_response(resp, stopwatch.elapsed()),
+            Err(err) => observer.on_error(err, stopwatch.elapsed()),
+        }
+        result
+    }
+
+    /// Middleware that reports requests to an observer, see [`LayeredClient::with_observer`]
+    #[cfg(not(target_arch = "wasm32"))]
+    struct Observed<O>(O);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl<O: RequestObserver> Middleware for Observed<O> {
+        fn handle<'a>(
+            &'a self,
+            req: reqwest::Request,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<reqwest::Response, Error>> {
+            Box::pin(send_observed(&self.0, req, move |req| next.run(req)))
+        }
+    }
+
+    /// A client that sends its requests through a chain of [`Middleware`]
+    ///
+    /// Requests are built by the inner client, and passed through the middleware in the order
+    /// in which it was added.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub struct LayeredClient<C> {
+        inner: C,
+        middleware: Vec<Box<dyn Middleware>>,
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl<C> LayeredClient<C> {
+        /// Wrap a client, without any middleware
+        pub fn new(inner: C) -> Self {
+            Self {
+                inner,
+                middleware: vec![],
+            }
+        }
+
+        /// Add a middleware, which is run after the ones that were added before
+        pub fn layer(mut self, middleware: impl Middleware + 'static) -> Self {
+            self.middleware.push(Box::new(middleware));
+            self
+        }
+
+        /// Report the requests passed through this layer, and their responses, to an observer
+        ///
+        /// The observer sees requests as modified by the middleware added before it.
+        pub fn with_observer(self, observer: impl RequestObserver + 'static) -> Self {
+            self.layer(Observed(observer))
+        }
+
+        /// Return the inner client
+        pub fn inner(&self) -> &C {
+            &self.inner
+        }
+
+        async fn send_layered(
+            &self,
+            req: reqwest::RequestBuilder,
+        ) -> Result<reqwest::Response, Error> {
+            let (client, req) = req.build_split();
+            Next {
+                client: &client,
+                middleware: &self.middleware,
+            }
+            .run(req?)
+            .await
+        }
+    }
+
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    #[async_trait::async_trait]
+    impl<C: Client> Client for LayeredClient<C> {
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            self.inner.request(method, url).await
+        }
+
+        async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+            self.send_layered(req).await
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.inner.application()
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.inner.application_cache()
+        }
+    }
+
+    #[cfg(all(not(feature = "async"), not(target_arch = "wasm32")))]
+    impl<C: NativeClient> NativeClient for LayeredClient<C> {
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            self.inner.request(method, url).await
+        }
+
+        async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+            self.send_layered(req).await
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.inner.application()
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.inner.application_cache()
+        }
+    }
+
+    /// Send a request built with a client
+    ///
+    /// Generated code sends requests with this. In the browser the request is sent directly,
+    /// since clients can not wrap requests there.
+    pub async fn send(
+        client: &(impl NativeClient + ?Sized),
+        req: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return client.send(req).await;
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = client;
+            Ok(req.send().await?)
+        }
+    }
+
+    /// A client that retries requests as described by a [`RetryPolicy`]
+    ///
+    /// Every request sent by generated code is retried, in addition to any retries configured
+    /// for its method. Requests with a body that can not be cloned are sent only once.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub struct RetryingClient<C> {
+        inner: C,
+        policy: RetryPolicy,
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl<C> RetryingClient<C> {
+        /// Wrap a client, retrying its requests as described by `policy`
+        pub fn new(inner: C, policy: RetryPolicy) -> Self {
+            Self { inner, policy }
+        }
+
+        /// Return the inner client
+        pub fn inner(&self) -> &C {
+            &self.inner
+        }
+
+        /// Return the retry policy
+        pub fn policy(&self) -> &RetryPolicy {
+            &self.policy
+        }
+    }
+
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    #[async_trait::async_trait]
+    impl<C: Client> Client for RetryingClient<C> {
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            self.inner.request(method, url).await
+        }
+
+        async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+            send_with_retry(&self.inner, req, &self.policy).await
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.inner.application()
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.inner.application_cache()
+        }
+    }
+
+    #[cfg(all(not(feature = "async"), not(target_arch = "wasm32")))]
+    impl<C: NativeClient> NativeClient for RetryingClient<C> {
+        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+            self.inner.request(method, url).await
+        }
+
+        async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+            send_with_retry(&self.inner, req, &self.policy).await
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.inner.application()
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.inner.application_cache()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Send a request, retrying it as described by a retry policy
+    ///
+    /// Requests with a body that can not be cloned, like a stream, are sent only once.
+    pub async fn send_with_retry(
+        client: &(impl NativeClient + ?Sized),
+        req: reqwest::RequestBuilder,
+        policy: &RetryPolicy,
+    ) -> Result<reqwest::Response, Error> {
+        let mut attempt = 0;
+        loop {
+            let Some(retry) = req.try_clone() else {
+                return client.send(req).await;
+            };
+            let delay = match client.send(retry).await {
+                Ok(resp) => match policy.delay(attempt, resp.status(), resp.headers()) {
+                    Some(delay) => delay,
+                    None => return Ok(resp),
+                },
+                Err(err) => match policy.error_delay(attempt, &err) {
+                    Some(delay) => delay,
+                    None => return Err(err),
+                },
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    /// Send a request, without retrying it
+    ///
+    /// In the browser there is no timer to wait between retries with, so the policy is ignored.
+    pub async fn send_with_retry(
+        client: &(impl NativeClient + ?Sized),
+        req: reqwest::RequestBuilder,
+        _policy: &RetryPolicy,
+    ) -> Result<reqwest::Response, Error> {
+        send(client, req).await
+    }
+
+    /// Parse the JSON body of a response, after checking its status and content type
+    ///
+    /// See [`response::check_status`] for the meaning of `expected_statuses`.
+    pub async fn parse_json_response<T: serde::de::DeserializeOwned>(
+        resp: reqwest::Response,
+        expected_statuses: &[u16],
+    ) -> Result<T, Error> {
+        response::check_json_response(resp.status(), resp.headers(), expected_statuses)?;
+        Ok(resp.json::<T>().await?)
+    }
+
+    /// Fetch the JSON representation of a resource again, if it changed
+    ///
+    /// The request is conditional on the entity tag of `representation`, so that the server
+    /// only sends the representation if the resource was modified; it is only deserialized in
+    /// that case. Returns whether the representation changed.
+    pub async fn refresh<T: serde::de::DeserializeOwned>(
+        client: &(impl NativeClient + ?Sized),
+        url: &url::Url,
+        representation: &mut Tagged<T>,
+    ) -> Result<bool, Error> {
+        let mut req = client
+            .request(reqwest::Method::GET, url.clone())
+            .await
+            .header(reqwest::header::ACCEPT, "application/json");
+        if let Some(etag) = representation.etag.as_ref() {
+            req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let resp = send(client, req).await?;
+        if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(false);
+        }
+        let etag = resp
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        representation.value = parse_json_response(resp, &[]).await?;
+        representation.etag = etag;
+        Ok(true)
+    }
+
+    /// Fetch the WADL document at a URL through a cache
+    ///
+    /// A cached document is revalidated with a conditional request, and only downloaded and
+    /// parsed again if it changed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(client, cache), fields(url = %url), err)
+    )]
+    pub async fn get_wadl_application(
+        client: &(impl NativeClient + ?Sized),
+        url: &url::Url,
+        cache: &crate::cache::ApplicationCache,
+    ) -> Result<std::sync::Arc<crate::ast::Application>, Error> {
+        let cached = cache.get(url);
+        let mut req = client
+            .request(reqwest::Method::GET, url.clone())
+            .await
+            .header(reqwest::header::ACCEPT, WADL_MIME_TYPE);
+        for (name, value) in cached.iter().flat_map(|c| c.conditional_headers()) {
+            req = req.header(name, value);
+        }
+        #[cfg(feature = "tracing")]
+        let stopwatch = Stopwatch::start();
+        let res = send(client, req).await?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(status = %res.status(), elapsed = ?stopwatch.elapsed(), "fetched WADL");
+        if let (reqwest::StatusCode::NOT_MODIFIED, Some(cached)) = (res.status(), cached) {
+            return Ok(cached.application);
+        }
+        response::check_status(res.status(), &[])?;
+        let header = |name| {
+            res.headers()
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        cache.insert(url, &res.text().await?, etag, last_modified)
+    }
+
+    /// Look up the WADL resource at a URL
+    ///
+    /// The resource is looked up in `application` and then in the WADL cached by the client,
+    /// if any; otherwise the WADL is fetched from the URL itself, through the application
+    /// cache of the client if it has one.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(client, application),
+            fields(href = %href),
+            err
+        )
+    )]
+    pub async fn get_wadl_resource_by_href(
+        client: &(impl NativeClient + ?Sized),
+        href: &url::Url,
+        application: Option<&crate::ast::Application>,
+    ) -> Result<crate::ast::Resource, Error> {
+        if let Some(resource) = application
+            .into_iter()
+            .chain(client.application())
+            .find_map(|a| a.get_resource_by_href(href))
+        {
+            #[cfg(feature = "tracing")]
+            tracing::trace!("found resource in known WADL");
+            return Ok(resource.clone());
+        }
+
+        if let Some(cache) = client.application_cache() {
+            let application = get_wadl_application(client, href, cache).await?;
+            return application
+                .get_resource_by_href(href)
+                .cloned()
+                .ok_or_else(|| Error::ResourceNotFound(href.clone()));
+        }
+
+        let mut req = client.request(reqwest::Method::GET, href.clone()).await;
+
+        req = req.header(reqwest::header::ACCEPT, super::WADL_MIME_TYPE);
+
+        #[cfg(feature = "tracing")]
+        let stopwatch = Stopwatch::start();
+        let res = send(client, req).await?;
+
+        response::check_status(res.status(), &[])?;
+
+        let text = res.text().await?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(size = text.len(), elapsed = ?stopwatch.elapsed(), "fetched WADL");
+
+        let application = super::parse_string(&text)?;
+
+        let resource = application
+            .get_resource_by_href(href)
+            .ok_or_else(|| Error::ResourceNotFound(href.clone()))?;
+
+        Ok(resource.clone())
+    }
+
+    /// A resource whose representation can be fetched, so that [`Link`]s to it can be followed
+    ///
+    /// Implemented by generated code for resource types with a GET method without arguments.
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    pub trait Fetch: Resource {
+        /// The representation returned by the GET method
+        type Representation: Send;
+
+        /// Fetch the representation of the resource at a URL
+        ///
+        /// The URL is not checked against the paths of the resource type, since links returned
+        /// by the service are trusted to point to resources of the right type.
+        fn fetch<'a>(
+            url: &'a url::Url,
+            client: &'a dyn Client,
+        ) -> BoxFuture<'a, Result<Self::Representation, Error>>;
+    }
+
+    /// Follow [`Link`]s with an async client
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    pub trait Follow {
+        /// The representation of the resource that is linked to
+        type Representation;
+
+        /// Fetch the representation of the resource that is linked to
+        fn follow<'a>(
+            &'a self,
+            client: &'a dyn Client,
+        ) -> BoxFuture<'a, Result<Self::Representation, Error>>;
+    }
+
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    impl<T: Fetch> Follow for Link<T> {
+        type Representation = T::Representation;
+
+        fn follow<'a>(
+            &'a self,
+            client: &'a dyn Client,
+        ) -> BoxFuture<'a, Result<T::Representation, Error>> {
+            T::fetch(self.url(), client)
+        }
+    }
+
+    /// Fetch the representations of many resources, at most `limit` at a time
+    ///
+    /// The results are returned in the order of `urls`, regardless of the order in which the
+    /// responses arrive. A failure to fetch one resource does not stop the others from being
+    /// fetched.
+    ///
+    /// ```no_run
+    /// # async fn example(client: &dyn wadl::r#async::Client, urls: Vec<url::Url>) {
+    /// # struct Person;
+    /// # impl wadl::Resource for Person { fn url(&self) -> &url::Url { unimplemented!() } }
+    /// # impl wadl::r#async::Fetch for Person {
+    /// #     type Representation = ();
+    /// #     fn fetch<'a>(_: &'a url::Url, _: &'a dyn wadl::r#async::Client)
+    /// #         -> wadl::r#async::BoxFuture<'a, Result<(), wadl::Error>> { unimplemented!() }
+    /// # }
+    /// let people = wadl::r#async::fetch_all::<Person>(client, urls, 8).await;
+    /// # }
+    /// ```
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    pub async fn fetch_all<T: Fetch>(
+        client: &dyn Client,
+        urls: impl IntoIterator<Item = url::Url>,
+        limit: usize,
+    ) -> Vec<Result<T::Representation, Error>> {
+        let urls = urls.into_iter().collect::<Vec<_>>();
+        join_bounded(urls.iter().map(|url| T::fetch(url, client)), limit).await
+    }
+
+    /// Follow many [`Link`]s, at most `limit` at a time
+    ///
+    /// See [`fetch_all`].
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    pub async fn follow_all<'b, T: Fetch + 'b>(
+        client: &dyn Client,
+        links: impl IntoIterator<Item = &'b Link<T>>,
+        limit: usize,
+    ) -> Vec<Result<T::Representation, Error>> {
+        fetch_all::<T>(
+            client,
+            links.into_iter().map(|link| link.url().clone()),
+            limit,
+        )
+        .await
+    }
+
+    /// Run futures with at most `limit` of them in flight, returning their outputs in order
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    async fn join_bounded<'a, T>(
+        futures: impl Iterator<Item = BoxFuture<'a, T>>,
+        limit: usize,
+    ) -> Vec<T> {
+        let mut futures = futures.enumerate();
+        let mut running = Vec::<(usize, BoxFuture<'a, T>)>::new();
+        let mut outputs = Vec::<Option<T>>::new();
+        std::future::poll_fn(|cx| loop {
+            while running.len() < limit.max(1) {
+                let Some((i, future)) = futures.next() else {
+                    break;
+                };
+                outputs.push(None);
+                running.push((i, future));
+            }
+            if running.is_empty() {
+                return std::task::Poll::Ready(());
+            }
+            let before = running.len();
+            running.retain_mut(|(i, future)| match future.as_mut().poll(cx) {
+                std::task::Poll::Ready(output) => {
+                    outputs[*i] = Some(output);
+                    false
+                }
+                std::task::Poll::Pending => true,
+            });
+            if running.len() == before {
+                return std::task::Poll::Pending;
+            }
+        })
+        .await;
+        outputs
+            .into_iter()
+            .map(|output| output.expect("all futures completed"))
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+/// Blocking features
+pub mod blocking {
+    use super::*;
+
+    /// A client for a WADL API
+    pub trait Client {
+        /// Create a new request builder
+        fn request(
+            &self,
+            method: reqwest::Method,
+            url: url::Url,
+        ) -> reqwest::blocking::RequestBuilder;
+
+        /// Send a request built with [`Client::request`]
+        ///
+        /// Generated code sends all requests through this, so that implementations can wrap
+        /// them, e.g. with [`LayeredClient`].
+        fn send(
+            &self,
+            req: reqwest::blocking::RequestBuilder,
+        ) -> Result<reqwest::blocking::Response, Error> {
+            Ok(req.send()?)
+        }
+
+        /// Return the WADL of the service, if the client has it cached
+        ///
+        /// [`get_wadl_resource_by_href`] looks resources up in it before fetching their WADL.
+        fn application(&self) -> Option<&crate::ast::Application> {
+            None
+        }
+
+        /// Return the cache that WADL documents fetched with this client are kept in, if any
+        ///
+        /// [`get_wadl_resource_by_href`] fetches WADL documents through it.
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            None
+        }
+    }
+
+    impl Client for reqwest::blocking::Client {
+        fn request(
+            &self,
+            method: reqwest::Method,
+            url: url::Url,
+        ) -> reqwest::blocking::RequestBuilder {
+            self.request(method, url)
+        }
+    }
+
+    /// Create a reqwest client builder, configured for talking to WADL services
+    ///
+    /// The client identifies itself with [`USER_AGENT`], gives up after [`CONNECT_TIMEOUT`] and
+    /// [`TIMEOUT`], and trusts the root certificates in the PEM bundle named by
+    /// [`CA_BUNDLE_ENV`] in addition to the default ones. Proxies are taken from the
+    /// environment (`HTTPS_PROXY`, `HTTP_PROXY`, `NO_PROXY`) as usual. All of these can be
+    /// overridden on the returned builder.
+    ///
+    /// ```no_run
+    /// let client = wadl::blocking::WadlClient::new(wadl::blocking::client_builder()?.build()?);
+    /// # Ok::<(), wadl::Error>(())
+    /// ```
+    pub fn client_builder() -> Result<reqwest::blocking::ClientBuilder, Error> {
+        let mut builder = reqwest::blocking::Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(TIMEOUT);
+        for certificate in env_root_certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        Ok(builder)
+    }
+
+    /// A client for a WADL API, with a base URL, default headers and credentials
+    ///
+    /// It can be passed to generated code wherever that takes a client. The headers and
+    /// credentials are sent with every request.
+    #[derive(Debug, Clone)]
+    pub struct WadlClient {
+        client: reqwest::blocking::Client,
+        base_url: Option<url::Url>,
+        headers: reqwest::header::HeaderMap,
+        auth: Option<Auth>,
+        application: Option<std::sync::Arc<crate::ast::Application>>,
+        cache: Option<std::sync::Arc<crate::cache::ApplicationCache>>,
+        observer: Option<SharedObserver>,
+    }
+
+    impl Default for WadlClient {
+        fn default() -> Self {
+            Self::new(reqwest::blocking::Client::new())
+        }
+    }
+
+    impl WadlClient {
+        /// Wrap a reqwest client
+        pub fn new(client: reqwest::blocking::Client) -> Self {
+            Self {
+                client,
+                base_url: None,
+                headers: reqwest::header::HeaderMap::new(),
+                auth: None,
+                application: None,
+                cache: None,
+                observer: None,
+            }
+        }
+
+        /// Create a client for the service described by the WADL at a URL
+        ///
+        /// See [`WadlClient::with_wadl_url`].
+        pub fn from_wadl_url(url: &url::Url) -> Result<Self, Error> {
+            Self::default().with_wadl_url(url)
+        }
+
+        /// Fetch and cache the WADL of the service
+        ///
+        /// The WADL is fetched with the headers and credentials of this client. Unless a base
+        /// URL was set, the base of the first resources in the WADL is used.
+        pub fn with_wadl_url(mut self, url: &url::Url) -> Result<Self, Error> {
+            let application = if let Some(cache) = self.cache.as_ref() {
+                get_wadl_application(&self, url, cache)?
+            } else {
+                let res = self.send(
+                    self.request(reqwest::Method::GET, url.clone())
+                        .header(reqwest::header::ACCEPT, WADL_MIME_TYPE),
+                )?;
+                response::check_status(res.status(), &[])?;
+                std::sync::Arc::new(parse_string(&res.text()?)?)
+            };
+            if self.base_url.is_none() {
+                self.base_url = application.resources.iter().find_map(|r| r.base.clone());
+            }
+            self.application = Some(application);
+            Ok(self)
+        }
+
+        /// Set the URL that relative paths are resolved against, see [`WadlClient::join`]
+        pub fn with_base_url(mut self, base_url: url::Url) -> Self {
+            self.base_url = Some(base_url);
+            self
+        }
+
+        /// Add a header to send with every request
+        pub fn with_header(
+            mut self,
+            name: reqwest::header::HeaderName,
+            value: reqwest::header::HeaderValue,
+        ) -> Self {
+            self.headers.insert(name, value);
+            self
+        }
+
+        /// Set the credentials to send with every request
+        pub fn with_auth(mut self, auth: Auth) -> Self {
+            self.auth = Some(auth);
+            self
+        }
+
+        /// Keep the WADL documents fetched with this client in a cache
+        ///
+        /// Set this before [`WadlClient::with_wadl_url`] to fetch the WADL of the service
+        /// through the cache as well.
+        pub fn with_cache(mut self, cache: std::sync::Arc<crate::cache::ApplicationCache>) -> Self {
+            self.cache = Some(cache);
+            self
+        }
+
+        /// Report every request sent with this client, and its response, to an observer
+        pub fn with_observer(mut self, observer: impl RequestObserver + 'static) -> Self {
+            self.observer = Some(SharedObserver(std::sync::Arc::new(observer)));
+            self
+        }
+
+        /// Return the wrapped reqwest client
+        pub fn client(&self) -> &reqwest::blocking::Client {
+            &self.client
+        }
+
+        /// Return the base URL of the service
+        pub fn base_url(&self) -> Option<&url::Url> {
+            self.base_url.as_ref()
+        }
+
+        /// Return the cached WADL of the service
+        pub fn application(&self) -> Option<&crate::ast::Application> {
+            self.application.as_deref()
+        }
+
+        /// Resolve a path relative to the base URL
+        pub fn join(&self, path: &str) -> Result<url::Url, Error> {
+            Ok(self
+                .base_url
+                .as_ref()
+                .ok_or(Error::InvalidUrl)?
+                .join(path)?)
+        }
+    }
+
+    impl Client for WadlClient {
+        fn request(
+            &self,
+            method: reqwest::Method,
+            url: url::Url,
+        ) -> reqwest::blocking::RequestBuilder {
+            let req = self
+                .client
+                .request(method, url)
+                .headers(self.headers.clone());
+            match &self.auth {
+                None => req,
+                Some(Auth::Bearer(token)) => req.bearer_auth(token),
+                Some(Auth::Basic { username, password }) => {
+                    req.basic_auth(username, password.as_ref())
+                }
+            }
+        }
+
+        fn send(
+            &self,
+            req: reqwest::blocking::RequestBuilder,
+        ) -> Result<reqwest::blocking::Response, Error> {
+            match &self.observer {
+                Some(observer) => {
+                    let (client, req) = req.build_split();
+                    send_observed(&*observer.0, req?, |req| Ok(client.execute(req)?))
+                }
+                None => Ok(req.send()?),
+            }
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.application.as_deref()
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.cache.as_deref()
+        }
     }
 
-    #[async_trait::async_trait]
-    impl Client for reqwest::Client {
-        async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
-            self.request(method, url)
+    /// A response body that is streamed, rather than read into memory
+    ///
+    /// Generated methods return this for binary response bodies, like those with media type
+    /// `application/octet-stream`.
+    #[derive(Debug)]
+    pub struct ByteStream(reqwest::blocking::Response);
+
+    impl ByteStream {
+        /// Stream the body of a response
+        pub fn new(response: reqwest::blocking::Response) -> Self {
+            Self(response)
         }
     }
 
-    /// Get the WADL AST from a URL.
-    pub async fn get_wadl_resource_by_href(
-        client: &dyn Client,
-        href: &url::Url,
-    ) -> Result<crate::ast::Resource, Error> {
-        let mut req = client.request(reqwest::Method::GET, href.clone()).await;
+    impl std::io::Read for ByteStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
 
-        req = req.header(reqwest::header::ACCEPT, super::WADL_MIME_TYPE);
+    /// Data to upload as part of a multipart request
+    ///
+    /// Files and readers are sent without reading them into memory first.
+    pub enum Upload {
+        /// Data in memory
+        Bytes(Vec<u8>),
 
-        let res = req.send().await?;
+        /// The contents of a file, with its name as file name
+        Path(std::path::PathBuf),
 
-        let text = res.text().await?;
+        /// The data from a reader
+        Reader(Box<dyn std::io::Read + Send>),
+    }
 
-        let application = super::parse_string(&text)?;
+    impl Upload {
+        /// Create an upload that streams the data from a reader
+        pub fn reader(reader: impl std::io::Read + Send + 'static) -> Self {
+            Upload::Reader(Box::new(reader))
+        }
 
-        let resource = application.get_resource_by_href(href).unwrap();
+        /// Create the part of a multipart form for this upload
+        pub fn into_part(self) -> std::io::Result<reqwest::blocking::multipart::Part> {
+            Ok(match self {
+                Upload::Bytes(data) => reqwest::blocking::multipart::Part::bytes(data),
+                Upload::Path(path) => reqwest::blocking::multipart::Part::file(path)?,
+                Upload::Reader(reader) => reqwest::blocking::multipart::Part::reader(reader),
+            })
+        }
+    }
 
-        Ok(resource.clone())
+    impl From<Vec<u8>> for Upload {
+        fn from(data: Vec<u8>) -> Self {
+            Upload::Bytes(data)
+        }
     }
-}
 
-#[cfg(feature = "blocking")]
-/// Blocking features
-pub mod blocking {
-    use super::*;
+    impl From<std::path::PathBuf> for Upload {
+        fn from(path: std::path::PathBuf) -> Self {
+            Upload::Path(path)
+        }
+    }
 
-    /// A client for a WADL API
-    pub trait Client {
-        /// Create a new request builder
+    impl From<&std::path::Path> for Upload {
+        fn from(path: &std::path::Path) -> Self {
+            Upload::Path(path.to_path_buf())
+        }
+    }
+
+    /// Middleware that wraps the requests sent by a [`LayeredClient`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wadl::blocking::{Middleware, Next};
+    ///
+    /// struct UserAgent;
+    ///
+    /// impl Middleware for UserAgent {
+    ///     fn handle(
+    ///         &self,
+    ///         mut req: reqwest::blocking::Request,
+    ///         next: Next<'_>,
+    ///     ) -> Result<reqwest::blocking::Response, wadl::Error> {
+    ///         req.headers_mut().insert(
+    ///             reqwest::header::USER_AGENT,
+    ///             reqwest::header::HeaderValue::from_static("my-app/1.0"),
+    ///         );
+    ///         next.run(req)
+    ///     }
+    /// }
+    ///
+    /// let client = wadl::blocking::LayeredClient::new(reqwest::blocking::Client::new())
+    ///     .layer(UserAgent);
+    /// ```
+    pub trait Middleware: Send + Sync {
+        /// Handle a request, usually by passing it on to `next`
+        fn handle(
+            &self,
+            req: reqwest::blocking::Request,
+            next: Next<'_>,
+        ) -> Result<reqwest::blocking::Response, Error>;
+    }
+
+    /// The rest of the middleware chain, ending in the HTTP client
+    #[derive(Clone, Copy)]
+    pub struct Next<'a> {
+        client: &'a reqwest::blocking::Client,
+        middleware: &'a [Box<dyn Middleware>],
+    }
+
+    impl Next<'_> {
+        /// Pass a request on to the next middleware, or send it if there is none
+        pub fn run(
+            self,
+            req: reqwest::blocking::Request,
+        ) -> Result<reqwest::blocking::Response, Error> {
+            match self.middleware.split_first() {
+                Some((first, rest)) => first.handle(
+                    req,
+                    Next {
+                        client: self.client,
+                        middleware: rest,
+                    },
+                ),
+                None => Ok(self.client.execute(req)?),
+            }
+        }
+    }
+
+    /// Observe the requests sent by a [`WadlClient`] or [`LayeredClient`]
+    ///
+    /// Register one with [`WadlClient::with_observer`] or [`LayeredClient::with_observer`], e.g.
+    /// to log requests, to assert on them in tests or to record them as fixtures.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wadl::blocking::RequestObserver;
+    ///
+    /// struct Logger;
+    ///
+    /// impl RequestObserver for Logger {
+    ///     fn on_response(&self, resp: &reqwest::blocking::Response, elapsed: std::time::Duration) {
+    ///         log::debug!("{}: {} in {:?}", resp.url(), resp.status(), elapsed);
+    ///     }
+    /// }
+    ///
+    /// let client = wadl::blocking::WadlClient::default().with_observer(Logger);
+    /// ```
+    pub trait RequestObserver: Send + Sync {
+        /// Called before a request is sent
+        fn on_request(&self, _req: &reqwest::blocking::Request) {}
+
+        /// Called when the response to a request has been received
+        ///
+        /// `elapsed` is the time between sending the request and receiving the headers of the
+        /// response; the body may not have been read yet.
+        fn on_response(&self, _resp: &reqwest::blocking::Response, _elapsed: std::time::Duration) {}
+
+        /// Called when sending a request failed, without a response
+        fn on_error(&self, _err: &Error, _elapsed: std::time::Duration) {}
+    }
+
+    impl<O: RequestObserver + ?Sized> RequestObserver for std::sync::Arc<O> {
+        fn on_request(&self, req: &reqwest::blocking::Request) {
+            (**self).on_request(req)
+        }
+
+        fn on_response(&self, resp: &reqwest::blocking::Response, elapsed: std::time::Duration) {
+            (**self).on_response(resp, elapsed)
+        }
+
+        fn on_error(&self, err: &Error, elapsed: std::time::Duration) {
+            (**self).on_error(err, elapsed)
+        }
+    }
+
+    #[derive(Clone)]
+    struct SharedObserver(std::sync::Arc<dyn RequestObserver>);
+
+    impl std::fmt::Debug for SharedObserver {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.debug_struct("RequestObserver").finish_non_exhaustive()
+        }
+    }
+
+    /// Send a request with `execute`, reporting it and its outcome to an observer
+    fn send_observed(
+        observer: &(impl RequestObserver + ?Sized),
+        req: reqwest::blocking::Request,
+        execute: impl FnOnce(reqwest::blocking::Request) -> Result<reqwest::blocking::Response, Error>,
+    ) -> Result<reqwest::blocking::Response, Error> {
+        observer.on_request(&req);
+        let stopwatch = Stopwatch::start();
+        let result = execute(req);
+        match &result {
+            Ok(resp) => observer.on_response(resp, stopwatch.elapsed()),
+            Err(err) => observer.on_error(err, stopwatch.elapsed()),
+        }
+        result
+    }
+
+    /// Middleware that reports requests to an observer, see [`LayeredClient::with_observer`]
+    struct Observed<O>(O);
+
+    impl<O: RequestObserver> Middleware for Observed<O> {
+        fn handle(
+            &self,
+            req: reqwest::blocking::Request,
+            next: Next<'_>,
+        ) -> Result<reqwest::blocking::Response, Error> {
+            send_observed(&self.0, req, |req| next.run(req))
+        }
+    }
+
+    /// A client that sends its requests through a chain of [`Middleware`]
+    ///
+    /// Requests are built by the inner client, and passed through the middleware in the order
+    /// in which it was added.
+    pub struct LayeredClient<C> {
+        inner: C,
+        middleware: Vec<Box<dyn Middleware>>,
+    }
+
+    impl<C> LayeredClient<C> {
+        /// Wrap a client, without any middleware
+        pub fn new(inner: C) -> Self {
+            Self {
+                inner,
+                middleware: vec![],
+            }
+        }
+
+        /// Add a middleware, which is run after the ones that were added before
+        pub fn layer(mut self, middleware: impl Middleware + 'static) -> Self {
+            self.middleware.push(Box::new(middleware));
+            self
+        }
+
+        /// Report the requests passed through this layer, and their responses, to an observer
+        ///
+        /// The observer sees requests as modified by the middleware added before it.
+        pub fn with_observer(self, observer: impl RequestObserver + 'static) -> Self {
+            self.layer(Observed(observer))
+        }
+
+        /// Return the inner client
+        pub fn inner(&self) -> &C {
+            &self.inner
+        }
+    }
+
+    impl<C: Client> Client for LayeredClient<C> {
         fn request(
             &self,
             method: reqwest::Method,
             url: url::Url,
-        ) -> reqwest::blocking::RequestBuilder;
+        ) -> reqwest::blocking::RequestBuilder {
+            self.inner.request(method, url)
+        }
+
+        fn send(
+            &self,
+            req: reqwest::blocking::RequestBuilder,
+        ) -> Result<reqwest::blocking::Response, Error> {
+            let (client, req) = req.build_split();
+            Next {
+                client: &client,
+                middleware: &self.middleware,
+            }
+            .run(req?)
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.inner.application()
+        }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.inner.application_cache()
+        }
     }
 
-    impl Client for reqwest::blocking::Client {
+    /// A client that retries requests as described by a [`RetryPolicy`]
+    ///
+    /// Every request sent by generated code is retried, in addition to any retries configured
+    /// for its method. Requests with a body that can not be cloned are sent only once.
+    pub struct RetryingClient<C> {
+        inner: C,
+        policy: RetryPolicy,
+    }
+
+    impl<C> RetryingClient<C> {
+        /// Wrap a client, retrying its requests as described by `policy`
+        pub fn new(inner: C, policy: RetryPolicy) -> Self {
+            Self { inner, policy }
+        }
+
+        /// Return the inner client
+        pub fn inner(&self) -> &C {
+            &self.inner
+        }
+
+        /// Return the retry policy
+        pub fn policy(&self) -> &RetryPolicy {
+            &self.policy
+        }
+    }
+
+    impl<C: Client> Client for RetryingClient<C> {
         fn request(
             &self,
             method: reqwest::Method,
             url: url::Url,
         ) -> reqwest::blocking::RequestBuilder {
-            self.request(method, url)
+            self.inner.request(method, url)
+        }
+
+        fn send(
+            &self,
+            req: reqwest::blocking::RequestBuilder,
+        ) -> Result<reqwest::blocking::Response, Error> {
+            send_with_retry(&self.inner, req, &self.policy)
+        }
+
+        fn application(&self) -> Option<&crate::ast::Application> {
+            self.inner.application()
         }
+
+        fn application_cache(&self) -> Option<&crate::cache::ApplicationCache> {
+            self.inner.application_cache()
+        }
+    }
+
+    /// Send a request, retrying it as described by a retry policy
+    ///
+    /// Requests with a body that can not be cloned, like a reader, are sent only once.
+    pub fn send_with_retry(
+        client: &(impl Client + ?Sized),
+        req: reqwest::blocking::RequestBuilder,
+        policy: &RetryPolicy,
+    ) -> Result<reqwest::blocking::Response, Error> {
+        let mut attempt = 0;
+        loop {
+            let Some(retry) = req.try_clone() else {
+                return client.send(req);
+            };
+            let delay = match client.send(retry) {
+                Ok(resp) => match policy.delay(attempt, resp.status(), resp.headers()) {
+                    Some(delay) => delay,
+                    None => return Ok(resp),
+                },
+                Err(err) => match policy.error_delay(attempt, &err) {
+                    Some(delay) => delay,
+                    None => return Err(err),
+                },
+            };
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Parse the JSON body of a response, after checking its status and content type
+    ///
+    /// See [`response::check_status`] for the meaning of `expected_statuses`.
+    pub fn parse_json_response<T: serde::de::DeserializeOwned>(
+        resp: reqwest::blocking::Response,
+        expected_statuses: &[u16],
+    ) -> Result<T, Error> {
+        response::check_json_response(resp.status(), resp.headers(), expected_statuses)?;
+        Ok(resp.json::<T>()?)
+    }
+
+    /// Fetch the JSON representation of a resource again, if it changed
+    ///
+    /// The request is conditional on the entity tag of `representation`, so that the server
+    /// only sends the representation if the resource was modified; it is only deserialized in
+    /// that case. Returns whether the representation changed.
+    ///
+    /// ```no_run
+    /// # fn poll(client: &dyn wadl::blocking::Client, url: &url::Url) -> Result<(), wadl::Error> {
+    /// let mut person = wadl::Tagged::<serde_json::Value> { value: Default::default(), etag: None };
+    /// loop {
+    ///     if wadl::blocking::refresh(client, url, &mut person)? {
+    ///         println!("{}", person.value);
+    ///     }
+    ///     std::thread::sleep(std::time::Duration::from_secs(60));
+    /// }
+    /// # }
+    /// ```
+    pub fn refresh<T: serde::de::DeserializeOwned>(
+        client: &(impl Client + ?Sized),
+        url: &url::Url,
+        representation: &mut Tagged<T>,
+    ) -> Result<bool, Error> {
+        let mut req = client
+            .request(reqwest::Method::GET, url.clone())
+            .header(reqwest::header::ACCEPT, "application/json");
+        if let Some(etag) = representation.etag.as_ref() {
+            req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let resp = client.send(req)?;
+        if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(false);
+        }
+        let etag = resp
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        representation.value = parse_json_response(resp, &[])?;
+        representation.etag = etag;
+        Ok(true)
+    }
+
+    /// Fetch the WADL document at a URL through a cache
+    ///
+    /// A cached document is revalidated with a conditional request, and only downloaded and
+    /// parsed again if it changed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(client, cache), fields(url = %url), err)
+    )]
+    pub fn get_wadl_application(
+        client: &(impl Client + ?Sized),
+        url: &url::Url,
+        cache: &crate::cache::ApplicationCache,
+    ) -> Result<std::sync::Arc<crate::ast::Application>, Error> {
+        let cached = cache.get(url);
+        let mut req = client
+            .request(reqwest::Method::GET, url.clone())
+            .header(reqwest::header::ACCEPT, WADL_MIME_TYPE);
+        for (name, value) in cached.iter().flat_map(|c| c.conditional_headers()) {
+            req = req.header(name, value);
+        }
+        #[cfg(feature = "tracing")]
+        let stopwatch = Stopwatch::start();
+        let res = client.send(req)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(status = %res.status(), elapsed = ?stopwatch.elapsed(), "fetched WADL");
+        if let (reqwest::StatusCode::NOT_MODIFIED, Some(cached)) = (res.status(), cached) {
+            return Ok(cached.application);
+        }
+        response::check_status(res.status(), &[])?;
+        let header = |name| {
+            res.headers()
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        cache.insert(url, &res.text()?, etag, last_modified)
     }
 
-    /// Get the WADL AST from a URL.
+    /// Look up the WADL resource at a URL
+    ///
+    /// The resource is looked up in `application` and then in the WADL cached by the client,
+    /// if any; otherwise the WADL is fetched from the URL itself, through the application
+    /// cache of the client if it has one.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(client, application),
+            fields(href = %href),
+            err
+        )
+    )]
     pub fn get_wadl_resource_by_href(
         client: &dyn Client,
         href: &url::Url,
+        application: Option<&crate::ast::Application>,
     ) -> Result<crate::ast::Resource, Error> {
+        if let Some(resource) = application
+            .into_iter()
+            .chain(client.application())
+            .find_map(|a| a.get_resource_by_href(href))
+        {
+            #[cfg(feature = "tracing")]
+            tracing::trace!("found resource in known WADL");
+            return Ok(resource.clone());
+        }
+
+        if let Some(cache) = client.application_cache() {
+            let application = get_wadl_application(client, href, cache)?;
+            return application
+                .get_resource_by_href(href)
+                .cloned()
+                .ok_or_else(|| Error::ResourceNotFound(href.clone()));
+        }
+
         let mut req = client.request(reqwest::Method::GET, href.clone());
 
         req = req.header(reqwest::header::ACCEPT, WADL_MIME_TYPE);
 
-        let res = req.send()?;
+        #[cfg(feature = "tracing")]
+        let stopwatch = Stopwatch::start();
+        let res = client.send(req)?;
+
+        response::check_status(res.status(), &[])?;
 
         let text = res.text()?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(size = text.len(), elapsed = ?stopwatch.elapsed(), "fetched WADL");
 
         let application = parse_string(&text)?;
 
-        let resource = application.get_resource_by_href(href).unwrap();
+        let resource = application
+            .get_resource_by_href(href)
+            .ok_or_else(|| Error::ResourceNotFound(href.clone()))?;
 
         Ok(resource.clone())
     }
+
+    /// A resource whose representation can be fetched, so that [`Link`]s to it can be followed
+    ///
+    /// Implemented by generated code for resource types with a GET method without arguments.
+    pub trait Fetch: Resource {
+        /// The representation returned by the GET method
+        type Representation;
+
+        /// Fetch the representation of the resource at a URL
+        ///
+        /// The URL is not checked against the paths of the resource type, since links returned
+        /// by the service are trusted to point to resources of the right type.
+        fn fetch(url: &url::Url, client: &dyn Client) -> Result<Self::Representation, Error>;
+    }
+
+    /// Follow [`Link`]s with a blocking client
+    pub trait Follow {
+        /// The representation of the resource that is linked to
+        type Representation;
+
+        /// Fetch the representation of the resource that is linked to
+        fn follow(&self, client: &dyn Client) -> Result<Self::Representation, Error>;
+    }
+
+    impl<T: Fetch> Follow for Link<T> {
+        type Representation = T::Representation;
+
+        fn follow(&self, client: &dyn Client) -> Result<T::Representation, Error> {
+            T::fetch(self.url(), client)
+        }
+    }
 }
 
-#[derive(Debug)]
 /// The error type for this crate.
+///
+/// Variants are grouped into errors sending a request, errors in the response of the server,
+/// and errors decoding a response. Use [`Error::kind`] to classify them without matching on
+/// the variants; new variants may be added in any release. Wrapped errors are returned by
+/// [`std::error::Error::source`].
+#[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
+    // Transport errors: the request could not be built or sent
     /// The URL is invalid.
     InvalidUrl,
 
+    /// The URL could not be parsed.
+    Url(url::ParseError),
+
+    /// The name of an HTTP method is invalid.
+    InvalidMethod(String),
+
     /// A reqwest error occurred.
+    #[cfg(feature = "http")]
     Reqwest(reqwest::Error),
 
-    /// The URL could not be parsed.
-    Url(url::ParseError),
+    /// An I/O error occurred.
+    Io(std::io::Error),
 
-    /// The JSON could not be parsed.
-    Json(serde_json::Error),
+    #[cfg(any(feature = "tower", feature = "ureq"))]
+    /// The HTTP request could not be built.
+    Http(http::Error),
 
-    /// The WADL could not be parsed.
-    Wadl(ParseError),
+    #[cfg(feature = "tower")]
+    /// The service returned an error.
+    Service(Box<dyn std::error::Error + Send + Sync>),
 
+    #[cfg(feature = "ureq")]
+    /// A ureq error occurred.
+    Ureq(::ureq::Error),
+
+    // Protocol errors: the server responded, but not as expected
     /// The response status was not handled by the library.
+    #[cfg(feature = "http")]
     UnhandledStatus(reqwest::StatusCode),
 
     /// The response content type was not handled by the library.
     UnhandledContentType(Option<mime::Mime>),
 
-    /// An I/O error occurred.
-    Io(std::io::Error),
+    /// The resource was modified since its entity tag was retrieved.
+    PreconditionFailed,
+
+    /// The resource was not found in the WADL of the service.
+    ResourceNotFound(url::Url),
+
+    // Decode errors: the body or headers of the response could not be parsed
+    /// The JSON could not be parsed.
+    Json(serde_json::Error),
+
+    /// The WADL could not be parsed.
+    Wadl(ParseError),
+
+    /// A field of a form-urlencoded body was missing or could not be parsed.
+    InvalidFormField(String),
+
+    /// A response header was missing or could not be parsed.
+    InvalidHeader(String),
+
+    /// A value is not one of the options of a generated options enum, whose name is given.
+    InvalidOption(&'static str, String),
+}
+
+/// The class of failure of an [`Error`], as returned by [`Error::kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The request timed out
+    Timeout,
+
+    /// No connection could be made to the server
+    Connect,
+
+    /// The server responded that the resource does not exist, or it is not in the WADL
+    NotFound,
+
+    /// The server responded with `429 Too Many Requests`
+    RateLimited,
+
+    /// The server responded with `401 Unauthorized` or `403 Forbidden`
+    Unauthorized,
+
+    /// The resource was modified since its entity tag was retrieved
+    PreconditionFailed,
+
+    /// The server responded with a `5xx` status
+    ServerError,
+
+    /// The server responded with another unexpected status
+    Status,
+
+    /// The response could not be parsed
+    Decode,
+
+    /// The request could not be built, e.g. because of an invalid URL
+    InvalidRequest,
+
+    /// Any other error, e.g. an I/O error
+    Other,
+}
+
+#[cfg(feature = "http")]
+impl ErrorKind {
+    /// Return the kind of error for an unexpected response status
+    fn from_status(status: reqwest::StatusCode) -> Self {
+        match status.as_u16() {
+            404 | 410 => ErrorKind::NotFound,
+            429 => ErrorKind::RateLimited,
+            401 | 403 => ErrorKind::Unauthorized,
+            412 => ErrorKind::PreconditionFailed,
+            500..=599 => ErrorKind::ServerError,
+            _ => ErrorKind::Status,
+        }
+    }
+}
+
+impl Error {
+    /// Return the class of failure of this error
+    pub fn kind(&self) -> ErrorKind {
+        #[cfg(feature = "http")]
+        if let Some(status) = self.status() {
+            return ErrorKind::from_status(status);
+        }
+        match self {
+            #[cfg(feature = "http")]
+            Error::Reqwest(err) if err.is_timeout() => ErrorKind::Timeout,
+            #[cfg(feature = "http")]
+            Error::Reqwest(err) if err.is_connect() => ErrorKind::Connect,
+            #[cfg(feature = "http")]
+            Error::Reqwest(err) if err.is_decode() => ErrorKind::Decode,
+            #[cfg(feature = "http")]
+            Error::Reqwest(err) if err.is_builder() => ErrorKind::InvalidRequest,
+            #[cfg(feature = "http")]
+            Error::Reqwest(_) => ErrorKind::Other,
+            Error::Io(err) => match err.kind() {
+                std::io::ErrorKind::TimedOut => ErrorKind::Timeout,
+                std::io::ErrorKind::ConnectionRefused => ErrorKind::Connect,
+                _ => ErrorKind::Other,
+            },
+            Error::InvalidUrl | Error::Url(_) | Error::InvalidMethod(_) => {
+                ErrorKind::InvalidRequest
+            }
+            Error::Json(_)
+            | Error::Wadl(_)
+            | Error::UnhandledContentType(_)
+            | Error::InvalidFormField(_)
+            | Error::InvalidHeader(_)
+            | Error::InvalidOption(..) => ErrorKind::Decode,
+            Error::PreconditionFailed => ErrorKind::PreconditionFailed,
+            Error::ResourceNotFound(_) => ErrorKind::NotFound,
+            #[cfg(feature = "http")]
+            Error::UnhandledStatus(status) => ErrorKind::from_status(*status),
+            #[cfg(any(feature = "tower", feature = "ureq"))]
+            Error::Http(_) => ErrorKind::InvalidRequest,
+            #[cfg(feature = "tower")]
+            Error::Service(_) => ErrorKind::Other,
+            #[cfg(feature = "ureq")]
+            Error::Ureq(err) => match err {
+                ::ureq::Error::Timeout(_) => ErrorKind::Timeout,
+                ::ureq::Error::HostNotFound | ::ureq::Error::ConnectionFailed => ErrorKind::Connect,
+                ::ureq::Error::Http(_) | ::ureq::Error::BadUri(_) => ErrorKind::InvalidRequest,
+                _ => ErrorKind::Other,
+            },
+        }
+    }
+
+    /// Return the status of the response that caused this error, if any
+    #[cfg(feature = "http")]
+    pub fn status(&self) -> Option<reqwest::StatusCode> {
+        match self {
+            Error::UnhandledStatus(status) => Some(*status),
+            Error::PreconditionFailed => Some(reqwest::StatusCode::PRECONDITION_FAILED),
+            Error::Reqwest(err) => err.status(),
+            #[cfg(feature = "ureq")]
+            Error::Ureq(::ureq::Error::StatusCode(status)) => {
+                reqwest::StatusCode::from_u16(*status).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Return whether the request timed out
+    pub fn is_timeout(&self) -> bool {
+        self.kind() == ErrorKind::Timeout
+    }
+
+    /// Return whether no connection could be made to the server
+    pub fn is_connect(&self) -> bool {
+        self.kind() == ErrorKind::Connect
+    }
+
+    /// Return whether the resource was not found
+    pub fn is_not_found(&self) -> bool {
+        self.kind() == ErrorKind::NotFound
+    }
+
+    /// Return whether the server rejected the request because too many requests were made
+    pub fn is_rate_limited(&self) -> bool {
+        self.kind() == ErrorKind::RateLimited
+    }
+
+    /// Return whether the request may succeed if it is sent again later
+    ///
+    /// This is the case for timeouts, connection errors, rate limiting and server errors
+    /// other than `501 Not Implemented`.
+    pub fn is_retryable(&self) -> bool {
+        match self.kind() {
+            ErrorKind::Timeout | ErrorKind::Connect | ErrorKind::RateLimited => true,
+            #[cfg(feature = "http")]
+            ErrorKind::ServerError => self.status() != Some(reqwest::StatusCode::NOT_IMPLEMENTED),
+            _ => false,
+        }
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -151,20 +2527,55 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Error::InvalidUrl => write!(f, "Invalid URL"),
+            #[cfg(feature = "http")]
             Error::Reqwest(err) => write!(f, "Reqwest error: {}", err),
             Error::Url(err) => write!(f, "URL error: {}", err),
             Error::Json(err) => write!(f, "JSON error: {}", err),
             Error::Wadl(err) => write!(f, "WADL error: {}", err),
             Error::UnhandledContentType(Some(c)) => write!(f, "Unhandled content type: {}", c),
             Error::UnhandledContentType(None) => write!(f, "No content type"),
+            #[cfg(feature = "http")]
             Error::UnhandledStatus(s) => write!(f, "Unhandled status: {}", s),
             Error::Io(err) => write!(f, "IO error: {}", err),
+            Error::PreconditionFailed => write!(f, "Precondition failed"),
+            Error::InvalidFormField(name) => write!(f, "Invalid form field: {}", name),
+            Error::InvalidHeader(name) => write!(f, "Invalid header: {}", name),
+            Error::InvalidOption(name, value) => {
+                write!(f, "Invalid value for {}: {}", name, value)
+            }
+            Error::ResourceNotFound(url) => write!(f, "Resource not found in WADL: {}", url),
+            Error::InvalidMethod(name) => write!(f, "Invalid HTTP method: {}", name),
+            #[cfg(any(feature = "tower", feature = "ureq"))]
+            Error::Http(err) => write!(f, "HTTP error: {}", err),
+            #[cfg(feature = "tower")]
+            Error::Service(err) => write!(f, "Service error: {}", err),
+            #[cfg(feature = "ureq")]
+            Error::Ureq(err) => write!(f, "Ureq error: {}", err),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Url(err) => Some(err),
+            #[cfg(feature = "http")]
+            Error::Reqwest(err) => Some(err),
+            Error::Io(err) => Some(err),
+            #[cfg(any(feature = "tower", feature = "ureq"))]
+            Error::Http(err) => Some(err),
+            #[cfg(feature = "tower")]
+            Error::Service(err) => Some(err.as_ref()),
+            #[cfg(feature = "ureq")]
+            Error::Ureq(err) => Some(err),
+            Error::Json(err) => Some(err),
+            Error::Wadl(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
+#[cfg(feature = "http")]
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Self {
         Error::Reqwest(err)
@@ -182,3 +2593,241 @@ impl From<ParseError> for Error {
         Error::Wadl(err)
     }
 }
+
+#[cfg(any(feature = "tower", feature = "ureq"))]
+impl From<http::Error> for Error {
+    fn from(err: http::Error) -> Self {
+        Error::Http(err)
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl From<::ureq::Error> for Error {
+    fn from(err: ::ureq::Error) -> Self {
+        Error::Ureq(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(feature = "blocking", feature = "async-native"))]
+    #[test]
+    fn test_root_certificates() {
+        assert!(root_certificates(None).unwrap().is_empty());
+        assert!(matches!(
+            root_certificates(Some(std::path::Path::new("/nonexistent/ca.pem"))),
+            Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn test_url_matches_template() {
+        let base: Url = "https://api.example.com/1.0/".parse().unwrap();
+        let url: Url = "https://api.example.com/1.0/people/jelmer".parse().unwrap();
+        assert!(url_matches_template(Some(&base), "people/{name}", &url));
+        assert!(!url_matches_template(Some(&base), "people", &url));
+        assert!(!url_matches_template(Some(&base), "bugs/{id}", &url));
+        assert!(url_matches_template(None, "1.0/people/{name}", &url));
+
+        let root: Url = "https://api.example.com/1.0/".parse().unwrap();
+        assert!(url_matches_template(Some(&base), "", &root));
+
+        let other: Url = "https://example.com/1.0/people/jelmer".parse().unwrap();
+        assert!(!url_matches_template(Some(&base), "people/{name}", &other));
+
+        let sibling: Url = "https://api.example.com/1.01/people".parse().unwrap();
+        assert!(!url_matches_template(
+            Some(&base),
+            "{version}/people",
+            &sibling
+        ));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_error_kind() {
+        let not_found = Error::UnhandledStatus(reqwest::StatusCode::NOT_FOUND);
+        assert_eq!(not_found.kind(), ErrorKind::NotFound);
+        assert!(not_found.is_not_found());
+        assert!(!not_found.is_retryable());
+        assert!(Error::ResourceNotFound("https://example.com/".parse().unwrap()).is_not_found());
+
+        let rate_limited = Error::UnhandledStatus(reqwest::StatusCode::TOO_MANY_REQUESTS);
+        assert!(rate_limited.is_rate_limited());
+        assert!(rate_limited.is_retryable());
+
+        let unavailable = Error::UnhandledStatus(reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(unavailable.kind(), ErrorKind::ServerError);
+        assert!(unavailable.is_retryable());
+        assert!(!Error::UnhandledStatus(reqwest::StatusCode::NOT_IMPLEMENTED).is_retryable());
+
+        assert_eq!(
+            Error::PreconditionFailed.status(),
+            Some(reqwest::StatusCode::PRECONDITION_FAILED)
+        );
+        assert_eq!(
+            Error::InvalidHeader("etag".to_string()).kind(),
+            ErrorKind::Decode
+        );
+        assert_eq!(Error::InvalidUrl.kind(), ErrorKind::InvalidRequest);
+        let invalid_option = Error::InvalidOption("Status", "bogus".to_string());
+        assert_eq!(invalid_option.kind(), ErrorKind::Decode);
+        assert_eq!(
+            invalid_option.to_string(),
+            "Invalid value for Status: bogus"
+        );
+
+        let timeout = Error::Io(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert!(timeout.is_timeout());
+        assert!(timeout.is_retryable());
+        let refused = Error::Io(std::io::Error::from(std::io::ErrorKind::ConnectionRefused));
+        assert!(refused.is_connect());
+        assert_eq!(refused.status(), None);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_parse() {
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata};
+
+        /// Record the names of spans and the messages of events
+        #[derive(Default, Clone)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut names = self.0.lock().unwrap();
+                names.push(span.metadata().name().to_string());
+                Id::from_u64(names.len() as u64)
+            }
+
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                struct Message(Option<String>);
+
+                impl tracing::field::Visit for Message {
+                    fn record_debug(
+                        &mut self,
+                        field: &tracing::field::Field,
+                        value: &dyn std::fmt::Debug,
+                    ) {
+                        if field.name() == "message" {
+                            self.0 = Some(format!("{:?}", value));
+                        }
+                    }
+                }
+
+                let mut message = Message(None);
+                event.record(&mut message);
+                self.0.lock().unwrap().extend(message.0);
+            }
+
+            fn enter(&self, _span: &Id) {}
+
+            fn exit(&self, _span: &Id) {}
+        }
+
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            parse_string(
+                r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+                    <resources base="https://api.example.com/1.0/">
+                        <resource path="people" id="people"/>
+                    </resources>
+                </application>"#,
+            )
+            .unwrap();
+        });
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["parse_string", "parse", "parsed WADL"]
+        );
+    }
+
+    #[test]
+    fn test_error_source() {
+        use std::error::Error as _;
+        let err = Error::from(std::io::Error::other("disk on fire"));
+        assert_eq!(err.source().unwrap().to_string(), "disk on fire");
+
+        let err = Error::from(parse_string("<application").unwrap_err());
+        let source = err.source().unwrap();
+        assert!(source.is::<ParseError>());
+        assert!(source.source().is_some());
+
+        assert!(Error::InvalidUrl.source().is_none());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_retry_policy_delay() {
+        use std::time::Duration;
+        let policy = RetryPolicy::new(2)
+            .with_backoff(Duration::from_secs(1))
+            .with_max_backoff(Duration::from_secs(3))
+            .with_jitter(false);
+        let headers = reqwest::header::HeaderMap::new();
+        let unavailable = reqwest::StatusCode::SERVICE_UNAVAILABLE;
+        assert_eq!(
+            policy.delay(0, unavailable, &headers),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            policy.delay(1, unavailable, &headers),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(policy.delay(2, unavailable, &headers), None);
+        assert_eq!(
+            policy.delay(0, reqwest::StatusCode::NOT_FOUND, &headers),
+            None
+        );
+
+        let policy = policy.with_statuses([404]).with_jitter(true);
+        assert_eq!(policy.delay(0, unavailable, &headers), None);
+        let delay = policy
+            .delay(1, reqwest::StatusCode::NOT_FOUND, &headers)
+            .unwrap();
+        assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(2));
+
+        let policy = RetryPolicy::new(5).with_max_backoff(Duration::from_secs(60));
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "10".parse().unwrap());
+        assert_eq!(
+            policy.delay(0, unavailable, &headers),
+            Some(Duration::from_secs(10))
+        );
+        headers.insert(reqwest::header::RETRY_AFTER, "3600".parse().unwrap());
+        assert_eq!(policy.delay(0, unavailable, &headers), None);
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            "Sun, 06 Nov 1994 08:49:37 GMT".parse().unwrap(),
+        );
+        assert_eq!(policy.delay(0, unavailable, &headers), Some(Duration::ZERO));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(784111777))
+        );
+        assert_eq!(
+            parse_http_date("Thu, 29 Feb 2024 00:00:00 GMT"),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1709164800))
+        );
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("soon"), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Typed links between resources
+//!
+//! Representations link to other resources by URL. A [`Link`] records the type of the
+//! resource it points to, so that it can be followed to fetch the representation of that
+//! resource without naming its type again: see `wadl::blocking::Follow` and
+//! `wadl::r#async::Follow`. Generated code uses it for links to resource types when
+//! [`Config::typed_links`](crate::codegen::Config::typed_links) is set.
+
+use crate::{Error, Resource};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::marker::PhantomData;
+use url::Url;
+
+/// A link to a resource of type `T`
+///
+/// Serialized as the URL of the resource. The link is lazy: nothing is fetched until it is
+/// followed.
+pub struct Link<T> {
+    url: Url,
+    resource: PhantomData<fn() -> T>,
+}
+
+impl<T> Link<T> {
+    /// Create a link to the resource at a URL
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            resource: PhantomData,
+        }
+    }
+
+    /// Return the URL of the resource
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Convert into the URL of the resource
+    pub fn into_url(self) -> Url {
+        self.url
+    }
+}
+
+impl<T: TryFrom<Url, Error = Error>> Link<T> {
+    /// Return the resource that is linked to
+    ///
+    /// Fails with [`Error::InvalidUrl`] if the URL is not one of a resource of type `T`.
+    pub fn resource(&self) -> Result<T, Error> {
+        T::try_from(self.url.clone())
+    }
+}
+
+impl<T: Resource> From<&T> for Link<T> {
+    fn from(resource: &T) -> Self {
+        Self::new(resource.url().clone())
+    }
+}
+
+impl<T> From<Url> for Link<T> {
+    fn from(url: Url) -> Self {
+        Self::new(url)
+    }
+}
+
+impl<T> From<Link<T>> for Url {
+    fn from(link: Link<T>) -> Self {
+        link.url
+    }
+}
+
+impl<T> std::str::FromStr for Link<T> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Ok(Self::new(s.parse()?))
+    }
+}
+
+impl<T> std::ops::Deref for Link<T> {
+    type Target = Url;
+
+    fn deref(&self) -> &Url {
+        &self.url
+    }
+}
+
+// These are implemented by hand, since deriving them would require `T` to implement them too
+
+impl<T> Clone for Link<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.url.clone())
+    }
+}
+
+impl<T> std::fmt::Debug for Link<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Link").field(&self.url.as_str()).finish()
+    }
+}
+
+impl<T> std::fmt::Display for Link<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.url.fmt(f)
+    }
+}
+
+impl<T> PartialEq for Link<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.url == other.url
+    }
+}
+
+impl<T> Eq for Link<T> {}
+
+impl<T> PartialOrd for Link<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Link<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.url.cmp(&other.url)
+    }
+}
+
+impl<T> std::hash::Hash for Link<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.url.hash(state)
+    }
+}
+
+impl<T> Serialize for Link<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.url.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Link<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Url::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Person(Url);
+
+    impl Resource for Person {
+        fn url(&self) -> &Url {
+            &self.0
+        }
+    }
+
+    impl TryFrom<Url> for Person {
+        type Error = Error;
+
+        fn try_from(url: Url) -> Result<Self, Error> {
+            if url.path().starts_with("/~") {
+                Ok(Person(url))
+            } else {
+                Err(Error::InvalidUrl)
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entry {
+        owner_link: Link<Person>,
+    }
+
+    #[test]
+    fn test_serde() {
+        let entry: Entry =
+            serde_json::from_str(r#"{"owner_link": "https://api.example.com/~jo"}"#).unwrap();
+        assert_eq!(entry.owner_link.as_str(), "https://api.example.com/~jo");
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"owner_link":"https://api.example.com/~jo"}"#
+        );
+        assert!(serde_json::from_str::<Entry>(r#"{"owner_link": "not a url"}"#).is_err());
+    }
+
+    #[test]
+    fn test_resource() {
+        let link = Link::<Person>::new("https://api.example.com/~jo".parse().unwrap());
+        let person = link.resource().unwrap();
+        assert_eq!(Link::from(&person), link);
+
+        let link = Link::<Person>::new("https://api.example.com/bugs".parse().unwrap());
+        assert!(matches!(link.resource(), Err(Error::InvalidUrl)));
+        assert_eq!(
+            Url::from(link),
+            "https://api.example.com/bugs".parse::<Url>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,423 @@
+//! Checks for common problems in WADL documents
+//!
+//! A [`Linter`] runs a set of [`Rule`]s over an application, each reported with a
+//! [`Severity`] that can be changed per rule:
+//!
+//! ```
+//! use wadl::lint::{Linter, Rule, Severity};
+//!
+//! let app = wadl::parse_string(r#"<application xmlns="http://wadl.dev.java.net/2009/02">
+//!     <resource_type id="people">
+//!         <method name="GET"/>
+//!     </resource_type>
+//! </application>"#).unwrap();
+//! let mut linter = Linter::default();
+//! linter.enable(Rule::MissingMethodId, Severity::Error);
+//! let findings = linter.lint(&app);
+//! assert_eq!(findings[0].rule, Rule::MissingMethodId);
+//! assert_eq!(findings[0].severity, Severity::Error);
+//! ```
+
+use crate::ast::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth knowing about, but often intentional
+    Info,
+
+    /// Likely a problem
+    Warning,
+
+    /// A problem that should fail the check
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            s => Err(format!(
+                "unknown severity {:?}, expected info, warning or error",
+                s
+            )),
+        }
+    }
+}
+
+/// A check that can be run over an application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// A method has no id, so generated code has to derive a name for it
+    MissingMethodId,
+
+    /// A response has no status
+    MissingResponseStatus,
+
+    /// A param has no docs
+    UndocumentedParam,
+
+    /// An id is not in kebab case, e.g. `people-get`
+    NonKebabCaseId,
+
+    /// A representation defined at the application level is not referred to
+    UnusedRepresentation,
+}
+
+impl Rule {
+    /// All rules
+    pub const ALL: &'static [Rule] = &[
+        Rule::MissingMethodId,
+        Rule::MissingResponseStatus,
+        Rule::UndocumentedParam,
+        Rule::NonKebabCaseId,
+        Rule::UnusedRepresentation,
+    ];
+
+    /// The name of the rule, e.g. `missing-method-id`
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::MissingMethodId => "missing-method-id",
+            Rule::MissingResponseStatus => "missing-response-status",
+            Rule::UndocumentedParam => "undocumented-param",
+            Rule::NonKebabCaseId => "non-kebab-case-id",
+            Rule::UnusedRepresentation => "unused-representation",
+        }
+    }
+
+    /// A short description of what the rule checks
+    pub fn description(self) -> &'static str {
+        match self {
+            Rule::MissingMethodId => "methods without an id",
+            Rule::MissingResponseStatus => "responses without a status",
+            Rule::UndocumentedParam => "params without docs",
+            Rule::NonKebabCaseId => "ids that are not in kebab case",
+            Rule::UnusedRepresentation => "representations that nothing refers to",
+        }
+    }
+
+    /// The severity the rule is reported with by default
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Rule::UndocumentedParam => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .iter()
+            .copied()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| format!("unknown rule {:?}", s))
+    }
+}
+
+/// A problem found by a [`Linter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// The rule that found the problem
+    pub rule: Rule,
+
+    /// The severity the rule is reported with
+    pub severity: Severity,
+
+    /// Where the problem is, e.g. `people-get/ws.size` for a param of a method
+    pub location: String,
+
+    /// A description of the problem
+    pub message: String,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}: {} [{}]",
+            self.severity, self.location, self.message, self.rule
+        )
+    }
+}
+
+/// Runs the enabled rules over applications
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linter {
+    rules: BTreeMap<Rule, Severity>,
+}
+
+/// Enables all rules, with their default severities
+impl Default for Linter {
+    fn default() -> Self {
+        Self {
+            rules: Rule::ALL
+                .iter()
+                .map(|rule| (*rule, rule.default_severity()))
+                .collect(),
+        }
+    }
+}
+
+/// Return whether an id is in kebab case
+fn is_kebab_case(id: &str) -> bool {
+    !id.is_empty()
+        && id.split('-').all(|word| {
+            !word.is_empty()
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// The state of a run of a [`Linter`]
+struct Run<'a> {
+    rules: &'a BTreeMap<Rule, Severity>,
+    findings: Vec<Finding>,
+}
+
+impl Run<'_> {
+    fn report(
+        &mut self,
+        rule: Rule,
+        location: impl FnOnce() -> String,
+        message: impl FnOnce() -> String,
+    ) {
+        if let Some(severity) = self.rules.get(&rule) {
+            self.findings.push(Finding {
+                rule,
+                severity: *severity,
+                location: location(),
+                message: message(),
+            });
+        }
+    }
+
+    fn check_id(&mut self, kind: &str, id: Option<&str>, location: &str) {
+        if let Some(id) = id.filter(|id| !is_kebab_case(id)) {
+            self.report(
+                Rule::NonKebabCaseId,
+                || location.to_string(),
+                || format!("{} id {:?} is not in kebab case", kind, id),
+            );
+        }
+    }
+
+    fn check_params(&mut self, owner: &str, params: &[Param]) {
+        for param in params {
+            let location = format!("{}/{}", owner, param.name);
+            self.check_id("param", param.id.as_deref(), &location);
+            if param.doc.is_none() {
+                self.report(
+                    Rule::UndocumentedParam,
+                    || location,
+                    || format!("param {} has no docs", param.name),
+                );
+            }
+        }
+    }
+
+    fn check_representations(&mut self, owner: &str, representations: &[Representation]) {
+        for representation in representations {
+            if let Representation::Definition(def) = representation {
+                self.check_representation_def(owner, def);
+            }
+        }
+    }
+
+    fn check_representation_def(&mut self, owner: &str, def: &RepresentationDef) {
+        let owner = def.id.as_deref().unwrap_or(owner);
+        self.check_id("representation", def.id.as_deref(), owner);
+        self.check_params(owner, &def.params);
+    }
+
+    fn check_methods(&mut self, owner: &str, methods: &[Method]) {
+        for method in methods {
+            let location = if method.id.is_empty() {
+                self.report(
+                    Rule::MissingMethodId,
+                    || format!("{}/{}", owner, method.name),
+                    || format!("{} method has no id", method.name),
+                );
+                format!("{}/{}", owner, method.name)
+            } else {
+                self.check_id("method", Some(&method.id), &method.id);
+                method.id.clone()
+            };
+            self.check_params(&location, &method.request.params);
+            self.check_representations(&location, &method.request.representations);
+            for response in &method.responses {
+                if response.status.is_none() {
+                    self.report(
+                        Rule::MissingResponseStatus,
+                        || location.clone(),
+                        || "response has no status".to_string(),
+                    );
+                }
+                self.check_params(&location, &response.params);
+                self.check_representations(&location, &response.representations);
+            }
+        }
+    }
+
+    fn check_resources(&mut self, parent: &str, resources: &[Resource]) {
+        for resource in resources {
+            let path = format!(
+                "{}/{}",
+                parent,
+                resource
+                    .path
+                    .as_deref()
+                    .unwrap_or_default()
+                    .trim_matches('/')
+            );
+            self.check_id("resource", resource.id.as_deref(), &path);
+            self.check_params(&path, &resource.params);
+            self.check_methods(&path, &resource.methods);
+            self.check_resources(&path, &resource.subresources);
+        }
+    }
+}
+
+impl Linter {
+    /// Create a linter that runs no rules
+    pub fn none() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+        }
+    }
+
+    /// Run a rule, reporting its findings with the given severity
+    pub fn enable(&mut self, rule: Rule, severity: Severity) {
+        self.rules.insert(rule, severity);
+    }
+
+    /// Stop running a rule
+    pub fn disable(&mut self, rule: Rule) {
+        self.rules.remove(&rule);
+    }
+
+    /// Return the severity a rule is reported with, if it is run
+    pub fn severity(&self, rule: Rule) -> Option<Severity> {
+        self.rules.get(&rule).copied()
+    }
+
+    /// Run the enabled rules over an application
+    pub fn lint(&self, app: &Application) -> Vec<Finding> {
+        let mut run = Run {
+            rules: &self.rules,
+            findings: vec![],
+        };
+        for resources in &app.resources {
+            run.check_resources("", &resources.resources);
+        }
+        for resource_type in &app.resource_types {
+            run.check_id("resource type", Some(&resource_type.id), &resource_type.id);
+            run.check_params(&resource_type.id, &resource_type.params);
+            run.check_methods(&resource_type.id, &resource_type.methods);
+            run.check_resources(&resource_type.id, &resource_type.subresources);
+        }
+        for representation in &app.representations {
+            run.check_representation_def("", representation);
+        }
+
+        if self.rules.contains_key(&Rule::UnusedRepresentation) {
+            let mut used = HashSet::new();
+            app.clone().for_each_reference(&mut |r| {
+                if let Reference::Representation(RepresentationRef::Id(id)) = r {
+                    used.insert(id.clone());
+                }
+            });
+            for id in app.representations.iter().filter_map(|r| r.id.as_deref()) {
+                if !used.contains(id) {
+                    run.report(
+                        Rule::UnusedRepresentation,
+                        || id.to_string(),
+                        || format!("representation {} is not referred to", id),
+                    );
+                }
+            }
+        }
+        run.findings
+    }
+}
+
+#[test]
+fn test_lint() {
+    let app = crate::parse_string(
+        r##"<application xmlns="http://wadl.dev.java.net/2009/02">
+            <resources base="https://api.example.com/1.0/">
+                <resource path="people" type="#people"/>
+            </resources>
+            <resource_type id="people">
+                <method name="GET" id="getPeople">
+                    <request>
+                        <param name="ws.size" style="query"><doc>The page size</doc></param>
+                        <param name="q" style="query"/>
+                    </request>
+                    <response>
+                        <representation href="#person-page"/>
+                    </response>
+                </method>
+                <method name="POST">
+                    <response status="201"/>
+                </method>
+            </resource_type>
+            <representation id="person-page" mediaType="application/json"/>
+            <representation id="person" mediaType="application/json"/>
+        </application>"##,
+    )
+    .unwrap();
+    let findings = Linter::default().lint(&app);
+    assert_eq!(
+        findings.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+        [
+            "warning: getPeople: method id \"getPeople\" is not in kebab case [non-kebab-case-id]",
+            "info: getPeople/q: param q has no docs [undocumented-param]",
+            "warning: getPeople: response has no status [missing-response-status]",
+            "warning: people/POST: POST method has no id [missing-method-id]",
+            "warning: person: representation person is not referred to [unused-representation]",
+        ]
+    );
+
+    let mut linter = Linter::none();
+    linter.enable(Rule::MissingMethodId, Severity::Error);
+    assert_eq!(
+        linter.severity(Rule::MissingMethodId),
+        Some(Severity::Error)
+    );
+    let findings = linter.lint(&app);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity, Severity::Error);
+    linter.disable(Rule::MissingMethodId);
+    assert!(linter.lint(&app).is_empty());
+
+    assert_eq!(
+        "unused-representation".parse(),
+        Ok(Rule::UnusedRepresentation)
+    );
+    assert!("unused".parse::<Rule>().is_err());
+}
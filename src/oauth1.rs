@@ -0,0 +1,522 @@
+//! OAuth 1.0a request signing, as required by the Launchpad API
+//!
+//! Only the `PLAINTEXT` signature method is supported, which relies on TLS to keep the secrets
+//! safe. Requests are signed by adding a [`Signer`] as middleware to a
+//! [`LayeredClient`](crate::blocking::LayeredClient):
+//!
+//! ```no_run
+//! use wadl::oauth1::{Consumer, Signer, Token};
+//!
+//! let signer = Signer::new(
+//!     Consumer::new("my-app"),
+//!     Token::new("access-token", "access-token-secret"),
+//! )
+//! .with_realm("https://api.launchpad.net/");
+//! let client = wadl::blocking::LayeredClient::new(reqwest::blocking::Client::new()).layer(signer);
+//! ```
+//!
+//! Access tokens are obtained with the helpers in [`blocking`] or [`r#async`]: fetch a request
+//! token, have the user authorize it at [`Endpoints::authorize_url`], and exchange it for an
+//! access token.
+
+use crate::Error;
+use url::Url;
+
+/// The root URL of Launchpad, which hands out tokens for its API
+pub const LAUNCHPAD_ROOT: &str = "https://launchpad.net/";
+
+/// The realm of the Launchpad API
+pub const LAUNCHPAD_REALM: &str = "https://api.launchpad.net/";
+
+/// The application that makes requests on behalf of a user
+#[derive(Clone, PartialEq, Eq)]
+pub struct Consumer {
+    /// The consumer key, identifying the application
+    pub key: String,
+
+    /// The consumer secret, which is empty for Launchpad
+    pub secret: String,
+}
+
+impl Consumer {
+    /// Create a consumer without a secret, as used by Launchpad
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            secret: String::new(),
+        }
+    }
+
+    /// Set the consumer secret
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = secret.into();
+        self
+    }
+}
+
+impl std::fmt::Debug for Consumer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Consumer")
+            .field("key", &self.key)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+/// A request token or access token, along with its secret
+#[derive(Clone, PartialEq, Eq)]
+pub struct Token {
+    /// The token
+    pub key: String,
+
+    /// The token secret
+    pub secret: String,
+}
+
+impl Token {
+    /// Create a token
+    pub fn new(key: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            secret: secret.into(),
+        }
+    }
+
+    /// Parse a token from a form-urlencoded response body
+    #[cfg_attr(
+        not(any(feature = "blocking", feature = "async-native")),
+        allow(dead_code)
+    )]
+    fn from_form(body: &[u8]) -> Result<Self, Error> {
+        let mut key = None;
+        let mut secret = None;
+        for (name, value) in form_urlencoded::parse(body) {
+            match name.as_ref() {
+                "oauth_token" => key = Some(value.into_owned()),
+                "oauth_token_secret" => secret = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            key: key.ok_or_else(|| Error::InvalidFormField("oauth_token".to_string()))?,
+            secret: secret
+                .ok_or_else(|| Error::InvalidFormField("oauth_token_secret".to_string()))?,
+        })
+    }
+}
+
+impl std::fmt::Debug for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Token")
+            .field("key", &self.key)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Percent-encode a value as described in RFC 5849, section 3.6
+fn encode(value: &str) -> String {
+    let mut ret = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            ret.push(b as char);
+        } else {
+            ret.push_str(&format!("%{:02X}", b));
+        }
+    }
+    ret
+}
+
+/// Return the `PLAINTEXT` signature for a consumer and an optional token
+fn plaintext_signature(consumer: &Consumer, token: Option<&Token>) -> String {
+    format!(
+        "{}&{}",
+        encode(&consumer.secret),
+        encode(token.map_or("", |t| t.secret.as_str()))
+    )
+}
+
+/// Return a nonce that is unique within this process
+fn nonce() -> String {
+    use std::hash::{BuildHasher, Hasher};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    format!("{:016x}", hasher.finish())
+}
+
+/// Signs requests with an access token, using the `PLAINTEXT` signature method
+///
+/// Implements [`Middleware`](crate::blocking::Middleware) for both blocking and async
+/// [`LayeredClient`](crate::blocking::LayeredClient)s.
+#[derive(Debug, Clone)]
+pub struct Signer {
+    consumer: Consumer,
+    token: Token,
+    realm: Option<String>,
+}
+
+impl Signer {
+    /// Create a signer for requests made by `consumer` with an access token
+    pub fn new(consumer: Consumer, token: Token) -> Self {
+        Self {
+            consumer,
+            token,
+            realm: None,
+        }
+    }
+
+    /// Set the realm, e.g. [`LAUNCHPAD_REALM`]
+    pub fn with_realm(mut self, realm: impl Into<String>) -> Self {
+        self.realm = Some(realm.into());
+        self
+    }
+
+    /// Return the value of the `Authorization` header for a new request
+    pub fn authorization(&self) -> String {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut params = vec![];
+        if let Some(realm) = self.realm.as_ref() {
+            params.push(("realm", realm.clone()));
+        }
+        params.extend([
+            ("oauth_consumer_key", self.consumer.key.clone()),
+            ("oauth_token", self.token.key.clone()),
+            ("oauth_signature_method", "PLAINTEXT".to_string()),
+            (
+                "oauth_signature",
+                plaintext_signature(&self.consumer, Some(&self.token)),
+            ),
+            ("oauth_timestamp", timestamp.to_string()),
+            ("oauth_nonce", nonce()),
+            ("oauth_version", "1.0".to_string()),
+        ]);
+        let params = params
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, encode(value)))
+            .collect::<Vec<_>>();
+        format!("OAuth {}", params.join(", "))
+    }
+
+    #[cfg_attr(
+        not(any(feature = "blocking", feature = "async-native")),
+        allow(dead_code)
+    )]
+    fn header_value(&self) -> reqwest::header::HeaderValue {
+        // Only contains percent-encoded values, so it is always a valid header value
+        reqwest::header::HeaderValue::from_str(&self.authorization())
+            .expect("percent-encoded header value")
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl crate::blocking::Middleware for Signer {
+    fn handle(
+        &self,
+        mut req: reqwest::blocking::Request,
+        next: crate::blocking::Next<'_>,
+    ) -> Result<reqwest::blocking::Response, Error> {
+        req.headers_mut()
+            .insert(reqwest::header::AUTHORIZATION, self.header_value());
+        next.run(req)
+    }
+}
+
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+impl crate::r#async::Middleware for Signer {
+    fn handle<'a>(
+        &'a self,
+        mut req: reqwest::Request,
+        next: crate::r#async::Next<'a>,
+    ) -> crate::r#async::BoxFuture<'a, Result<reqwest::Response, Error>> {
+        req.headers_mut()
+            .insert(reqwest::header::AUTHORIZATION, self.header_value());
+        next.run(req)
+    }
+}
+
+/// The URLs used to obtain an access token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// Where request tokens are fetched
+    pub request_token: Url,
+
+    /// Where the user authorizes a request token
+    pub authorize: Url,
+
+    /// Where an authorized request token is exchanged for an access token
+    pub access_token: Url,
+}
+
+impl Endpoints {
+    /// Return the endpoints of a Launchpad instance, e.g. [`LAUNCHPAD_ROOT`]
+    pub fn launchpad(root: &Url) -> Result<Self, Error> {
+        Ok(Self {
+            request_token: root.join("+request-token")?,
+            authorize: root.join("+authorize-token")?,
+            access_token: root.join("+access-token")?,
+        })
+    }
+
+    /// Return the URL at which the user can authorize a request token
+    pub fn authorize_url(&self, request_token: &Token) -> Url {
+        let mut url = self.authorize.clone();
+        url.query_pairs_mut()
+            .append_pair("oauth_token", &request_token.key);
+        url
+    }
+}
+
+/// Return the form fields of a request for a token
+#[cfg_attr(
+    not(any(feature = "blocking", feature = "async-native")),
+    allow(dead_code)
+)]
+fn token_request_form(consumer: &Consumer, token: Option<&Token>) -> Vec<(&'static str, String)> {
+    let mut form = vec![("oauth_consumer_key", consumer.key.clone())];
+    if let Some(token) = token {
+        form.push(("oauth_token", token.key.clone()));
+    }
+    form.push(("oauth_signature_method", "PLAINTEXT".to_string()));
+    form.push(("oauth_signature", plaintext_signature(consumer, token)));
+    form
+}
+
+/// Obtain tokens with a blocking client
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking {
+    use super::{token_request_form, Consumer, Endpoints, Token};
+    use crate::blocking::Client;
+    use crate::Error;
+
+    fn fetch_token(
+        client: &(impl Client + ?Sized),
+        url: &url::Url,
+        form: &[(&str, String)],
+    ) -> Result<Token, Error> {
+        let req = client
+            .request(reqwest::Method::POST, url.clone())
+            .form(form);
+        let resp = client.send(req)?;
+        crate::response::check_status(resp.status(), &[])?;
+        Token::from_form(&resp.bytes()?)
+    }
+
+    /// Fetch a request token, which the user should authorize at [`Endpoints::authorize_url`]
+    pub fn request_token(
+        client: &(impl Client + ?Sized),
+        endpoints: &Endpoints,
+        consumer: &Consumer,
+    ) -> Result<Token, Error> {
+        fetch_token(
+            client,
+            &endpoints.request_token,
+            &token_request_form(consumer, None),
+        )
+    }
+
+    /// Exchange a request token that the user has authorized for an access token
+    pub fn access_token(
+        client: &(impl Client + ?Sized),
+        endpoints: &Endpoints,
+        consumer: &Consumer,
+        request_token: &Token,
+    ) -> Result<Token, Error> {
+        fetch_token(
+            client,
+            &endpoints.access_token,
+            &token_request_form(consumer, Some(request_token)),
+        )
+    }
+}
+
+/// Obtain tokens with an async client
+#[cfg(feature = "async-native")]
+pub mod r#async {
+    use super::{token_request_form, Consumer, Endpoints, Token};
+    use crate::r#async::NativeClient;
+    use crate::Error;
+
+    async fn fetch_token(
+        client: &(impl NativeClient + ?Sized),
+        url: &url::Url,
+        form: &[(&str, String)],
+    ) -> Result<Token, Error> {
+        let req = client
+            .request(reqwest::Method::POST, url.clone())
+            .await
+            .form(form);
+        let resp = crate::r#async::send(client, req).await?;
+        crate::response::check_status(resp.status(), &[])?;
+        Token::from_form(&resp.bytes().await?)
+    }
+
+    /// Fetch a request token, which the user should authorize at [`Endpoints::authorize_url`]
+    pub async fn request_token(
+        client: &(impl NativeClient + ?Sized),
+        endpoints: &Endpoints,
+        consumer: &Consumer,
+    ) -> Result<Token, Error> {
+        fetch_token(
+            client,
+            &endpoints.request_token,
+            &token_request_form(consumer, None),
+        )
+        .await
+    }
+
+    /// Exchange a request token that the user has authorized for an access token
+    pub async fn access_token(
+        client: &(impl NativeClient + ?Sized),
+        endpoints: &Endpoints,
+        consumer: &Consumer,
+        request_token: &Token,
+    ) -> Result<Token, Error> {
+        fetch_token(
+            client,
+            &endpoints.access_token,
+            &token_request_form(consumer, Some(request_token)),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("abc-._~XYZ019"), "abc-._~XYZ019");
+        assert_eq!(encode("a b&c=d/é"), "a%20b%26c%3Dd%2F%C3%A9");
+    }
+
+    #[test]
+    fn test_authorization() {
+        let signer = Signer::new(Consumer::new("my app"), Token::new("tok", "s&cret"))
+            .with_realm(LAUNCHPAD_REALM);
+        let header = signer.authorization();
+        assert!(
+            header.starts_with(
+                "OAuth realm=\"https%3A%2F%2Fapi.launchpad.net%2F\", oauth_consumer_key=\"my%20app\", oauth_token=\"tok\", oauth_signature_method=\"PLAINTEXT\", oauth_signature=\"%26s%2526cret\", oauth_timestamp=\""
+            ),
+            "{}",
+            header
+        );
+        assert!(header.ends_with(", oauth_version=\"1.0\""), "{}", header);
+        assert_ne!(signer.authorization(), header);
+        assert!(!format!("{:?}", signer).contains("s&cret"));
+    }
+
+    #[test]
+    fn test_launchpad_endpoints() {
+        let endpoints = Endpoints::launchpad(&LAUNCHPAD_ROOT.parse().unwrap()).unwrap();
+        assert_eq!(
+            endpoints.request_token.as_str(),
+            "https://launchpad.net/+request-token"
+        );
+        assert_eq!(
+            endpoints
+                .authorize_url(&Token::new("req", "secret"))
+                .as_str(),
+            "https://launchpad.net/+authorize-token?oauth_token=req"
+        );
+    }
+
+    #[cfg(all(feature = "testing", feature = "blocking"))]
+    #[test]
+    fn test_blocking_token_dance() {
+        use crate::testing::{MockClient, MockResponse};
+        let client = MockClient::new();
+        client.expect(
+            reqwest::Method::POST,
+            "https://launchpad.net/+request-token",
+            MockResponse::new(200).with_body("oauth_token=req&oauth_token_secret=reqsecret"),
+        );
+        client.expect(
+            reqwest::Method::POST,
+            "https://launchpad.net/+access-token",
+            MockResponse::new(200).with_body("oauth_token=acc&oauth_token_secret=accsecret"),
+        );
+        let endpoints = Endpoints::launchpad(&LAUNCHPAD_ROOT.parse().unwrap()).unwrap();
+        let consumer = Consumer::new("my-app");
+
+        let request_token = blocking::request_token(&client, &endpoints, &consumer).unwrap();
+        assert_eq!(request_token, Token::new("req", "reqsecret"));
+        let access_token =
+            blocking::access_token(&client, &endpoints, &consumer, &request_token).unwrap();
+        assert_eq!(access_token, Token::new("acc", "accsecret"));
+
+        let requests = client.requests();
+        assert_eq!(
+            requests[0].body,
+            b"oauth_consumer_key=my-app&oauth_signature_method=PLAINTEXT&oauth_signature=%26"
+        );
+        assert_eq!(
+            requests[1].body,
+            b"oauth_consumer_key=my-app&oauth_token=req&oauth_signature_method=PLAINTEXT&oauth_signature=%26reqsecret"
+        );
+    }
+
+    #[cfg(all(feature = "testing", feature = "blocking"))]
+    #[test]
+    fn test_blocking_signer() {
+        use crate::blocking::{Client, LayeredClient};
+        use crate::testing::{MockClient, MockResponse};
+        let client = LayeredClient::new(MockClient::new()).layer(Signer::new(
+            Consumer::new("my-app"),
+            Token::new("acc", "accsecret"),
+        ));
+        client.inner().expect(
+            reqwest::Method::GET,
+            "https://api.launchpad.net/devel/",
+            MockResponse::new(200),
+        );
+
+        let req = client.request(
+            reqwest::Method::GET,
+            "https://api.launchpad.net/devel/".parse().unwrap(),
+        );
+        client.send(req).unwrap();
+        let requests = client.inner().requests();
+        let header = requests[0].header("authorization").unwrap();
+        assert!(
+            header.starts_with("OAuth oauth_consumer_key=\"my-app\", oauth_token=\"acc\""),
+            "{}",
+            header
+        );
+    }
+
+    #[cfg(all(feature = "testing", feature = "async"))]
+    #[tokio::test]
+    async fn test_async_token_dance() {
+        use crate::testing::{MockClient, MockResponse};
+        let client = MockClient::new();
+        client.expect(
+            reqwest::Method::POST,
+            "https://launchpad.net/+request-token",
+            MockResponse::new(200).with_body("oauth_token=req&oauth_token_secret=reqsecret"),
+        );
+        client.expect(
+            reqwest::Method::POST,
+            "https://launchpad.net/+access-token",
+            MockResponse::new(401),
+        );
+        let endpoints = Endpoints::launchpad(&LAUNCHPAD_ROOT.parse().unwrap()).unwrap();
+        let consumer = Consumer::new("my-app");
+
+        let request_token = r#async::request_token(&client, &endpoints, &consumer)
+            .await
+            .unwrap();
+        assert_eq!(request_token, Token::new("req", "reqsecret"));
+        // The user has not authorized the request token yet
+        assert!(matches!(
+            r#async::access_token(&client, &endpoints, &consumer, &request_token).await,
+            Err(Error::UnhandledStatus(reqwest::StatusCode::UNAUTHORIZED))
+        ));
+    }
+}
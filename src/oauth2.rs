@@ -0,0 +1,227 @@
+//! OAuth 2.0 bearer tokens, refreshed when they are rejected
+//!
+//! [`BearerAuth`] is middleware for a [`LayeredClient`](crate::blocking::LayeredClient) that
+//! adds the current access token to every request. When the server responds with
+//! `401 Unauthorized`, it obtains a new token from a callback and retries the request once:
+//!
+//! ```no_run
+//! use wadl::oauth2::BearerAuth;
+//!
+//! let auth = BearerAuth::new("access-token", || {
+//!     // Exchange the refresh token for a new access token
+//!     Ok::<_, wadl::Error>("new-access-token".to_string())
+//! });
+//! let client = wadl::blocking::LayeredClient::new(reqwest::blocking::Client::new()).layer(auth);
+//! ```
+//!
+//! With an async client, the callback returns a future instead.
+
+use std::sync::RwLock;
+
+/// Middleware that authenticates requests with an OAuth 2.0 bearer token
+///
+/// The callback is called without arguments, and returns the new access token: a
+/// `Result<String, wadl::Error>` for blocking clients, or a future of one for async clients.
+/// If several requests are rejected at the same time, only the first one refreshes the token.
+pub struct BearerAuth<F> {
+    token: RwLock<String>,
+    #[cfg_attr(
+        not(any(feature = "blocking", feature = "async-native")),
+        allow(dead_code)
+    )]
+    refresh: F,
+}
+
+impl<F> BearerAuth<F> {
+    /// Create the middleware, with the current access token and a callback to refresh it
+    pub fn new(token: impl Into<String>, refresh: F) -> Self {
+        Self {
+            token: RwLock::new(token.into()),
+            refresh,
+        }
+    }
+
+    /// Return the current access token
+    pub fn token(&self) -> String {
+        self.token.read().unwrap().clone()
+    }
+
+    #[cfg_attr(
+        not(any(feature = "blocking", feature = "async-native")),
+        allow(dead_code)
+    )]
+    fn header_value(token: &str) -> Result<reqwest::header::HeaderValue, crate::Error> {
+        reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|_| crate::Error::InvalidHeader("authorization".to_string()))
+    }
+
+    /// Return whether a rejected token is still current, i.e. no other request refreshed it
+    #[cfg_attr(
+        not(any(feature = "blocking", feature = "async-native")),
+        allow(dead_code)
+    )]
+    fn needs_refresh(&self, rejected: &str) -> bool {
+        *self.token.read().unwrap() == rejected
+    }
+}
+
+impl<F> std::fmt::Debug for BearerAuth<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BearerAuth")
+            .field("token", &"<redacted>")
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl<F> crate::blocking::Middleware for BearerAuth<F>
+where
+    F: Fn() -> Result<String, crate::Error> + Send + Sync,
+{
+    fn handle(
+        &self,
+        mut req: reqwest::blocking::Request,
+        next: crate::blocking::Next<'_>,
+    ) -> Result<reqwest::blocking::Response, crate::Error> {
+        let token = self.token();
+        req.headers_mut()
+            .insert(reqwest::header::AUTHORIZATION, Self::header_value(&token)?);
+        // Requests with a body that can not be cloned are not retried
+        let Some(mut retry) = req.try_clone() else {
+            return next.run(req);
+        };
+        let resp = next.run(req)?;
+        if resp.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(resp);
+        }
+        if self.needs_refresh(&token) {
+            let refreshed = (self.refresh)()?;
+            *self.token.write().unwrap() = refreshed;
+        }
+        retry.headers_mut().insert(
+            reqwest::header::AUTHORIZATION,
+            Self::header_value(&self.token())?,
+        );
+        next.run(retry)
+    }
+}
+
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+impl<F, Fut> crate::r#async::Middleware for BearerAuth<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: std::future::Future<Output = Result<String, crate::Error>> + Send,
+{
+    fn handle<'a>(
+        &'a self,
+        mut req: reqwest::Request,
+        next: crate::r#async::Next<'a>,
+    ) -> crate::r#async::BoxFuture<'a, Result<reqwest::Response, crate::Error>> {
+        Box::pin(async move {
+            let token = self.token();
+            req.headers_mut()
+                .insert(reqwest::header::AUTHORIZATION, Self::header_value(&token)?);
+            // Requests with a body that can not be cloned are not retried
+            let Some(mut retry) = req.try_clone() else {
+                return next.run(req).await;
+            };
+            let resp = next.run(req).await?;
+            if resp.status() != reqwest::StatusCode::UNAUTHORIZED {
+                return Ok(resp);
+            }
+            if self.needs_refresh(&token) {
+                let refreshed = (self.refresh)().await?;
+                *self.token.write().unwrap() = refreshed;
+            }
+            retry.headers_mut().insert(
+                reqwest::header::AUTHORIZATION,
+                Self::header_value(&self.token())?,
+            );
+            next.run(retry).await
+        })
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "testing",
+    any(feature = "blocking", feature = "async")
+))]
+mod tests {
+    use super::*;
+    use crate::testing::{MockClient, MockResponse};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn mock() -> MockClient {
+        let client = MockClient::new();
+        client.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/people",
+            MockResponse::new(200),
+        );
+        client.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/admin",
+            MockResponse::new(401),
+        );
+        client
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_bearer_auth() {
+        use crate::blocking::{Client, LayeredClient};
+        static REFRESHES: AtomicUsize = AtomicUsize::new(0);
+        let client = LayeredClient::new(mock()).layer(BearerAuth::new("old", || {
+            REFRESHES.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, crate::Error>("new".to_string())
+        }));
+
+        let get = |path: &str| {
+            let req = client.request(
+                reqwest::Method::GET,
+                format!("https://api.example.com/1.0/{}", path)
+                    .parse()
+                    .unwrap(),
+            );
+            client.send(req).unwrap().status()
+        };
+        assert_eq!(get("people"), reqwest::StatusCode::OK);
+        assert_eq!(REFRESHES.load(Ordering::SeqCst), 0);
+
+        // Still rejected after refreshing, so the 401 is returned after a single retry
+        assert_eq!(get("admin"), reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(REFRESHES.load(Ordering::SeqCst), 1);
+
+        let requests = client.inner().requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].header("authorization"), Some("Bearer old"));
+        assert_eq!(requests[1].header("authorization"), Some("Bearer old"));
+        assert_eq!(requests[2].header("authorization"), Some("Bearer new"));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_bearer_auth() {
+        use crate::r#async::{Client, LayeredClient};
+        static REFRESHES: AtomicUsize = AtomicUsize::new(0);
+        let client = LayeredClient::new(mock()).layer(BearerAuth::new("old", || async {
+            REFRESHES.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, crate::Error>("new".to_string())
+        }));
+
+        let req = client
+            .request(
+                reqwest::Method::GET,
+                "https://api.example.com/1.0/admin".parse().unwrap(),
+            )
+            .await;
+        let resp = crate::r#async::send(&client, req).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(REFRESHES.load(Ordering::SeqCst), 1);
+
+        let requests = client.inner().requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].header("authorization"), Some("Bearer new"));
+    }
+}
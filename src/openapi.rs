@@ -0,0 +1,1356 @@
+//! Conversion of WADL documents to and from OpenAPI
+//!
+//! ```
+//! let app = wadl::parse_string(r#"<application xmlns="http://wadl.dev.java.net/2009/02">
+//!     <resources base="https://api.example.com/1.0/">
+//!         <resource path="people">
+//!             <method name="GET" id="people-get"/>
+//!         </resource>
+//!     </resources>
+//! </application>"#).unwrap();
+//! let spec = wadl::openapi::convert(&app);
+//! assert_eq!(spec["paths"]["/people"]["get"]["operationId"], "people-get");
+//! println!("{}", serde_yaml::to_string(&spec).unwrap());
+//! ```
+use crate::ast::*;
+use serde_yaml::{Mapping, Value};
+
+/// The version of OpenAPI that WADL documents are converted to
+pub const OPENAPI_VERSION: &str = "3.0.3";
+
+/// The HTTP methods that OpenAPI has operations for
+const OPERATION_METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+fn map<'a>(entries: impl IntoIterator<Item = (&'a str, Value)>) -> Value {
+    Value::Mapping(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Value::from(key), value))
+            .collect(),
+    )
+}
+
+/// Return the text of some docs, without markup
+fn doc_text<'a>(docs: impl IntoIterator<Item = &'a Doc>) -> Option<String> {
+    let text = docs
+        .into_iter()
+        .map(|doc| {
+            let mut text = String::new();
+            let mut in_tag = false;
+            for c in doc.content.chars() {
+                match c {
+                    '<' => in_tag = true,
+                    '>' if in_tag => in_tag = false,
+                    c if !in_tag => text.push(c),
+                    _ => {}
+                }
+            }
+            text.trim().to_string()
+        })
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Return the OpenAPI type and format of an XML Schema type
+fn xsd_type(name: &str) -> (&'static str, Option<&'static str>) {
+    match name.split_once(':').map_or(name, |(_, local)| local) {
+        "int" | "unsignedShort" => ("integer", Some("int32")),
+        "long" | "unsignedInt" => ("integer", Some("int64")),
+        "integer" | "short" | "byte" | "unsignedLong" | "unsignedByte" | "nonNegativeInteger"
+        | "positiveInteger" | "nonPositiveInteger" | "negativeInteger" => ("integer", None),
+        "boolean" => ("boolean", None),
+        "float" => ("number", Some("float")),
+        "double" => ("number", Some("double")),
+        "decimal" => ("number", None),
+        "date" => ("string", Some("date")),
+        "dateTime" => ("string", Some("date-time")),
+        "anyURI" => ("string", Some("uri")),
+        "base64Binary" => ("string", Some("byte")),
+        _ => ("string", None),
+    }
+}
+
+/// Return a value of a param as a value of its OpenAPI type, if possible
+fn typed_value(value: &str, r#type: &str) -> Value {
+    let parsed = match r#type {
+        "integer" => value.parse::<i64>().ok().map(Value::from),
+        "number" => value.parse::<f64>().ok().map(Value::from),
+        "boolean" => value.parse::<bool>().ok().map(Value::from),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| Value::from(value))
+}
+
+/// Return the schema of the values of a param
+fn param_schema(param: &Param) -> Value {
+    let (r#type, format) = if param.links.is_empty() {
+        xsd_type(&param.r#type)
+    } else {
+        ("string", Some("uri"))
+    };
+    let mut schema = vec![("type", Value::from(r#type))];
+    if let Some(format) = format {
+        schema.push(("format", Value::from(format)));
+    }
+    if let Some(options) = param.options.as_ref() {
+        schema.push((
+            "enum",
+            Value::Sequence(options.keys().map(|key| typed_value(key, r#type)).collect()),
+        ));
+    } else if let Some(fixed) = param.fixed.as_ref() {
+        schema.push(("enum", Value::Sequence(vec![typed_value(fixed, r#type)])));
+    }
+    if let Some(default) = param.default.as_ref() {
+        schema.push(("default", typed_value(default, r#type)));
+    }
+    let schema = map(schema);
+    if param.repeating {
+        map([("type", Value::from("array")), ("items", schema)])
+    } else {
+        schema
+    }
+}
+
+/// Return the schema of a representation
+fn representation_schema(def: &RepresentationDef) -> Value {
+    let mut schema = vec![];
+    if let Some(description) = doc_text(&def.docs) {
+        schema.push(("description", Value::from(description)));
+    }
+    if !def.params.is_empty() {
+        schema.push(("type", Value::from("object")));
+        let properties = def
+            .params
+            .iter()
+            .map(|param| {
+                let mut property = param_schema(param);
+                if let (Some(description), Value::Mapping(mapping)) =
+                    (doc_text(&param.doc), &mut property)
+                {
+                    mapping.insert("description".into(), description.into());
+                }
+                (Value::from(param.name.as_str()), property)
+            })
+            .collect::<Mapping>();
+        schema.push(("properties", Value::Mapping(properties)));
+        let required = def
+            .params
+            .iter()
+            .filter(|param| param.required)
+            .map(|param| Value::from(param.name.as_str()))
+            .collect::<Vec<_>>();
+        if !required.is_empty() {
+            schema.push(("required", Value::Sequence(required)));
+        }
+    }
+    map(schema)
+}
+
+/// Return a reference to the schema of a representation defined at the application level
+fn schema_ref(id: &str) -> Value {
+    map([("$ref", Value::from(format!("#/components/schemas/{}", id)))])
+}
+
+/// Return the content of a request or response, keyed by media type
+fn content(representations: &[Representation], app: &Application) -> Option<Value> {
+    let mut content = Mapping::new();
+    for representation in representations {
+        let id = match representation {
+            Representation::Reference(r) => r.id(),
+            Representation::Definition(def) => def.id.as_deref(),
+        };
+        let defined = id.and_then(|id| {
+            app.representations
+                .iter()
+                .find(|def| def.id.as_deref() == Some(id))
+        });
+        let (media_type, schema) = match (representation, defined) {
+            (_, Some(def)) => (
+                def.media_type.as_ref(),
+                schema_ref(def.id.as_ref().unwrap()),
+            ),
+            (Representation::Definition(def), None) => {
+                (def.media_type.as_ref(), representation_schema(def))
+            }
+            (Representation::Reference(_), None) => (None, map([])),
+        };
+        let media_type = media_type.map_or("*/*", |m| m.essence_str());
+        content
+            .entry(media_type.into())
+            .or_insert_with(|| map([("schema", schema)]));
+    }
+    (!content.is_empty()).then_some(Value::Mapping(content))
+}
+
+/// Return a parameter of an operation or a path
+fn parameter(param: &Param) -> Option<Value> {
+    let location = match param.style {
+        ParamStyle::Template => "path",
+        ParamStyle::Query => "query",
+        ParamStyle::Header => "header",
+        ParamStyle::Plain | ParamStyle::Matrix => {
+            log::warn!("Unable to convert {:?} param {}", param.style, param.name);
+            return None;
+        }
+    };
+    let mut parameter = vec![
+        ("name", Value::from(param.name.as_str())),
+        ("in", Value::from(location)),
+    ];
+    if let Some(description) = doc_text(&param.doc) {
+        parameter.push(("description", Value::from(description)));
+    }
+    if param.required || param.style == ParamStyle::Template {
+        parameter.push(("required", Value::from(true)));
+    }
+    parameter.push(("schema", param_schema(param)));
+    Some(map(parameter))
+}
+
+fn operation(method: &Method, app: &Application) -> Value {
+    let mut operation = vec![];
+    if !method.id.is_empty() {
+        operation.push(("operationId", Value::from(method.id.as_str())));
+    }
+    if let Some(description) = doc_text(&method.docs) {
+        operation.push(("description", Value::from(description)));
+    }
+    let parameters = method
+        .request
+        .params
+        .iter()
+        .filter_map(parameter)
+        .collect::<Vec<_>>();
+    if !parameters.is_empty() {
+        operation.push(("parameters", Value::Sequence(parameters)));
+    }
+    if let Some(content) = content(&method.request.representations, app) {
+        let mut body = vec![];
+        if let Some(description) = doc_text(&method.request.docs) {
+            body.push(("description", Value::from(description)));
+        }
+        body.push(("content", content));
+        operation.push(("requestBody", map(body)));
+    }
+
+    let mut responses = Mapping::new();
+    for response in &method.responses {
+        let status = response
+            .status
+            .map_or_else(|| "default".to_string(), |s| s.to_string());
+        if responses.contains_key(status.as_str()) {
+            continue;
+        }
+        let mut entry = vec![(
+            "description",
+            Value::from(doc_text(&response.docs).unwrap_or_else(|| "Response".to_string())),
+        )];
+        let headers = response
+            .params
+            .iter()
+            .filter(|param| param.style == ParamStyle::Header)
+            .map(|param| {
+                let mut header = vec![];
+                if let Some(description) = doc_text(&param.doc) {
+                    header.push(("description", Value::from(description)));
+                }
+                header.push(("schema", param_schema(param)));
+                (Value::from(param.name.as_str()), map(header))
+            })
+            .collect::<Mapping>();
+        if !headers.is_empty() {
+            entry.push(("headers", Value::Mapping(headers)));
+        }
+        if let Some(content) = content(&response.representations, app) {
+            entry.push(("content", content));
+        }
+        responses.insert(status.into(), map(entry));
+    }
+    if responses.is_empty() {
+        responses.insert(
+            "default".into(),
+            map([("description", Value::from("Response"))]),
+        );
+    }
+    operation.push(("responses", Value::Mapping(responses)));
+    map(operation)
+}
+
+/// Add the path items of a resource and its subresources
+///
+/// # Arguments
+/// * `parent` - The path of the parent resource
+/// * `template_params` - The template params of the ancestors of the resource
+/// * `server` - The server to list for the paths, if they don't use the default one
+fn add_resource(
+    paths: &mut Mapping,
+    resource: &Resource,
+    parent: &str,
+    template_params: &[Param],
+    server: Option<&url::Url>,
+    app: &Application,
+) {
+    let path = format!(
+        "{}/{}",
+        parent.trim_end_matches('/'),
+        resource
+            .path
+            .as_deref()
+            .unwrap_or_default()
+            .trim_matches('/')
+    );
+    let mut template_params = template_params.to_vec();
+    template_params.extend(
+        resource
+            .params
+            .iter()
+            .filter(|param| param.style == ParamStyle::Template)
+            .cloned(),
+    );
+
+    let operations = resource
+        .methods
+        .iter()
+        .filter_map(|method| {
+            let name = method.name.to_lowercase();
+            if !OPERATION_METHODS.contains(&name.as_str()) {
+                log::warn!("Unable to convert {} method {}", method.name, method.id);
+                return None;
+            }
+            Some((Value::from(name), operation(method, app)))
+        })
+        .collect::<Vec<_>>();
+    if !operations.is_empty() {
+        let item = paths
+            .entry(path.as_str().into())
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+        let Value::Mapping(item) = item else {
+            unreachable!()
+        };
+        if item.is_empty() {
+            if let Some(description) = doc_text(&resource.docs) {
+                item.insert("description".into(), description.into());
+            }
+            if let Some(server) = server {
+                item.insert(
+                    "servers".into(),
+                    Value::Sequence(vec![map([("url", Value::from(server.as_str()))])]),
+                );
+            }
+            let parameters = template_params
+                .iter()
+                .chain(
+                    resource
+                        .params
+                        .iter()
+                        .filter(|param| param.style != ParamStyle::Template),
+                )
+                .filter_map(parameter)
+                .collect::<Vec<_>>();
+            if !parameters.is_empty() {
+                item.insert("parameters".into(), Value::Sequence(parameters));
+            }
+        }
+        for (name, operation) in operations {
+            item.entry(name).or_insert(operation);
+        }
+    }
+
+    for subresource in &resource.subresources {
+        add_resource(paths, subresource, &path, &template_params, server, app);
+    }
+}
+
+/// Convert a WADL application to an OpenAPI document
+///
+/// Resource types are flattened into the resources of those types, and representations
+/// defined at the application level become schemas. Matrix params and methods that OpenAPI
+/// has no operations for can not be converted, and are left out with a warning.
+pub fn convert(app: &Application) -> Value {
+    let mut app = app.clone();
+    // Links are plain URIs in OpenAPI, so the resource types of their targets needn't be kept
+    app.for_each_reference(&mut |r| {
+        if let Reference::LinkTarget(rt) = r {
+            *rt = ResourceTypeRef::Empty;
+        }
+    });
+    app.flatten_resource_types();
+
+    let mut info = vec![(
+        "title",
+        Value::from(
+            app.docs
+                .iter()
+                .find_map(|doc| doc.title.clone())
+                .unwrap_or_else(|| "API".to_string()),
+        ),
+    )];
+    if let Some(description) = doc_text(&app.docs) {
+        info.push(("description", Value::from(description)));
+    }
+    // WADL has no notion of versions
+    info.push(("version", Value::from("1.0")));
+
+    let bases = app
+        .resources
+        .iter()
+        .filter_map(|resources| resources.base.as_ref())
+        .collect::<Vec<_>>();
+    let mut paths = Mapping::new();
+    for resources in &app.resources {
+        let server = resources.base.as_ref().filter(|_| bases.len() > 1);
+        for resource in &resources.resources {
+            add_resource(&mut paths, resource, "", &[], server, &app);
+        }
+    }
+
+    let mut spec = vec![
+        ("openapi", Value::from(OPENAPI_VERSION)),
+        ("info", map(info)),
+    ];
+    if !bases.is_empty() {
+        spec.push((
+            "servers",
+            Value::Sequence(
+                bases
+                    .iter()
+                    .map(|url| map([("url", Value::from(url.as_str()))]))
+                    .collect(),
+            ),
+        ));
+    }
+    spec.push(("paths", Value::Mapping(paths)));
+
+    let schemas = app
+        .representations
+        .iter()
+        .filter_map(|def| {
+            def.id
+                .as_deref()
+                .map(|id| (Value::from(id), representation_schema(def)))
+        })
+        .collect::<Mapping>();
+    if !schemas.is_empty() {
+        spec.push(("components", map([("schemas", Value::Mapping(schemas))])));
+    }
+    map(spec)
+}
+
+/// An error importing an OpenAPI document
+#[derive(Debug)]
+pub enum ImportError {
+    /// The document is not valid YAML or JSON.
+    Yaml(serde_yaml::Error),
+
+    /// The document is not an OpenAPI 3 or Swagger 2 document.
+    UnsupportedVersion,
+
+    /// The URL of a server could not be parsed.
+    Url(url::ParseError),
+}
+
+impl From<serde_yaml::Error> for ImportError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ImportError::Yaml(e)
+    }
+}
+
+impl From<url::ParseError> for ImportError {
+    fn from(e: url::ParseError) -> Self {
+        ImportError::Url(e)
+    }
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ImportError::Yaml(e) => write!(f, "invalid document: {}", e),
+            ImportError::UnsupportedVersion => {
+                f.write_str("not an OpenAPI 3 or Swagger 2 document")
+            }
+            ImportError::Url(e) => write!(f, "invalid server URL: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Return a doc with the given text, if there is any
+fn text_doc(title: Option<&str>, text: Option<&str>) -> Option<Doc> {
+    if title.is_none() && text.is_none() {
+        return None;
+    }
+    Some(Doc {
+        title: title.map(str::to_string),
+        content: text.unwrap_or_default().trim().to_string(),
+        ..Default::default()
+    })
+}
+
+/// Return a scalar as it would be written in a WADL document
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Return the XML Schema type of the values of an OpenAPI schema
+fn schema_xsd_type(schema: &Value) -> &'static str {
+    match (schema["type"].as_str(), schema["format"].as_str()) {
+        (Some("integer"), Some("int32")) => "xsd:int",
+        (Some("integer"), _) => "xsd:long",
+        (Some("number"), Some("float")) => "xsd:float",
+        (Some("number"), _) => "xsd:double",
+        (Some("boolean"), _) => "xsd:boolean",
+        (Some("string"), Some("date")) => "xsd:date",
+        (Some("string"), Some("date-time")) => "xsd:dateTime",
+        (Some("string"), Some("uri")) => "xsd:anyURI",
+        (Some("string"), Some("byte")) => "xsd:base64Binary",
+        (Some("string"), _) => "xsd:string",
+        _ => "xsd:anyType",
+    }
+}
+
+/// Return the name of the representation a schema refers to
+fn schema_ref_name(schema: &Value) -> Option<&str> {
+    let reference = schema["$ref"].as_str()?;
+    reference
+        .strip_prefix("#/components/schemas/")
+        .or_else(|| reference.strip_prefix("#/definitions/"))
+}
+
+/// Imports the definitions of an OpenAPI 3 or Swagger 2 document
+struct Importer<'a> {
+    spec: &'a Value,
+
+    /// Whether the document is a Swagger 2 document
+    swagger: bool,
+}
+
+impl<'a> Importer<'a> {
+    /// Follow local references, e.g. to shared parameters and responses
+    fn resolve(&self, mut value: &'a Value) -> &'a Value {
+        // Bound the number of references followed, in case of cycles
+        for _ in 0..16 {
+            let Some(pointer) = value["$ref"].as_str().and_then(|r| r.strip_prefix("#/")) else {
+                break;
+            };
+            let mut target = self.spec;
+            for segment in pointer.split('/') {
+                target = &target[segment.replace("~1", "/").replace("~0", "~").as_str()];
+            }
+            if target.is_null() {
+                log::warn!("Unable to resolve reference {}", pointer);
+                break;
+            }
+            value = target;
+        }
+        value
+    }
+
+    fn base(&self) -> Result<Option<url::Url>, ImportError> {
+        let base = if self.swagger {
+            let Some(host) = self.spec["host"].as_str() else {
+                return Ok(None);
+            };
+            format!(
+                "{}://{}{}",
+                self.spec["schemes"][0].as_str().unwrap_or("https"),
+                host,
+                self.spec["basePath"].as_str().unwrap_or("/")
+            )
+        } else {
+            let server = &self.spec["servers"][0];
+            let Some(url) = server["url"].as_str() else {
+                return Ok(None);
+            };
+            let mut url = url.to_string();
+            if let Some(variables) = server["variables"].as_mapping() {
+                for (name, variable) in variables {
+                    if let (Some(name), Some(default)) =
+                        (name.as_str(), scalar_text(&variable["default"]))
+                    {
+                        url = url.replace(&format!("{{{}}}", name), &default);
+                    }
+                }
+            }
+            url
+        };
+        let mut base = match url::Url::parse(&base) {
+            Ok(base) => base,
+            Err(url::ParseError::RelativeUrlWithoutBase) => {
+                log::warn!("Ignoring relative server URL {}", base);
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        // Resource paths are relative to the base
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        Ok(Some(base))
+    }
+
+    /// Return a param for a value of a schema
+    fn schema_param(
+        &self,
+        name: &str,
+        schema: &'a Value,
+        style: ParamStyle,
+        required: bool,
+        description: Option<&str>,
+    ) -> Param {
+        let schema = self.resolve(schema);
+        let repeating = schema["type"] == "array";
+        let item = if repeating {
+            self.resolve(&schema["items"])
+        } else {
+            schema
+        };
+        let values = item["enum"]
+            .as_sequence()
+            .map(|values| values.iter().filter_map(scalar_text).collect::<Vec<_>>());
+        // A single allowed value is how fixed params are converted
+        let (fixed, options) = match values {
+            Some(mut values) if values.len() == 1 => (values.pop(), None),
+            values => (None, values.map(Options::from)),
+        };
+        Param {
+            style,
+            id: None,
+            name: name.to_string(),
+            r#type: schema_xsd_type(item).to_string(),
+            path: None,
+            required,
+            repeating,
+            fixed,
+            default: scalar_text(&schema["default"]),
+            doc: text_doc(None, description.or(schema["description"].as_str())),
+            links: vec![],
+            options,
+        }
+    }
+
+    /// Return the params for the properties of an object schema
+    fn property_params(&self, schema: &'a Value, style: ParamStyle) -> Vec<Param> {
+        let schema = self.resolve(schema);
+        let mut params = schema["allOf"]
+            .as_sequence()
+            .into_iter()
+            .flatten()
+            .flat_map(|part| self.property_params(part, style.clone()))
+            .collect::<Vec<_>>();
+        let required = schema["required"]
+            .as_sequence()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>();
+        for (name, property) in schema["properties"].as_mapping().into_iter().flatten() {
+            let Some(name) = name.as_str() else {
+                continue;
+            };
+            params.push(self.schema_param(
+                name,
+                property,
+                style.clone(),
+                required.contains(&name),
+                None,
+            ));
+        }
+        params
+    }
+
+    /// Return the representation of a body with the given media type and schema
+    fn representation(&self, media_type: &str, schema: &'a Value) -> Representation {
+        let media_type = media_type.parse::<mime::Mime>().ok();
+        if let Some(name) = schema_ref_name(schema) {
+            if media_type
+                .as_ref()
+                .is_none_or(|m| m.essence_str() == "application/json")
+            {
+                return Representation::Reference(RepresentationRef::Id(name.to_string()));
+            }
+        }
+        let style = if media_type.as_ref().is_some_and(|m| {
+            m.essence_str() == "application/x-www-form-urlencoded"
+                || m.essence_str() == "multipart/form-data"
+        }) {
+            ParamStyle::Query
+        } else {
+            ParamStyle::Plain
+        };
+        Representation::Definition(RepresentationDef {
+            media_type,
+            params: self.property_params(schema, style),
+            ..Default::default()
+        })
+    }
+
+    /// Return the representations of the content of a request or response
+    ///
+    /// # Arguments
+    /// * `object` - The request body or response object
+    /// * `default_media_types` - The media types of a Swagger 2 schema
+    fn representations(
+        &self,
+        object: &'a Value,
+        default_media_types: &[&str],
+    ) -> Vec<Representation> {
+        if self.swagger {
+            if object["schema"].is_null() {
+                return vec![];
+            }
+            default_media_types
+                .iter()
+                .map(|media_type| self.representation(media_type, &object["schema"]))
+                .collect()
+        } else {
+            object["content"]
+                .as_mapping()
+                .into_iter()
+                .flatten()
+                .filter_map(|(media_type, content)| {
+                    Some(self.representation(media_type.as_str()?, &content["schema"]))
+                })
+                .collect()
+        }
+    }
+
+    /// Return the param for a parameter, and where it goes
+    fn parameter(&self, parameter: &'a Value) -> Option<(&'a str, Param)> {
+        let parameter = self.resolve(parameter);
+        let name = parameter["name"].as_str()?;
+        let location = parameter["in"].as_str()?;
+        let style = match location {
+            "path" => ParamStyle::Template,
+            "query" | "formData" => ParamStyle::Query,
+            "header" => ParamStyle::Header,
+            // Swagger 2 request bodies are handled separately
+            "body" => return None,
+            _ => {
+                log::warn!("Unable to import {} parameter {}", location, name);
+                return None;
+            }
+        };
+        // Swagger 2 describes the values of non-body parameters in the parameter itself
+        let schema = if self.swagger {
+            parameter
+        } else {
+            &parameter["schema"]
+        };
+        let param = self.schema_param(
+            name,
+            schema,
+            style,
+            parameter["required"]
+                .as_bool()
+                .unwrap_or(location == "path"),
+            parameter["description"].as_str(),
+        );
+        Some((location, param))
+    }
+
+    /// Return the media types that a Swagger 2 operation consumes or produces
+    fn media_types(&self, operation: &'a Value, key: &str) -> Vec<&'a str> {
+        let media_types = operation[key]
+            .as_sequence()
+            .or_else(|| self.spec[key].as_sequence())
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>();
+        if media_types.is_empty() {
+            vec!["application/json"]
+        } else {
+            media_types
+        }
+    }
+
+    /// Return the method of an operation, and the template params that it uses
+    fn method(
+        &self,
+        name: &str,
+        operation: &'a Value,
+        path_params: &'a Value,
+        id: &str,
+    ) -> (Method, Vec<Param>) {
+        let mut template_params = vec![];
+        let mut request = Request::default();
+        let parameters = path_params
+            .as_sequence()
+            .into_iter()
+            .flatten()
+            .chain(operation["parameters"].as_sequence().into_iter().flatten());
+        let mut form_params = vec![];
+        for parameter in parameters {
+            match self.parameter(parameter) {
+                Some(("path", param)) => template_params.push(param),
+                Some(("formData", param)) => form_params.push(param),
+                Some((_, param)) => {
+                    // Operation parameters override those of the path
+                    request
+                        .params
+                        .retain(|p| p.name != param.name || p.style != param.style);
+                    request.params.push(param);
+                }
+                None => {}
+            }
+        }
+
+        if self.swagger {
+            let consumes = self.media_types(operation, "consumes");
+            if let Some(body) = operation["parameters"]
+                .as_sequence()
+                .into_iter()
+                .flatten()
+                .map(|p| self.resolve(p))
+                .find(|p| p["in"] == "body")
+            {
+                request.representations = self.representations(body, &consumes);
+                request
+                    .docs
+                    .extend(text_doc(None, body["description"].as_str()));
+            }
+            if !form_params.is_empty() {
+                let media_type = consumes
+                    .iter()
+                    .find(|m| m.starts_with("multipart/"))
+                    .unwrap_or(&"application/x-www-form-urlencoded");
+                request
+                    .representations
+                    .push(Representation::Definition(RepresentationDef {
+                        media_type: media_type.parse().ok(),
+                        params: form_params,
+                        ..Default::default()
+                    }));
+            }
+        } else if !operation["requestBody"].is_null() {
+            let body = self.resolve(&operation["requestBody"]);
+            request.representations = self.representations(body, &[]);
+            request
+                .docs
+                .extend(text_doc(None, body["description"].as_str()));
+        }
+
+        let produces = if self.swagger {
+            self.media_types(operation, "produces")
+        } else {
+            vec![]
+        };
+        // Generated code returns the content of a single response, and reports other statuses
+        // as errors, so only the successful response is imported
+        let statuses = operation["responses"]
+            .as_mapping()
+            .into_iter()
+            .flatten()
+            .filter_map(|(status, response)| Some((scalar_text(status)?, response)))
+            .collect::<Vec<_>>();
+        let responses = statuses
+            .iter()
+            .find(|(status, _)| status.starts_with('2'))
+            .or_else(|| statuses.iter().find(|(status, _)| status == "default"))
+            .map(|(status, response)| {
+                let response = self.resolve(response);
+                Response {
+                    docs: text_doc(None, response["description"].as_str())
+                        .into_iter()
+                        .collect(),
+                    // Generated code only reads response headers that link to resources
+                    params: vec![],
+                    // Ranges such as 2XX and the default response have no single status
+                    status: status.parse().ok(),
+                    representations: self.representations(response, &produces),
+                }
+            })
+            .into_iter()
+            .collect();
+
+        let method = Method {
+            id: operation["operationId"]
+                .as_str()
+                .map_or_else(|| format!("{}-{}", name, id), str::to_string),
+            name: name.to_uppercase(),
+            docs: text_doc(
+                operation["summary"].as_str(),
+                operation["description"].as_str(),
+            )
+            .into_iter()
+            .collect(),
+            request,
+            responses,
+        };
+        (method, template_params)
+    }
+
+    fn import(&self) -> Result<Application, ImportError> {
+        let info = &self.spec["info"];
+        let schemas = if self.swagger {
+            &self.spec["definitions"]
+        } else {
+            &self.spec["components"]["schemas"]
+        };
+        let representations = schemas
+            .as_mapping()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, schema)| {
+                Some(RepresentationDef {
+                    id: Some(name.as_str()?.to_string()),
+                    media_type: Some(mime::APPLICATION_JSON),
+                    docs: text_doc(None, schema["description"].as_str())
+                        .into_iter()
+                        .collect(),
+                    params: self.property_params(schema, ParamStyle::Plain),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let mut resources = vec![];
+        let mut resource_types: Vec<ResourceType> = vec![];
+        for (path, item) in self.spec["paths"].as_mapping().into_iter().flatten() {
+            let Some(path) = path.as_str() else {
+                continue;
+            };
+            let item = self.resolve(item);
+            let id = resource_type_id(path, &resource_types);
+            let mut params = vec![];
+            let mut methods = vec![];
+            for name in OPERATION_METHODS {
+                let operation = &item[*name];
+                if operation.is_null() {
+                    continue;
+                }
+                let (method, template_params) =
+                    self.method(name, operation, &item["parameters"], &id);
+                for param in template_params {
+                    if !params.iter().any(|p: &Param| p.name == param.name) {
+                        params.push(param);
+                    }
+                }
+                methods.push(method);
+            }
+            if methods.is_empty() {
+                continue;
+            }
+            resource_types.push(ResourceType {
+                id: id.clone(),
+                query_type: mime::APPLICATION_WWW_FORM_URLENCODED,
+                methods,
+                docs: text_doc(item["summary"].as_str(), item["description"].as_str())
+                    .into_iter()
+                    .collect(),
+                subresources: vec![],
+                params: vec![],
+            });
+            resources.push(Resource {
+                id: None,
+                path: Some(path.trim_start_matches('/').to_string()),
+                r#type: vec![ResourceTypeRef::Id(id)],
+                query_type: mime::APPLICATION_WWW_FORM_URLENCODED,
+                methods: vec![],
+                docs: vec![],
+                subresources: vec![],
+                params,
+            });
+        }
+
+        Ok(Application {
+            resources: vec![Resources {
+                base: self.base()?,
+                resources,
+            }],
+            resource_types,
+            docs: text_doc(info["title"].as_str(), info["description"].as_str())
+                .into_iter()
+                .collect(),
+            grammars: vec![],
+            representations,
+        })
+    }
+}
+
+/// Return a unique id for the resource type of a path, e.g. `people-name` for
+/// `/people/{name}`
+fn resource_type_id(path: &str, resource_types: &[ResourceType]) -> String {
+    let id = path
+        .split('/')
+        .map(|segment| segment.trim_matches(|c| c == '{' || c == '}'))
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let id = if id.is_empty() {
+        "root".to_string()
+    } else {
+        id
+    };
+    let mut candidate = id.clone();
+    let mut n = 2;
+    while resource_types.iter().any(|rt| rt.id == candidate) {
+        candidate = format!("{}-{}", id, n);
+        n += 1;
+    }
+    candidate
+}
+
+/// Import an OpenAPI 3 or Swagger 2 document
+///
+/// Each path becomes a resource with a resource type of its own, and the schemas of the
+/// document become representations. Schemas of properties that are not simple values are
+/// imported as `xsd:anyType`. Only the successful response of each operation is imported,
+/// without its headers, and cookie parameters are left out with a warning.
+pub fn import(spec: &Value) -> Result<Application, ImportError> {
+    let swagger = if spec["openapi"]
+        .as_str()
+        .is_some_and(|v| v.starts_with("3."))
+    {
+        false
+    } else if spec["swagger"].as_str() == Some("2.0") {
+        true
+    } else {
+        return Err(ImportError::UnsupportedVersion);
+    };
+    Importer { spec, swagger }.import()
+}
+
+/// Import an OpenAPI 3 or Swagger 2 document from its YAML or JSON text
+pub fn import_str(text: &str) -> Result<Application, ImportError> {
+    import(&serde_yaml::from_str(text)?)
+}
+
+#[test]
+fn test_convert() {
+    let app = crate::parse_string(
+        r##"<application xmlns="http://wadl.dev.java.net/2009/02"
+                         xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+            <doc title="People API">The people API</doc>
+            <resources base="https://api.example.com/1.0/">
+                <resource path="people" type="#people">
+                    <resource path="{name}">
+                        <param name="name" style="template" type="xsd:string"/>
+                        <method name="GET" id="person-get">
+                            <response status="200">
+                                <representation href="#person"/>
+                            </response>
+                            <response status="404"><doc>Not found</doc></response>
+                        </method>
+                    </resource>
+                </resource>
+            </resources>
+            <resource_type id="people">
+                <param name="X-Trace" style="header"/>
+                <method name="GET" id="people-get">
+                    <request>
+                        <param name="ws.size" style="query" type="xsd:int" default="10"/>
+                    </request>
+                    <response>
+                        <param name="ETag" style="header"/>
+                        <representation mediaType="application/json">
+                            <param name="total" style="plain" type="xsd:int" required="true"/>
+                        </representation>
+                    </response>
+                </method>
+                <method name="PROPFIND" id="people-propfind"/>
+            </resource_type>
+            <representation id="person" mediaType="application/json">
+                <param name="name" style="plain" required="true"><doc>The name</doc></param>
+                <param name="karma" style="plain" type="xsd:long"/>
+                <param name="status" style="plain">
+                    <option value="active"/>
+                    <option value="inactive"/>
+                </param>
+                <param name="friends_collection_link" style="plain" repeating="true">
+                    <link resource_type="#people"/>
+                </param>
+            </representation>
+        </application>"##,
+    )
+    .unwrap();
+    let spec = convert(&app);
+    let yaml = serde_yaml::to_string(&spec).unwrap();
+    assert!(
+        yaml.starts_with("openapi: 3.0.3\ninfo:\n  title: People API\n"),
+        "{}",
+        yaml
+    );
+    assert_eq!(spec["info"]["description"], "The people API");
+    assert_eq!(spec["servers"][0]["url"], "https://api.example.com/1.0/");
+
+    let people = &spec["paths"]["/people"];
+    assert_eq!(people["parameters"][0]["name"], "X-Trace");
+    assert_eq!(people["parameters"][0]["in"], "header");
+    let get = &people["get"];
+    assert_eq!(get["operationId"], "people-get");
+    assert_eq!(get["parameters"][0]["in"], "query");
+    assert_eq!(get["parameters"][0]["schema"]["type"], "integer");
+    assert_eq!(get["parameters"][0]["schema"]["default"], 10);
+    let response = &get["responses"]["default"];
+    assert_eq!(response["headers"]["ETag"]["schema"]["type"], "string");
+    let schema = &response["content"]["application/json"]["schema"];
+    assert_eq!(schema["properties"]["total"]["format"], "int32");
+    assert_eq!(schema["required"][0], "total");
+    assert!(people.get("propfind").is_none());
+
+    let person = &spec["paths"]["/people/{name}"];
+    assert_eq!(person["parameters"][0]["in"], "path");
+    assert_eq!(person["parameters"][0]["required"], true);
+    let responses = &person["get"]["responses"];
+    assert_eq!(
+        responses["200"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/person"
+    );
+    assert_eq!(responses["404"]["description"], "Not found");
+
+    let person = &spec["components"]["schemas"]["person"];
+    assert_eq!(person["properties"]["name"]["description"], "The name");
+    assert_eq!(person["properties"]["karma"]["format"], "int64");
+    assert_eq!(person["properties"]["status"]["enum"][1], "inactive");
+    let friends = &person["properties"]["friends_collection_link"];
+    assert_eq!(friends["type"], "array");
+    assert_eq!(friends["items"]["format"], "uri");
+}
+
+#[test]
+fn test_import() {
+    let app = import_str(
+        r##"
+openapi: 3.0.3
+info:
+  title: People API
+  description: The people API
+  version: "1.0"
+servers:
+  - url: https://{host}/1.0
+    variables:
+      host:
+        default: api.example.com
+paths:
+  /people:
+    get:
+      operationId: people-get
+      parameters:
+        - $ref: "#/components/parameters/size"
+      responses:
+        "200":
+          description: The people
+          headers:
+            ETag:
+              schema:
+                type: string
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  entries:
+                    type: array
+                    items:
+                      $ref: "#/components/schemas/person"
+  /people/{name}:
+    parameters:
+      - name: name
+        in: path
+        schema:
+          type: string
+    get:
+      summary: Get a person
+      responses:
+        "200":
+          description: The person
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/person"
+        default:
+          description: An error
+    patch:
+      operationId: person-patch
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/person"
+      responses:
+        "204":
+          description: Updated
+components:
+  parameters:
+    size:
+      name: ws.size
+      in: query
+      schema:
+        type: integer
+        format: int32
+        default: 10
+  schemas:
+    person:
+      type: object
+      required: [name]
+      properties:
+        name:
+          type: string
+          description: The name
+        karma:
+          type: integer
+        status:
+          type: string
+          enum: [active, inactive]
+"##,
+    )
+    .unwrap();
+    assert_eq!(app.docs[0].title.as_deref(), Some("People API"));
+    assert_eq!(app.docs[0].content, "The people API");
+    assert_eq!(
+        app.resources[0].base.as_ref().unwrap().as_str(),
+        "https://api.example.com/1.0/"
+    );
+
+    let resources = &app.resources[0].resources;
+    assert_eq!(resources[0].path.as_deref(), Some("people"));
+    assert_eq!(
+        resources[0].r#type,
+        vec![ResourceTypeRef::Id("people".into())]
+    );
+    assert_eq!(resources[1].path.as_deref(), Some("people/{name}"));
+    assert_eq!(resources[1].params[0].style, ParamStyle::Template);
+    assert!(resources[1].params[0].required);
+
+    let people = &app.resource_types[0];
+    let get = &people.methods[0];
+    assert_eq!(get.id, "people-get");
+    assert_eq!(get.request.params[0].name, "ws.size");
+    assert_eq!(get.request.params[0].style, ParamStyle::Query);
+    assert_eq!(get.request.params[0].r#type, "xsd:int");
+    assert_eq!(get.request.params[0].default.as_deref(), Some("10"));
+    assert_eq!(get.responses[0].status, Some(200));
+    let Representation::Definition(def) = &get.responses[0].representations[0] else {
+        panic!("expected an inline representation");
+    };
+    assert_eq!(def.params[0].name, "entries");
+    assert!(def.params[0].repeating);
+    assert_eq!(def.params[0].r#type, "xsd:anyType");
+
+    let person = &app.resource_types[1];
+    assert_eq!(person.id, "people-name");
+    assert_eq!(person.methods[0].id, "get-people-name");
+    assert_eq!(
+        person.methods[0].docs[0].title.as_deref(),
+        Some("Get a person")
+    );
+    assert_eq!(person.methods[0].responses.len(), 1);
+    assert_eq!(person.methods[1].name, "PATCH");
+    assert!(matches!(
+        &person.methods[1].request.representations[0],
+        Representation::Reference(RepresentationRef::Id(id)) if id == "person"
+    ));
+
+    let person = &app.representations[0];
+    assert_eq!(person.id.as_deref(), Some("person"));
+    assert_eq!(person.media_type, Some(mime::APPLICATION_JSON));
+    assert!(person.params[0].required);
+    assert_eq!(person.params[0].doc.as_ref().unwrap().content, "The name");
+    assert_eq!(person.params[1].r#type, "xsd:long");
+    assert!(!person.params[1].required);
+    assert_eq!(
+        person.params[2]
+            .options
+            .as_ref()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        vec!["active", "inactive"]
+    );
+}
+
+#[test]
+fn test_import_swagger() {
+    let app = import_str(
+        r##"{
+            "swagger": "2.0",
+            "info": {"title": "Pets", "version": "1"},
+            "host": "pets.example.com",
+            "basePath": "/v2",
+            "schemes": ["http"],
+            "produces": ["application/json"],
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "addPet",
+                        "parameters": [
+                            {"name": "body", "in": "body", "schema": {"$ref": "#/definitions/Pet"}},
+                            {"name": "X-Request-Id", "in": "header", "type": "string"}
+                        ],
+                        "responses": {"200": {"description": "OK", "schema": {"$ref": "#/definitions/Pet"}}}
+                    }
+                },
+                "/pets/{id}/photo": {
+                    "post": {
+                        "consumes": ["multipart/form-data"],
+                        "parameters": [
+                            {"name": "id", "in": "path", "required": true, "type": "integer"},
+                            {"name": "caption", "in": "formData", "type": "string"}
+                        ],
+                        "responses": {"204": {"description": "Uploaded"}}
+                    }
+                }
+            },
+            "definitions": {
+                "Pet": {"type": "object", "properties": {"tags": {"type": "array", "items": {"type": "string"}}}}
+            }
+        }"##,
+    )
+    .unwrap();
+    assert_eq!(
+        app.resources[0].base.as_ref().unwrap().as_str(),
+        "http://pets.example.com/v2/"
+    );
+    let add = &app.resource_types[0].methods[0];
+    assert!(matches!(
+        &add.request.representations[0],
+        Representation::Reference(RepresentationRef::Id(id)) if id == "Pet"
+    ));
+    assert_eq!(add.request.params[0].style, ParamStyle::Header);
+    assert!(matches!(
+        &add.responses[0].representations[0],
+        Representation::Reference(RepresentationRef::Id(id)) if id == "Pet"
+    ));
+
+    let photo = &app.resources[0].resources[1];
+    assert_eq!(photo.params[0].r#type, "xsd:long");
+    let upload = &app.resource_types[1].methods[0];
+    let Representation::Definition(form) = &upload.request.representations[0] else {
+        panic!("expected a form representation");
+    };
+    assert_eq!(
+        form.media_type.as_ref().unwrap().essence_str(),
+        "multipart/form-data"
+    );
+    assert_eq!(form.params[0].style, ParamStyle::Query);
+
+    assert!(app.representations[0].params[0].repeating);
+    assert_eq!(app.representations[0].params[0].r#type, "xsd:string");
+}
+
+#[test]
+fn test_import_converted() {
+    let app = crate::parse_string(include_str!("../tests/people-wadl.xml")).unwrap();
+    let imported = import(&convert(&app)).unwrap();
+    assert_eq!(imported.resources[0].base, app.resources[0].base);
+    assert!(imported
+        .resource_types
+        .iter()
+        .flat_map(|rt| rt.methods.iter())
+        .any(|m| m.id == "person-get"));
+    assert!(matches!(
+        import_str("swagger: '1.2'"),
+        Err(ImportError::UnsupportedVersion)
+    ));
+}
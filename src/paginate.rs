@@ -0,0 +1,476 @@
+//! Iteration over paginated collections
+//!
+//! Collections are often returned one page at a time, with a link to the next page. [`Pager`]
+//! (and [`PageStream`] for async code) follows those links, and yields the items of all pages.
+//! They take a closure that fetches the page at a URL, and one that extracts the URL of the
+//! next page from a page; pages are turned into items with [`IntoIterator`].
+//!
+//! ```no_run
+//! use wadl::paginate::Pager;
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Page {
+//!     entries: Vec<String>,
+//!     next_collection_link: Option<url::Url>,
+//! }
+//!
+//! impl IntoIterator for Page {
+//!     type Item = String;
+//!     type IntoIter = std::vec::IntoIter<String>;
+//!
+//!     fn into_iter(self) -> Self::IntoIter {
+//!         self.entries.into_iter()
+//!     }
+//! }
+//!
+//! let client = reqwest::blocking::Client::new();
+//! let pager = Pager::new(
+//!     "https://api.example.com/1.0/people".parse().unwrap(),
+//!     |url| Ok::<Page, wadl::Error>(client.get(url.clone()).send()?.json()?),
+//!     |page| page.next_collection_link.clone(),
+//! );
+//! for name in pager {
+//!     println!("{}", name?);
+//! }
+//! # Ok::<(), wadl::Error>(())
+//! ```
+
+use crate::Error;
+use url::Url;
+
+/// Fetch the page at a URL
+type FetchPage<'a, P> = Box<dyn FnMut(&Url) -> Result<P, Error> + 'a>;
+
+/// Return the URL of the page after a page
+type NextPage<'a, P> = Box<dyn Fn(&P) -> Option<Url> + 'a>;
+
+/// Fetch the page at a URL, asynchronously
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+type FetchPageAsync<'a, P> =
+    Box<dyn FnMut(Url) -> crate::r#async::BoxFuture<'a, Result<P, Error>> + Send + 'a>;
+
+/// Return the URL of the page after a page, from any thread
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+type NextPageAsync<'a, P> = Box<dyn Fn(&P) -> Option<Url> + Send + 'a>;
+
+enum Source<'a, P> {
+    Fetch {
+        fetch: FetchPage<'a, P>,
+        next: NextPage<'a, P>,
+        url: Option<Url>,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    Prefetch(std::sync::mpsc::Receiver<Result<P, Error>>),
+    Done,
+}
+
+/// An iterator over the items of a paginated collection
+///
+/// Pages are fetched when the items of the previous page have been consumed. Iteration stops
+/// after the first error.
+pub struct Pager<'a, P: IntoIterator> {
+    items: Option<P::IntoIter>,
+    source: Source<'a, P>,
+}
+
+impl<'a, P: IntoIterator> Pager<'a, P> {
+    /// Iterate over the items of the page at `url`, and of the pages that follow it
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the first page
+    /// * `fetch` - Fetch the page at a URL
+    /// * `next` - Return the URL of the page after a page, if any
+    pub fn new(
+        url: Url,
+        fetch: impl FnMut(&Url) -> Result<P, Error> + 'a,
+        next: impl Fn(&P) -> Option<Url> + 'a,
+    ) -> Self {
+        Self {
+            items: None,
+            source: Source::Fetch {
+                fetch: Box::new(fetch),
+                next: Box::new(next),
+                url: Some(url),
+            },
+        }
+    }
+
+    /// Iterate over the items of a page that has already been fetched, and of the pages that
+    /// follow it
+    pub fn from_page(
+        first: P,
+        fetch: impl FnMut(&Url) -> Result<P, Error> + 'a,
+        next: impl Fn(&P) -> Option<Url> + 'a,
+    ) -> Self {
+        let url = next(&first);
+        Self {
+            items: Some(first.into_iter()),
+            source: Source::Fetch {
+                fetch: Box::new(fetch),
+                next: Box::new(next),
+                url,
+            },
+        }
+    }
+
+    /// Iterate over the items of the pages starting at `url`, fetching pages on a background
+    /// thread
+    ///
+    /// Up to `pages` pages (at least one) are fetched ahead of the page whose items are being
+    /// consumed. The thread stops when the pager is dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn prefetching(
+        url: Url,
+        pages: usize,
+        mut fetch: impl FnMut(&Url) -> Result<P, Error> + Send + 'static,
+        next: impl Fn(&P) -> Option<Url> + Send + 'static,
+    ) -> Self
+    where
+        P: Send + 'static,
+    {
+        // The thread holds on to one page while it waits for room in the channel
+        let (sender, receiver) = std::sync::mpsc::sync_channel(pages.max(1) - 1);
+        std::thread::spawn(move || {
+            let mut url = Some(url);
+            while let Some(current) = url.take() {
+                let page = fetch(&current);
+                if let Ok(page) = page.as_ref() {
+                    url = next(page);
+                }
+                if sender.send(page).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            items: None,
+            source: Source::Prefetch(receiver),
+        }
+    }
+
+    /// Return the next page, if any
+    fn next_page(&mut self) -> Option<Result<P, Error>> {
+        match &mut self.source {
+            Source::Fetch { fetch, next, url } => {
+                let page = fetch(&url.take()?);
+                if let Ok(page) = page.as_ref() {
+                    *url = next(page);
+                }
+                Some(page)
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Source::Prefetch(receiver) => receiver.recv().ok(),
+            Source::Done => None,
+        }
+    }
+}
+
+impl<P: IntoIterator> Iterator for Pager<'_, P> {
+    type Item = Result<P::Item, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.items.as_mut().and_then(|items| items.next()) {
+                return Some(Ok(item));
+            }
+            match self.next_page()? {
+                Ok(page) => self.items = Some(page.into_iter()),
+                Err(err) => {
+                    self.items = None;
+                    self.source = Source::Done;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+impl<P: IntoIterator> std::fmt::Debug for Pager<'_, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let next = match &self.source {
+            Source::Fetch { url, .. } => url.as_ref().map(|url| url.as_str()),
+            #[cfg(not(target_arch = "wasm32"))]
+            Source::Prefetch(_) => Some("<prefetching>"),
+            Source::Done => None,
+        };
+        f.debug_struct("Pager")
+            .field("next", &next)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A stream of the items of a paginated collection
+///
+/// By default, a page is fetched when the items of the previous page have been consumed; use
+/// [`PageStream::with_prefetch`] to fetch pages while items are being consumed. Items of pages
+/// that were fetched before an error are yielded before it, and the stream ends after it.
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+pub struct PageStream<'a, P: IntoIterator> {
+    fetch: FetchPageAsync<'a, P>,
+    next: NextPageAsync<'a, P>,
+    url: Option<Url>,
+    pending: Option<crate::r#async::BoxFuture<'a, Result<P, Error>>>,
+    pages: std::collections::VecDeque<P::IntoIter>,
+    error: Option<Error>,
+    prefetch: usize,
+}
+
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+impl<'a, P: IntoIterator> PageStream<'a, P> {
+    /// Stream the items of the page at `url`, and of the pages that follow it
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the first page
+    /// * `fetch` - Fetch the page at a URL
+    /// * `next` - Return the URL of the page after a page, if any
+    pub fn new(
+        url: Url,
+        fetch: impl FnMut(Url) -> crate::r#async::BoxFuture<'a, Result<P, Error>> + Send + 'a,
+        next: impl Fn(&P) -> Option<Url> + Send + 'a,
+    ) -> Self {
+        Self::starting_at(Some(url), None, Box::new(fetch), Box::new(next))
+    }
+
+    /// Stream the items of a page that has already been fetched, and of the pages that follow
+    /// it
+    pub fn from_page(
+        first: P,
+        fetch: impl FnMut(Url) -> crate::r#async::BoxFuture<'a, Result<P, Error>> + Send + 'a,
+        next: impl Fn(&P) -> Option<Url> + Send + 'a,
+    ) -> Self {
+        Self::starting_at(next(&first), Some(first), Box::new(fetch), Box::new(next))
+    }
+
+    fn starting_at(
+        url: Option<Url>,
+        first: Option<P>,
+        fetch: FetchPageAsync<'a, P>,
+        next: NextPageAsync<'a, P>,
+    ) -> Self {
+        Self {
+            fetch,
+            next,
+            url,
+            pending: None,
+            pages: first.into_iter().map(|page| page.into_iter()).collect(),
+            error: None,
+            prefetch: 0,
+        }
+    }
+
+    /// Fetch up to `pages` pages ahead of the page whose items are being consumed
+    ///
+    /// Pages are only fetched while the stream is polled.
+    pub fn with_prefetch(mut self, pages: usize) -> Self {
+        self.prefetch = pages;
+        self
+    }
+}
+
+// The fields are never pinned
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+impl<P: IntoIterator> Unpin for PageStream<'_, P> {}
+
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+impl<P: IntoIterator> futures_core::Stream for PageStream<'_, P> {
+    type Item = Result<P::Item, Error>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        use std::task::Poll;
+        let this = self.get_mut();
+        loop {
+            // The page whose items are being consumed counts as well
+            if this.pending.is_none() && this.pages.len() <= this.prefetch {
+                if let Some(url) = this.url.take() {
+                    this.pending = Some((this.fetch)(url));
+                }
+            }
+            if let Some(pending) = this.pending.as_mut() {
+                if let Poll::Ready(page) = pending.as_mut().poll(cx) {
+                    this.pending = None;
+                    match page {
+                        Ok(page) => {
+                            this.url = (this.next)(&page);
+                            this.pages.push_back(page.into_iter());
+                        }
+                        Err(err) => this.error = Some(err),
+                    }
+                    continue;
+                }
+            }
+            if let Some(items) = this.pages.front_mut() {
+                match items.next() {
+                    Some(item) => return Poll::Ready(Some(Ok(item))),
+                    None => {
+                        this.pages.pop_front();
+                        continue;
+                    }
+                }
+            }
+            if let Some(err) = this.error.take() {
+                return Poll::Ready(Some(Err(err)));
+            }
+            return if this.pending.is_some() {
+                Poll::Pending
+            } else {
+                Poll::Ready(None)
+            };
+        }
+    }
+}
+
+#[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+impl<P: IntoIterator> std::fmt::Debug for PageStream<'_, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PageStream")
+            .field("next", &self.url.as_ref().map(|url| url.as_str()))
+            .field("prefetch", &self.prefetch)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Page {
+        items: Vec<u32>,
+        next: Option<Url>,
+    }
+
+    impl IntoIterator for Page {
+        type Item = u32;
+        type IntoIter = std::vec::IntoIter<u32>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.items.into_iter()
+        }
+    }
+
+    /// Fetch a page of a collection of `pages` pages of 2 items each, the last of which fails
+    /// to fetch if `fail` is set
+    fn fetch_page(url: &Url, pages: u32, fail: bool) -> Result<Page, Error> {
+        let n: u32 = url.path().trim_start_matches('/').parse().unwrap();
+        if fail && n == pages - 1 {
+            return Err(Error::InvalidUrl);
+        }
+        Ok(Page {
+            items: vec![n * 2, n * 2 + 1],
+            next: (n + 1 < pages)
+                .then(|| format!("https://example.com/{}", n + 1).parse().unwrap()),
+        })
+    }
+
+    fn first() -> Url {
+        "https://example.com/0".parse().unwrap()
+    }
+
+    #[test]
+    fn test_pager() {
+        let fetched = std::cell::RefCell::new(vec![]);
+        let mut pager = Pager::new(
+            first(),
+            |url| {
+                fetched.borrow_mut().push(url.path().to_string());
+                fetch_page(url, 3, false)
+            },
+            |page| page.next.clone(),
+        );
+        assert_eq!(pager.next().unwrap().unwrap(), 0);
+        // Pages are fetched lazily
+        assert_eq!(*fetched.borrow(), vec!["/0"]);
+        assert_eq!(
+            pager.collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(*fetched.borrow(), vec!["/0", "/1", "/2"]);
+    }
+
+    #[test]
+    fn test_pager_error() {
+        let pager = Pager::from_page(
+            fetch_page(&first(), 3, true).unwrap(),
+            |url| fetch_page(url, 3, true),
+            |page| page.next.clone(),
+        );
+        let items = pager.collect::<Vec<_>>();
+        assert_eq!(items.len(), 5);
+        assert_eq!(items[3].as_ref().unwrap(), &3);
+        assert!(matches!(items[4], Err(Error::InvalidUrl)));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_pager_prefetching() {
+        let pager = Pager::prefetching(
+            first(),
+            2,
+            |url| fetch_page(url, 4, false),
+            |page| page.next.clone(),
+        );
+        assert_eq!(
+            pager.collect::<Result<Vec<_>, _>>().unwrap(),
+            (0..8).collect::<Vec<_>>()
+        );
+
+        let pager = Pager::prefetching(
+            first(),
+            1,
+            |url| fetch_page(url, 4, true),
+            |page| page.next.clone(),
+        );
+        let items = pager.collect::<Vec<_>>();
+        assert_eq!(items.len(), 7);
+        assert!(items[6].is_err());
+    }
+
+    #[cfg(all(feature = "async-native", not(target_arch = "wasm32")))]
+    #[tokio::test]
+    async fn test_page_stream() {
+        use futures_core::Stream;
+        use std::sync::{Arc, Mutex};
+
+        async fn collect(mut stream: PageStream<'_, Page>) -> Vec<Result<u32, Error>> {
+            let mut items = vec![];
+            while let Some(item) =
+                std::future::poll_fn(|cx| std::pin::Pin::new(&mut stream).poll_next(cx)).await
+            {
+                items.push(item);
+            }
+            items
+        }
+
+        let fetched = Arc::new(Mutex::new(vec![]));
+        let log = fetched.clone();
+        let stream = PageStream::new(
+            first(),
+            move |url| {
+                log.lock().unwrap().push(url.path().to_string());
+                Box::pin(async move { fetch_page(&url, 3, false) })
+            },
+            |page| page.next.clone(),
+        )
+        .with_prefetch(1);
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&stream);
+        let items = collect(stream).await;
+        assert_eq!(
+            items.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            (0..6).collect::<Vec<_>>()
+        );
+        assert_eq!(*fetched.lock().unwrap(), vec!["/0", "/1", "/2"]);
+
+        let stream = PageStream::from_page(
+            fetch_page(&first(), 3, true).unwrap(),
+            |url| Box::pin(async move { fetch_page(&url, 3, true) }),
+            |page| page.next.clone(),
+        )
+        .with_prefetch(2);
+        let items = collect(stream).await;
+        assert_eq!(items.len(), 5);
+        assert!(matches!(items[4], Err(Error::InvalidUrl)));
+    }
+}
@@ -59,7 +59,16 @@ impl std::fmt::Display for Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Xml(e) => Some(e),
+            Error::Url(e) => Some(e),
+            Error::Mime(e) => Some(e),
+        }
+    }
+}
 
 pub fn parse_options(element: &Element) -> Option<Options> {
     let mut options = Options::new();
@@ -73,7 +82,12 @@ pub fn parse_options(element: &Element) -> Option<Options> {
                     .get("mediaType")
                     .cloned()
                     .map(|x| x.parse().unwrap());
-                options.insert(value.unwrap(), media_type);
+                let value = value.unwrap();
+                let docs = parse_docs(element);
+                if let Some(doc) = docs.into_iter().next() {
+                    options.set_doc(&value, doc);
+                }
+                options.insert(value, media_type);
             }
         }
     }
@@ -90,7 +104,9 @@ fn test_parse_options() {
     let xml = r#"
         <param name="format">
             <option value="json" mediaType="application/json"/>
-            <option value="xml" mediaType="application/xml"/>
+            <option value="xml" mediaType="application/xml">
+                <doc>XML output</doc>
+            </option>
         </param>
     "#;
     let element = Element::parse(xml.as_bytes()).unwrap();
@@ -104,6 +120,8 @@ fn test_parse_options() {
         options.get("xml").unwrap(),
         &Some("application/xml".parse().unwrap())
     );
+    assert_eq!(options.get_doc("json"), None);
+    assert_eq!(options.get_doc("xml").unwrap().content, "XML output");
 }
 
 /// Parse a `param` element.
@@ -180,6 +198,7 @@ pub fn parse_params(resource_element: &Element, allowed_styles: &[ParamStyle]) -
                     .map(|s| s == "true")
                     .unwrap_or(false);
                 let fixed = element.attributes.get("fixed").cloned();
+                let default = element.attributes.get("default").cloned();
                 if !allowed_styles.contains(&style) {
                     log::warn!(
                         "Invalid param style: {:?} for element {} (expected one of: {:?})",
@@ -198,6 +217,7 @@ pub fn parse_params(resource_element: &Element, allowed_styles: &[ParamStyle]) -
                     required,
                     repeating,
                     fixed,
+                    default,
                     links,
                     options,
                     doc: if doc.len() == 1 {
@@ -493,6 +513,10 @@ fn parse_resource_type(resource_type_element: &Element) -> Result<ResourceType,
 }
 
 /// Parse an XML application description from a reader.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, err)
+)]
 pub fn parse<R: Read>(reader: R) -> Result<Application, Error> {
     let mut resources = Vec::new();
     let mut resource_types = Vec::new();
@@ -533,7 +557,7 @@ pub fn parse<R: Read>(reader: R) -> Result<Application, Error> {
 
     let representations = parse_representations(&root);
 
-    Ok(Application {
+    let application = Application {
         resources,
         docs,
         resource_types,
@@ -545,21 +569,41 @@ pub fn parse<R: Read>(reader: R) -> Result<Application, Error> {
                 Representation::Reference(_) => panic!("Reference in root"),
             })
             .collect(),
-    })
+    };
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        resources = application.iter_all_resources().count(),
+        resource_types = application.resource_types.len(),
+        representations = application.representations.len(),
+        "parsed WADL"
+    );
+    Ok(application)
 }
 
 /// Parse an XML application description from a file.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+)]
 pub fn parse_file<P: AsRef<std::path::Path>>(path: P) -> Result<Application, Error> {
     let file = std::fs::File::open(path).map_err(Error::Io)?;
     parse(file)
 }
 
 /// Parse a string containing an XML application description.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = s.len()))
+)]
 pub fn parse_string(s: &str) -> Result<Application, Error> {
     parse(s.as_bytes())
 }
 
 /// Parse a byte slice containing an XML application description.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(size = bytes.len()))
+)]
 pub fn parse_bytes(bytes: &[u8]) -> Result<Application, Error> {
     parse(bytes)
 }
@@ -640,6 +684,7 @@ fn test_parse_representations() {
         assert_eq!(r.params[0].style, ParamStyle::Plain);
         assert!(r.params[0].required);
         assert_eq!(r.params[0].fixed, Some("baz".to_string()));
+        assert_eq!(r.params[0].default, Some("bar".to_string()));
         assert_eq!(r.params[0].doc.as_ref().unwrap().content, "Foo");
         assert_eq!(
             r.params[0].doc.as_ref().unwrap().lang,
@@ -764,7 +809,7 @@ fn parse_method(method_element: &Element) -> Method {
     let request_element = method_element
         .children
         .iter()
-        .find(|node| node.as_element().map_or(false, |e| e.name == "request"))
+        .find(|node| node.as_element().is_some_and(|e| e.name == "request"))
         .and_then(|node| node.as_element());
 
     let request = request_element.map(parse_request).unwrap_or_default();
@@ -772,7 +817,7 @@ fn parse_method(method_element: &Element) -> Method {
     let responses = method_element
         .children
         .iter()
-        .filter(|node| node.as_element().map_or(false, |e| e.name == "response"))
+        .filter(|node| node.as_element().is_some_and(|e| e.name == "response"))
         .map(|node| node.as_element().unwrap())
         .map(parse_response)
         .collect();
@@ -0,0 +1,237 @@
+//! Fields that distinguish `null` from a missing value, and JSON Patch operations
+//!
+//! In a JSON merge patch (RFC 7396), a field that is left out is left unchanged, while `null`
+//! removes it. `Option<T>` can not express both, so generated code uses [`Patch`] or
+//! `Option<Option<T>>` for such fields, depending on
+//! [`Config::nullable_field_style`](crate::codegen::Config::nullable_field_style).
+//!
+//! Methods that take a JSON Patch document (RFC 6902) take a list of [`PatchOperation`]s.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The value of a field that can be left out, set to `null` or set to a value
+///
+/// Fields of this type should be annotated with
+/// `#[serde(default, skip_serializing_if = "wadl::Patch::is_unchanged")]`, so that
+/// [`Patch::Unchanged`] fields are left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Patch<T> {
+    /// The field is missing, i.e. left unchanged
+    #[default]
+    Unchanged,
+
+    /// The field is `null`, i.e. cleared
+    Null,
+
+    /// The field is set to a value
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    /// Return whether the field is missing
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Patch::Unchanged)
+    }
+
+    /// Return whether the field is `null`
+    pub fn is_null(&self) -> bool {
+        matches!(self, Patch::Null)
+    }
+
+    /// Return the value of the field, if it is set to one
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Convert to a reference to the value
+    pub fn as_ref(&self) -> Patch<&T> {
+        match self {
+            Patch::Unchanged => Patch::Unchanged,
+            Patch::Null => Patch::Null,
+            Patch::Value(value) => Patch::Value(value),
+        }
+    }
+
+    /// Map the value of the field
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Patch<U> {
+        match self {
+            Patch::Unchanged => Patch::Unchanged,
+            Patch::Null => Patch::Null,
+            Patch::Value(value) => Patch::Value(f(value)),
+        }
+    }
+}
+
+impl<T> From<T> for Patch<T> {
+    fn from(value: T) -> Self {
+        Patch::Value(value)
+    }
+}
+
+impl<T> From<Option<Option<T>>> for Patch<T> {
+    fn from(value: Option<Option<T>>) -> Self {
+        match value {
+            None => Patch::Unchanged,
+            Some(None) => Patch::Null,
+            Some(Some(value)) => Patch::Value(value),
+        }
+    }
+}
+
+impl<T> From<Patch<T>> for Option<Option<T>> {
+    fn from(value: Patch<T>) -> Self {
+        match value {
+            Patch::Unchanged => None,
+            Patch::Null => Some(None),
+            Patch::Value(value) => Some(Some(value)),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    /// Serialize the field, as `null` if it is unchanged or cleared
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Unchanged | Patch::Null => serializer.serialize_none(),
+            Patch::Value(value) => serializer.serialize_some(value),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    /// Deserialize a field that is present, with missing fields left to `#[serde(default)]`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            None => Patch::Null,
+            Some(value) => Patch::Value(value),
+        })
+    }
+}
+
+/// Deserialize a field that is present into `Some`, so that `null` becomes `Some(None)`
+///
+/// For use with `#[serde(default, deserialize_with = "wadl::patch::deserialize_some")]` on
+/// `Option<Option<T>>` fields, so that missing fields become `None`.
+pub fn deserialize_some<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+/// An operation in a JSON Patch document, as described in RFC 6902
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Add a value
+    Add {
+        /// The location to add the value at, as JSON pointer
+        path: String,
+        /// The value to add
+        value: serde_json::Value,
+    },
+
+    /// Remove a value
+    Remove {
+        /// The location of the value to remove, as JSON pointer
+        path: String,
+    },
+
+    /// Replace a value
+    Replace {
+        /// The location of the value to replace, as JSON pointer
+        path: String,
+        /// The new value
+        value: serde_json::Value,
+    },
+
+    /// Move a value
+    Move {
+        /// The location of the value to move, as JSON pointer
+        from: String,
+        /// The location to move the value to, as JSON pointer
+        path: String,
+    },
+
+    /// Copy a value
+    Copy {
+        /// The location of the value to copy, as JSON pointer
+        from: String,
+        /// The location to copy the value to, as JSON pointer
+        path: String,
+    },
+
+    /// Check that a value matches, failing the whole patch otherwise
+    Test {
+        /// The location of the value to check, as JSON pointer
+        path: String,
+        /// The expected value
+        value: serde_json::Value,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Changes {
+        #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+        name: Patch<String>,
+
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_some"
+        )]
+        email: Option<Option<String>>,
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let changes: Changes = serde_json::from_str("{}").unwrap();
+        assert_eq!(changes, Changes::default());
+
+        let changes: Changes = serde_json::from_str(r#"{"name": null, "email": null}"#).unwrap();
+        assert_eq!(changes.name, Patch::Null);
+        assert_eq!(changes.email, Some(None));
+
+        let changes: Changes =
+            serde_json::from_str(r#"{"name": "Jo", "email": "jo@example.com"}"#).unwrap();
+        assert_eq!(changes.name, Patch::Value("Jo".to_string()));
+        assert_eq!(changes.email, Some(Some("jo@example.com".to_string())));
+    }
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(serde_json::to_string(&Changes::default()).unwrap(), "{}");
+        let changes = Changes {
+            name: Patch::Null,
+            email: Some(None),
+        };
+        assert_eq!(
+            serde_json::to_string(&changes).unwrap(),
+            r#"{"name":null,"email":null}"#
+        );
+        let changes = Changes {
+            name: "Jo".to_string().into(),
+            email: Some(Some("jo@example.com".to_string())),
+        };
+        assert_eq!(
+            serde_json::to_string(&changes).unwrap(),
+            r#"{"name":"Jo","email":"jo@example.com"}"#
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Patch::<u32>::from(None), Patch::Unchanged);
+        assert_eq!(Patch::<u32>::from(Some(None)), Patch::Null);
+        assert_eq!(Patch::from(Some(Some(1))), Patch::Value(1));
+        assert_eq!(Option::<Option<u32>>::from(Patch::Null), Some(None));
+        assert_eq!(Patch::Value(2).map(|x| x * 2).value(), Some(&4));
+        assert!(Patch::<u32>::Null.as_ref().is_null());
+    }
+}
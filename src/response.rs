@@ -0,0 +1,254 @@
+//! Helpers for handling responses in generated code
+//!
+//! Generated methods call these rather than inlining the status and content type checks, which
+//! keeps the generated code small for large APIs.
+
+use crate::Error;
+
+/// Return the media type of a response, if it has a valid `Content-Type` header
+pub fn content_type(headers: &reqwest::header::HeaderMap) -> Option<mime::Mime> {
+    headers
+        .get(reqwest::header::CONTENT_TYPE)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// Check that a response has one of the expected statuses
+///
+/// If `expected_statuses` is empty, any successful status is accepted.
+pub fn check_status(status: reqwest::StatusCode, expected_statuses: &[u16]) -> Result<(), Error> {
+    let expected = if expected_statuses.is_empty() {
+        status.is_success()
+    } else {
+        expected_statuses.contains(&status.as_u16())
+    };
+    if expected {
+        Ok(())
+    } else {
+        Err(Error::UnhandledStatus(status))
+    }
+}
+
+/// Check that a response has an expected status and a JSON body
+pub fn check_json_response(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    expected_statuses: &[u16],
+) -> Result<(), Error> {
+    check_status(status, expected_statuses)?;
+    match content_type(headers) {
+        Some(c) if c.essence_str() == mime::APPLICATION_JSON.essence_str() => Ok(()),
+        c => Err(Error::UnhandledContentType(c)),
+    }
+}
+
+/// Check whether the media type of a response matches that of a representation
+///
+/// Besides the essence (e.g. `application/json`), every parameter of `media_type` has to be
+/// present in `content_type`. If `profile` is set, it has to be one of the profiles listed in
+/// the `profile` parameter of `content_type`.
+pub fn media_type_matches(
+    content_type: Option<&mime::Mime>,
+    media_type: &str,
+    profile: Option<&str>,
+) -> bool {
+    let (Some(content_type), Ok(media_type)) = (content_type, media_type.parse::<mime::Mime>())
+    else {
+        return false;
+    };
+    if content_type.essence_str() != media_type.essence_str() {
+        return false;
+    }
+    if !media_type
+        .params()
+        .all(|(name, value)| content_type.get_param(name) == Some(value))
+    {
+        return false;
+    }
+    match profile {
+        Some(profile) => content_type
+            .get_param("profile")
+            .is_some_and(|p| p.as_str().split_whitespace().any(|p| p == profile)),
+        None => true,
+    }
+}
+
+/// The fields of a form-urlencoded response body
+///
+/// Generated representations that are sent as `application/x-www-form-urlencoded` use this to
+/// convert the fields to their types.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FormFields(Vec<(String, String)>);
+
+impl FormFields {
+    /// Parse a form-urlencoded body
+    pub fn parse(body: &[u8]) -> Self {
+        Self(
+            form_urlencoded::parse(body)
+                .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                .collect(),
+        )
+    }
+
+    fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, Error> {
+        value
+            .parse()
+            .map_err(|_| Error::InvalidFormField(name.to_string()))
+    }
+
+    /// Return the first value of a field, if it is present
+    pub fn value<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, Error> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| Self::parse_value(name, v))
+            .transpose()
+    }
+
+    /// Return the first value of a field that has to be present
+    pub fn required<T: std::str::FromStr>(&self, name: &str) -> Result<T, Error> {
+        self.value(name)?
+            .ok_or_else(|| Error::InvalidFormField(name.to_string()))
+    }
+
+    /// Return all values of a field
+    pub fn values<T: std::str::FromStr>(&self, name: &str) -> Result<Vec<T>, Error> {
+        self.0
+            .iter()
+            .filter(|(k, _)| k == name)
+            .map(|(_, v)| Self::parse_value(name, v))
+            .collect()
+    }
+
+    /// Return all values of a field, or `None` if it is not present
+    pub fn optional_values<T: std::str::FromStr>(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<T>>, Error> {
+        let values = self.values(name)?;
+        Ok(if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        })
+    }
+
+    /// Return the fields other than the given ones, with their first value
+    pub fn unknown(&self, known: &[&str]) -> std::collections::HashMap<String, serde_json::Value> {
+        let mut ret = std::collections::HashMap::new();
+        for (k, v) in &self.0 {
+            if !known.contains(&k.as_str()) {
+                ret.entry(k.clone())
+                    .or_insert_with(|| serde_json::Value::String(v.clone()));
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(any(feature = "tower", feature = "ureq"))]
+/// Parse the JSON body of a response with a collected body
+///
+/// See [`check_status`] for the meaning of `expected_statuses`.
+pub fn parse_json_http_response<T: serde::de::DeserializeOwned, B: AsRef<[u8]>>(
+    resp: &http::Response<B>,
+    expected_statuses: &[u16],
+) -> Result<T, Error> {
+    check_json_response(resp.status(), resp.headers(), expected_statuses)?;
+    Ok(serde_json::from_slice(resp.body().as_ref())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_status() {
+        assert!(check_status(reqwest::StatusCode::OK, &[]).is_ok());
+        assert!(check_status(reqwest::StatusCode::CREATED, &[201]).is_ok());
+        assert!(matches!(
+            check_status(reqwest::StatusCode::OK, &[201]),
+            Err(Error::UnhandledStatus(reqwest::StatusCode::OK))
+        ));
+        assert!(matches!(
+            check_status(reqwest::StatusCode::NOT_FOUND, &[]),
+            Err(Error::UnhandledStatus(reqwest::StatusCode::NOT_FOUND))
+        ));
+    }
+
+    #[test]
+    fn test_check_json_response() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert!(matches!(
+            check_json_response(reqwest::StatusCode::OK, &headers, &[]),
+            Err(Error::UnhandledContentType(None))
+        ));
+        headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            "application/json; charset=utf-8".parse().unwrap(),
+        );
+        assert!(check_json_response(reqwest::StatusCode::OK, &headers, &[]).is_ok());
+        headers.insert(reqwest::header::CONTENT_TYPE, "text/html".parse().unwrap());
+        assert!(matches!(
+            check_json_response(reqwest::StatusCode::OK, &headers, &[]),
+            Err(Error::UnhandledContentType(Some(_)))
+        ));
+    }
+
+    #[test]
+    fn test_media_type_matches() {
+        let content_type: mime::Mime =
+            "application/json; profile=\"http://example.com/a http://example.com/b\""
+                .parse()
+                .unwrap();
+        assert!(media_type_matches(
+            Some(&content_type),
+            "application/json",
+            None
+        ));
+        assert!(media_type_matches(
+            Some(&content_type),
+            "application/json",
+            Some("http://example.com/b")
+        ));
+        assert!(!media_type_matches(
+            Some(&content_type),
+            "application/json",
+            Some("http://example.com/c")
+        ));
+        assert!(!media_type_matches(
+            Some(&content_type),
+            "application/json; charset=utf-8",
+            None
+        ));
+        assert!(!media_type_matches(Some(&content_type), "text/html", None));
+        assert!(!media_type_matches(None, "application/json", None));
+    }
+
+    #[test]
+    fn test_form_fields() {
+        let fields = FormFields::parse(b"name=jelmer&karma=42&tag=a&tag=b&other=x%20y");
+        assert_eq!(fields.required::<String>("name").unwrap(), "jelmer");
+        assert_eq!(fields.value::<i32>("karma").unwrap(), Some(42));
+        assert_eq!(fields.value::<i32>("missing").unwrap(), None);
+        assert_eq!(
+            fields.values::<String>("tag").unwrap(),
+            vec!["a".to_string(), "b".to_string()]
+        );
+        assert_eq!(fields.optional_values::<String>("missing").unwrap(), None);
+        assert!(matches!(
+            fields.required::<i32>("missing"),
+            Err(Error::InvalidFormField(name)) if name == "missing"
+        ));
+        assert!(matches!(
+            fields.value::<i32>("name"),
+            Err(Error::InvalidFormField(name)) if name == "name"
+        ));
+        assert_eq!(
+            fields.unknown(&["name", "karma", "tag"]),
+            maplit::hashmap! {"other".to_string() => serde_json::Value::String("x y".to_string())}
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Statistics about the contents of WADL documents
+//!
+//! [`Application::stats`] counts the definitions of an application and reports which of them
+//! lack documentation, e.g. to track the doc coverage of an API description over time.
+
+use crate::ast::*;
+use std::collections::BTreeMap;
+
+/// How well the items of a kind are documented
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// The number of items
+    pub total: usize,
+
+    /// The names of the items without docs
+    pub undocumented: Vec<String>,
+}
+
+impl Coverage {
+    /// The number of items with docs
+    pub fn documented(&self) -> usize {
+        self.total - self.undocumented.len()
+    }
+
+    fn add(&mut self, name: impl FnOnce() -> String, documented: bool) {
+        self.total += 1;
+        if !documented {
+            self.undocumented.push(name());
+        }
+    }
+}
+
+/// Statistics about an application, see [`Application::stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of resources, including nested resources
+    pub resources: usize,
+
+    /// The resource types, by id
+    pub resource_types: Coverage,
+
+    /// The methods, by id
+    pub methods: Coverage,
+
+    /// The params, by name prefixed with the id or path of what they belong to, e.g.
+    /// `people-get/ws.size`
+    pub params: Coverage,
+
+    /// The representations defined at the application level, by id
+    pub representations: Coverage,
+
+    /// The number of representations defined inline in requests and responses
+    pub inline_representations: usize,
+
+    /// The number of representations of each media type
+    pub media_types: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    /// The fraction of resource types, methods, params and representations with docs
+    ///
+    /// Returns 1 if there are none.
+    pub fn doc_coverage(&self) -> f64 {
+        let kinds = [
+            &self.resource_types,
+            &self.methods,
+            &self.params,
+            &self.representations,
+        ];
+        let total = kinds.iter().map(|c| c.total).sum::<usize>();
+        if total == 0 {
+            return 1.0;
+        }
+        kinds.iter().map(|c| c.documented()).sum::<usize>() as f64 / total as f64
+    }
+
+    fn add_params(&mut self, owner: &str, params: &[Param]) {
+        for param in params {
+            self.params
+                .add(|| format!("{}/{}", owner, param.name), param.doc.is_some());
+        }
+    }
+
+    fn add_representation_def(&mut self, def: &RepresentationDef, owner: &str) {
+        if let Some(media_type) = def.media_type.as_ref() {
+            *self
+                .media_types
+                .entry(media_type.essence_str().to_string())
+                .or_default() += 1;
+        }
+        self.add_params(def.id.as_deref().unwrap_or(owner), &def.params);
+    }
+
+    fn add_representations(&mut self, representations: &[Representation], owner: &str) {
+        for representation in representations {
+            if let Representation::Definition(def) = representation {
+                self.inline_representations += 1;
+                self.add_representation_def(def, owner);
+            }
+        }
+    }
+
+    fn add_methods(&mut self, methods: &[Method]) {
+        for method in methods {
+            self.methods
+                .add(|| method.id.clone(), !method.docs.is_empty());
+            self.add_params(&method.id, &method.request.params);
+            self.add_representations(&method.request.representations, &method.id);
+            for response in &method.responses {
+                self.add_params(&method.id, &response.params);
+                self.add_representations(&response.representations, &method.id);
+            }
+        }
+    }
+
+    fn add_resources(&mut self, parent: &str, resources: &[Resource]) {
+        for resource in resources {
+            self.resources += 1;
+            let path = format!(
+                "{}/{}",
+                parent,
+                resource
+                    .path
+                    .as_deref()
+                    .unwrap_or_default()
+                    .trim_matches('/')
+            );
+            self.add_params(&path, &resource.params);
+            self.add_methods(&resource.methods);
+            self.add_resources(&path, &resource.subresources);
+        }
+    }
+}
+
+impl Application {
+    /// Collect statistics about the definitions in this application
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        for resources in &self.resources {
+            stats.add_resources("", &resources.resources);
+        }
+        for resource_type in &self.resource_types {
+            stats
+                .resource_types
+                .add(|| resource_type.id.clone(), !resource_type.docs.is_empty());
+            stats.add_params(&resource_type.id, &resource_type.params);
+            stats.add_methods(&resource_type.methods);
+            stats.add_resources(&resource_type.id, &resource_type.subresources);
+        }
+        for representation in &self.representations {
+            stats.representations.add(
+                || representation.id.clone().unwrap_or_default(),
+                !representation.docs.is_empty(),
+            );
+            stats.add_representation_def(representation, "");
+        }
+        stats
+    }
+}
+
+#[test]
+fn test_stats() {
+    let app = crate::parse_string(
+        r##"<application xmlns="http://wadl.dev.java.net/2009/02">
+            <resources base="https://api.example.com/1.0/">
+                <resource path="people" type="#people">
+                    <resource path="{name}">
+                        <param name="name" style="template"/>
+                        <method name="DELETE" id="person-delete"><doc>Delete a person</doc></method>
+                    </resource>
+                </resource>
+            </resources>
+            <resource_type id="people">
+                <doc>The people</doc>
+                <method name="GET" id="people-get">
+                    <request>
+                        <param name="ws.size" style="query"><doc>The page size</doc></param>
+                    </request>
+                    <response>
+                        <representation href="#person"/>
+                        <representation mediaType="text/plain"/>
+                    </response>
+                </method>
+            </resource_type>
+            <resource_type id="person"/>
+            <representation id="person" mediaType="application/json">
+                <param name="karma" style="plain"/>
+            </representation>
+        </application>"##,
+    )
+    .unwrap();
+    let stats = app.stats();
+    assert_eq!(stats.resources, 2);
+    assert_eq!(stats.resource_types.total, 2);
+    assert_eq!(stats.resource_types.undocumented, ["person"]);
+    assert_eq!(stats.methods.total, 2);
+    assert_eq!(stats.methods.undocumented, ["people-get"]);
+    assert_eq!(stats.params.total, 3);
+    assert_eq!(
+        stats.params.undocumented,
+        ["/people/{name}/name", "person/karma"]
+    );
+    assert_eq!(stats.representations.undocumented, ["person"]);
+    assert_eq!(stats.inline_representations, 1);
+    assert_eq!(
+        stats.media_types.into_iter().collect::<Vec<_>>(),
+        [
+            ("application/json".to_string(), 1),
+            ("text/plain".to_string(), 1)
+        ]
+    );
+    assert_eq!(app.stats().doc_coverage(), 3.0 / 8.0);
+}
@@ -0,0 +1,1199 @@
+//! Utilities for testing code that uses generated clients.
+//!
+//! [`MockClient`] implements the `Client` traits, but rather than talking to the real service
+//! it serves canned responses from a local HTTP server. Requests are matched on their method
+//! and original URL.
+//!
+//! ```no_run
+//! use wadl::testing::{MockClient, MockResponse};
+//!
+//! let client = MockClient::new();
+//! client.expect(
+//!     reqwest::Method::GET,
+//!     "https://api.example.com/1.0/~*",
+//!     MockResponse::json(200, &serde_json::json!({"name": "jdoe"})),
+//! );
+//! ```
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Name of the header used to pass the original URL to the mock server.
+const ORIGINAL_URL_HEADER: &str = "x-wadl-mock-url";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A canned response served by [`MockClient`].
+pub struct MockResponse {
+    /// The status code of the response.
+    pub status: u16,
+
+    /// The headers of the response.
+    pub headers: Vec<(String, String)>,
+
+    /// The body of the response.
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// Create a new response with the given status code and an empty body.
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    /// Create a new JSON response.
+    pub fn json(status: u16, value: &serde_json::Value) -> Self {
+        Self::new(status)
+            .with_header(reqwest::header::CONTENT_TYPE.as_str(), "application/json")
+            .with_body(serde_json::to_vec(value).unwrap())
+    }
+
+    /// Add a header to the response.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the body of the response.
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A request received by [`MockClient`].
+pub struct RecordedRequest {
+    /// The HTTP method of the request.
+    pub method: reqwest::Method,
+
+    /// The original URL of the request.
+    pub url: url::Url,
+
+    /// The headers of the request, with lowercase names.
+    pub headers: Vec<(String, String)>,
+
+    /// The body of the request.
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Get the value of a header.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.headers
+            .iter()
+            .find(|(n, _)| n == &name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+struct Expectation {
+    method: reqwest::Method,
+    pattern: String,
+    response: MockResponse,
+}
+
+#[derive(Default)]
+struct State {
+    expectations: Vec<Expectation>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// Match a URL against a pattern, in which `*` matches any sequence of characters.
+fn matches_pattern(pattern: &str, url: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = url.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    for (i, part) in parts.iter().enumerate() {
+        if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+/// A client that serves canned responses, for use in tests.
+///
+/// Expectations are matched in the order in which they were registered; the first
+/// expectation whose method and URL pattern match is used. Requests that do not match any
+/// expectation receive a `501 Not Implemented` response.
+pub struct MockClient {
+    state: Arc<Mutex<State>>,
+    addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    #[cfg(feature = "blocking")]
+    blocking_client: std::sync::OnceLock<reqwest::blocking::Client>,
+    #[cfg(feature = "async-native")]
+    async_client: std::sync::OnceLock<reqwest::Client>,
+    #[cfg(feature = "ureq")]
+    ureq_agent: std::sync::OnceLock<::ureq::Agent>,
+}
+
+impl Default for MockClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClient {
+    /// Create a new mock client, starting a local server to serve responses.
+    pub fn new() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("unable to bind mock server");
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let server_state = state.clone();
+        let server_shutdown = shutdown.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if server_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = server_state.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = handle_connection(stream, &state) {
+                            log::warn!("Error handling mock request: {}", e);
+                        }
+                    });
+                }
+            }
+        });
+
+        Self {
+            state,
+            addr,
+            shutdown,
+            #[cfg(feature = "blocking")]
+            blocking_client: std::sync::OnceLock::new(),
+            #[cfg(feature = "async-native")]
+            async_client: std::sync::OnceLock::new(),
+            #[cfg(feature = "ureq")]
+            ureq_agent: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Register a canned response for requests matching a method and URL pattern.
+    ///
+    /// # Arguments
+    /// * `method` - The HTTP method to match
+    /// * `pattern` - The URL to match; `*` matches any sequence of characters
+    /// * `response` - The response to serve
+    pub fn expect(&self, method: reqwest::Method, pattern: &str, response: MockResponse) {
+        self.state.lock().unwrap().expectations.push(Expectation {
+            method,
+            pattern: pattern.to_string(),
+            response,
+        });
+    }
+
+    /// Return all requests received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Rewrite a URL so that it points at the local server.
+    fn local_url(&self, url: &url::Url) -> url::Url {
+        let mut local = url.clone();
+        local.set_scheme("http").unwrap();
+        local.set_host(Some(&self.addr.ip().to_string())).unwrap();
+        local.set_port(Some(self.addr.port())).unwrap();
+        local.set_fragment(None);
+        local
+    }
+}
+
+impl Drop for MockClient {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the accept loop so that it notices the shutdown
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, state: &Mutex<State>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line)? == 0 {
+        return Ok(());
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.clone())
+    };
+
+    let mut body = vec![];
+    if let Some(length) = header("content-length").and_then(|l| l.parse::<usize>().ok()) {
+        body.resize(length, 0);
+        reader.read_exact(&mut body)?;
+    } else if header("transfer-encoding").as_deref() == Some("chunked") {
+        loop {
+            let mut size_line = String::new();
+            reader.read_line(&mut size_line)?;
+            let size = usize::from_str_radix(size_line.trim(), 16).unwrap_or(0);
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk)?;
+            if size == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    }
+
+    let url = header(ORIGINAL_URL_HEADER)
+        .and_then(|u| u.parse::<url::Url>().ok())
+        .unwrap_or_else(|| format!("http://localhost{}", path).parse().unwrap());
+    let method = reqwest::Method::from_bytes(method.as_bytes()).unwrap_or(reqwest::Method::GET);
+
+    let response = {
+        let mut state = state.lock().unwrap();
+        let response = state
+            .expectations
+            .iter()
+            .find(|e| e.method == method && matches_pattern(&e.pattern, url.as_str()))
+            .map(|e| e.response.clone())
+            .unwrap_or_else(|| {
+                MockResponse::new(501).with_body(format!("No mock for {} {}", method, url))
+            });
+        state.requests.push(RecordedRequest {
+            method,
+            url,
+            headers: headers
+                .into_iter()
+                .filter(|(n, _)| n != ORIGINAL_URL_HEADER)
+                .collect(),
+            body,
+        });
+        response
+    };
+
+    let reason = reqwest::StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Unknown");
+    write!(stream, "HTTP/1.1 {} {}\r\n", response.status, reason)?;
+    for (name, value) in response.headers.iter() {
+        write!(stream, "{}: {}\r\n", name, value)?;
+    }
+    write!(
+        stream,
+        "content-length: {}\r\nconnection: close\r\n\r\n",
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+#[cfg(feature = "blocking")]
+impl crate::blocking::Client for MockClient {
+    fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::blocking::RequestBuilder {
+        let client = self
+            .blocking_client
+            .get_or_init(reqwest::blocking::Client::new);
+        client
+            .request(method, self.local_url(&url))
+            .header(ORIGINAL_URL_HEADER, url.as_str())
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl crate::r#async::Client for MockClient {
+    async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+        let client = self.async_client.get_or_init(reqwest::Client::new);
+        client
+            .request(method, self.local_url(&url))
+            .header(ORIGINAL_URL_HEADER, url.as_str())
+    }
+}
+
+#[cfg(all(feature = "async-native", not(feature = "async")))]
+impl crate::r#async::NativeClient for MockClient {
+    async fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+        let client = self.async_client.get_or_init(reqwest::Client::new);
+        client
+            .request(method, self.local_url(&url))
+            .header(ORIGINAL_URL_HEADER, url.as_str())
+    }
+}
+
+#[cfg(feature = "ureq")]
+impl crate::ureq::Client for MockClient {
+    fn send(
+        &self,
+        mut request: crate::ureq::http::Request<Vec<u8>>,
+    ) -> Result<crate::ureq::http::Response<Vec<u8>>, crate::Error> {
+        let url: url::Url = request.uri().to_string().parse()?;
+        *request.uri_mut() = self
+            .local_url(&url)
+            .as_str()
+            .parse()
+            .map_err(|_| crate::Error::InvalidUrl)?;
+        request.headers_mut().insert(
+            ORIGINAL_URL_HEADER,
+            url.as_str().parse().map_err(|_| crate::Error::InvalidUrl)?,
+        );
+        let agent = self
+            .ureq_agent
+            .get_or_init(::ureq::Agent::new_with_defaults);
+        agent.send(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern(
+            "https://example.com/",
+            "https://example.com/"
+        ));
+        assert!(!matches_pattern(
+            "https://example.com/",
+            "https://example.com/a"
+        ));
+        assert!(matches_pattern(
+            "https://example.com/*",
+            "https://example.com/a"
+        ));
+        assert!(matches_pattern(
+            "https://example.com/*/bugs?*",
+            "https://example.com/foo/bugs?ws.op=search"
+        ));
+        assert!(!matches_pattern(
+            "https://example.com/*/bugs",
+            "https://example.com/foo/people"
+        ));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_mock_client() {
+        use crate::blocking::Client;
+        let client = MockClient::new();
+        client.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/~*",
+            MockResponse::json(200, &serde_json::json!({"name": "jdoe"})),
+        );
+
+        let resp = client
+            .request(
+                reqwest::Method::GET,
+                "https://api.example.com/1.0/~jdoe".parse().unwrap(),
+            )
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+        let body: serde_json::Value = resp.json().unwrap();
+        assert_eq!(body, serde_json::json!({"name": "jdoe"}));
+
+        let resp = client
+            .request(
+                reqwest::Method::POST,
+                "https://api.example.com/1.0/bugs".parse().unwrap(),
+            )
+            .body("data")
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::NOT_IMPLEMENTED);
+
+        let requests = client.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].url.as_str(),
+            "https://api.example.com/1.0/~jdoe"
+        );
+        assert_eq!(requests[1].method, reqwest::Method::POST);
+        assert_eq!(requests[1].body, b"data");
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_byte_stream() {
+        use crate::blocking::Client;
+        use std::io::Read;
+        let client = MockClient::new();
+        client.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/file",
+            MockResponse::new(200).with_body(vec![0u8, 1, 2, 255]),
+        );
+
+        let resp = client
+            .request(
+                reqwest::Method::GET,
+                "https://api.example.com/1.0/file".parse().unwrap(),
+            )
+            .send()
+            .unwrap();
+        let mut body = vec![];
+        crate::blocking::ByteStream::new(resp)
+            .read_to_end(&mut body)
+            .unwrap();
+        assert_eq!(body, vec![0u8, 1, 2, 255]);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_send_with_retry() {
+        use crate::blocking::Client;
+        let client = MockClient::new();
+        client.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/busy",
+            MockResponse::new(503).with_header("Retry-After", "0"),
+        );
+        let policy = crate::RetryPolicy {
+            retries: 2,
+            backoff: std::time::Duration::from_secs(60),
+            ..Default::default()
+        };
+
+        let req = client.request(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/busy".parse().unwrap(),
+        );
+        let resp = crate::blocking::send_with_retry(&client, req, &policy).unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(client.requests().len(), 3);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_retrying_client() {
+        use crate::blocking::{Client, RetryingClient};
+        let client = RetryingClient::new(
+            MockClient::new(),
+            crate::RetryPolicy::new(2).with_backoff(std::time::Duration::ZERO),
+        );
+        client.inner().expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/busy",
+            MockResponse::new(502),
+        );
+        client.inner().expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/missing",
+            MockResponse::new(404),
+        );
+
+        let get = |path: &str| {
+            let req = client.request(
+                reqwest::Method::GET,
+                format!("https://api.example.com/1.0/{}", path)
+                    .parse()
+                    .unwrap(),
+            );
+            client.send(req).unwrap().status()
+        };
+        assert_eq!(get("busy"), reqwest::StatusCode::BAD_GATEWAY);
+        assert_eq!(client.inner().requests().len(), 3);
+        assert_eq!(get("missing"), reqwest::StatusCode::NOT_FOUND);
+        assert_eq!(client.inner().requests().len(), 4);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_retry_connection_error() {
+        use crate::blocking::{Client, LayeredClient, Middleware, Next, RetryingClient};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+        struct CountAttempts;
+
+        impl Middleware for CountAttempts {
+            fn handle(
+                &self,
+                req: reqwest::blocking::Request,
+                next: Next<'_>,
+            ) -> Result<reqwest::blocking::Response, crate::Error> {
+                ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+                next.run(req)
+            }
+        }
+
+        // Nothing listens on a port that was just released
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let client = RetryingClient::new(
+            LayeredClient::new(reqwest::blocking::Client::new()).layer(CountAttempts),
+            crate::RetryPolicy::new(2).with_backoff(std::time::Duration::ZERO),
+        );
+        let req = client.request(
+            reqwest::Method::GET,
+            format!("http://{}/", addr).parse().unwrap(),
+        );
+        assert!(matches!(client.send(req), Err(crate::Error::Reqwest(e)) if e.is_connect()));
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 3);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_retrying_client() {
+        use crate::r#async::{Client, RetryingClient};
+        let client = RetryingClient::new(
+            MockClient::new(),
+            crate::RetryPolicy::new(1).with_backoff(std::time::Duration::ZERO),
+        );
+        client.inner().expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/busy",
+            MockResponse::new(429).with_header("Retry-After", "0"),
+        );
+
+        let req = client
+            .request(
+                reqwest::Method::GET,
+                "https://api.example.com/1.0/busy".parse().unwrap(),
+            )
+            .await;
+        let resp = crate::r#async::send(&client, req).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(client.inner().requests().len(), 2);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_byte_stream() {
+        use crate::r#async::Client;
+        use futures_core::Stream;
+        let client = MockClient::new();
+        client.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/file",
+            MockResponse::new(200).with_body(vec![0u8, 1, 2, 255]),
+        );
+
+        let resp = client
+            .request(
+                reqwest::Method::GET,
+                "https://api.example.com/1.0/file".parse().unwrap(),
+            )
+            .await
+            .send()
+            .await
+            .unwrap();
+        let mut stream = crate::r#async::ByteStream::new(resp);
+        let mut body = vec![];
+        while let Some(chunk) =
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut stream).poll_next(cx)).await
+        {
+            body.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(body, vec![0u8, 1, 2, 255]);
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    const PEOPLE_WADL: &str = r#"<application xmlns="http://research.sun.com/wadl/2006/10">
+        <resources base="https://api.example.com/1.0/">
+            <resource path="people" id="people"/>
+        </resources>
+    </application>"#;
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_wadl_client() {
+        use crate::blocking::{Client, WadlClient};
+        let mock = MockClient::new();
+        mock.expect(
+            reqwest::Method::GET,
+            "http://localhost/1.0/",
+            MockResponse::new(200).with_body(PEOPLE_WADL),
+        );
+
+        let wadl_url = mock.local_url(&"https://api.example.com/1.0/".parse().unwrap());
+        let client = WadlClient::new(reqwest::blocking::Client::new())
+            .with_header(
+                reqwest::header::USER_AGENT,
+                reqwest::header::HeaderValue::from_static("wadl-test"),
+            )
+            .with_auth(crate::Auth::Bearer("secret".to_string()))
+            .with_wadl_url(&wadl_url)
+            .unwrap();
+        assert_eq!(
+            client.base_url().map(|u| u.as_str()),
+            Some("https://api.example.com/1.0/")
+        );
+        assert_eq!(
+            client.join("people").unwrap().as_str(),
+            "https://api.example.com/1.0/people"
+        );
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].header("authorization"), Some("Bearer secret"));
+        assert_eq!(requests[0].header("user-agent"), Some("wadl-test"));
+
+        // Resources are looked up in the cached WADL, rather than fetched again
+        let people = crate::blocking::get_wadl_resource_by_href(
+            &client,
+            &"https://api.example.com/1.0/people".parse().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(people.id.as_deref(), Some("people"));
+        assert!(Client::application(&client).is_some());
+        assert_eq!(mock.requests().len(), 1);
+
+        mock.expect(
+            reqwest::Method::GET,
+            "http://localhost/missing",
+            MockResponse::new(404),
+        );
+        let missing = mock.local_url(&"https://api.example.com/missing".parse().unwrap());
+        assert!(matches!(
+            WadlClient::from_wadl_url(&missing),
+            Err(crate::Error::UnhandledStatus(
+                reqwest::StatusCode::NOT_FOUND
+            ))
+        ));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_get_wadl_resource_by_href() {
+        use crate::blocking::get_wadl_resource_by_href;
+        let mock = MockClient::new();
+        mock.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/*",
+            MockResponse::new(200).with_body(PEOPLE_WADL),
+        );
+
+        let people = "https://api.example.com/1.0/people".parse().unwrap();
+        let application = crate::parse_string(PEOPLE_WADL).unwrap();
+        let resource = get_wadl_resource_by_href(&mock, &people, Some(&application)).unwrap();
+        assert_eq!(resource.id.as_deref(), Some("people"));
+        assert!(mock.requests().is_empty());
+
+        let resource = get_wadl_resource_by_href(&mock, &people, None).unwrap();
+        assert_eq!(resource.id.as_deref(), Some("people"));
+        assert_eq!(mock.requests().len(), 1);
+
+        let bugs = "https://api.example.com/1.0/bugs".parse().unwrap();
+        assert!(matches!(
+            get_wadl_resource_by_href(&mock, &bugs, Some(&application)),
+            Err(crate::Error::ResourceNotFound(url)) if url == bugs
+        ));
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_application_cache() {
+        use crate::blocking::{get_wadl_resource_by_href, WadlClient};
+        use crate::cache::ApplicationCache;
+        let mock = MockClient::new();
+        mock.expect(
+            reqwest::Method::GET,
+            "http://localhost/1.0/",
+            MockResponse::new(200)
+                .with_header("ETag", "\"v1\"")
+                .with_body(PEOPLE_WADL),
+        );
+        let cache = std::sync::Arc::new(ApplicationCache::default());
+
+        let wadl_url = mock.local_url(&"https://api.example.com/1.0/".parse().unwrap());
+        let client = WadlClient::default()
+            .with_cache(cache.clone())
+            .with_wadl_url(&wadl_url)
+            .unwrap();
+        assert_eq!(
+            cache.get(&wadl_url).unwrap().etag.as_deref(),
+            Some("\"v1\"")
+        );
+
+        // The cached document is revalidated
+        crate::blocking::get_wadl_application(&client, &wadl_url, &cache).unwrap();
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].header("if-none-match"), None);
+        assert_eq!(requests[1].header("if-none-match"), Some("\"v1\""));
+
+        // An unchanged document is not parsed again
+        mock.expect(
+            reqwest::Method::GET,
+            "http://localhost/people",
+            MockResponse::new(304),
+        );
+        let people = mock.local_url(&"https://api.example.com/people".parse().unwrap());
+        let application = cache.insert(&people, PEOPLE_WADL, None, None).unwrap();
+        let client = WadlClient::default().with_cache(cache.clone());
+        assert!(std::sync::Arc::ptr_eq(
+            &crate::blocking::get_wadl_application(&client, &people, &cache).unwrap(),
+            &application
+        ));
+        assert!(matches!(
+            get_wadl_resource_by_href(&client, &people, None),
+            Err(crate::Error::ResourceNotFound(_))
+        ));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_client_builder() {
+        let mock = MockClient::new();
+        mock.expect(
+            reqwest::Method::GET,
+            "http://localhost/",
+            MockResponse::new(200),
+        );
+        let client = crate::blocking::client_builder().unwrap().build().unwrap();
+        let url = mock.local_url(&"https://api.example.com/".parse().unwrap());
+        client.get(url).send().unwrap();
+        assert_eq!(
+            mock.requests()[0].header("user-agent"),
+            Some(crate::USER_AGENT)
+        );
+    }
+
+    #[cfg(all(feature = "blocking", feature = "gzip"))]
+    #[test]
+    fn test_blocking_gzip() {
+        use crate::blocking::Client;
+        // {"name":"jdoe"}, compressed with gzip
+        const BODY: &[u8] = &[
+            31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 171, 86, 202, 75, 204, 77, 85, 178, 82, 202, 74, 201,
+            79, 85, 170, 5, 0, 233, 180, 183, 73, 15, 0, 0, 0,
+        ];
+        let client = MockClient::new();
+        client.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/~jdoe",
+            MockResponse::new(200)
+                .with_header("content-type", "application/json")
+                .with_header("content-encoding", "gzip")
+                .with_body(BODY),
+        );
+        let req = client.request(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/~jdoe".parse().unwrap(),
+        );
+        let resp = client.send(req).unwrap();
+        assert_eq!(
+            resp.json::<serde_json::Value>().unwrap(),
+            serde_json::json!({"name": "jdoe"})
+        );
+        let accept_encoding = client.requests()[0]
+            .header("accept-encoding")
+            .unwrap()
+            .to_string();
+        assert!(accept_encoding.contains("gzip"), "{}", accept_encoding);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_wadl_client() {
+        use crate::r#async::WadlClient;
+        let mock = MockClient::new();
+        mock.expect(
+            reqwest::Method::GET,
+            "http://localhost/1.0/",
+            MockResponse::new(200).with_body(PEOPLE_WADL),
+        );
+
+        let wadl_url = mock.local_url(&"https://api.example.com/1.0/".parse().unwrap());
+        let client = WadlClient::default()
+            .with_auth(crate::Auth::Basic {
+                username: "jdoe".to_string(),
+                password: Some("secret".to_string()),
+            })
+            .with_wadl_url(&wadl_url)
+            .await
+            .unwrap();
+        assert!(client.application().is_some());
+        assert_eq!(
+            mock.requests()[0].header("authorization"),
+            Some("Basic amRvZTpzZWNyZXQ=")
+        );
+
+        let people = crate::r#async::get_wadl_resource_by_href(
+            &client,
+            &"https://api.example.com/1.0/people".parse().unwrap(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(people.id.as_deref(), Some("people"));
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    const TAG: reqwest::header::HeaderName = reqwest::header::HeaderName::from_static("x-tag");
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_layered_client() {
+        use crate::blocking::{Client, LayeredClient, Middleware, Next};
+
+        struct Tag(&'static str);
+
+        impl Middleware for Tag {
+            fn handle(
+                &self,
+                mut req: reqwest::blocking::Request,
+                next: Next<'_>,
+            ) -> Result<reqwest::blocking::Response, crate::Error> {
+                let tag = match req.headers().get(TAG) {
+                    Some(tag) => format!("{},{}", tag.to_str().unwrap(), self.0),
+                    None => self.0.to_string(),
+                };
+                req.headers_mut().insert(TAG, tag.parse().unwrap());
+                next.run(req)
+            }
+        }
+
+        struct NotFoundIsGone;
+
+        impl Middleware for NotFoundIsGone {
+            fn handle(
+                &self,
+                req: reqwest::blocking::Request,
+                next: Next<'_>,
+            ) -> Result<reqwest::blocking::Response, crate::Error> {
+                let resp = next.run(req)?;
+                if resp.status() != reqwest::StatusCode::NOT_FOUND {
+                    return Ok(resp);
+                }
+                let mut rewritten = http::Response::new(resp.bytes()?.to_vec());
+                *rewritten.status_mut() = reqwest::StatusCode::GONE;
+                Ok(rewritten.into())
+            }
+        }
+
+        let client = LayeredClient::new(MockClient::new())
+            .layer(Tag("outer"))
+            .layer(NotFoundIsGone)
+            .layer(Tag("inner"));
+        client.inner().expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/missing",
+            MockResponse::new(404),
+        );
+
+        let req = client.request(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/missing".parse().unwrap(),
+        );
+        let resp = client.send(req).unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::GONE);
+        assert_eq!(
+            client.inner().requests()[0].header("x-tag"),
+            Some("outer,inner")
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_layered_client() {
+        use crate::r#async::{BoxFuture, Client, LayeredClient, Middleware, Next};
+
+        struct Tag(&'static str);
+
+        impl Middleware for Tag {
+            fn handle<'a>(
+                &'a self,
+                mut req: reqwest::Request,
+                next: Next<'a>,
+            ) -> BoxFuture<'a, Result<reqwest::Response, crate::Error>> {
+                req.headers_mut().insert(TAG, self.0.parse().unwrap());
+                next.run(req)
+            }
+        }
+
+        struct NotFoundIsGone;
+
+        impl Middleware for NotFoundIsGone {
+            fn handle<'a>(
+                &'a self,
+                req: reqwest::Request,
+                next: Next<'a>,
+            ) -> BoxFuture<'a, Result<reqwest::Response, crate::Error>> {
+                Box::pin(async move {
+                    let resp = next.run(req).await?;
+                    if resp.status() != reqwest::StatusCode::NOT_FOUND {
+                        return Ok(resp);
+                    }
+                    let mut rewritten = http::Response::new(resp.bytes().await?.to_vec());
+                    *rewritten.status_mut() = reqwest::StatusCode::GONE;
+                    Ok(rewritten.into())
+                })
+            }
+        }
+
+        let client = LayeredClient::new(MockClient::new())
+            .layer(NotFoundIsGone)
+            .layer(Tag("async"));
+        client.inner().expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/missing",
+            MockResponse::new(404),
+        );
+
+        let req = client
+            .request(
+                reqwest::Method::GET,
+                "https://api.example.com/1.0/missing".parse().unwrap(),
+            )
+            .await;
+        let resp = crate::r#async::send(&client, req).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::GONE);
+        assert_eq!(client.inner().requests()[0].header("x-tag"), Some("async"));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_request_observer() {
+        use crate::blocking::{Client, LayeredClient, RequestObserver, WadlClient};
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl RequestObserver for Recorder {
+            fn on_request(&self, req: &reqwest::blocking::Request) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", req.method(), req.url().path()));
+            }
+
+            fn on_response(
+                &self,
+                resp: &reqwest::blocking::Response,
+                _elapsed: std::time::Duration,
+            ) {
+                self.0.lock().unwrap().push(resp.status().to_string());
+            }
+
+            fn on_error(&self, _err: &crate::Error, _elapsed: std::time::Duration) {
+                self.0.lock().unwrap().push("error".to_string());
+            }
+        }
+
+        let mock = MockClient::new();
+        mock.expect(
+            reqwest::Method::GET,
+            "http://localhost/1.0/",
+            MockResponse::new(200).with_body(PEOPLE_WADL),
+        );
+        let recorder = Arc::new(Recorder::default());
+        let wadl_url = mock.local_url(&"https://api.example.com/1.0/".parse().unwrap());
+        WadlClient::default()
+            .with_observer(recorder.clone())
+            .with_wadl_url(&wadl_url)
+            .unwrap();
+        assert_eq!(*recorder.0.lock().unwrap(), ["GET /1.0/", "200 OK"]);
+
+        let recorder = Arc::new(Recorder::default());
+        let client = LayeredClient::new(MockClient::new()).with_observer(recorder.clone());
+        client.inner().expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/missing",
+            MockResponse::new(404),
+        );
+        let req = client.request(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/missing".parse().unwrap(),
+        );
+        client.send(req).unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["GET /1.0/missing", "404 Not Found"]
+        );
+
+        // Connection errors are reported as well
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let recorder = Arc::new(Recorder::default());
+        let client = WadlClient::default().with_observer(recorder.clone());
+        let req = client.request(
+            reqwest::Method::GET,
+            format!("http://{}/", addr).parse().unwrap(),
+        );
+        assert!(client.send(req).is_err());
+        assert_eq!(*recorder.0.lock().unwrap(), ["GET /", "error"]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_request_observer() {
+        use crate::r#async::{Client, LayeredClient, RequestObserver, WadlClient};
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl RequestObserver for Recorder {
+            fn on_request(&self, req: &reqwest::Request) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", req.method(), req.url().path()));
+            }
+
+            fn on_response(&self, resp: &reqwest::Response, _elapsed: std::time::Duration) {
+                self.0.lock().unwrap().push(resp.status().to_string());
+            }
+        }
+
+        let mock = MockClient::new();
+        mock.expect(
+            reqwest::Method::GET,
+            "http://localhost/1.0/",
+            MockResponse::new(200).with_body(PEOPLE_WADL),
+        );
+        let recorder = Arc::new(Recorder::default());
+        let wadl_url = mock.local_url(&"https://api.example.com/1.0/".parse().unwrap());
+        WadlClient::default()
+            .with_observer(recorder.clone())
+            .with_wadl_url(&wadl_url)
+            .await
+            .unwrap();
+        assert_eq!(*recorder.0.lock().unwrap(), ["GET /1.0/", "200 OK"]);
+
+        let recorder = Arc::new(Recorder::default());
+        let client = LayeredClient::new(MockClient::new()).with_observer(recorder.clone());
+        client.inner().expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/missing",
+            MockResponse::new(404),
+        );
+        let req = client
+            .request(
+                reqwest::Method::GET,
+                "https://api.example.com/1.0/missing".parse().unwrap(),
+            )
+            .await;
+        client.send(req).await.unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["GET /1.0/missing", "404 Not Found"]
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_fetch_all() {
+        use crate::r#async::{BoxFuture, Client, Fetch};
+        use std::sync::atomic::AtomicUsize;
+
+        static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+        static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+        struct Person(url::Url);
+
+        impl crate::Resource for Person {
+            fn url(&self) -> &url::Url {
+                &self.0
+            }
+        }
+
+        impl Fetch for Person {
+            type Representation = serde_json::Value;
+
+            fn fetch<'a>(
+                url: &'a url::Url,
+                client: &'a dyn Client,
+            ) -> BoxFuture<'a, Result<serde_json::Value, crate::Error>> {
+                Box::pin(async move {
+                    let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+                    MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+                    let req = client.request(reqwest::Method::GET, url.clone()).await;
+                    let resp = crate::r#async::send(client, req).await;
+                    IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+                    let resp = resp?;
+                    crate::response::check_status(resp.status(), &[])?;
+                    Ok(resp.json().await?)
+                })
+            }
+        }
+
+        let client = MockClient::new();
+        for name in ["alice", "bob", "carol", "dave"] {
+            client.expect(
+                reqwest::Method::GET,
+                &format!("https://api.example.com/1.0/~{}", name),
+                MockResponse::json(200, &serde_json::json!({ "name": name })),
+            );
+        }
+        client.expect(
+            reqwest::Method::GET,
+            "https://api.example.com/1.0/~*",
+            MockResponse::new(404),
+        );
+
+        let links = ["alice", "bob", "nobody", "carol", "dave"]
+            .iter()
+            .map(|name| {
+                format!("https://api.example.com/1.0/~{}", name)
+                    .parse::<crate::Link<Person>>()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let people = crate::r#async::follow_all(&client, &links, 2).await;
+        let names = people
+            .iter()
+            .map(|person| match person {
+                Ok(person) => person["name"].as_str().unwrap(),
+                Err(_) => "-",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["alice", "bob", "-", "carol", "dave"]);
+        assert!(matches!(
+            people[2],
+            Err(crate::Error::UnhandledStatus(
+                reqwest::StatusCode::NOT_FOUND
+            ))
+        ));
+        assert_eq!(MAX_IN_FLIGHT.load(Ordering::SeqCst), 2);
+        assert_eq!(client.requests().len(), 5);
+
+        assert!(
+            crate::r#async::fetch_all::<Person>(&client, std::iter::empty(), 2)
+                .await
+                .is_empty()
+        );
+    }
+}
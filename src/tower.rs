@@ -0,0 +1,128 @@
+//! Support for generated clients that send requests through a `tower::Service`
+//!
+//! Code generated with [`Config::tower`](crate::codegen::Config) set sends its requests
+//! through [`HttpService`], which is implemented for any `tower::Service` that accepts
+//! `http::Request<Body>` and returns an `http::Response`. This allows layers from the tower
+//! ecosystem (retries, authentication, metrics, ...) to be used with generated clients.
+//!
+//! Services that manage their own connection pools can use hyper directly: the pooled client
+//! from `hyper_util` is a `tower::Service`, and connections established with hyper can be
+//! wrapped in [`HyperConnection`] (with the `hyper` feature).
+
+pub use bytes::Bytes;
+pub use http;
+
+/// The body of requests sent by generated code
+pub type Body = http_body_util::Full<bytes::Bytes>;
+
+/// A HTTP service that generated code can send requests to
+pub trait HttpService: Send {
+    /// Send a request, and return the response with its body collected
+    fn send(
+        &mut self,
+        request: http::Request<Body>,
+    ) -> impl std::future::Future<Output = Result<http::Response<bytes::Bytes>, crate::Error>> + Send;
+}
+
+fn service_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> crate::Error {
+    crate::Error::Service(err.into())
+}
+
+impl<S, B> HttpService for S
+where
+    S: tower_service::Service<http::Request<Body>, Response = http::Response<B>> + Send,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send,
+    B: http_body::Body + Send,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    async fn send(
+        &mut self,
+        request: http::Request<Body>,
+    ) -> Result<http::Response<bytes::Bytes>, crate::Error> {
+        std::future::poll_fn(|cx| self.poll_ready(cx))
+            .await
+            .map_err(service_error)?;
+        let response = self.call(request).await.map_err(service_error)?;
+        let (parts, body) = response.into_parts();
+        let body = http_body_util::BodyExt::collect(body)
+            .await
+            .map_err(service_error)?
+            .to_bytes();
+        Ok(http::Response::from_parts(parts, body))
+    }
+}
+
+#[cfg(feature = "hyper")]
+/// A connection established with hyper, for use as [`HttpService`]
+///
+/// All requests are sent over this connection; the caller is responsible for driving the
+/// connection future returned by the handshake.
+pub enum HyperConnection {
+    /// A HTTP/1 connection
+    Http1(hyper::client::conn::http1::SendRequest<Body>),
+
+    /// A HTTP/2 connection
+    Http2(hyper::client::conn::http2::SendRequest<Body>),
+}
+
+#[cfg(feature = "hyper")]
+impl From<hyper::client::conn::http1::SendRequest<Body>> for HyperConnection {
+    fn from(sender: hyper::client::conn::http1::SendRequest<Body>) -> Self {
+        HyperConnection::Http1(sender)
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl From<hyper::client::conn::http2::SendRequest<Body>> for HyperConnection {
+    fn from(sender: hyper::client::conn::http2::SendRequest<Body>) -> Self {
+        HyperConnection::Http2(sender)
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl tower_service::Service<http::Request<Body>> for HyperConnection {
+    type Response = http::Response<hyper::body::Incoming>;
+    type Error = hyper::Error;
+    type Future = std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send>,
+    >;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        match self {
+            HyperConnection::Http1(sender) => sender.poll_ready(cx),
+            HyperConnection::Http2(sender) => sender.poll_ready(cx),
+        }
+    }
+
+    fn call(&mut self, mut request: http::Request<Body>) -> Self::Future {
+        match self {
+            HyperConnection::Http1(sender) => {
+                // HTTP/1 requests are sent in origin-form, with the authority in the Host header
+                if let Some(host) = request
+                    .uri()
+                    .authority()
+                    .and_then(|a| http::HeaderValue::from_str(a.as_str()).ok())
+                {
+                    request
+                        .headers_mut()
+                        .entry(http::header::HOST)
+                        .or_insert(host);
+                }
+                if let Some(origin) = request
+                    .uri()
+                    .path_and_query()
+                    .and_then(|p| p.as_str().parse().ok())
+                {
+                    *request.uri_mut() = origin;
+                }
+                Box::pin(sender.send_request(request))
+            }
+            HyperConnection::Http2(sender) => Box::pin(sender.send_request(request)),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Support for generated clients that send requests with [`ureq`]
+//!
+//! Code generated with [`Config::ureq`](crate::codegen::Config) is blocking, and sends its
+//! requests through [`Client`], which is implemented for [`ureq::Agent`]. Unlike the reqwest-based
+//! blocking client, this does not start an async runtime for every client.
+
+pub use ureq::http;
+
+/// A blocking HTTP client that generated code can send requests to
+pub trait Client {
+    /// Send a request, and return the response with its body read into memory
+    fn send(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>, crate::Error>;
+}
+
+impl Client for ureq::Agent {
+    fn send(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>, crate::Error> {
+        // Error statuses are handled by the generated code
+        let request = self
+            .configure_request(request)
+            .http_status_as_error(false)
+            .build();
+        let response = self.run(request)?;
+        let (parts, mut body) = response.into_parts();
+        let body = body.with_config().limit(u64::MAX).read_to_vec()?;
+        Ok(http::Response::from_parts(parts, body))
+    }
+}
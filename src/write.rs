@@ -0,0 +1,381 @@
+use crate::ast::*;
+use std::io::Write;
+
+/// The namespace of WADL documents.
+const WADL_NAMESPACE: &str = "http://wadl.dev.java.net/2009/02";
+
+/// The namespace of WADL documents written against the 2006 draft of the specification.
+const WADL_2006_NAMESPACE: &str = "http://research.sun.com/wadl/2006/10";
+
+/// The XML declaration that precedes each element in the contents of a [`Doc`]
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>";
+
+/// An element of the document being written
+struct Node {
+    name: &'static str,
+    attributes: Vec<(&'static str, String)>,
+    children: Vec<Child>,
+}
+
+enum Child {
+    Element(Node),
+    /// Markup that is written as-is, e.g. the contents of a `doc` element
+    Markup(String),
+}
+
+impl Node {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            attributes: vec![],
+            children: vec![],
+        }
+    }
+
+    fn attribute(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.attributes.push((name, value.to_string()));
+        self
+    }
+
+    fn optional_attribute(self, name: &'static str, value: Option<impl ToString>) -> Self {
+        match value {
+            Some(value) => self.attribute(name, value),
+            None => self,
+        }
+    }
+
+    fn child(mut self, child: Node) -> Self {
+        self.children.push(Child::Element(child));
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node>) -> Self {
+        self.children
+            .extend(children.into_iter().map(Child::Element));
+        self
+    }
+
+    fn render(&self, indent: usize, out: &mut String) {
+        out.push_str(&"  ".repeat(indent));
+        out.push('<');
+        out.push_str(self.name);
+        for (name, value) in &self.attributes {
+            out.push_str(&format!(" {}=\"{}\"", name, escape(value, true)));
+        }
+        match self.children.as_slice() {
+            [] => out.push_str("/>\n"),
+            [Child::Markup(markup)] => {
+                out.push('>');
+                out.push_str(markup);
+                out.push_str(&format!("</{}>\n", self.name));
+            }
+            children => {
+                out.push_str(">\n");
+                for child in children {
+                    match child {
+                        Child::Element(node) => node.render(indent + 1, out),
+                        Child::Markup(markup) => {
+                            out.push_str(markup);
+                            out.push('\n');
+                        }
+                    }
+                }
+                out.push_str(&"  ".repeat(indent));
+                out.push_str(&format!("</{}>\n", self.name));
+            }
+        }
+    }
+}
+
+/// Escape text for use in XML
+fn escape(text: &str, attribute: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Return the markup for the contents of a `doc` element
+///
+/// The parser keeps child elements as serialized XML, each preceded by an XML declaration,
+/// but unescapes the text around them; so the elements are written as-is, and the text is
+/// escaped.
+fn doc_markup(content: &str) -> String {
+    let mut pieces = content.split(XML_DECLARATION);
+    let mut markup = escape(pieces.next().unwrap_or_default(), false);
+    for piece in pieces {
+        let end = element_len(piece);
+        markup.push_str(&piece[..end]);
+        markup.push_str(&escape(&piece[end..], false));
+    }
+    markup
+}
+
+/// Return the length of the serialized element at the start of some XML
+fn element_len(xml: &str) -> usize {
+    let mut depth = 0;
+    let mut pos = 0;
+    while let Some(start) = xml[pos..].find('<').map(|start| pos + start) {
+        let Some(end) = xml[start..].find('>').map(|end| start + end + 1) else {
+            break;
+        };
+        let tag = &xml[start..end];
+        if tag.starts_with("</") {
+            depth -= 1;
+        } else if !tag.ends_with("/>") && !tag.starts_with("<!") && !tag.starts_with("<?") {
+            depth += 1;
+        }
+        pos = end;
+        if depth == 0 {
+            return end;
+        }
+    }
+    xml.len()
+}
+
+fn doc_node(doc: &Doc) -> Node {
+    let mut node = Node::new("doc")
+        .optional_attribute("title", doc.title.as_ref())
+        .optional_attribute("xml:lang", doc.lang.as_ref())
+        .optional_attribute(
+            "xmlns",
+            doc.xmlns
+                .as_ref()
+                .filter(|ns| ![WADL_NAMESPACE, WADL_2006_NAMESPACE].contains(&ns.as_str())),
+        );
+    if !doc.content.is_empty() {
+        node.children.push(Child::Markup(doc_markup(&doc.content)));
+    }
+    node
+}
+
+fn resource_type_ref(r: &ResourceTypeRef) -> Option<String> {
+    match r {
+        ResourceTypeRef::Id(id) => Some(format!("#{}", id)),
+        ResourceTypeRef::Link(url) => Some(url.to_string()),
+        ResourceTypeRef::Empty => None,
+    }
+}
+
+fn param_node(param: &Param) -> Node {
+    let style = match param.style {
+        ParamStyle::Plain => "plain",
+        ParamStyle::Matrix => "matrix",
+        ParamStyle::Query => "query",
+        ParamStyle::Header => "header",
+        ParamStyle::Template => "template",
+    };
+    Node::new("param")
+        .attribute("name", &param.name)
+        .attribute("style", style)
+        .optional_attribute("id", param.id.as_ref())
+        .attribute("type", &param.r#type)
+        .optional_attribute("path", param.path.as_ref())
+        .optional_attribute("required", param.required.then_some("true"))
+        .optional_attribute("repeating", param.repeating.then_some("true"))
+        .optional_attribute("fixed", param.fixed.as_ref())
+        .optional_attribute("default", param.default.as_ref())
+        .children(param.doc.iter().map(doc_node))
+        .children(param.options.iter().flat_map(|options| {
+            options.iter().map(|(value, media_type)| {
+                Node::new("option")
+                    .attribute("value", value)
+                    .optional_attribute("mediaType", media_type)
+                    .children(options.get_doc(value).map(doc_node))
+            })
+        }))
+        .children(param.links.iter().map(|link| {
+            Node::new("link")
+                .optional_attribute(
+                    "resource_type",
+                    link.resource_type.as_ref().and_then(resource_type_ref),
+                )
+                .optional_attribute("rel", link.relation.as_ref())
+                .optional_attribute("rev", link.reverse_relation.as_ref())
+                .children(link.doc.iter().map(doc_node))
+        }))
+}
+
+fn representation_def_node(def: &RepresentationDef) -> Node {
+    Node::new("representation")
+        .optional_attribute("id", def.id.as_ref())
+        .optional_attribute("mediaType", def.media_type.as_ref())
+        .optional_attribute("element", def.element.as_ref())
+        .optional_attribute("profile", def.profile.as_ref())
+        .children(def.docs.iter().map(doc_node))
+        .children(def.params.iter().map(param_node))
+}
+
+fn representation_node(representation: &Representation) -> Node {
+    match representation {
+        Representation::Reference(RepresentationRef::Id(id)) => {
+            Node::new("representation").attribute("href", format!("#{}", id))
+        }
+        Representation::Reference(RepresentationRef::Link(url)) => {
+            Node::new("representation").attribute("href", url)
+        }
+        Representation::Definition(def) => representation_def_node(def),
+    }
+}
+
+fn method_node(method: &Method) -> Node {
+    let request = &method.request;
+    let mut node = Node::new("method")
+        .optional_attribute("id", Some(&method.id).filter(|id| !id.is_empty()))
+        .attribute("name", &method.name)
+        .children(method.docs.iter().map(doc_node));
+    if !request.docs.is_empty() || !request.params.is_empty() || !request.representations.is_empty()
+    {
+        node = node.child(
+            Node::new("request")
+                .children(request.docs.iter().map(doc_node))
+                .children(request.params.iter().map(param_node))
+                .children(request.representations.iter().map(representation_node)),
+        );
+    }
+    node.children(method.responses.iter().map(|response| {
+        Node::new("response")
+            .optional_attribute("status", response.status)
+            .children(response.docs.iter().map(doc_node))
+            .children(response.params.iter().map(param_node))
+            .children(response.representations.iter().map(representation_node))
+    }))
+}
+
+fn query_type_attribute(query_type: &mime::Mime) -> Option<&mime::Mime> {
+    Some(query_type).filter(|t| **t != mime::APPLICATION_WWW_FORM_URLENCODED)
+}
+
+fn resource_node(resource: &Resource) -> Node {
+    let types = resource
+        .r#type
+        .iter()
+        .filter_map(resource_type_ref)
+        .collect::<Vec<_>>();
+    Node::new("resource")
+        .optional_attribute("id", resource.id.as_ref())
+        .optional_attribute("path", resource.path.as_ref())
+        .optional_attribute("type", Some(types.join(" ")).filter(|t| !t.is_empty()))
+        .optional_attribute("queryType", query_type_attribute(&resource.query_type))
+        .children(resource.docs.iter().map(doc_node))
+        .children(resource.params.iter().map(param_node))
+        .children(resource.methods.iter().map(method_node))
+        .children(resource.subresources.iter().map(resource_node))
+}
+
+fn resource_type_node(resource_type: &ResourceType) -> Node {
+    Node::new("resource_type")
+        .attribute("id", &resource_type.id)
+        .optional_attribute("queryType", query_type_attribute(&resource_type.query_type))
+        .children(resource_type.docs.iter().map(doc_node))
+        .children(resource_type.params.iter().map(param_node))
+        .children(resource_type.methods.iter().map(method_node))
+        .children(resource_type.subresources.iter().map(resource_node))
+}
+
+fn application_node(app: &Application) -> Node {
+    let mut node = Node::new("application")
+        .attribute("xmlns", WADL_NAMESPACE)
+        .children(app.docs.iter().map(doc_node));
+    if !app.grammars.is_empty() {
+        node = node.child(
+            Node::new("grammars").children(
+                app.grammars
+                    .iter()
+                    .map(|grammar| Node::new("include").attribute("href", &grammar.href)),
+            ),
+        );
+    }
+    node.children(app.resources.iter().map(|resources| {
+        Node::new("resources")
+            .optional_attribute("base", resources.base.as_ref())
+            .children(resources.resources.iter().map(resource_node))
+    }))
+    .children(app.resource_types.iter().map(resource_type_node))
+    .children(app.representations.iter().map(representation_def_node))
+}
+
+/// Write an application description as XML to a writer.
+pub fn write<W: Write>(app: &Application, mut writer: W) -> std::io::Result<()> {
+    writer.write_all(write_string(app).as_bytes())
+}
+
+/// Write an application description as an XML string.
+pub fn write_string(app: &Application) -> String {
+    let mut out = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    application_node(app).render(0, &mut out);
+    out
+}
+
+#[test]
+fn test_write_roundtrip() {
+    let wadl = r##"<application xmlns="http://wadl.dev.java.net/2009/02">
+        <doc title="People">The <b xmlns="http://www.w3.org/1999/xhtml">people</b> API &amp; more</doc>
+        <grammars>
+            <include href="people.xsd"/>
+        </grammars>
+        <resources base="https://api.example.com/1.0/">
+            <resource path="people" type="#people https://example.com/common.wadl#paged"/>
+        </resources>
+        <resource_type id="people">
+            <param name="ws.size" style="query" type="xsd:int" default="10"/>
+            <method name="GET" id="people-get">
+                <response status="200">
+                    <representation href="#person-page"/>
+                </response>
+            </method>
+            <method name="POST" id="people-new">
+                <request>
+                    <representation mediaType="application/x-www-form-urlencoded">
+                        <param name="name" style="query" required="true"/>
+                    </representation>
+                </request>
+            </method>
+        </resource_type>
+        <representation id="person-page" mediaType="application/json">
+            <param name="entries" style="plain" path="$.entries" repeating="true"/>
+            <param name="next_link" style="plain" path="$.next_link">
+                <link resource_type="#people" rel="next"/>
+            </param>
+            <param name="status" style="plain" path="$.status">
+                <option value="active"><doc>Active &lt;now&gt;</doc></option>
+                <option value="inactive"/>
+            </param>
+        </representation>
+    </application>"##;
+    let app = crate::parse_string(wadl).unwrap();
+    let written = write_string(&app);
+    let reparsed = crate::parse_string(&written).unwrap();
+    assert_eq!(write_string(&reparsed), written);
+
+    assert_eq!(reparsed.docs, app.docs);
+    assert_eq!(reparsed.grammars[0].href.as_str(), "people.xsd");
+    assert_eq!(
+        reparsed.resources[0].resources[0].r#type,
+        app.resources[0].resources[0].r#type
+    );
+    assert_eq!(reparsed.representations, app.representations);
+    let (people, original) = (&reparsed.resource_types[0], &app.resource_types[0]);
+    assert_eq!(people.params, original.params);
+    assert_eq!(
+        people.methods[1].request.representations[0].as_def(),
+        original.methods[1].request.representations[0].as_def()
+    );
+    assert!(
+        written.contains("<option value=\"active\">\n"),
+        "{}",
+        written
+    );
+    assert!(
+        written.contains("<doc>Active &lt;now&gt;</doc>"),
+        "{}",
+        written
+    );
+}
@@ -0,0 +1,204 @@
+#![cfg(feature = "codegen")]
+use wadl::codegen::{
+    assert_golden, format_code, generate, generate_formatted, generate_server,
+    generate_server_types, Config,
+};
+
+fn check_golden(name: &str, input: &str, config: &Config) {
+    let app = wadl::parse_file(format!("tests/{}.xml", input)).unwrap();
+    let code = generate_formatted(&app, config).unwrap();
+    assert_golden(format!("tests/{}.rs.golden", name), &code);
+}
+
+#[test]
+fn generate_people_wadl() {
+    check_golden("people-wadl", "people-wadl", &Config::default());
+}
+
+#[test]
+fn generate_tickets_wadl() {
+    check_golden("tickets-wadl", "tickets-wadl", &Config::default());
+}
+
+#[test]
+fn generate_tickets_wadl_async() {
+    check_golden(
+        "tickets-wadl-async",
+        "tickets-wadl",
+        &Config::builder().r#async(true).build(),
+    );
+}
+
+#[test]
+fn generate_sample_wadls() {
+    for name in ["sample-wadl", "yahoo-wadl", "fish-eye-wadl", "jira_wadl"] {
+        let app = wadl::parse_file(format!("tests/{}.xml", name)).unwrap();
+        syn::parse_file(&generate(&app, &Config::default())).unwrap();
+    }
+}
+
+#[test]
+fn generate_is_deterministic() {
+    let app = wadl::parse_file("tests/people-wadl.xml").unwrap();
+    let expected = generate(&app, &Config::default());
+    for _ in 0..10 {
+        let app = wadl::parse_file("tests/people-wadl.xml").unwrap();
+        assert_eq!(generate(&app, &Config::default()), expected);
+    }
+}
+
+#[test]
+fn generate_resource_traits() {
+    check_golden(
+        "people-wadl-traits",
+        "people-wadl",
+        &Config::builder().generate_resource_traits(true).build(),
+    );
+}
+
+#[test]
+fn generate_any_resource() {
+    check_golden(
+        "people-wadl-any-resource",
+        "people-wadl",
+        &Config::builder().generate_any_resource(true).build(),
+    );
+}
+
+#[test]
+fn generate_typed_links() {
+    check_golden(
+        "people-wadl-typed-links",
+        "people-wadl",
+        &Config::builder().typed_links(true).build(),
+    );
+    check_golden(
+        "people-wadl-async-typed-links",
+        "people-wadl",
+        &Config::builder().r#async(true).typed_links(true).build(),
+    );
+}
+
+#[test]
+fn generate_server_people_wadl() {
+    let app = wadl::parse_file("tests/people-wadl.xml").unwrap();
+    let code = format_code(&generate_server(&app, &Config::default())).unwrap();
+    assert_golden("tests/people-wadl-server.rs.golden", &code);
+}
+
+#[test]
+fn generate_server_types_people_wadl() {
+    let app = wadl::parse_file("tests/people-wadl.xml").unwrap();
+    let code = format_code(&generate_server_types(&app, &Config::default())).unwrap();
+    assert_golden("tests/people-wadl-server-types.rs.golden", &code);
+}
+
+#[test]
+fn generate_tower() {
+    check_golden(
+        "people-wadl-tower",
+        "people-wadl",
+        &Config::builder().tower(true).build(),
+    );
+}
+
+#[test]
+fn generate_async() {
+    check_golden(
+        "people-wadl-async",
+        "people-wadl",
+        &Config::builder().r#async(true).build(),
+    );
+}
+
+#[test]
+fn generate_native_async() {
+    check_golden(
+        "people-wadl-native-async",
+        "people-wadl",
+        &Config::builder()
+            .r#async(true)
+            .native_async_client(true)
+            .build(),
+    );
+}
+
+#[test]
+fn generate_ureq() {
+    check_golden(
+        "people-wadl-ureq",
+        "people-wadl",
+        &Config::builder().ureq(true).build(),
+    );
+}
+
+#[test]
+fn generate_blocking_and_async() {
+    check_golden(
+        "people-wadl-dual",
+        "people-wadl",
+        &Config::builder().blocking_and_async(true).build(),
+    );
+}
+
+#[test]
+fn generate_feature_gates() {
+    let app = wadl::parse_file("tests/people-wadl.xml").unwrap();
+    let config = Config::builder()
+        .feature_gate(|resource_type, method| match (resource_type, method) {
+            ("Person", None) => Some("person".to_string()),
+            ("People", Some("find")) => Some("search".to_string()),
+            _ => None,
+        })
+        .generate_resource_traits(true)
+        .generate_any_resource(true)
+        .build();
+    let code = generate_formatted(&app, &config).unwrap();
+    for expected in [
+        "#[cfg(feature = \"person\")]\n/// A person.\npub struct Person(reqwest::Url);",
+        "#[cfg(feature = \"person\")]\nimpl Person {",
+        "#[cfg(feature = \"person\")]\nimpl wadl::Resource for Person {",
+        "#[cfg(feature = \"person\")]\nimpl PersonOps for Person {",
+        "    #[cfg(feature = \"person\")]\n    pub fn self_link(&self) -> Person {",
+        "    #[cfg(feature = \"person\")]\n    pub fn set_self_link(&mut self, value: Person) {",
+        "    #[cfg(feature = \"search\")]\n    /// Search for people by name.",
+        "    #[cfg(feature = \"person\")]\n    /// Create a new person.",
+        "    #[cfg(feature = \"person\")]\n    /// A [`Person`] resource\n    Person(Person),",
+        "        #[cfg(feature = \"person\")]\n        if wadl::url_matches_template(",
+        "            #[cfg(feature = \"person\")]\n            AnyResource::Person(r)",
+    ] {
+        assert!(
+            code.contains(expected),
+            "missing {:?} in:\n{}",
+            expected,
+            code
+        );
+    }
+    assert_eq!(code.matches("#[cfg(feature = \"search\")]").count(), 3);
+}
+
+#[test]
+#[ignore = "runs cargo check, which needs the dependencies of generated code"]
+fn verify_people_wadl() {
+    let app = wadl::parse_file("tests/people-wadl.xml").unwrap();
+    let code = generate(&app, &Config::default());
+    let verifier = wadl::codegen::Verifier::new()
+        .target_dir(concat!(env!("CARGO_TARGET_TMPDIR"), "/verify"))
+        .offline(true);
+    verifier.verify(&code).unwrap_or_else(|errors| {
+        panic!(
+            "{}",
+            errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    });
+
+    let errors = verifier
+        .verify(&format!("{}\nfn broken() -> u32 {{ \"\" }}\n", code))
+        .unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(code.lines().count() + 2));
+}
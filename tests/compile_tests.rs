@@ -0,0 +1,846 @@
+//! Check that the golden generated code compiles against this crate.
+#![cfg(all(feature = "codegen", feature = "blocking"))]
+
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people {
+    use wadl::Resource;
+    include!("people-wadl.rs.golden");
+}
+
+#[test]
+fn people_representation_roundtrip() {
+    let person: people::PersonFull = serde_json::from_str(
+        r#"{
+            "self_link": "https://api.example.com/1.0/~jdoe",
+            "display_name": "J. Doe",
+            "karma": null,
+            "date_created": "2024-01-01T00:00:00Z",
+            "is_team": false,
+            "status": "active"
+        }"#,
+    )
+    .unwrap();
+    use wadl::Resource;
+    assert_eq!(
+        person.self_link().url().as_str(),
+        "https://api.example.com/1.0/~jdoe"
+    );
+    assert_eq!(person.status, people::Status::Active);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn people_refresh() {
+    use wadl::testing::{MockClient, MockResponse};
+
+    let person: people::Person = "https://api.example.com/1.0/people/jdoe".parse().unwrap();
+    let json = serde_json::json!({
+        "self_link": "https://api.example.com/1.0/~jdoe",
+        "display_name": "J. Doe",
+        "karma": 10,
+        "date_created": "2024-01-01T00:00:00Z",
+        "is_team": false,
+        "status": "active"
+    });
+    let mut representation = wadl::Tagged {
+        value: serde_json::from_value::<people::PersonFull>(json.clone()).unwrap(),
+        etag: Some("\"1\"".to_string()),
+    };
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/people/jdoe",
+        MockResponse::new(304),
+    );
+    assert!(!person.refresh(&client, &mut representation).unwrap());
+    assert_eq!(client.requests()[0].header("if-none-match"), Some("\"1\""));
+
+    let mut changed = json;
+    changed["display_name"] = "Jo Doe".into();
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/people/jdoe",
+        MockResponse::json(200, &changed).with_header("etag", "\"2\""),
+    );
+    assert!(person.refresh(&client, &mut representation).unwrap());
+    assert_eq!(representation.display_name, "Jo Doe");
+    assert_eq!(representation.etag.as_deref(), Some("\"2\""));
+}
+
+#[test]
+fn people_resource_from_url() {
+    let person: people::Person = "https://api.example.com/1.0/people/jdoe".parse().unwrap();
+    assert_eq!(
+        person.to_string(),
+        "https://api.example.com/1.0/people/jdoe"
+    );
+    assert!(matches!(
+        "https://api.example.com/1.0/bugs/1".parse::<people::Person>(),
+        Err(wadl::Error::InvalidUrl)
+    ));
+    assert!(matches!(
+        "not a url".parse::<people::Person>(),
+        Err(wadl::Error::Url(_))
+    ));
+
+    let url: url::Url = "https://api.example.com/1.0/people".parse().unwrap();
+    let people = people::People::try_from(url.clone()).unwrap();
+    use wadl::Resource;
+    assert_eq!(people.url(), &url);
+}
+
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_traits {
+    use wadl::Resource;
+    include!("people-wadl-traits.rs.golden");
+}
+
+#[test]
+fn people_trait_fake() {
+    use people_traits::{People, PeopleOps, PersonPage, Status};
+
+    struct FakePeople;
+
+    impl PeopleOps for FakePeople {
+        fn find(
+            &self,
+            _client: &dyn wadl::blocking::Client,
+            text: &str,
+            _status: Option<&Status>,
+        ) -> Result<PersonPage, wadl::Error> {
+            Ok(PersonPage {
+                total_size: text.len() as i32,
+                next_collection_link: None,
+                first_entry_link: None,
+            })
+        }
+
+        fn new(
+            &self,
+            _client: &dyn wadl::blocking::Client,
+            _display_name: &str,
+        ) -> Result<people_traits::Person, wadl::Error> {
+            Err(wadl::Error::InvalidUrl)
+        }
+    }
+
+    fn count(people: &dyn PeopleOps, client: &dyn wadl::blocking::Client) -> i32 {
+        people.find(client, "jdoe", None).unwrap().total_size
+    }
+
+    let client = reqwest::blocking::Client::new();
+    assert_eq!(count(&FakePeople, &client), 4);
+    fn assert_impl<T: PeopleOps>() {}
+    assert_impl::<People>();
+}
+
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_any_resource {
+    use wadl::Resource;
+    include!("people-wadl-any-resource.rs.golden");
+}
+
+#[test]
+fn people_any_resource_from_url() {
+    use people_any_resource::AnyResource;
+    use wadl::Resource;
+
+    let url: url::Url = "https://api.example.com/1.0/people/jdoe".parse().unwrap();
+    let resource = AnyResource::from_url(url.clone()).unwrap();
+    assert!(matches!(resource, AnyResource::Person(_)));
+    assert_eq!(resource.url(), &url);
+
+    let url: url::Url = "https://api.example.com/1.0/people".parse().unwrap();
+    assert!(matches!(
+        AnyResource::from_url(url),
+        Some(AnyResource::People(_))
+    ));
+
+    let url: url::Url = "https://api.example.com/1.0/".parse().unwrap();
+    assert!(matches!(
+        AnyResource::from_url(url),
+        Some(AnyResource::ServiceRoot(_))
+    ));
+
+    let url: url::Url = "https://api.example.com/1.0/bugs/1/comments"
+        .parse()
+        .unwrap();
+    assert!(AnyResource::from_url(url).is_none());
+}
+
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_server {
+    use wadl::Resource;
+    include!("people-wadl.rs.golden");
+    include!("people-wadl-server.rs.golden");
+}
+
+/// Server stubs without the client code
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_server_types {
+    include!("people-wadl-server-types.rs.golden");
+    include!("people-wadl-server.rs.golden");
+}
+
+#[tokio::test]
+async fn people_server_router() {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use people_server::*;
+    use tower::ServiceExt;
+
+    struct Server;
+
+    impl ServiceRootHandler for Server {
+        async fn get(&self) -> Result<ServiceRootJson, axum::response::Response> {
+            Err(axum::response::IntoResponse::into_response(
+                StatusCode::NOT_FOUND,
+            ))
+        }
+    }
+
+    impl PeopleHandler for Server {
+        async fn find(
+            &self,
+            query: PeopleFindQuery,
+        ) -> Result<PersonPage, axum::response::Response> {
+            assert_eq!(query.status, Some(Status::Inactive));
+            Ok(PersonPage {
+                total_size: query.text.len() as i32,
+                next_collection_link: None,
+                first_entry_link: None,
+            })
+        }
+
+        async fn new(&self, form: PeopleNewForm) -> Result<(), axum::response::Response> {
+            assert_eq!(form.display_name, "J. Doe");
+            Ok(())
+        }
+    }
+
+    impl PersonHandler for Server {
+        async fn get(&self, name: String) -> Result<PersonFull, axum::response::Response> {
+            Err(axum::response::IntoResponse::into_response((
+                StatusCode::NOT_FOUND,
+                name,
+            )))
+        }
+
+        async fn patch(
+            &self,
+            _name: String,
+            _representation: PersonFull,
+        ) -> Result<(), axum::response::Response> {
+            Ok(())
+        }
+    }
+
+    let response = router(Server)
+        .oneshot(
+            Request::get("/people?ws.op=find&text=jdoe&status=inactive")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let page: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(page["total_size"], 4);
+
+    let response = router(Server)
+        .oneshot(Request::get("/people").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = router(Server)
+        .oneshot(Request::get("/people/jdoe").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(&body[..], b"jdoe");
+}
+
+#[cfg(feature = "tower")]
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_tower {
+    use wadl::Resource;
+    include!("people-wadl-tower.rs.golden");
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn people_tower_service() {
+    use wadl::tower::{http, Body};
+
+    let mut service = tower::service_fn(|req: http::Request<Body>| async move {
+        assert_eq!(req.method(), http::Method::GET);
+        assert_eq!(
+            req.uri().to_string(),
+            "https://api.example.com/1.0/?ws.op=find&text=jdoe"
+        );
+        let response = http::Response::builder()
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"total_size": 3}"#))
+            .unwrap();
+        Ok::<_, std::convert::Infallible>(response)
+    });
+
+    let root: people_tower::ServiceRootJson =
+        serde_json::from_str(r#"{"people_collection_link": "https://api.example.com/1.0/"}"#)
+            .unwrap();
+    let people = root.people_collection_link().unwrap();
+    let page = people.find(&mut service, "jdoe", None).await.unwrap();
+    assert_eq!(page.total_size, 3);
+}
+
+#[cfg(feature = "hyper")]
+#[tokio::test]
+async fn people_tower_hyper_connection() {
+    let app = axum::Router::new().route(
+        "/1.0/people",
+        axum::routing::get(|| async { axum::Json(serde_json::json!({"total_size": 5})) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let (sender, connection) =
+        hyper::client::conn::http1::handshake(hyper_util::rt::TokioIo::new(stream))
+            .await
+            .unwrap();
+    tokio::spawn(connection);
+    let mut service = wadl::tower::HyperConnection::from(sender);
+
+    let root: people_tower::ServiceRootJson = serde_json::from_value(serde_json::json!({
+        "people_collection_link": format!("http://{}/1.0/people", addr)
+    }))
+    .unwrap();
+    let people = root.people_collection_link().unwrap();
+    let page = people.find(&mut service, "jdoe", None).await.unwrap();
+    assert_eq!(page.total_size, 5);
+}
+
+#[cfg(feature = "async")]
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_async {
+    use wadl::Resource;
+    include!("people-wadl-async.rs.golden");
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[tokio::test]
+async fn people_async_mock_client() {
+    use wadl::testing::{MockClient, MockResponse};
+    use wadl::Resource;
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/people?*",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "total_size": 1,
+                "first_entry_link": "https://api.example.com/1.0/~jdoe"
+            }),
+        ),
+    );
+    client.expect(
+        reqwest::Method::POST,
+        "https://api.example.com/1.0/people",
+        MockResponse::new(201).with_header("Location", "https://api.example.com/1.0/~jdoe"),
+    );
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/~jdoe",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "self_link": "https://api.example.com/1.0/~jdoe",
+                "display_name": "J. Doe",
+                "date_created": "2024-01-01T00:00:00Z",
+                "is_team": false,
+                "status": "active"
+            }),
+        ),
+    );
+
+    let root: people_async::ServiceRootJson =
+        serde_json::from_str(r#"{"people_collection_link": "https://api.example.com/1.0/people"}"#)
+            .unwrap();
+    let people = root.people_collection_link().unwrap();
+
+    let page = people.find(&client, "jdoe", None).await.unwrap();
+    assert_eq!(page.total_size, 1);
+    let first = page.first_entry_link(&client).await.unwrap().unwrap();
+    assert_eq!(first.display_name, "J. Doe");
+
+    let person = people.new(&client, "J. Doe").await.unwrap();
+    assert_eq!(person.url().as_str(), "https://api.example.com/1.0/~jdoe");
+    assert_eq!(
+        client.requests().last().unwrap().body,
+        b"ws.op=new&display_name=J.+Doe".to_vec()
+    );
+
+    // Unexpected requests are answered with 501 Not Implemented
+    assert!(matches!(
+        person.patch(&client, &serde_json::from_value(serde_json::json!({
+            "self_link": "https://api.example.com/1.0/~jdoe",
+            "display_name": "J. Doe",
+            "date_created": "2024-01-01T00:00:00Z",
+            "is_team": false,
+            "status": "active"
+        })).unwrap()).await,
+        Err(wadl::Error::UnhandledStatus(s)) if s.as_u16() == 501
+    ));
+}
+
+#[cfg(feature = "async-native")]
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_native_async {
+    use wadl::Resource;
+    include!("people-wadl-native-async.rs.golden");
+}
+
+#[cfg(all(feature = "async-native", feature = "testing"))]
+#[tokio::test]
+async fn people_native_async_mock_client() {
+    use wadl::testing::{MockClient, MockResponse};
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/people/jdoe",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "self_link": "https://api.example.com/1.0/people/jdoe",
+                "display_name": "J. Doe",
+                "date_created": "2024-01-01T00:00:00Z",
+                "is_team": false,
+                "status": "active"
+            }),
+        ),
+    );
+
+    let person: people_native_async::Person =
+        "https://api.example.com/1.0/people/jdoe".parse().unwrap();
+    let full = person.get(&client).await.unwrap();
+    assert_eq!(full.display_name, "J. Doe");
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[tokio::test]
+async fn people_async_missing_location() {
+    use wadl::testing::{MockClient, MockResponse};
+    use wadl::Resource;
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::POST,
+        "https://api.example.com/1.0/people",
+        MockResponse::new(201),
+    );
+
+    let root: people_async::ServiceRootJson =
+        serde_json::from_str(r#"{"people_collection_link": "https://api.example.com/1.0/people"}"#)
+            .unwrap();
+    let people = root.people_collection_link().unwrap();
+    assert_eq!(people.url().as_str(), "https://api.example.com/1.0/people");
+    assert!(matches!(
+        people.new(&client, "J. Doe").await,
+        Err(wadl::Error::InvalidHeader(name)) if name == "Location"
+    ));
+}
+
+#[cfg(feature = "async")]
+#[allow(unused_mut, dead_code, clippy::all)]
+mod tickets_async {
+    use wadl::Resource;
+    include!("tickets-wadl-async.rs.golden");
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[tokio::test]
+async fn tickets_async_json_request() {
+    use wadl::testing::{MockClient, MockResponse};
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::POST,
+        "https://tickets.example.com/api/tickets",
+        MockResponse::new(201),
+    );
+
+    let tickets: tickets_async::Tickets =
+        "https://tickets.example.com/api/tickets".parse().unwrap();
+    let assignee: tickets_async::User = "https://tickets.example.com/api/users/jdoe"
+        .parse()
+        .unwrap();
+    tickets
+        .create(
+            &client,
+            "Crash on startup",
+            &2,
+            Some(&true),
+            &["crash".to_string()],
+            &tickets_async::Severity::Major,
+            Some(&assignee),
+        )
+        .await
+        .unwrap();
+
+    let body: serde_json::Value =
+        serde_json::from_slice(&client.requests().last().unwrap().body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "kind": "ticket",
+            "title": "Crash on startup",
+            "priority": 2,
+            "urgent": true,
+            "labels": ["crash"],
+            "severity": "major",
+            "assignee": "https://tickets.example.com/api/users/jdoe"
+        })
+    );
+}
+
+#[cfg(feature = "testing")]
+fn mock_user_pages(client: &wadl::testing::MockClient) {
+    use wadl::testing::MockResponse;
+
+    client.expect(
+        reqwest::Method::GET,
+        "https://tickets.example.com/api/users?size=2",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "total_size": 3,
+                "entries": [
+                    "https://tickets.example.com/api/users/alice",
+                    "https://tickets.example.com/api/users/bob"
+                ],
+                "next_collection_link": "https://tickets.example.com/api/users-2"
+            }),
+        ),
+    );
+    client.expect(
+        reqwest::Method::GET,
+        "https://tickets.example.com/api/users-2",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "total_size": 3,
+                "entries": ["https://tickets.example.com/api/users/carol"]
+            }),
+        ),
+    );
+}
+
+#[allow(unused_mut, dead_code, clippy::all)]
+mod tickets {
+    use wadl::Resource;
+    include!("tickets-wadl.rs.golden");
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn tickets_paginated_users() {
+    let client = wadl::testing::MockClient::new();
+    mock_user_pages(&client);
+
+    let users: tickets::Users = "https://tickets.example.com/api/users".parse().unwrap();
+    let names = users
+        .list_all(&client, Some(&2))
+        .unwrap()
+        .map(|user| user.map(|url| url.path().to_string()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        names,
+        vec!["/api/users/alice", "/api/users/bob", "/api/users/carol"]
+    );
+    assert_eq!(client.requests().len(), 2);
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[tokio::test]
+async fn tickets_async_paginated_users() {
+    use futures_core::Stream;
+
+    let client = wadl::testing::MockClient::new();
+    mock_user_pages(&client);
+
+    let users: tickets_async::Users = "https://tickets.example.com/api/users".parse().unwrap();
+    let mut stream = users.list_all(&client, Some(&2)).await.unwrap();
+    let mut names = vec![];
+    while let Some(user) =
+        std::future::poll_fn(|cx| std::pin::Pin::new(&mut stream).poll_next(cx)).await
+    {
+        names.push(user.unwrap().path().to_string());
+    }
+    assert_eq!(
+        names,
+        vec!["/api/users/alice", "/api/users/bob", "/api/users/carol"]
+    );
+}
+
+#[cfg(feature = "ureq")]
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_ureq {
+    use wadl::Resource;
+    include!("people-wadl-ureq.rs.golden");
+}
+
+#[cfg(all(feature = "ureq", feature = "testing"))]
+#[test]
+fn people_ureq_mock_client() {
+    use wadl::testing::{MockClient, MockResponse};
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/people?*",
+        MockResponse::json(200, &serde_json::json!({"total_size": 1})),
+    );
+    client.expect(
+        reqwest::Method::POST,
+        "https://api.example.com/1.0/people",
+        MockResponse::new(201).with_header("Location", "https://api.example.com/1.0/~jdoe"),
+    );
+
+    let root: people_ureq::ServiceRootJson =
+        serde_json::from_str(r#"{"people_collection_link": "https://api.example.com/1.0/people"}"#)
+            .unwrap();
+    let people = root.people_collection_link().unwrap();
+
+    let page = people.find(&client, "jdoe", None).unwrap();
+    assert_eq!(page.total_size, 1);
+
+    let person = people.new(&client, "J. Doe").unwrap();
+    use wadl::Resource;
+    assert_eq!(person.url().as_str(), "https://api.example.com/1.0/~jdoe");
+    assert_eq!(
+        client.requests().last().unwrap().body,
+        b"ws.op=new&display_name=J.+Doe".to_vec()
+    );
+
+    assert!(matches!(
+        person.get(&client),
+        Err(wadl::Error::UnhandledStatus(s)) if s.as_u16() == 501
+    ));
+}
+
+#[cfg(feature = "async")]
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_dual {
+    use wadl::Resource;
+    include!("people-wadl-dual.rs.golden");
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[tokio::test]
+async fn people_dual_clients() {
+    use wadl::testing::{MockClient, MockResponse};
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/people?*",
+        MockResponse::json(200, &serde_json::json!({"total_size": 2})),
+    );
+
+    let root: people_dual::ServiceRootJson =
+        serde_json::from_str(r#"{"people_collection_link": "https://api.example.com/1.0/people"}"#)
+            .unwrap();
+    let people = root.people_collection_link().unwrap();
+
+    {
+        use people_dual::aio::PeopleOps;
+        let page = people.find(&client, "jdoe", None).await.unwrap();
+        assert_eq!(page.total_size, 2);
+    }
+
+    let page = tokio::task::spawn_blocking(move || {
+        use people_dual::blocking::PeopleOps;
+        people.find(&client, "jdoe", None).unwrap()
+    })
+    .await
+    .unwrap();
+    assert_eq!(page.total_size, 2);
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[tokio::test]
+async fn people_async_link_navigation() {
+    use wadl::testing::{MockClient, MockResponse};
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/~owner",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "self_link": "https://api.example.com/1.0/~owner",
+                "display_name": "Owner",
+                "date_created": "2024-01-01T00:00:00Z",
+                "is_team": false,
+                "status": "active"
+            }),
+        ),
+    );
+
+    let team: people_async::PersonFull = serde_json::from_value(serde_json::json!({
+        "self_link": "https://api.example.com/1.0/~team",
+        "display_name": "Team",
+        "team_owner_link": "https://api.example.com/1.0/~owner",
+        "date_created": "2024-01-01T00:00:00Z",
+        "is_team": true,
+        "status": "active"
+    }))
+    .unwrap();
+    let owner = team.owner(&client).await.unwrap().unwrap();
+    assert_eq!(owner.display_name, "Owner");
+    assert_eq!(owner.owner(&client).await.unwrap(), None);
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[tokio::test]
+async fn people_async_conditional_patch() {
+    use wadl::testing::{MockClient, MockResponse};
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/~jdoe",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "self_link": "https://api.example.com/1.0/~jdoe",
+                "display_name": "J. Doe",
+                "date_created": "2024-01-01T00:00:00Z",
+                "is_team": false,
+                "status": "active"
+            }),
+        )
+        .with_header("ETag", "\"abc\""),
+    );
+    client.expect(
+        reqwest::Method::PATCH,
+        "https://api.example.com/1.0/~jdoe",
+        MockResponse::new(412),
+    );
+
+    let person: people_async::Person =
+        serde_json::from_value::<people_async::PersonFull>(serde_json::json!({
+            "self_link": "https://api.example.com/1.0/~jdoe",
+            "display_name": "J. Doe",
+            "date_created": "2024-01-01T00:00:00Z",
+            "is_team": false,
+            "status": "active"
+        }))
+        .unwrap()
+        .self_link();
+
+    let mut full = person.get(&client).await.unwrap();
+    assert_eq!(full.etag.as_deref(), Some("\"abc\""));
+    full.display_name = "John Doe".to_string();
+    let etag = full.etag.clone().unwrap();
+    assert!(matches!(
+        person.patch_if_match(&client, &full, &etag).await,
+        Err(wadl::Error::PreconditionFailed)
+    ));
+    let request = client.requests().pop().unwrap();
+    assert!(request
+        .headers
+        .contains(&("if-match".to_string(), "\"abc\"".to_string())));
+}
+
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_typed_links {
+    use wadl::Resource;
+    include!("people-wadl-typed-links.rs.golden");
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn people_typed_links_follow() {
+    use wadl::blocking::Follow;
+    use wadl::testing::{MockClient, MockResponse};
+    use wadl::Resource;
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/~jdoe",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "self_link": "https://api.example.com/1.0/~jdoe",
+                "team_owner_link": "https://api.example.com/1.0/~team",
+                "display_name": "J. Doe",
+                "date_created": "2024-01-01T00:00:00Z",
+                "is_team": false,
+                "status": "active"
+            }),
+        ),
+    );
+
+    let link: wadl::Link<people_typed_links::Person> =
+        "https://api.example.com/1.0/~jdoe".parse().unwrap();
+    let person = link.follow(&client).unwrap();
+    assert_eq!(person.display_name, "J. Doe");
+    assert_eq!(person.self_link, link);
+    assert_eq!(
+        person.team_owner_link.as_ref().unwrap().as_str(),
+        "https://api.example.com/1.0/~team"
+    );
+    // The accessors still return resources
+    assert_eq!(person.self_link().url(), link.url());
+}
+
+#[cfg(feature = "async")]
+#[allow(unused_mut, dead_code, clippy::all)]
+mod people_async_typed_links {
+    use wadl::Resource;
+    include!("people-wadl-async-typed-links.rs.golden");
+}
+
+#[cfg(all(feature = "async", feature = "testing"))]
+#[tokio::test]
+async fn people_async_typed_links_follow() {
+    use wadl::r#async::Follow;
+    use wadl::testing::{MockClient, MockResponse};
+
+    let client = MockClient::new();
+    client.expect(
+        reqwest::Method::GET,
+        "https://api.example.com/1.0/",
+        MockResponse::json(
+            200,
+            &serde_json::json!({
+                "people_collection_link": "https://api.example.com/1.0/people"
+            }),
+        ),
+    );
+
+    let root: wadl::Link<people_async_typed_links::ServiceRoot> =
+        "https://api.example.com/1.0/".parse().unwrap();
+    let root = root.follow(&client).await.unwrap();
+    assert_eq!(
+        root.people_collection_link.unwrap().as_str(),
+        "https://api.example.com/1.0/people"
+    );
+}
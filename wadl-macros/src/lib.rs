@@ -0,0 +1,127 @@
+#![deny(missing_docs)]
+//! Procedural macros for generating Rust code from WADL files
+//!
+//! This is a companion crate to [`wadl`](https://docs.rs/wadl), for users who would rather
+//! not use a build script or commit generated code.
+
+use proc_macro::TokenStream;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, LitBool, LitStr, Token};
+
+/// A `key = value` option passed to `include_wadl!`
+struct MacroOption {
+    key: Ident,
+    value: LitBool,
+}
+
+impl Parse for MacroOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.call(Ident::parse_any)?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(MacroOption { key, value })
+    }
+}
+
+/// The arguments of `include_wadl!`
+struct IncludeWadl {
+    path: LitStr,
+    options: Punctuated<MacroOption, Token![,]>,
+}
+
+impl Parse for IncludeWadl {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let options = if input.parse::<Option<Token![,]>>()?.is_some() {
+            Punctuated::parse_terminated(input)?
+        } else {
+            Punctuated::new()
+        };
+        Ok(IncludeWadl { path, options })
+    }
+}
+
+fn build_config(
+    options: &Punctuated<MacroOption, Token![,]>,
+) -> syn::Result<wadl::codegen::Config> {
+    let mut config = wadl::codegen::Config::default();
+    for option in options {
+        let value = option.value.value;
+        match option.key.unraw().to_string().as_str() {
+            "async" => config.r#async = value,
+            "blocking_and_async" => config.blocking_and_async = value,
+            "tower" => config.tower = value,
+            "ureq" => config.ureq = value,
+            "native_async_client" => config.native_async_client = value,
+            "strip_code_examples" => config.strip_code_examples = value,
+            "skip_serializing_none" => config.skip_serializing_none = value,
+            "capture_unknown_fields" => config.capture_unknown_fields = value,
+            "generate_resource_traits" => config.generate_resource_traits = value,
+            "options_non_exhaustive" => config.options_non_exhaustive = value,
+            _ => {
+                return Err(syn::Error::new(
+                    option.key.span(),
+                    format!("unknown option `{}`", option.key),
+                ))
+            }
+        }
+    }
+    Ok(config)
+}
+
+/// Generate a client from a WADL file, inline.
+///
+/// The path is relative to the directory containing the `Cargo.toml` of the crate that
+/// invokes the macro. Boolean code generation options can be passed as `key = value`
+/// pairs: `async`, `blocking_and_async`, `tower`, `ureq`, `native_async_client`,
+/// `strip_code_examples`, `skip_serializing_none`, `capture_unknown_fields`, `generate_resource_traits` and
+/// `options_non_exhaustive`.
+///
+/// # Example
+///
+/// ```ignore
+/// wadl_macros::include_wadl!("api/launchpad.wadl", async = true);
+/// ```
+#[proc_macro]
+pub fn include_wadl(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as IncludeWadl);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &IncludeWadl) -> syn::Result<proc_macro2::TokenStream> {
+    let config = build_config(&input.options)?;
+
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_default();
+    let path = manifest_dir.join(input.path.value());
+
+    let app = wadl::parse_file(&path).map_err(|e| {
+        syn::Error::new(
+            input.path.span(),
+            format!("unable to parse {}: {}", path.display(), e),
+        )
+    })?;
+
+    let code = wadl::codegen::generate(&app, &config);
+    let code: proc_macro2::TokenStream = code.parse().map_err(|e| {
+        syn::Error::new(
+            input.path.span(),
+            format!("generated code is not valid Rust: {}", e),
+        )
+    })?;
+
+    // Referencing the file makes the compiler rebuild when it changes
+    let path = path.to_string_lossy();
+    Ok(quote::quote! {
+        const _: &[u8] = include_bytes!(#path);
+        #[allow(unused_imports)]
+        use wadl::Resource as _;
+        #code
+    })
+}
@@ -0,0 +1,32 @@
+mod people {
+    wadl_macros::include_wadl!("../tests/people-wadl.xml");
+}
+
+mod people_traits {
+    wadl_macros::include_wadl!("../tests/people-wadl.xml", generate_resource_traits = true);
+}
+
+#[test]
+fn include_people_wadl() {
+    use wadl::Resource;
+
+    let root: people::ServiceRootJson =
+        serde_json::from_str(r#"{"people_collection_link": "https://api.example.com/1.0/people"}"#)
+            .unwrap();
+    let people = root.people_collection_link().unwrap();
+    assert_eq!(people.url().as_str(), "https://api.example.com/1.0/people");
+    assert_eq!(
+        "inactive".parse::<people::Status>().unwrap(),
+        people::Status::Inactive
+    );
+    assert!(matches!(
+        "bogus".parse::<people::Status>(),
+        Err(wadl::Error::InvalidOption("Status", _))
+    ));
+}
+
+#[test]
+fn include_people_wadl_traits() {
+    fn assert_impl<T: people_traits::PeopleOps>() {}
+    assert_impl::<people_traits::People>();
+}